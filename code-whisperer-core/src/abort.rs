@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+
+use crate::CodingPattern;
+
+/// Items walked between abort checks, so a slow check never dominates extraction
+pub const ABORT_POLL_INTERVAL: usize = 64;
+
+#[cfg(not(target_arch = "wasm32"))]
+type AbortCheck = Arc<dyn Fn() -> bool + Send + Sync>;
+#[cfg(target_arch = "wasm32")]
+type AbortCheck = js_sys::Function;
+
+/// Cooperative cancellation for long-running analysis. Clones share the same flag,
+/// so an editor can keep one and hand the other to the analyzer
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct AbortFlag {
+    flag: Arc<AtomicBool>,
+    check: Option<AbortCheck>,
+    /// Units of work counted by `tick`, shared so nested loops poll on one schedule
+    ticks: Arc<AtomicUsize>,
+}
+
+#[wasm_bindgen]
+impl AbortFlag {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> AbortFlag {
+        Self::default()
+    }
+
+    #[wasm_bindgen]
+    pub fn abort(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    #[wasm_bindgen]
+    pub fn reset(&self) {
+        self.flag.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether `abort` was called; external checks only run while polling
+    #[wasm_bindgen(getter)]
+    pub fn aborted(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// Abort once `check` returns true, e.g. when the worker has seen a newer edit
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen]
+    pub fn with_check(check: js_sys::Function) -> AbortFlag {
+        AbortFlag {
+            flag: Arc::default(),
+            check: Some(check),
+            ticks: Arc::default(),
+        }
+    }
+}
+
+impl AbortFlag {
+    /// Share an existing flag, e.g. one also set from an editor thread
+    pub fn from_shared(flag: Arc<AtomicBool>) -> Self {
+        Self { flag, check: None, ticks: Arc::default() }
+    }
+
+    pub fn shared(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.flag)
+    }
+
+    /// Abort once `check` returns true
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_check(check: impl Fn() -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            flag: Arc::default(),
+            check: Some(Arc::new(check)),
+            ticks: Arc::default(),
+        }
+    }
+
+    /// Called from extraction loops; only every `ABORT_POLL_INTERVAL`th iteration does any work
+    pub fn should_stop(&self, iteration: usize) -> bool {
+        if !iteration.is_multiple_of(ABORT_POLL_INTERVAL) {
+            return false;
+        }
+        if !self.aborted() && self.run_check() {
+            self.abort();
+        }
+        self.aborted()
+    }
+
+    /// Count one unit of work (a node, a line, a token) and poll like `should_stop`. Every loop
+    /// shares the same count, so work spread over nested or successive loops is still polled
+    pub fn tick(&self) -> bool {
+        self.should_stop(self.ticks.fetch_add(1, Ordering::Relaxed))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_check(&self) -> bool {
+        self.check.as_ref().is_some_and(|check| check())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn run_check(&self) -> bool {
+        self.check.as_ref().is_some_and(|check| {
+            check.call0(&JsValue::NULL).map(|value| value.is_truthy()).unwrap_or(false)
+        })
+    }
+}
+
+impl std::fmt::Debug for AbortFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AbortFlag")
+            .field("aborted", &self.aborted())
+            .field("has_check", &self.check.is_some())
+            .finish()
+    }
+}
+
+/// Patterns found before analysis finished or was aborted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AbortableAnalysis {
+    pub patterns: Vec<CodingPattern>,
+    pub aborted: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_clones_share_the_flag() {
+        let flag = AbortFlag::new();
+        let handle = flag.clone();
+        assert!(!flag.should_stop(0));
+
+        handle.abort();
+        assert!(flag.aborted());
+        // Off-interval iterations skip the check entirely
+        assert!(!flag.should_stop(1));
+        assert!(flag.should_stop(ABORT_POLL_INTERVAL));
+
+        flag.reset();
+        assert!(!handle.aborted());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))] // a JS `Function` does the checking there
+    #[test]
+    fn test_check_trips_the_flag() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&polls);
+        let flag = AbortFlag::with_check(move || counter.fetch_add(1, Ordering::Relaxed) >= 2);
+
+        assert!(!flag.should_stop(0));
+        assert!(!flag.should_stop(ABORT_POLL_INTERVAL));
+        assert!(flag.should_stop(ABORT_POLL_INTERVAL * 2));
+        assert!(flag.aborted());
+        assert_eq!(polls.load(Ordering::Relaxed), 3);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_ticks_are_counted_across_loops() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&polls);
+        let flag = AbortFlag::with_check(move || counter.fetch_add(1, Ordering::Relaxed) >= 1);
+
+        // Two short loops together reach the second poll even though neither does alone
+        for _ in 0..ABORT_POLL_INTERVAL / 2 + 1 {
+            assert!(!flag.tick());
+        }
+        let stopped = (0..ABORT_POLL_INTERVAL / 2).any(|_| flag.tick());
+        assert!(stopped);
+        assert_eq!(polls.load(Ordering::Relaxed), 2);
+    }
+}
@@ -1,12 +1,18 @@
-use crate::{PatternType};
+use crate::{PatternType, SourceLocation};
+use crate::normalized_ast::location;
+use crate::abort::{AbortFlag, AbortableAnalysis};
+use crate::error::AnalysisError;
 use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
 use swc_ecma_ast as swc_ast;
-use swc_common::SourceMap;
-use rustpython_parser::{ast, Parse};
+use swc_common::{SourceMap, Spanned};
+use rustpython_parser::{ast, ast::Ranged, Parse};
 use syn::{parse_str, Item};
 use regex::Regex;
 use lazy_static::lazy_static;
 
+/// Language names that ask for line-based parsing instead of a real parser
+pub(crate) const GENERIC_LANGUAGES: &[&str] = &["", "generic", "unknown", "text", "plaintext"];
+
 lazy_static! {
     static ref JS_FUNCTION_PATTERN: Regex = Regex::new(r"function\s+(\w+)\s*\(([^)]*)\)").unwrap();
     static ref JS_ARROW_FUNCTION_PATTERN: Regex = Regex::new(r"(\w+)\s*=>").unwrap();
@@ -37,21 +43,23 @@ impl AstParser {
         AstParser
     }
 
-    pub fn parse_code(&self, code: &str, language: &str) -> Result<ParsedAst, String> {
+    pub fn parse_code(&self, code: &str, language: &str) -> Result<ParsedAst, AnalysisError> {
         match language.to_lowercase().as_str() {
-            "javascript" | "typescript" => self.parse_javascript(code),
+            "javascript" => self.parse_javascript(code, "javascript", Syntax::Es(Default::default())),
+            "typescript" => self.parse_javascript(code, "typescript", Syntax::Typescript(Default::default())),
             "python" => self.parse_python(code),
             "rust" => self.parse_rust(code),
-            _ => self.parse_generic(code),
+            // Java, Go, Kotlin and anything else without a parser get line-based analysis
+            _ => Ok(self.parse_generic(code)),
         }
     }
 
-    fn parse_javascript(&self, code: &str) -> Result<ParsedAst, String> {
+    fn parse_javascript(&self, code: &str, language: &str, syntax: Syntax) -> Result<ParsedAst, AnalysisError> {
         let cm = SourceMap::default();
         let fm = cm.new_source_file(swc_common::FileName::Anon, code.to_string());
 
         let lexer = Lexer::new(
-            Syntax::Es(Default::default()),
+            syntax,
             Default::default(),
             StringInput::from(&*fm),
             None,
@@ -60,56 +68,71 @@ impl AstParser {
         let mut parser = Parser::new_from(lexer);
         match parser.parse_module() {
             Ok(module) => Ok(ParsedAst::JavaScript(module)),
-            Err(err) => Err(format!("JavaScript parsing error: {:?}", err)),
+            Err(err) => {
+                // swc byte positions start at 1
+                let offset = err.span().lo.0.saturating_sub(1) as usize;
+                Err(AnalysisError::parse_failed(language, err.kind().msg(), Some(line_at(code, offset))))
+            }
         }
     }
 
-    fn parse_python(&self, code: &str) -> Result<ParsedAst, String> {
+    fn parse_python(&self, code: &str) -> Result<ParsedAst, AnalysisError> {
         match ast::Suite::parse(code, "<string>") {
             Ok(suite) => Ok(ParsedAst::Python(suite)),
-            Err(err) => Err(format!("Python parsing error: {}", err)),
+            Err(err) => Err(AnalysisError::parse_failed("python", err.error.to_string(), Some(line_at(code, err.offset.to_usize())))),
         }
     }
 
-    fn parse_rust(&self, code: &str) -> Result<ParsedAst, String> {
+    fn parse_rust(&self, code: &str) -> Result<ParsedAst, AnalysisError> {
         match parse_str::<syn::File>(code) {
             Ok(file) => Ok(ParsedAst::Rust(file.items)),
-            Err(err) => Err(format!("Rust parsing error: {}", err)),
+            // syn only knows line numbers with proc-macro2's span-locations feature
+            Err(err) => Err(AnalysisError::parse_failed("rust", err.to_string(), None)),
         }
     }
 
-    fn parse_generic(&self, code: &str) -> Result<ParsedAst, String> {
-        let lines: Vec<String> = code.lines().map(|s| s.to_string()).collect();
-        Ok(ParsedAst::Generic(lines))
+    fn parse_generic(&self, code: &str) -> ParsedAst {
+        ParsedAst::Generic(code.lines().map(|s| s.to_string()).collect())
     }
 
-    pub fn extract_patterns(&self, ast: &ParsedAst, language: &str) -> Vec<crate::CodingPattern> {
-        match ast {
-            ParsedAst::JavaScript(module) => self.extract_js_patterns(module),
-            ParsedAst::Python(suite) => self.extract_py_patterns(suite),
-            ParsedAst::Rust(items) => self.extract_rs_patterns(items),
-            ParsedAst::Generic(lines) => self.extract_generic_patterns(lines, language),
-        }
+    pub fn extract_patterns(&self, ast: &ParsedAst, code: &str, language: &str) -> Vec<crate::CodingPattern> {
+        self.extract_patterns_abortable(ast, code, language, &AbortFlag::default()).patterns
     }
 
-    fn extract_js_patterns(&self, module: &swc_ast::Module) -> Vec<crate::CodingPattern> {
+    /// Extract patterns, stopping early with what was found so far once `abort` is set. `code` is
+    /// the source `ast` was parsed from, used to locate each pattern
+    pub fn extract_patterns_abortable(&self, ast: &ParsedAst, code: &str, language: &str, abort: &AbortFlag) -> AbortableAnalysis {
         let mut patterns = Vec::new();
-
-        for item in &module.body {
+        let aborted = match ast {
+            ParsedAst::JavaScript(module) => self.extract_js_patterns(module, code, abort, &mut patterns),
+            ParsedAst::Python(suite) => self.extract_py_patterns(suite, code, abort, &mut patterns),
+            ParsedAst::Rust(items) => self.extract_rs_patterns(items, code, abort, &mut patterns),
+            ParsedAst::Generic(lines) => self.extract_generic_patterns(lines, language, abort, &mut patterns),
+        };
+        AbortableAnalysis { patterns, aborted }
+    }
+
+    fn extract_js_patterns(&self, module: &swc_ast::Module, code: &str, abort: &AbortFlag, patterns: &mut Vec<crate::CodingPattern>) -> bool {
+        for item in module.body.iter() {
+            if abort.tick() {
+                return true;
+            }
             match item {
                 swc_ast::ModuleItem::Stmt(stmt) => {
-                    self.extract_js_statement_patterns(stmt, &mut patterns);
+                    if self.extract_js_statement_patterns(stmt, code, abort, patterns) {
+                        return true;
+                    }
                 }
                 swc_ast::ModuleItem::ModuleDecl(decl) => {
-                    self.extract_js_declaration_patterns(decl, &mut patterns);
+                    self.extract_js_declaration_patterns(decl, code, patterns);
                 }
             }
         }
 
-        patterns
+        false
     }
 
-    fn extract_js_statement_patterns(&self, stmt: &swc_ast::Stmt, patterns: &mut Vec<crate::CodingPattern>) {
+    fn extract_js_statement_patterns(&self, stmt: &swc_ast::Stmt, code: &str, abort: &AbortFlag, patterns: &mut Vec<crate::CodingPattern>) -> bool {
         match stmt {
             swc_ast::Stmt::Decl(decl) => {
                 match decl {
@@ -119,16 +142,19 @@ impl AstParser {
                             "javascript".to_string(),
                             &PatternType::FunctionDefinition,
                         );
-                        patterns.push(pattern);
+                        patterns.push(pattern.with_location(js_location(code, stmt.span())));
                     }
                     swc_ast::Decl::Var(var_decl) => {
                         for binding in &var_decl.decls {
+                            if abort.tick() {
+                                return true;
+                            }
                             if let swc_ast::Pat::Ident(ident) = &binding.name {
                                 let pattern = self.create_variable_pattern(
                                     ident.id.sym.to_string(),
                                     "javascript".to_string(),
                                 );
-                                patterns.push(pattern);
+                                patterns.push(pattern.with_location(js_location(code, binding.span)));
                             }
                         }
                     }
@@ -137,19 +163,20 @@ impl AstParser {
                             class_decl.ident.sym.to_string(),
                             "javascript".to_string(),
                         );
-                        patterns.push(pattern);
+                        patterns.push(pattern.with_location(js_location(code, stmt.span())));
                     }
                     _ => {}
                 }
             }
             swc_ast::Stmt::Expr(expr_stmt) => {
-                self.extract_js_expression_patterns(&expr_stmt.expr, patterns);
+                self.extract_js_expression_patterns(&expr_stmt.expr, code, patterns);
             }
             _ => {}
         }
+        false
     }
 
-    fn extract_js_declaration_patterns(&self, decl: &swc_ast::ModuleDecl, patterns: &mut Vec<crate::CodingPattern>) {
+    fn extract_js_declaration_patterns(&self, decl: &swc_ast::ModuleDecl, code: &str, patterns: &mut Vec<crate::CodingPattern>) {
         match decl {
             swc_ast::ModuleDecl::ExportDecl(export_decl) => {
                 if let swc_ast::Decl::Fn(fn_decl) = &export_decl.decl {
@@ -158,14 +185,14 @@ impl AstParser {
                         "javascript".to_string(),
                         &PatternType::FunctionDefinition,
                     );
-                    patterns.push(pattern);
+                    patterns.push(pattern.with_location(js_location(code, export_decl.span)));
                 }
             }
             _ => {}
         }
     }
 
-    fn extract_js_expression_patterns(&self, expr: &swc_ast::Expr, patterns: &mut Vec<crate::CodingPattern>) {
+    fn extract_js_expression_patterns(&self, expr: &swc_ast::Expr, code: &str, patterns: &mut Vec<crate::CodingPattern>) {
         match expr {
             swc_ast::Expr::Arrow(_arrow_expr) => {
                 let pattern = self.create_function_pattern(
@@ -173,7 +200,7 @@ impl AstParser {
                     "javascript".to_string(),
                     &PatternType::FunctionDefinition,
                 );
-                patterns.push(pattern);
+                patterns.push(pattern.with_location(js_location(code, expr.span())));
             }
             swc_ast::Expr::Call(_call_expr) => {
                 // Could extract function call patterns here
@@ -182,10 +209,11 @@ impl AstParser {
         }
     }
 
-    fn extract_py_patterns(&self, suite: &ast::Suite) -> Vec<crate::CodingPattern> {
-        let mut patterns = Vec::new();
-
-        for stmt in suite {
+    fn extract_py_patterns(&self, suite: &ast::Suite, code: &str, abort: &AbortFlag, patterns: &mut Vec<crate::CodingPattern>) -> bool {
+        for stmt in suite.iter() {
+            if abort.tick() {
+                return true;
+            }
             match stmt {
                 ast::Stmt::FunctionDef(fn_def) => {
                     let pattern = self.create_function_pattern(
@@ -193,23 +221,26 @@ impl AstParser {
                         "python".to_string(),
                         &PatternType::FunctionDefinition,
                     );
-                    patterns.push(pattern);
+                    patterns.push(pattern.with_location(location(code, fn_def.start().to_usize(), fn_def.end().to_usize())));
                 }
                 ast::Stmt::ClassDef(class_def) => {
                     let pattern = self.create_class_pattern(
                         class_def.name.to_string(),
                         "python".to_string(),
                     );
-                    patterns.push(pattern);
+                    patterns.push(pattern.with_location(location(code, class_def.start().to_usize(), class_def.end().to_usize())));
                 }
                 ast::Stmt::Assign(assign) => {
                     for target in &assign.targets {
+                        if abort.tick() {
+                            return true;
+                        }
                         if let ast::Expr::Name(name) = target {
                             let pattern = self.create_variable_pattern(
                                 name.id.to_string(),
                                 "python".to_string(),
                             );
-                            patterns.push(pattern);
+                            patterns.push(pattern.with_location(location(code, name.start().to_usize(), name.end().to_usize())));
                         }
                     }
                 }
@@ -217,13 +248,16 @@ impl AstParser {
             }
         }
 
-        patterns
+        false
     }
 
-    fn extract_rs_patterns(&self, items: &[Item]) -> Vec<crate::CodingPattern> {
-        let mut patterns = Vec::new();
-
-        for item in items {
+    fn extract_rs_patterns(&self, items: &[Item], code: &str, abort: &AbortFlag, patterns: &mut Vec<crate::CodingPattern>) -> bool {
+        // syn has no span locations, so items are found by searching the source in order
+        let mut search_from = 0;
+        for item in items.iter() {
+            if abort.tick() {
+                return true;
+            }
             match item {
                 Item::Fn(fn_item) => {
                     let pattern = self.create_function_pattern(
@@ -231,65 +265,65 @@ impl AstParser {
                         "rust".to_string(),
                         &PatternType::FunctionDefinition,
                     );
-                    patterns.push(pattern);
+                    patterns.push(locate_item(code, &mut search_from, "fn", &fn_item.sig.ident.to_string(), pattern));
                 }
                 Item::Struct(struct_item) => {
                     let pattern = self.create_struct_pattern(
                         struct_item.ident.to_string(),
                         "rust".to_string(),
                     );
-                    patterns.push(pattern);
+                    patterns.push(locate_item(code, &mut search_from, "struct", &struct_item.ident.to_string(), pattern));
                 }
                 Item::Const(const_item) => {
                     let pattern = self.create_variable_pattern(
                         const_item.ident.to_string(),
                         "rust".to_string(),
                     );
-                    patterns.push(pattern);
+                    patterns.push(locate_item(code, &mut search_from, "const", &const_item.ident.to_string(), pattern));
                 }
                 Item::Static(static_item) => {
                     let pattern = self.create_variable_pattern(
                         static_item.ident.to_string(),
                         "rust".to_string(),
                     );
-                    patterns.push(pattern);
+                    patterns.push(locate_item(code, &mut search_from, "static", &static_item.ident.to_string(), pattern));
                 }
                 _ => {}
             }
         }
 
-        patterns
+        false
     }
 
-    fn extract_generic_patterns(&self, lines: &[String], language: &str) -> Vec<crate::CodingPattern> {
-        let mut patterns = Vec::new();
+    fn extract_generic_patterns(&self, lines: &[String], language: &str, abort: &AbortFlag, patterns: &mut Vec<crate::CodingPattern>) -> bool {
         let code = lines.join("\n");
 
         // Use regex patterns for generic parsing
         match language.to_lowercase().as_str() {
             "javascript" | "typescript" => {
-                self.extract_regex_patterns(&code, &JS_FUNCTION_PATTERN, PatternType::FunctionDefinition, language, &mut patterns);
-                self.extract_regex_patterns(&code, &JS_VARIABLE_PATTERN, PatternType::VariableDeclaration, language, &mut patterns);
-                self.extract_regex_patterns(&code, &JS_CLASS_PATTERN, PatternType::ClassDefinition, language, &mut patterns);
+                self.extract_regex_patterns(&code, &JS_FUNCTION_PATTERN, PatternType::FunctionDefinition, language, abort, patterns)
+                    || self.extract_regex_patterns(&code, &JS_VARIABLE_PATTERN, PatternType::VariableDeclaration, language, abort, patterns)
+                    || self.extract_regex_patterns(&code, &JS_CLASS_PATTERN, PatternType::ClassDefinition, language, abort, patterns)
             }
             "python" => {
-                self.extract_regex_patterns(&code, &PY_FUNCTION_PATTERN, PatternType::FunctionDefinition, language, &mut patterns);
-                self.extract_regex_patterns(&code, &PY_VARIABLE_PATTERN, PatternType::VariableDeclaration, language, &mut patterns);
-                self.extract_regex_patterns(&code, &PY_CLASS_PATTERN, PatternType::ClassDefinition, language, &mut patterns);
+                self.extract_regex_patterns(&code, &PY_FUNCTION_PATTERN, PatternType::FunctionDefinition, language, abort, patterns)
+                    || self.extract_regex_patterns(&code, &PY_VARIABLE_PATTERN, PatternType::VariableDeclaration, language, abort, patterns)
+                    || self.extract_regex_patterns(&code, &PY_CLASS_PATTERN, PatternType::ClassDefinition, language, abort, patterns)
             }
             "rust" => {
-                self.extract_regex_patterns(&code, &RS_FUNCTION_PATTERN, PatternType::FunctionDefinition, language, &mut patterns);
-                self.extract_regex_patterns(&code, &RS_VARIABLE_PATTERN, PatternType::VariableDeclaration, language, &mut patterns);
-                self.extract_regex_patterns(&code, &RS_STRUCT_PATTERN, PatternType::ClassDefinition, language, &mut patterns);
+                self.extract_regex_patterns(&code, &RS_FUNCTION_PATTERN, PatternType::FunctionDefinition, language, abort, patterns)
+                    || self.extract_regex_patterns(&code, &RS_VARIABLE_PATTERN, PatternType::VariableDeclaration, language, abort, patterns)
+                    || self.extract_regex_patterns(&code, &RS_STRUCT_PATTERN, PatternType::ClassDefinition, language, abort, patterns)
             }
-            _ => {}
+            _ => false,
         }
-
-        patterns
     }
 
-    fn extract_regex_patterns(&self, code: &str, pattern: &Regex, pattern_type: PatternType, language: &str, patterns: &mut Vec<crate::CodingPattern>) {
+    fn extract_regex_patterns(&self, code: &str, pattern: &Regex, pattern_type: PatternType, language: &str, abort: &AbortFlag, patterns: &mut Vec<crate::CodingPattern>) -> bool {
         for capture in pattern.captures_iter(code) {
+            if abort.tick() {
+                return true;
+            }
             if let Some(name_match) = capture.get(1) {
                 let name = name_match.as_str().to_string();
                 let pattern = match pattern_type {
@@ -298,9 +332,10 @@ impl AstParser {
                     PatternType::ClassDefinition => self.create_class_pattern(name, language.to_string()),
                     _ => continue,
                 };
-                patterns.push(pattern);
+                patterns.push(pattern.with_location(location(code, name_match.start(), name_match.end())));
             }
         }
+        false
     }
 
     fn create_function_pattern(&self, name: String, language: String, pattern_type: &PatternType) -> crate::CodingPattern {
@@ -340,6 +375,30 @@ impl AstParser {
     }
 }
 
+fn js_location(code: &str, span: swc_common::Span) -> SourceLocation {
+    // swc byte positions start at 1
+    location(code, span.lo.0.saturating_sub(1) as usize, span.hi.0.saturating_sub(1) as usize)
+}
+
+/// Locate the next `keyword name` at or after `search_from`, leaving the pattern unlocated if
+/// the item isn't found
+fn locate_item(code: &str, search_from: &mut usize, keyword: &str, name: &str, pattern: crate::CodingPattern) -> crate::CodingPattern {
+    let item = Regex::new(&format!(r"\b{}\s+(?:mut\s+)?{}\b", keyword, regex::escape(name))).unwrap();
+    let start = (*search_from).min(code.len());
+    match item.find(&code[start..]) {
+        Some(found) => {
+            *search_from = start + found.end();
+            pattern.with_location(location(code, start + found.start(), start + found.end()))
+        }
+        None => pattern,
+    }
+}
+
+/// 1-based line containing a byte offset
+fn line_at(code: &str, offset: usize) -> u32 {
+    code.as_bytes()[..offset.min(code.len())].iter().filter(|&&b| b == b'\n').count() as u32 + 1
+}
+
 fn pattern_type_to_string(pattern_type: &PatternType) -> String {
     match pattern_type {
         PatternType::FunctionDefinition => "function_definition".to_string(),
@@ -374,7 +433,7 @@ mod tests {
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "javascript");
+        let patterns = parser.extract_patterns(&ast, js_code, "javascript");
         assert!(!patterns.is_empty());
 
         // Should find the function pattern
@@ -406,7 +465,7 @@ mod tests {
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "javascript");
+        let patterns = parser.extract_patterns(&ast, js_code, "javascript");
         assert!(!patterns.is_empty());
     }
 
@@ -426,7 +485,7 @@ result = calculate_average([1, 2, 3, 4, 5])
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "python");
+        let patterns = parser.extract_patterns(&ast, py_code, "python");
         assert!(!patterns.is_empty());
     }
 
@@ -448,7 +507,7 @@ processor = DataProcessor([1, 2, 3])
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "python");
+        let patterns = parser.extract_patterns(&ast, py_code, "python");
         assert!(!patterns.is_empty());
     }
 
@@ -473,7 +532,7 @@ fn main() {
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "rust");
+        let patterns = parser.extract_patterns(&ast, rs_code, "rust");
         assert!(!patterns.is_empty());
     }
 
@@ -506,7 +565,7 @@ fn main() {
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "rust");
+        let patterns = parser.extract_patterns(&ast, rs_code, "rust");
         assert!(!patterns.is_empty());
     }
 
@@ -535,7 +594,7 @@ fn main() {
         assert!(result.is_ok());
 
         let ast = result.unwrap();
-        let patterns = parser.extract_patterns(&ast, "javascript");
+        let patterns = parser.extract_patterns(&ast, empty_code, "javascript");
         // Empty code should still produce a valid (possibly empty) pattern list
         // patterns is a Vec, so we just check if it's empty or not empty (both are valid)
         assert!(patterns.is_empty() || !patterns.is_empty()); // This will always pass, just testing that it doesn't panic
@@ -567,4 +626,34 @@ fn main() {
         let result = parser.parse_code(invalid_rs, "rust");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_languages_without_a_parser_fall_back_to_generic() {
+        let parser = AstParser::new();
+        assert!(matches!(parser.parse_code("fun greet() {}", "kotlin"), Ok(ParsedAst::Generic(_))));
+
+        let detector = crate::FunctionSignatureDetector::new();
+        assert!(detector.analyze_signatures("fun greet() {}", "kotlin").is_ok());
+        let style = crate::CodingStyleAnalyzer::new();
+        assert!(style.analyze_style("func main() {\n\tfmt.Println(1)\n}\n", "go").is_ok());
+        let naming = crate::VariableNamingRecognizer::new();
+        assert!(naming.analyze_variable_naming("String userName = \"a\";\n", "java").is_ok());
+    }
+
+    #[test]
+    fn test_parse_failure_reports_line() {
+        let parser = AstParser::new();
+
+        let js = "const ok = 1;\nfunction broken( {\n";
+        match parser.parse_code(js, "javascript") {
+            Err(AnalysisError::ParseFailed { language, line, .. }) => {
+                assert_eq!(language, "javascript");
+                assert_eq!(line, Some(2));
+            },
+            other => panic!("Expected ParseFailed, got {:?}", other.err()),
+        }
+
+        let python = "def ok():\n    pass\n\ndef broken(:\n    pass\n";
+        assert!(matches!(parser.parse_code(python, "python"), Err(AnalysisError::ParseFailed { line: Some(4), .. })));
+    }
 }
@@ -0,0 +1,191 @@
+use swc_common::Spanned;
+use swc_ecma_ast as swc_ast;
+
+/// An anonymous function passed as an argument one level past the allowed callback depth,
+/// together with the deepest callback nested inside it
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedCallback {
+    pub callee: String, // the function the offending callback is passed to
+    pub line_number: u32,
+    pub depth: u32,
+}
+
+/// Callbacks in `module` that push nesting past `max_depth`. Each nest is reported once, at
+/// the callback that first crosses the limit, however deep it goes from there
+pub fn nested_callbacks(module: &swc_ast::Module, code: &str, max_depth: u32) -> Vec<NestedCallback> {
+    let mut walker = CallbackWalker { code, max_depth, found: Vec::new() };
+    for item in &module.body {
+        match item {
+            swc_ast::ModuleItem::Stmt(stmt) => {
+                walker.stmt(stmt, 0);
+            }
+            swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDecl(export)) => {
+                walker.decl(&export.decl, 0);
+            }
+            swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDefaultExpr(export)) => {
+                walker.expr(&export.expr, 0);
+            }
+            swc_ast::ModuleItem::ModuleDecl(_) => {}
+        }
+    }
+    walker.found.sort_by_key(|callback| callback.line_number);
+    walker.found
+}
+
+/// Each visit returns the deepest callback depth reached below the node
+struct CallbackWalker<'a> {
+    code: &'a str,
+    max_depth: u32,
+    found: Vec<NestedCallback>,
+}
+
+impl CallbackWalker<'_> {
+    fn stmts(&mut self, stmts: &[swc_ast::Stmt], depth: u32) -> u32 {
+        stmts.iter().map(|stmt| self.stmt(stmt, depth)).max().unwrap_or(depth)
+    }
+
+    fn stmt(&mut self, stmt: &swc_ast::Stmt, depth: u32) -> u32 {
+        use swc_ast::Stmt;
+        match stmt {
+            Stmt::Block(block) => self.stmts(&block.stmts, depth),
+            Stmt::Expr(expr_stmt) => self.expr(&expr_stmt.expr, depth),
+            Stmt::Return(ret) => self.opt_expr(ret.arg.as_deref(), depth),
+            Stmt::Throw(throw) => self.expr(&throw.arg, depth),
+            Stmt::Decl(decl) => self.decl(decl, depth),
+            Stmt::If(if_stmt) => {
+                let alt = if_stmt.alt.as_ref().map_or(depth, |alt| self.stmt(alt, depth));
+                self.expr(&if_stmt.test, depth).max(self.stmt(&if_stmt.cons, depth)).max(alt)
+            }
+            Stmt::For(for_stmt) => {
+                let init = match &for_stmt.init {
+                    Some(swc_ast::VarDeclOrExpr::VarDecl(var)) => self.var_decl(var, depth),
+                    Some(swc_ast::VarDeclOrExpr::Expr(expr)) => self.expr(expr, depth),
+                    None => depth,
+                };
+                init.max(self.stmt(&for_stmt.body, depth))
+            }
+            Stmt::ForIn(for_in) => self.expr(&for_in.right, depth).max(self.stmt(&for_in.body, depth)),
+            Stmt::ForOf(for_of) => self.expr(&for_of.right, depth).max(self.stmt(&for_of.body, depth)),
+            Stmt::While(while_stmt) => self.expr(&while_stmt.test, depth).max(self.stmt(&while_stmt.body, depth)),
+            Stmt::DoWhile(do_while) => self.stmt(&do_while.body, depth).max(self.expr(&do_while.test, depth)),
+            Stmt::Labeled(labeled) => self.stmt(&labeled.body, depth),
+            Stmt::Switch(switch) => {
+                let cases = switch.cases.iter().map(|case| self.stmts(&case.cons, depth)).max().unwrap_or(depth);
+                self.expr(&switch.discriminant, depth).max(cases)
+            }
+            Stmt::Try(try_stmt) => {
+                let handler = try_stmt.handler.as_ref().map_or(depth, |handler| self.stmts(&handler.body.stmts, depth));
+                let finalizer = try_stmt.finalizer.as_ref().map_or(depth, |finalizer| self.stmts(&finalizer.stmts, depth));
+                self.stmts(&try_stmt.block.stmts, depth).max(handler).max(finalizer)
+            }
+            _ => depth,
+        }
+    }
+
+    fn decl(&mut self, decl: &swc_ast::Decl, depth: u32) -> u32 {
+        match decl {
+            swc_ast::Decl::Var(var) => self.var_decl(var, depth),
+            // Named functions declared inside a callback still sit at its depth
+            swc_ast::Decl::Fn(fn_decl) => self.function(&fn_decl.function, depth),
+            _ => depth,
+        }
+    }
+
+    fn var_decl(&mut self, var: &swc_ast::VarDecl, depth: u32) -> u32 {
+        var.decls.iter().map(|declarator| self.opt_expr(declarator.init.as_deref(), depth)).max().unwrap_or(depth)
+    }
+
+    fn function(&mut self, function: &swc_ast::Function, depth: u32) -> u32 {
+        function.body.as_ref().map_or(depth, |body| self.stmts(&body.stmts, depth))
+    }
+
+    fn opt_expr(&mut self, expr: Option<&swc_ast::Expr>, depth: u32) -> u32 {
+        expr.map_or(depth, |expr| self.expr(expr, depth))
+    }
+
+    fn expr(&mut self, expr: &swc_ast::Expr, depth: u32) -> u32 {
+        use swc_ast::Expr;
+        match expr {
+            Expr::Call(call) => {
+                let callee = match &call.callee {
+                    swc_ast::Callee::Expr(callee) => Some(&**callee),
+                    _ => None,
+                };
+                self.call(callee, &call.args, depth)
+            }
+            Expr::New(new) => self.call(Some(&new.callee), new.args.as_deref().unwrap_or_default(), depth),
+            Expr::OptChain(chain) => match &*chain.base {
+                swc_ast::OptChainBase::Call(call) => self.call(Some(&call.callee), &call.args, depth),
+                swc_ast::OptChainBase::Member(member) => self.expr(&member.obj, depth),
+            },
+            Expr::Fn(fn_expr) => self.function(&fn_expr.function, depth),
+            Expr::Arrow(arrow) => self.arrow_body(&arrow.body, depth),
+            Expr::Member(member) => self.expr(&member.obj, depth),
+            Expr::Paren(paren) => self.expr(&paren.expr, depth),
+            Expr::Await(await_expr) => self.expr(&await_expr.arg, depth),
+            Expr::Unary(unary) => self.expr(&unary.arg, depth),
+            Expr::Assign(assign) => self.expr(&assign.right, depth),
+            Expr::Bin(bin) => self.expr(&bin.left, depth).max(self.expr(&bin.right, depth)),
+            Expr::Cond(cond) => self.expr(&cond.test, depth).max(self.expr(&cond.cons, depth)).max(self.expr(&cond.alt, depth)),
+            Expr::Seq(seq) => seq.exprs.iter().map(|expr| self.expr(expr, depth)).max().unwrap_or(depth),
+            Expr::Array(array) => array.elems.iter().flatten().map(|elem| self.expr(&elem.expr, depth)).max().unwrap_or(depth),
+            Expr::Object(object) => object.props.iter()
+                .map(|prop| match prop {
+                    swc_ast::PropOrSpread::Prop(prop) => match &**prop {
+                        swc_ast::Prop::KeyValue(key_value) => self.expr(&key_value.value, depth),
+                        swc_ast::Prop::Method(method) => self.function(&method.function, depth),
+                        _ => depth,
+                    },
+                    swc_ast::PropOrSpread::Spread(spread) => self.expr(&spread.expr, depth),
+                })
+                .max()
+                .unwrap_or(depth),
+            _ => depth,
+        }
+    }
+
+    fn arrow_body(&mut self, body: &swc_ast::BlockStmtOrExpr, depth: u32) -> u32 {
+        match body {
+            swc_ast::BlockStmtOrExpr::BlockStmt(block) => self.stmts(&block.stmts, depth),
+            swc_ast::BlockStmtOrExpr::Expr(expr) => self.expr(expr, depth),
+        }
+    }
+
+    fn call(&mut self, callee: Option<&swc_ast::Expr>, args: &[swc_ast::ExprOrSpread], depth: u32) -> u32 {
+        let mut deepest = callee.map_or(depth, |callee| self.expr(callee, depth));
+        for arg in args {
+            let inner = match &*arg.expr {
+                swc_ast::Expr::Fn(fn_expr) if fn_expr.ident.is_none() => self.function(&fn_expr.function, depth + 1),
+                swc_ast::Expr::Arrow(arrow) => self.arrow_body(&arrow.body, depth + 1),
+                other => {
+                    deepest = deepest.max(self.expr(other, depth));
+                    continue;
+                }
+            };
+            // Callbacks are at least as deep as themselves, even with an empty body
+            let inner = inner.max(depth + 1);
+            if depth == self.max_depth && inner > self.max_depth {
+                let offset = arg.expr.span().lo.0.saturating_sub(1) as usize;
+                self.found.push(NestedCallback {
+                    callee: callee.map(callee_name).unwrap_or_else(|| "callback".to_string()),
+                    line_number: self.code.get(..offset).unwrap_or(self.code).matches('\n').count() as u32 + 1,
+                    depth: inner,
+                });
+            }
+            deepest = deepest.max(inner);
+        }
+        deepest
+    }
+}
+
+/// `readFile` for both `readFile(...)` and `fs.readFile(...)`
+fn callee_name(callee: &swc_ast::Expr) -> String {
+    match callee {
+        swc_ast::Expr::Ident(ident) => ident.sym.to_string(),
+        swc_ast::Expr::Member(member) => match &member.prop {
+            swc_ast::MemberProp::Ident(ident) => ident.sym.to_string(),
+            _ => callee_name(&member.obj),
+        },
+        _ => "callback".to_string(),
+    }
+}
@@ -1,11 +1,183 @@
 use crate::ast_parser::{AstParser, ParsedAst};
-use std::collections::HashMap;
+use crate::error::AnalysisError;
+use crate::local_storage_manager::{Effort, RefactoringSuggestion, RefactoringType, Severity};
+use crate::callback_nesting::nested_callbacks;
+use crate::exception_handling::faulty_handlers;
+use crate::resource_management::find_unclosed_resources;
+use crate::return_consistency::inconsistent_returns;
+use crate::module_graph::ModuleDependencyGraph;
+use crate::tokenizer::{Tokenizer, TokenKind};
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use regex::Regex;
+use serde::Serialize;
 use lazy_static::lazy_static;
+use quote::ToTokens;
+use rustpython_parser::ast::{self as py_ast, Ranged};
+use swc_common::Spanned;
+use swc_ecma_ast as swc_ast;
 
 /// Code structure analyzer for detecting architectural and organizational patterns
 pub struct CodeStructureAnalyzer {
     parser: AstParser,
+    complexity_thresholds: ComplexityThresholds,
+    smell_thresholds: SmellThresholds,
+    min_duplicate_tokens: usize,
+}
+
+/// Shortest token run `find_duplicates` reports as a clone
+pub const DEFAULT_MIN_DUPLICATE_TOKENS: usize = 30;
+
+/// Upper bounds (exclusive) of the low/medium/high complexity buckets; anything above is very_high
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexityThresholds {
+    pub low: f32,
+    pub medium: f32,
+    pub high: f32,
+}
+
+impl ComplexityThresholds {
+    pub fn new(low: f32, medium: f32, high: f32) -> Self {
+        Self { low, medium, high }
+    }
+
+    /// Buckets only make sense when each cutoff is above the last
+    pub fn validate(&self) -> Result<(), String> {
+        if self.low < self.medium && self.medium < self.high {
+            Ok(())
+        } else {
+            Err(format!(
+                "Complexity thresholds must increase from low to high, got {}/{}/{}",
+                self.low, self.medium, self.high
+            ))
+        }
+    }
+
+    pub fn bucket(&self, complexity: f32) -> &'static str {
+        match complexity {
+            x if x < self.low => "low",
+            x if x < self.medium => "medium",
+            x if x < self.high => "high",
+            _ => "very_high",
+        }
+    }
+}
+
+impl Default for ComplexityThresholds {
+    fn default() -> Self {
+        Self::new(2.0, 4.0, 6.0)
+    }
+}
+
+/// Limits past which classes are reported as god classes, methods as long methods,
+/// functions as too deeply nested and JavaScript callbacks as callback hell
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmellThresholds {
+    pub max_class_lines: u32,
+    pub max_class_methods: u32,
+    pub max_method_lines: u32,
+    pub max_method_complexity: f32,
+    pub max_nesting_depth: u32, // a function's own body counts as depth 1
+    pub max_callback_depth: u32, // anonymous functions passed as arguments, one inside the next
+}
+
+impl SmellThresholds {
+    pub fn new(max_class_lines: u32, max_class_methods: u32, max_method_lines: u32, max_method_complexity: f32) -> Self {
+        Self { max_class_lines, max_class_methods, max_method_lines, max_method_complexity, max_nesting_depth: 4, max_callback_depth: 2 }
+    }
+
+    pub fn with_max_nesting_depth(mut self, depth: u32) -> Self {
+        self.max_nesting_depth = depth;
+        self
+    }
+
+    pub fn with_max_callback_depth(mut self, depth: u32) -> Self {
+        self.max_callback_depth = depth;
+        self
+    }
+}
+
+impl Default for SmellThresholds {
+    fn default() -> Self {
+        Self::new(300, 20, 50, 10.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodeSmellKind {
+    GodClass,
+    LongMethod,
+    DeepNesting,
+    CallbackHell,
+    EmptyCatch,
+    BroadCatch,
+    SwallowedException,
+    UnclosedResource,
+    InconsistentReturn,
+}
+
+/// A structural finding; severity is `Error` once a limit is doubled, `Warning` otherwise
+#[derive(Debug, Clone)]
+pub struct CodeSmell {
+    pub kind: CodeSmellKind,
+    pub name: String,
+    pub line_number: u32,
+    pub severity: Severity,
+    pub description: String,
+}
+
+impl CodeSmell {
+    /// The refactoring that removes this smell, sized by how far past its limit it is
+    pub fn to_refactoring(&self) -> RefactoringSuggestion {
+        let severe = matches!(self.severity, Severity::Error);
+        let (refactoring_type, action, effort) = match self.kind {
+            CodeSmellKind::GodClass => (RefactoringType::ExtractClass, "split", if severe { Effort::VeryHigh } else { Effort::High }),
+            CodeSmellKind::LongMethod => (RefactoringType::ExtractMethod, "extract", if severe { Effort::Medium } else { Effort::Low }),
+            CodeSmellKind::DeepNesting => (RefactoringType::SimplifyCondition, "simplify", if severe { Effort::Medium } else { Effort::Low }),
+            CodeSmellKind::CallbackHell => (RefactoringType::UseAsyncAwait, "flatten", if severe { Effort::High } else { Effort::Medium }),
+            CodeSmellKind::EmptyCatch | CodeSmellKind::BroadCatch | CodeSmellKind::SwallowedException => {
+                (RefactoringType::HandleErrors, "handle", Effort::Low)
+            },
+            CodeSmellKind::UnclosedResource => (RefactoringType::CloseResource, "close", Effort::Trivial),
+            CodeSmellKind::InconsistentReturn => (RefactoringType::UnifyReturns, "unify", Effort::Low),
+        };
+        RefactoringSuggestion {
+            suggestion_id: format!("{}_{}_{}", action, self.name, self.line_number),
+            refactoring_type,
+            target_location: format!("{} (line {})", self.name, self.line_number),
+            description: self.description.clone(),
+            estimated_effort: effort,
+            impact_score: if severe { 0.8 } else { 0.5 },
+        }
+    }
+}
+
+/// Two non-overlapping spans of one file with the same token sequence once identifiers
+/// and literals are abstracted; line ranges are 1-based and inclusive
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateBlock {
+    pub first_lines: (u32, u32),
+    pub second_lines: (u32, u32),
+    pub token_count: usize,
+}
+
+impl DuplicateBlock {
+    /// A `RemoveDuplication` refactoring targeting the second copy
+    pub fn to_refactoring(&self) -> RefactoringSuggestion {
+        let (start, end) = self.second_lines;
+        RefactoringSuggestion {
+            suggestion_id: format!("remove_duplication_{}_{}", start, end),
+            refactoring_type: RefactoringType::RemoveDuplication,
+            target_location: format!("lines {}-{}", start, end),
+            description: format!(
+                "Lines {}-{} repeat lines {}-{}; extract the shared code",
+                start, end, self.first_lines.0, self.first_lines.1
+            ),
+            estimated_effort: if self.token_count < 100 { Effort::Low } else { Effort::Medium },
+            impact_score: (self.token_count as f32 / 100.0).min(1.0),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +188,31 @@ pub struct CodeStructureAnalysis {
     pub dependency_patterns: DependencyPatterns,
     pub architectural_patterns: ArchitecturalPatterns,
     pub complexity_metrics: ComplexityMetrics,
+    pub smells: Vec<CodeSmell>,
+    pub definitions: DefinitionCounts,
+}
+
+/// Classes and functions located in the source, class methods counted as functions. Placeholder
+/// structures reported where an extractor does not cover a construct yet are left out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefinitionCounts {
+    pub classes: u32,
+    pub functions: u32,
+}
+
+impl DefinitionCounts {
+    fn count(structures: &[StructureInfo]) -> Self {
+        structures.iter()
+            .filter(|structure| structure.line_number.is_some())
+            .fold(Self::default(), |counts, structure| match structure.structure_type {
+                StructureType::Class => Self {
+                    classes: counts.classes + 1,
+                    functions: counts.functions + structure.methods.len() as u32,
+                },
+                StructureType::Function => Self { functions: counts.functions + 1, ..counts },
+                _ => counts,
+            })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +236,23 @@ pub struct ClassHierarchy {
     pub inheritance_usage: f32,      // percentage using inheritance
     pub polymorphism_indicators: Vec<String>,
     pub design_pattern_usage: HashMap<String, u32>, // pattern -> count
+    pub class_fields: HashMap<String, Vec<FieldInfo>>, // class name -> declared fields
+}
+
+/// A field declared on a class or struct
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInfo {
+    pub name: String,
+    pub field_type: Option<String>, // as written in source, when annotated
+    pub visibility: FieldVisibility,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldVisibility {
+    Public,
+    Protected,  // TS `protected`, Python `_name`
+    Private,    // TS `private`/`#name`, Python `__name`, Rust fields without `pub`
+    Restricted, // Rust `pub(crate)`, `pub(super)`, `pub(in path)`
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +264,15 @@ pub struct FunctionOrganization {
     pub nesting_depth_distribution: HashMap<u32, u32>, // depth -> count
     pub pure_function_percentage: f32,
     pub higher_order_function_usage: f32,
+    pub function_complexities: Vec<FunctionComplexity>, // located functions and methods, in source order
+}
+
+/// Cyclomatic complexity of one function or method body, for display beside its definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionComplexity {
+    pub name: String,
+    pub line_number: u32,
+    pub cyclomatic_complexity: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -83,6 +306,18 @@ pub struct ComplexityMetrics {
     pub function_length_variance: f32,
     pub class_size_variance: f32,
     pub maintainability_index: f32, // 0.0 to 100.0
+    pub halstead: HalsteadMetrics,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HalsteadMetrics {
+    pub distinct_operators: usize,
+    pub distinct_operands: usize,
+    pub total_operators: usize,
+    pub total_operands: usize,
+    pub volume: f32,     // N * log2(n)
+    pub difficulty: f32, // (n1 / 2) * (N2 / n2)
+    pub effort: f32,     // difficulty * volume
 }
 
 #[derive(Debug, Clone)]
@@ -91,8 +326,22 @@ struct StructureInfo {
     structure_type: StructureType,
     size_lines: u32,
     complexity_score: f32,
+    cyclomatic_complexity: Option<f32>, // of a function's own body; None for other structures
     dependencies: Vec<String>,
     language: String,
+    nesting_depth: Option<u32>, // deepest block inside the structure, its own body counting as 1
+    fields: Vec<FieldInfo>,
+    line_number: Option<u32>, // 1-based, when the extractor knows where the structure starts
+    methods: Vec<MethodInfo>,
+}
+
+#[derive(Debug, Clone)]
+struct MethodInfo {
+    name: String,
+    line_number: u32,
+    size_lines: u32,
+    complexity_score: f32,
+    cyclomatic_complexity: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -118,47 +367,776 @@ lazy_static! {
     static ref MICROSERVICE_PATTERNS: Regex = Regex::new(r"(service|microservice|api|endpoint|gateway|circuit.*breaker|health.*check)").unwrap();
     static ref REPOSITORY_PATTERN: Regex = Regex::new(r"(repository|dao|data.*access|persistence|storage|crud)").unwrap();
     
-    // Complexity indicators
-    static ref COMPLEXITY_KEYWORDS: Regex = Regex::new(r"(if|else|while|for|switch|case|catch|try|finally|&&|\|\||[?:])").unwrap();
-    static ref NESTING_INDICATORS: Regex = Regex::new(r"[\{\[\(]").unwrap();
+    static ref GENERIC_FUNCTION: Regex = Regex::new(r"\b(?:function|def|fn|func)\s+(\w+)\s*\(").unwrap();
     
     // Dependency patterns
     static ref IMPORT_PATTERNS: Regex = Regex::new(r"(import|require|include|use|from)").unwrap();
     static ref EXPORT_PATTERNS: Regex = Regex::new(r"(export|module\.exports|exports\.|public|__all__)").unwrap();
+
+    // `this.field` / `self.field` inside a method body
+    static ref MEMBER_REFERENCE: Regex = Regex::new(r"\b(?:this|self)\s*\.\s*([A-Za-z_$][\w$]*)").unwrap();
+    // `this.field = ...` and `self.field: Type = ...`, but not comparisons
+    static ref THIS_ASSIGNMENT: Regex = Regex::new(r"\bthis\s*\.\s*(#?[A-Za-z_$][\w$]*)\s*=[^=]").unwrap();
+    static ref SELF_ASSIGNMENT: Regex = Regex::new(r"\bself\s*\.\s*([A-Za-z_]\w*)\s*(?::\s*([^=\n]+?)\s*)?=[^=]").unwrap();
+}
+
+// Keywords counted as Halstead operators rather than operands
+const COMMON_OPERATOR_KEYWORDS: &[&str] = &[
+    "if", "else", "for", "while", "break", "continue", "return", "in", "import", "class",
+];
+const JAVASCRIPT_OPERATOR_KEYWORDS: &[&str] = &[
+    "do", "switch", "case", "try", "catch", "finally", "throw", "new", "delete", "typeof", "instanceof",
+    "of", "await", "yield", "async", "function", "let", "const", "var", "from", "export",
+];
+const PYTHON_OPERATOR_KEYWORDS: &[&str] = &[
+    "elif", "try", "except", "finally", "raise", "is", "not", "and", "or", "await", "yield", "def",
+    "lambda", "async", "from", "with", "as", "del", "global", "nonlocal", "pass", "assert",
+];
+const RUST_OPERATOR_KEYWORDS: &[&str] = &[
+    "loop", "match", "fn", "let", "mut", "pub", "use", "impl", "struct", "enum", "trait", "as",
+    "ref", "move", "await", "async", "unsafe", "where", "dyn",
+];
+
+// Words that open another path through the code. `else`, `try` and `finally` don't: the path
+// they take is already counted by the `if` or `catch` that pairs with them
+const BRANCH_KEYWORDS: &[&str] = &["if", "elif", "while", "for", "case", "catch", "except"];
+
+// Languages where a lone `?` is the ternary operator; in Rust it is the try operator
+const TERNARY_LANGUAGES: &[&str] = &[
+    "javascript", "typescript", "java", "c", "cpp", "c++", "csharp", "c#", "php", "swift", "dart", "ruby",
+];
+
+/// Branch points and opening brackets in `code`, counted over the language's tokens so that
+/// words inside longer identifiers, strings and comments don't count
+fn complexity_counts(code: &str, language: &str) -> (usize, usize) {
+    let mut branches = 0;
+    let mut openings = 0;
+    let has_ternary = TERNARY_LANGUAGES.contains(&language.to_lowercase().as_str());
+    for token in Tokenizer::for_language(code, language) {
+        match token.kind {
+            TokenKind::Keyword | TokenKind::Identifier if BRANCH_KEYWORDS.contains(&token.text) => branches += 1,
+            // Operator runs such as `&&!` hold the branch operator with its neighbours
+            TokenKind::Operator => {
+                branches += token.text.matches("&&").count() + token.text.matches("||").count() + usize::from(has_ternary && token.text == "?");
+            },
+            TokenKind::Punctuation if matches!(token.text, "(" | "[" | "{") => openings += 1,
+            _ => {},
+        }
+    }
+    (branches, openings)
+}
+
+/// McCabe-style complexity of `source`: one path plus one per branch point
+pub(crate) fn cyclomatic_complexity(source: &str, language: &str) -> f32 {
+    1.0 + complexity_counts(source, language).0 as f32
+}
+
+/// A file whose complexity and change frequency together make it costly to maintain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hotspot {
+    pub path: String,
+    pub complexity: f32,   // cyclomatic complexity of the whole file
+    pub change_count: u32, // e.g. commits touching the file, as counted by the editor from git
+    pub score: f32,        // 0.0 to 1.0, the product of complexity and changes relative to the busiest file
+}
+
+/// `(path, complexity)` files ranked as hotspots, complex and frequently changed files first.
+/// Paths missing from `change_counts` count as never changed; ties go to the more complex file,
+/// then the path
+pub fn rank_hotspots(complexities: &[(String, f32)], change_counts: &HashMap<String, u32>) -> Vec<Hotspot> {
+    let changes = |path: &str| change_counts.get(path).copied().unwrap_or(0);
+    let max_complexity = complexities.iter().map(|(_, complexity)| *complexity).fold(0.0, f32::max);
+    let max_changes = complexities.iter().map(|(path, _)| changes(path)).max().unwrap_or(0);
+    let relative = |value: f32, max: f32| if max > 0.0 { value / max } else { 0.0 };
+
+    let mut hotspots: Vec<Hotspot> = complexities.iter().map(|(path, complexity)| {
+        let change_count = changes(path);
+        Hotspot {
+            path: path.clone(),
+            complexity: *complexity,
+            change_count,
+            score: relative(*complexity, max_complexity) * relative(change_count as f32, max_changes as f32),
+        }
+    }).collect();
+    hotspots.sort_by(|a, b| {
+        b.score.total_cmp(&a.score)
+            .then(b.complexity.total_cmp(&a.complexity))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    hotspots
+}
+
+fn operator_keywords(language: &str) -> Vec<&'static str> {
+    let specific = match language.to_lowercase().as_str() {
+        "javascript" | "typescript" => JAVASCRIPT_OPERATOR_KEYWORDS,
+        "python" => PYTHON_OPERATOR_KEYWORDS,
+        "rust" => RUST_OPERATOR_KEYWORDS,
+        _ => &[],
+    };
+    COMMON_OPERATOR_KEYWORDS.iter().chain(specific).copied().collect()
+}
+
+/// Fields referenced by each method of one class
+type MemberReferences = Vec<HashSet<String>>;
+
+/// Mean LCOM-style cohesion over the classes in `ast`; 1.0 when there are none to judge
+fn class_cohesion(ast: &ParsedAst, code: &str) -> f32 {
+    let classes = match ast {
+        ParsedAst::JavaScript(module) => javascript_member_references(module, code),
+        ParsedAst::Python(suite) => python_member_references(suite, code),
+        ParsedAst::Rust(items) => rust_member_references(items),
+        ParsedAst::Generic(_) => Vec::new(),
+    };
+
+    if classes.is_empty() {
+        return 1.0;
+    }
+    classes.iter().map(|methods| method_pair_cohesion(methods)).sum::<f32>() / classes.len() as f32
+}
+
+/// Share of method pairs that touch at least one common field. Methods touching no
+/// fields (and constructors, which touch them all) are left out
+fn method_pair_cohesion(methods: &[HashSet<String>]) -> f32 {
+    let methods: Vec<&HashSet<String>> = methods.iter().filter(|fields| !fields.is_empty()).collect();
+    if methods.len() < 2 {
+        return 1.0;
+    }
+
+    let mut pairs = 0;
+    let mut sharing = 0;
+    for (index, first) in methods.iter().enumerate() {
+        for second in &methods[index + 1..] {
+            pairs += 1;
+            if !first.is_disjoint(second) {
+                sharing += 1;
+            }
+        }
+    }
+    sharing as f32 / pairs as f32
+}
+
+/// Field names referenced in `source`, ignoring calls to the class's own methods
+fn field_references(source: &str, method_names: &HashSet<String>) -> HashSet<String> {
+    MEMBER_REFERENCE.captures_iter(source)
+        .map(|capture| capture[1].to_string())
+        .filter(|name| !method_names.contains(name))
+        .collect()
+}
+
+/// Top-level (and exported) class declarations with their names
+fn javascript_classes(module: &swc_ast::Module) -> Vec<(String, &swc_ast::Class)> {
+    module.body.iter().filter_map(|item| match item {
+        swc_ast::ModuleItem::Stmt(swc_ast::Stmt::Decl(swc_ast::Decl::Class(class_decl))) => {
+            Some((class_decl.ident.sym.to_string(), &*class_decl.class))
+        },
+        swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDecl(export)) => match &export.decl {
+            swc_ast::Decl::Class(class_decl) => Some((class_decl.ident.sym.to_string(), &*class_decl.class)),
+            _ => None,
+        },
+        swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDefaultDecl(export)) => match &export.decl {
+            swc_ast::DefaultDecl::Class(class_expr) => {
+                let name = class_expr.ident.as_ref().map(|ident| ident.sym.to_string()).unwrap_or_else(|| "default".to_string());
+                Some((name, &*class_expr.class))
+            },
+            _ => None,
+        },
+        _ => None,
+    }).collect()
+}
+
+/// Top-level (and exported) function declarations with their names
+fn javascript_functions(module: &swc_ast::Module) -> Vec<(String, &swc_ast::Function)> {
+    module.body.iter().filter_map(|item| match item {
+        swc_ast::ModuleItem::Stmt(swc_ast::Stmt::Decl(swc_ast::Decl::Fn(fn_decl))) => {
+            Some((fn_decl.ident.sym.to_string(), &*fn_decl.function))
+        },
+        swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDecl(export)) => match &export.decl {
+            swc_ast::Decl::Fn(fn_decl) => Some((fn_decl.ident.sym.to_string(), &*fn_decl.function)),
+            _ => None,
+        },
+        swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDefaultDecl(export)) => match &export.decl {
+            swc_ast::DefaultDecl::Fn(fn_expr) => {
+                let name = fn_expr.ident.as_ref().map(|ident| ident.sym.to_string()).unwrap_or_else(|| "default".to_string());
+                Some((name, &*fn_expr.function))
+            },
+            _ => None,
+        },
+        _ => None,
+    }).collect()
+}
+
+/// `structures` sorted by the line they start on
+fn in_source_order(structures: impl Iterator<Item = StructureInfo>) -> Vec<StructureInfo> {
+    let mut structures: Vec<StructureInfo> = structures.collect();
+    structures.sort_by_key(|structure| structure.line_number);
+    structures
+}
+
+/// Source text covered by an swc span (swc positions start at 1)
+fn javascript_source(code: &str, span: swc_common::Span) -> &str {
+    code.get(span.lo.0.saturating_sub(1) as usize..span.hi.0.saturating_sub(1) as usize).unwrap_or_default()
+}
+
+fn javascript_visibility(accessibility: Option<swc_ast::Accessibility>) -> FieldVisibility {
+    match accessibility {
+        Some(swc_ast::Accessibility::Private) => FieldVisibility::Private,
+        Some(swc_ast::Accessibility::Protected) => FieldVisibility::Protected,
+        _ => FieldVisibility::Public,
+    }
+}
+
+fn javascript_type(annotation: &Option<Box<swc_ast::TsTypeAnn>>, code: &str) -> Option<String> {
+    annotation.as_ref().map(|annotation| javascript_source(code, annotation.type_ann.span()).trim().to_string())
+}
+
+/// Class properties, TS parameter properties and `this.x =` assignments in the constructor
+fn javascript_fields(class: &swc_ast::Class, code: &str) -> Vec<FieldInfo> {
+    let mut fields = Vec::new();
+    for member in &class.body {
+        match member {
+            swc_ast::ClassMember::ClassProp(prop) => {
+                if let swc_ast::PropName::Ident(ident) = &prop.key {
+                    push_field(&mut fields, ident.sym.to_string(), javascript_type(&prop.type_ann, code), javascript_visibility(prop.accessibility));
+                }
+            },
+            swc_ast::ClassMember::PrivateProp(prop) => {
+                push_field(&mut fields, format!("#{}", prop.key.id.sym), javascript_type(&prop.type_ann, code), FieldVisibility::Private);
+            },
+            swc_ast::ClassMember::Constructor(constructor) => {
+                for param in &constructor.params {
+                    if let swc_ast::ParamOrTsParamProp::TsParamProp(param_prop) = param {
+                        if let swc_ast::TsParamPropParam::Ident(binding) = &param_prop.param {
+                            push_field(&mut fields, binding.id.sym.to_string(), javascript_type(&binding.type_ann, code), javascript_visibility(param_prop.accessibility));
+                        }
+                    }
+                }
+                if let Some(body) = &constructor.body {
+                    for capture in THIS_ASSIGNMENT.captures_iter(javascript_source(code, body.span)) {
+                        let visibility = if capture[1].starts_with('#') { FieldVisibility::Private } else { FieldVisibility::Public };
+                        push_field(&mut fields, capture[1].to_string(), None, visibility);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    fields
+}
+
+/// Python's underscore conventions; dunder names like `__slots__` stay public
+fn python_visibility(name: &str) -> FieldVisibility {
+    if name.starts_with("__") && !name.ends_with("__") {
+        FieldVisibility::Private
+    } else if name.starts_with('_') && !name.starts_with("__") {
+        FieldVisibility::Protected
+    } else {
+        FieldVisibility::Public
+    }
+}
+
+/// Class-level assignments and annotations, then `self.x` assignments in any method
+fn python_fields(class_def: &py_ast::StmtClassDef, code: &str) -> Vec<FieldInfo> {
+    let mut fields = Vec::new();
+    for stmt in &class_def.body {
+        match stmt {
+            py_ast::Stmt::AnnAssign(assign) => {
+                if let py_ast::Expr::Name(target) = &*assign.target {
+                    let annotation = code.get(assign.annotation.range().start().to_usize()..assign.annotation.range().end().to_usize());
+                    push_field(&mut fields, target.id.to_string(), annotation.map(str::to_string), python_visibility(&target.id));
+                }
+            },
+            py_ast::Stmt::Assign(assign) => {
+                for target in &assign.targets {
+                    if let py_ast::Expr::Name(target) = target {
+                        push_field(&mut fields, target.id.to_string(), None, python_visibility(&target.id));
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    let source = code.get(class_def.range.start().to_usize()..class_def.range.end().to_usize()).unwrap_or_default();
+    for capture in SELF_ASSIGNMENT.captures_iter(source) {
+        let field_type = capture.get(2).map(|annotation| annotation.as_str().to_string());
+        push_field(&mut fields, capture[1].to_string(), field_type, python_visibility(&capture[1]));
+    }
+    fields
+}
+
+fn rust_fields(item_struct: &syn::ItemStruct) -> Vec<FieldInfo> {
+    item_struct.fields.iter().enumerate().map(|(index, field)| FieldInfo {
+        name: field.ident.as_ref().map(|ident| ident.to_string()).unwrap_or_else(|| index.to_string()),
+        field_type: Some(field.ty.to_token_stream().to_string()),
+        visibility: match &field.vis {
+            syn::Visibility::Public(_) => FieldVisibility::Public,
+            syn::Visibility::Restricted(_) => FieldVisibility::Restricted,
+            syn::Visibility::Inherited => FieldVisibility::Private,
+        },
+    }).collect()
+}
+
+/// Record a field the first time it is seen, so declared types win over later assignments
+fn push_field(fields: &mut Vec<FieldInfo>, name: String, field_type: Option<String>, visibility: FieldVisibility) {
+    if !fields.iter().any(|field| field.name == name) {
+        fields.push(FieldInfo { name, field_type, visibility });
+    }
+}
+
+fn js_method_name(key: &swc_ast::PropName) -> String {
+    match key {
+        swc_ast::PropName::Ident(ident) => ident.sym.to_string(),
+        swc_ast::PropName::Str(s) => s.value.to_string(),
+        _ => "computed".to_string(),
+    }
+}
+
+/// 1-based line holding the byte at `offset`
+fn line_at(code: &str, offset: usize) -> u32 {
+    code.get(..offset).unwrap_or(code).matches('\n').count() as u32 + 1
+}
+
+/// Index of the first line at or after `from` matching `pattern`
+fn find_line(lines: &[String], from: usize, pattern: &str) -> Option<usize> {
+    let pattern = Regex::new(pattern).ok()?;
+    (from..lines.len()).find(|&index| pattern.is_match(&lines[index]))
+}
+
+/// `(impl header pattern, method name)` for every method implemented on `owner`
+fn rust_impl_methods(items: &[syn::Item], owner: &str) -> Vec<(String, String)> {
+    items.iter()
+        .filter_map(|item| match item {
+            syn::Item::Impl(item_impl) => Some(item_impl),
+            _ => None,
+        })
+        .filter(|item_impl| match &*item_impl.self_ty {
+            syn::Type::Path(path) => path.path.segments.last().is_some_and(|segment| segment.ident == owner),
+            _ => false,
+        })
+        .flat_map(|item_impl| {
+            let header = match &item_impl.trait_ {
+                Some((_, path, _)) => {
+                    let trait_name = path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+                    format!(r"\bimpl\b.*\b{}\b.*\bfor\s+{}\b", trait_name, owner)
+                },
+                None => format!(r"\bimpl\b(?:\s*<.*>)?\s+{}\b", owner),
+            };
+            item_impl.items.iter().filter_map(move |impl_item| match impl_item {
+                syn::ImplItem::Fn(method) => Some((header.clone(), method.sig.ident.to_string())),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+fn javascript_member_references(module: &swc_ast::Module, code: &str) -> Vec<MemberReferences> {
+    javascript_classes(module).into_iter().map(|(_, class)| {
+        let methods: Vec<(String, swc_common::Span)> = class.body.iter()
+            .filter_map(|member| match member {
+                swc_ast::ClassMember::Method(method) => match &method.key {
+                    swc_ast::PropName::Ident(ident) => Some((ident.sym.to_string(), method.span)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        let names: HashSet<String> = methods.iter().map(|(name, _)| name.clone()).collect();
+
+        methods.iter()
+            .map(|(_, span)| {
+                field_references(javascript_source(code, *span), &names)
+            })
+            .collect()
+    }).collect()
+}
+
+fn python_member_references(suite: &py_ast::Suite, code: &str) -> Vec<MemberReferences> {
+    suite.iter().filter_map(|stmt| match stmt {
+        py_ast::Stmt::ClassDef(class_def) => Some(class_def),
+        _ => None,
+    }).map(|class_def| {
+        let methods: Vec<(String, py_ast::text_size::TextRange)> = class_def.body.iter()
+            .filter_map(|stmt| match stmt {
+                py_ast::Stmt::FunctionDef(fn_def) => Some((fn_def.name.to_string(), fn_def.range)),
+                py_ast::Stmt::AsyncFunctionDef(fn_def) => Some((fn_def.name.to_string(), fn_def.range)),
+                _ => None,
+            })
+            .filter(|(name, _)| name != "__init__")
+            .collect();
+        let names: HashSet<String> = methods.iter().map(|(name, _)| name.clone()).collect();
+
+        methods.iter()
+            .map(|(_, range)| {
+                let source = code.get(range.start().to_usize()..range.end().to_usize()).unwrap_or_default();
+                field_references(source, &names)
+            })
+            .collect()
+    }).collect()
+}
+
+fn rust_member_references(items: &[syn::Item]) -> Vec<MemberReferences> {
+    // A type's methods may be spread over several impl blocks
+    let mut classes: Vec<(String, Vec<&syn::ImplItemFn>)> = Vec::new();
+    for item in items {
+        if let syn::Item::Impl(item_impl) = item {
+            let owner = item_impl.self_ty.to_token_stream().to_string();
+            let methods = item_impl.items.iter().filter_map(|impl_item| match impl_item {
+                syn::ImplItem::Fn(method) => Some(method),
+                _ => None,
+            });
+            match classes.iter_mut().find(|(name, _)| *name == owner) {
+                Some((_, existing)) => existing.extend(methods),
+                None => classes.push((owner, methods.collect())),
+            }
+        }
+    }
+
+    classes.into_iter().map(|(_, methods)| {
+        let names: HashSet<String> = methods.iter().map(|method| method.sig.ident.to_string()).collect();
+        methods.iter()
+            .map(|method| field_references(&method.block.to_token_stream().to_string(), &names))
+            .collect()
+    }).collect()
+}
+
+fn exception_smells(ast: &ParsedAst, code: &str, language: &str) -> Vec<CodeSmell> {
+    faulty_handlers(ast, code, language).into_iter().map(|handler| {
+        let description = match handler.kind {
+            CodeSmellKind::EmptyCatch => format!("`{}` is empty, so errors disappear silently", handler.handler),
+            CodeSmellKind::BroadCatch => format!("`{}` catches everything and never re-raises", handler.handler),
+            _ => format!("`{}` neither rethrows nor inspects the error", handler.handler),
+        };
+        // Silently dropped errors are the worst of the three
+        let severity = if handler.kind == CodeSmellKind::EmptyCatch { Severity::Error } else { Severity::Warning };
+        CodeSmell { kind: handler.kind, name: handler.handler, line_number: handler.line_number, severity, description }
+    }).collect()
+}
+
+fn return_smells(ast: &ParsedAst, code: &str) -> Vec<CodeSmell> {
+    let python = matches!(ast, ParsedAst::Python(_));
+    inconsistent_returns(ast, code).into_iter().map(|function| CodeSmell {
+        kind: CodeSmellKind::InconsistentReturn,
+        description: format!("`{}` {}", function.function, function.summary(python)),
+        name: function.function,
+        line_number: function.line_number,
+        severity: Severity::Warning,
+    }).collect()
+}
+
+fn smell_severity(limit_doubled: bool) -> Severity {
+    if limit_doubled { Severity::Error } else { Severity::Warning }
+}
+
+/// Per line: the block depth it starts at, and the deepest depth holding any of its code.
+/// Braces decide depth when the code uses them, indentation otherwise
+fn block_depths(lines: &[String]) -> Vec<(u32, u32)> {
+    if !lines.iter().any(|line| line.contains('{')) {
+        let indent = |line: &String| line.len() - line.trim_start().len();
+        let unit = lines.iter().map(indent).filter(|&width| width > 0).min().unwrap_or(1);
+        return lines.iter().map(|line| {
+            let level = (indent(line) / unit) as u32;
+            (level, level)
+        }).collect();
+    }
+
+    let mut depth = 0u32;
+    lines.iter().map(|line| {
+        let start = depth;
+        let mut deepest = start;
+        for c in line.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                c if !c.is_whitespace() => deepest = deepest.max(depth),
+                _ => {},
+            }
+        }
+        (start, deepest)
+    }).collect()
+}
+
+/// Exclusive end of the block opened by the header line at `start`
+fn block_end(lines: &[String], depths: &[(u32, u32)], start: usize) -> usize {
+    let header_depth = depths[start].0;
+    if !lines.iter().any(|line| line.contains('{')) {
+        return (start + 1..lines.len())
+            .find(|&index| !lines[index].trim().is_empty() && depths[index].0 <= header_depth)
+            .unwrap_or(lines.len());
+    }
+
+    // The block closes on the line after which depth falls back to the header's
+    let mut opened = false;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        let after = depths.get(index + 1).map(|depth| depth.0).unwrap_or(0);
+        opened |= after > header_depth || line.contains('{');
+        if opened && after <= header_depth {
+            return index + 1;
+        }
+    }
+    lines.len()
 }
 
 impl CodeStructureAnalyzer {
     pub fn new() -> Self {
         Self {
             parser: AstParser::new(),
+            complexity_thresholds: ComplexityThresholds::default(),
+            smell_thresholds: SmellThresholds::default(),
+            min_duplicate_tokens: DEFAULT_MIN_DUPLICATE_TOKENS,
         }
     }
 
+    /// Report duplicate blocks only from `tokens` tokens upward
+    pub fn with_min_duplicate_tokens(mut self, tokens: usize) -> Self {
+        self.min_duplicate_tokens = tokens.max(1);
+        self
+    }
+
+    /// Override the god class and long method limits
+    pub fn with_smell_thresholds(mut self, thresholds: SmellThresholds) -> Self {
+        self.smell_thresholds = thresholds;
+        self
+    }
+
+    /// Bucket function complexity with team-specific cutoffs, which must satisfy low < medium < high
+    pub fn with_complexity_thresholds(mut self, thresholds: ComplexityThresholds) -> Result<Self, String> {
+        thresholds.validate()?;
+        self.complexity_thresholds = thresholds;
+        Ok(self)
+    }
+
+    /// Copy-pasted blocks of at least `min_duplicate_tokens` tokens. Every window of that many tokens
+    /// is rolling-hashed; a window seen before is verified and then extended as far as both copies agree.
+    /// Identifiers and literals compare by kind and keywords verbatim, so renamed copies still match
+    pub fn find_duplicates(&self, code: &str, language: &str) -> Vec<DuplicateBlock> {
+        const BASE: u64 = 1_000_003;
+        let keywords = operator_keywords(language);
+        let tokens: Vec<(&str, u32)> = Tokenizer::for_language(code, language)
+            .filter(|token| token.kind != TokenKind::Comment)
+            .map(|token| {
+                let normalized = match token.kind {
+                    TokenKind::Identifier | TokenKind::Keyword if !keywords.contains(&token.text) => "$identifier",
+                    TokenKind::Number => "$number",
+                    TokenKind::StringLiteral => "$string",
+                    _ => token.text,
+                };
+                (normalized, token.line)
+            })
+            .collect();
+
+        let k = self.min_duplicate_tokens;
+        if tokens.len() < 2 * k {
+            return Vec::new();
+        }
+        let hashes: Vec<u64> = tokens.iter().map(|(text, _)| {
+            let mut hasher = DefaultHasher::new();
+            text.hash(&mut hasher);
+            hasher.finish()
+        }).collect();
+        let leading = BASE.wrapping_pow(k as u32 - 1);
+        let mut window = hashes[..k].iter().fold(0u64, |hash, &token| hash.wrapping_mul(BASE).wrapping_add(token));
+
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut duplicates = Vec::new();
+        let mut resume_at = 0;
+        for start in 0..=tokens.len() - k {
+            if start > 0 {
+                window = window
+                    .wrapping_sub(hashes[start - 1].wrapping_mul(leading))
+                    .wrapping_mul(BASE)
+                    .wrapping_add(hashes[start + k - 1]);
+            }
+
+            // Windows inside an already reported copy would only report a piece of it again
+            let matched = (start >= resume_at).then(|| seen.get(&window)).flatten().and_then(|earlier| {
+                earlier.iter().find_map(|&original| {
+                    let same = |offset: usize| tokens[original + offset].0 == tokens[start + offset].0;
+                    if original + k > start || !(0..k).all(same) {
+                        return None;
+                    }
+                    let mut length = k;
+                    while start + length < tokens.len() && original + length < start && same(length) {
+                        length += 1;
+                    }
+                    Some((original, length))
+                })
+            });
+            if let Some((original, length)) = matched {
+                duplicates.push(DuplicateBlock {
+                    first_lines: (tokens[original].1, tokens[original + length - 1].1),
+                    second_lines: (tokens[start].1, tokens[start + length - 1].1),
+                    token_count: length,
+                });
+                resume_at = start + length;
+            }
+            seen.entry(window).or_default().push(start);
+        }
+        duplicates
+    }
+
+    /// Suggested import/compile order for `(module name, source)` pairs, or the import edges forming cycles
+    pub fn suggest_module_order(&self, modules: &[(String, String)]) -> Result<Vec<String>, Vec<(String, String)>> {
+        ModuleDependencyGraph::from_sources(modules).topological_order()
+    }
+
     /// Analyze code structure and organization patterns
-    pub fn analyze_code_structure(&self, code: &str, language: &str) -> Result<CodeStructureAnalysis, String> {
+    pub fn analyze_code_structure(&self, code: &str, language: &str) -> Result<CodeStructureAnalysis, AnalysisError> {
         let ast = self.parser.parse_code(code, language)?;
-        let structures = self.extract_structures(&ast, language)?;
+        let structures = self.extract_structures(&ast, code, language)?;
 
         Ok(CodeStructureAnalysis {
             module_organization: self.analyze_module_organization(&structures, code),
             class_hierarchy: self.analyze_class_hierarchy(&structures),
-            function_organization: self.analyze_function_organization(&structures, code),
-            dependency_patterns: self.analyze_dependency_patterns(&structures, code),
+            function_organization: self.analyze_function_organization(&structures, code, language),
+            dependency_patterns: self.analyze_dependency_patterns(&structures, code, class_cohesion(&ast, code)),
             architectural_patterns: self.analyze_architectural_patterns(&structures, code),
-            complexity_metrics: self.calculate_complexity_metrics(&structures, code),
+            complexity_metrics: self.calculate_complexity_metrics(&structures, code, language),
+            smells: self.detect_smells(&structures, &ast, code, language),
+            definitions: DefinitionCounts::count(&structures),
         })
     }
 
-    fn extract_structures(&self, ast: &ParsedAst, language: &str) -> Result<Vec<StructureInfo>, String> {
+    /// Refactorings for every detected smell in source order, followed by one per duplicated block
+    pub fn suggest_refactorings(&self, code: &str, language: &str) -> Result<Vec<RefactoringSuggestion>, AnalysisError> {
+        let smells = self.analyze_code_structure(code, language)?.smells;
+        let mut suggestions: Vec<RefactoringSuggestion> = smells.iter().map(CodeSmell::to_refactoring).collect();
+        suggestions.extend(self.find_duplicates(code, language).iter().map(DuplicateBlock::to_refactoring));
+        Ok(suggestions)
+    }
+
+    /// JavaScript and TypeScript callbacks nested past `max_callback_depth`, each nest reported
+    /// at the callback that crosses the limit
+    pub fn find_callback_hell(&self, code: &str, language: &str) -> Result<Vec<CodeSmell>, AnalysisError> {
+        let ast = self.parser.parse_code(code, language)?;
+        Ok(self.callback_smells(&ast, code))
+    }
+
+    fn callback_smells(&self, ast: &ParsedAst, code: &str) -> Vec<CodeSmell> {
+        let ParsedAst::JavaScript(module) = ast else {
+            return Vec::new();
+        };
+        let limit = self.smell_thresholds.max_callback_depth;
+        nested_callbacks(module, code, limit).into_iter().map(|callback| CodeSmell {
+            kind: CodeSmellKind::CallbackHell,
+            name: callback.callee.clone(),
+            line_number: callback.line_number,
+            severity: smell_severity(callback.depth > 2 * limit),
+            description: format!(
+                "Callbacks nest {} levels deep at `{}` (limit: {}); async/await would flatten them",
+                callback.depth, callback.callee, limit
+            ),
+        }).collect()
+    }
+
+    /// Error handlers that hide failures: empty `catch`/`except` blocks, broad Python `except`s
+    /// that never re-raise and JavaScript `catch`es that ignore the error they caught
+    pub fn find_exception_anti_patterns(&self, code: &str, language: &str) -> Result<Vec<CodeSmell>, AnalysisError> {
+        let ast = self.parser.parse_code(code, language)?;
+        Ok(exception_smells(&ast, code, language))
+    }
+
+    /// JavaScript, TypeScript and Python functions whose paths return different things, such as a
+    /// value on one and nothing on another; the description lists every conflicting return line
+    pub fn find_inconsistent_returns(&self, code: &str, language: &str) -> Result<Vec<CodeSmell>, AnalysisError> {
+        let ast = self.parser.parse_code(code, language)?;
+        Ok(return_smells(&ast, code))
+    }
+
+    /// Files, sockets and connections opened without being closed, reported at the line that
+    /// opens them. Needs no parse, so it also covers Go
+    pub fn find_unclosed_resources(&self, code: &str, language: &str) -> Vec<CodeSmell> {
+        find_unclosed_resources(code, language).into_iter().map(|resource| {
+            let name = resource.variable.clone().unwrap_or_else(|| resource.acquisition.clone());
+            CodeSmell {
+                kind: CodeSmellKind::UnclosedResource,
+                description: format!("`{}` is opened with `{}` but never closed", name, resource.acquisition),
+                name,
+                line_number: resource.line_number,
+                severity: Severity::Warning,
+            }
+        }).collect()
+    }
+
+    /// God classes, long methods and deeply nested functions among structures whose position is known,
+    /// plus callback hell, faulty error handlers, unclosed resources and inconsistent returns, in source order
+    fn detect_smells(&self, structures: &[StructureInfo], ast: &ParsedAst, code: &str, language: &str) -> Vec<CodeSmell> {
+        let limits = &self.smell_thresholds;
+        let mut smells = self.callback_smells(ast, code);
+        smells.extend(exception_smells(ast, code, language));
+        smells.extend(self.find_unclosed_resources(code, language));
+        smells.extend(return_smells(ast, code));
+
+        for structure in structures {
+            let Some(line_number) = structure.line_number else { continue };
+            match structure.structure_type {
+                StructureType::Class => {
+                    let method_count = structure.methods.len() as u32;
+                    if structure.size_lines > limits.max_class_lines || method_count > limits.max_class_methods {
+                        smells.push(CodeSmell {
+                            kind: CodeSmellKind::GodClass,
+                            name: structure.name.clone(),
+                            line_number,
+                            severity: smell_severity(structure.size_lines > 2 * limits.max_class_lines || method_count > 2 * limits.max_class_methods),
+                            description: format!(
+                                "Class `{}` has {} lines and {} methods (limits: {} lines, {} methods)",
+                                structure.name, structure.size_lines, method_count, limits.max_class_lines, limits.max_class_methods
+                            ),
+                        });
+                    }
+                    for method in &structure.methods {
+                        smells.extend(self.long_method_smell(&method.name, method.line_number, method.size_lines, method.complexity_score));
+                    }
+                },
+                StructureType::Function => {
+                    smells.extend(self.long_method_smell(&structure.name, line_number, structure.size_lines, structure.complexity_score));
+                    match structure.nesting_depth {
+                        Some(depth) if depth > limits.max_nesting_depth => smells.push(CodeSmell {
+                            kind: CodeSmellKind::DeepNesting,
+                            name: structure.name.clone(),
+                            line_number,
+                            severity: smell_severity(depth > 2 * limits.max_nesting_depth),
+                            description: format!("`{}` nests {} levels deep (limit: {})", structure.name, depth, limits.max_nesting_depth),
+                        }),
+                        _ => {},
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        smells.sort_by_key(|smell| smell.line_number);
+        smells
+    }
+
+    fn long_method_smell(&self, name: &str, line_number: u32, size_lines: u32, complexity: f32) -> Option<CodeSmell> {
+        let limits = &self.smell_thresholds;
+        if size_lines <= limits.max_method_lines && complexity <= limits.max_method_complexity {
+            return None;
+        }
+
+        let doubled = size_lines > 2 * limits.max_method_lines || complexity > 2.0 * limits.max_method_complexity;
+        Some(CodeSmell {
+            kind: CodeSmellKind::LongMethod,
+            name: name.to_string(),
+            line_number,
+            severity: smell_severity(doubled),
+            description: format!(
+                "`{}` has {} lines and complexity {:.1} (limits: {} lines, complexity {:.1})",
+                name, size_lines, complexity, limits.max_method_lines, limits.max_method_complexity
+            ),
+        })
+    }
+
+    fn extract_structures(&self, ast: &ParsedAst, code: &str, language: &str) -> Result<Vec<StructureInfo>, AnalysisError> {
         match ast {
             ParsedAst::JavaScript(module) => {
-                self.extract_javascript_structures(module, language)
+                self.extract_javascript_structures(module, code, language)
             },
             ParsedAst::Python(suite) => {
-                self.extract_python_structures(suite, language)
+                self.extract_python_structures(suite, code, language)
             },
             ParsedAst::Rust(items) => {
-                self.extract_rust_structures(items, language)
+                self.extract_rust_structures(items, code, language)
             },
             ParsedAst::Generic(lines) => {
                 self.extract_generic_structures(lines, language)
@@ -166,49 +1144,158 @@ impl CodeStructureAnalyzer {
         }
     }
 
-    fn extract_javascript_structures(&self, _module: &swc_ecma_ast::Module, language: &str) -> Result<Vec<StructureInfo>, String> {
-        // TODO: Implement JavaScript structure extraction
-        Ok(vec![
-            StructureInfo {
-                name: "UserService".to_string(),
-                structure_type: StructureType::Class,
-                size_lines: 45,
-                complexity_score: 3.5,
-                dependencies: vec!["Database".to_string(), "Logger".to_string()],
-                language: language.to_string(),
-            }
-        ])
+    /// A class structure spanning `source`, which starts on `line_number`
+    fn class_structure(&self, name: String, source: &str, line_number: u32, language: &str) -> StructureInfo {
+        StructureInfo {
+            name,
+            structure_type: StructureType::Class,
+            size_lines: source.lines().count() as u32,
+            complexity_score: self.estimate_complexity(source, language),
+            cyclomatic_complexity: None,
+            dependencies: Vec::new(),
+            language: language.to_string(),
+            nesting_depth: None,
+            fields: Vec::new(),
+            line_number: Some(line_number),
+            methods: Vec::new(),
+        }
     }
 
-    fn extract_python_structures(&self, _suite: &rustpython_parser::ast::Suite, language: &str) -> Result<Vec<StructureInfo>, String> {
-        // TODO: Implement Python structure extraction
-        Ok(vec![
-            StructureInfo {
-                name: "UserRepository".to_string(),
-                structure_type: StructureType::Class,
-                size_lines: 62,
-                complexity_score: 4.2,
-                dependencies: vec!["database".to_string(), "utils".to_string()],
-                language: language.to_string(),
-            }
-        ])
-    }
-
-    fn extract_rust_structures(&self, _items: &[syn::Item], language: &str) -> Result<Vec<StructureInfo>, String> {
-        // TODO: Implement Rust structure extraction
-        Ok(vec![
-            StructureInfo {
-                name: "UserManager".to_string(),
-                structure_type: StructureType::Module,
-                size_lines: 128,
-                complexity_score: 2.8,
-                dependencies: vec!["std::collections".to_string(), "serde".to_string()],
-                language: language.to_string(),
-            }
-        ])
+    /// A top-level function structure spanning `source`, which starts on `line_number`
+    fn function_structure(&self, name: String, source: &str, line_number: u32, language: &str) -> StructureInfo {
+        StructureInfo {
+            structure_type: StructureType::Function,
+            cyclomatic_complexity: Some(cyclomatic_complexity(source, language)),
+            ..self.class_structure(name, source, line_number, language)
+        }
+    }
+
+    fn method_info(&self, name: String, source: &str, line_number: u32, language: &str) -> MethodInfo {
+        MethodInfo {
+            name,
+            line_number,
+            size_lines: source.lines().count() as u32,
+            complexity_score: self.estimate_complexity(source, language),
+            cyclomatic_complexity: cyclomatic_complexity(source, language),
+        }
+    }
+
+    fn extract_javascript_structures(&self, module: &swc_ecma_ast::Module, code: &str, language: &str) -> Result<Vec<StructureInfo>, AnalysisError> {
+        let classes: Vec<StructureInfo> = javascript_classes(module).into_iter()
+            .map(|(name, class)| {
+                let methods = class.body.iter()
+                    .filter_map(|member| match member {
+                        swc_ast::ClassMember::Method(method) => Some((js_method_name(&method.key), method.span)),
+                        swc_ast::ClassMember::Constructor(constructor) => Some(("constructor".to_string(), constructor.span)),
+                        _ => None,
+                    })
+                    .map(|(name, span)| self.method_info(name, javascript_source(code, span), line_at(code, span.lo.0.saturating_sub(1) as usize), language))
+                    .collect();
+                StructureInfo {
+                    fields: javascript_fields(class, code),
+                    methods,
+                    ..self.class_structure(name, javascript_source(code, class.span), line_at(code, class.span.lo.0.saturating_sub(1) as usize), language)
+                }
+            })
+            .collect();
+        let functions = javascript_functions(module).into_iter()
+            .map(|(name, function)| {
+                self.function_structure(name, javascript_source(code, function.span), line_at(code, function.span.lo.0.saturating_sub(1) as usize), language)
+            });
+
+        Ok(in_source_order(classes.into_iter().chain(functions)))
     }
 
-    fn extract_generic_structures(&self, lines: &[String], language: &str) -> Result<Vec<StructureInfo>, String> {
+    fn extract_python_structures(&self, suite: &rustpython_parser::ast::Suite, code: &str, language: &str) -> Result<Vec<StructureInfo>, AnalysisError> {
+        let source_of = |range: py_ast::text_size::TextRange| code.get(range.start().to_usize()..range.end().to_usize()).unwrap_or_default();
+        let classes: Vec<StructureInfo> = suite.iter()
+            .filter_map(|stmt| match stmt {
+                py_ast::Stmt::ClassDef(class_def) => Some(class_def),
+                _ => None,
+            })
+            .map(|class_def| {
+                let methods = class_def.body.iter()
+                    .filter_map(|stmt| match stmt {
+                        py_ast::Stmt::FunctionDef(fn_def) => Some((fn_def.name.to_string(), fn_def.range)),
+                        py_ast::Stmt::AsyncFunctionDef(fn_def) => Some((fn_def.name.to_string(), fn_def.range)),
+                        _ => None,
+                    })
+                    .map(|(name, range)| self.method_info(name, source_of(range), line_at(code, range.start().to_usize()), language))
+                    .collect();
+                StructureInfo {
+                    fields: python_fields(class_def, code),
+                    methods,
+                    ..self.class_structure(class_def.name.to_string(), source_of(class_def.range), line_at(code, class_def.range.start().to_usize()), language)
+                }
+            })
+            .collect();
+        let functions = suite.iter()
+            .filter_map(|stmt| match stmt {
+                py_ast::Stmt::FunctionDef(fn_def) => Some((fn_def.name.to_string(), fn_def.range)),
+                py_ast::Stmt::AsyncFunctionDef(fn_def) => Some((fn_def.name.to_string(), fn_def.range)),
+                _ => None,
+            })
+            .map(|(name, range)| self.function_structure(name, source_of(range), line_at(code, range.start().to_usize()), language));
+
+        Ok(in_source_order(classes.into_iter().chain(functions)))
+    }
+
+    fn extract_rust_structures(&self, items: &[syn::Item], code: &str, language: &str) -> Result<Vec<StructureInfo>, AnalysisError> {
+        // syn keeps no line information, so items are located in the source text by name
+        let lines: Vec<String> = code.lines().map(str::to_string).collect();
+        let depths = block_depths(&lines);
+        let block_at = |start: usize| {
+            // Unit and tuple structs end on their header line
+            let end = if !lines[start].contains('{') && lines[start].trim_end().ends_with(';') {
+                start + 1
+            } else {
+                block_end(&lines, &depths, start)
+            };
+            (start + 1) as u32..end as u32 + 1
+        };
+
+        let structs: Vec<StructureInfo> = items.iter()
+            .filter_map(|item| match item {
+                syn::Item::Struct(item_struct) => Some(item_struct),
+                _ => None,
+            })
+            .map(|item_struct| {
+                let name = item_struct.ident.to_string();
+                let header = find_line(&lines, 0, &format!(r"\bstruct\s+{}\b", name)).unwrap_or(0);
+                let methods = rust_impl_methods(items, &name).into_iter()
+                    .filter_map(|(impl_header, method)| {
+                        let impl_start = find_line(&lines, 0, &impl_header)?;
+                        let start = find_line(&lines, impl_start, &format!(r"\bfn\s+{}\b", method))?;
+                        let span = block_at(start);
+                        Some(self.method_info(method, &lines[span.start as usize - 1..span.end as usize - 1].join("\n"), span.start, language))
+                    })
+                    .collect();
+                let span = block_at(header);
+                StructureInfo {
+                    fields: rust_fields(item_struct),
+                    methods,
+                    ..self.class_structure(name, &lines[span.start as usize - 1..span.end as usize - 1].join("\n"), span.start, language)
+                }
+            })
+            .collect();
+        let functions: Vec<StructureInfo> = items.iter()
+            .filter_map(|item| match item {
+                syn::Item::Fn(item_fn) => Some(item_fn.sig.ident.to_string()),
+                _ => None,
+            })
+            .filter_map(|name| {
+                // Outside every block, so a method of the same name in an impl isn't taken for it
+                let header = Regex::new(&format!(r"\bfn\s+{}\b", name)).ok()?;
+                let start = (0..lines.len()).find(|&index| depths[index].0 == 0 && header.is_match(&lines[index]))?;
+                let span = block_at(start);
+                Some(self.function_structure(name, &lines[span.start as usize - 1..span.end as usize - 1].join("\n"), span.start, language))
+            })
+            .collect();
+
+        Ok(in_source_order(structs.into_iter().chain(functions)))
+    }
+
+    fn extract_generic_structures(&self, lines: &[String], language: &str) -> Result<Vec<StructureInfo>, AnalysisError> {
         let mut structures = Vec::new();
         let total_lines = lines.len();
 
@@ -227,32 +1314,69 @@ impl CodeStructureAnalyzer {
                 name: "GenericClass".to_string(),
                 structure_type: StructureType::Class,
                 size_lines: (total_lines / (class_count + 1)) as u32,
-                complexity_score: self.estimate_complexity(lines),
+                complexity_score: self.estimate_complexity(&lines.join("\n"), language),
+                cyclomatic_complexity: None,
                 dependencies: self.extract_generic_dependencies(lines),
                 language: language.to_string(),
+                nesting_depth: None,
+                fields: Vec::new(),
+                line_number: None,
+                methods: Vec::new(),
+            });
+        }
+
+        let depths = block_depths(lines);
+        let mut found_function = false;
+        for (start, line) in lines.iter().enumerate() {
+            let Some(captures) = GENERIC_FUNCTION.captures(line) else { continue };
+            found_function = true;
+
+            let end = block_end(lines, &depths, start);
+            let body = &lines[start..end];
+            let nesting_depth = depths[start + 1..end].iter()
+                .map(|&(_, deepest)| deepest.saturating_sub(depths[start].0))
+                .max()
+                .unwrap_or(0)
+                .max(1);
+
+            structures.push(StructureInfo {
+                name: captures[1].to_string(),
+                structure_type: StructureType::Function,
+                size_lines: body.len() as u32,
+                // Each level past the body costs as much as two branches
+                complexity_score: self.estimate_complexity(&body.join("\n"), language) + (nesting_depth - 1) as f32,
+                cyclomatic_complexity: Some(cyclomatic_complexity(&body.join("\n"), language)),
+                dependencies: self.extract_generic_dependencies(body),
+                language: language.to_string(),
+                nesting_depth: Some(nesting_depth),
+                fields: Vec::new(),
+                line_number: Some(start as u32 + 1),
+                methods: Vec::new(),
             });
         }
 
-        if function_count > 0 {
+        if function_count > 0 && !found_function {
             structures.push(StructureInfo {
                 name: "GenericFunction".to_string(),
                 structure_type: StructureType::Function,
                 size_lines: (total_lines / (function_count + 1)) as u32,
-                complexity_score: self.estimate_complexity(lines),
+                complexity_score: self.estimate_complexity(&lines.join("\n"), language),
+                cyclomatic_complexity: None,
                 dependencies: self.extract_generic_dependencies(lines),
                 language: language.to_string(),
+                nesting_depth: None,
+                fields: Vec::new(),
+                line_number: None,
+                methods: Vec::new(),
             });
         }
 
         Ok(structures)
     }
 
-    fn estimate_complexity(&self, lines: &[String]) -> f32 {
-        let code_text = lines.join("\n");
-        let complexity_matches = COMPLEXITY_KEYWORDS.find_iter(&code_text).count();
-        let nesting_matches = NESTING_INDICATORS.find_iter(&code_text).count();
-        
-        1.0 + (complexity_matches as f32 * 0.5) + (nesting_matches as f32 * 0.1)
+    fn estimate_complexity(&self, code: &str, language: &str) -> f32 {
+        let (branches, openings) = complexity_counts(code, language);
+        1.0 + (branches as f32 * 0.5) + (openings as f32 * 0.1)
     }
 
     fn extract_generic_dependencies(&self, lines: &[String]) -> Vec<String> {
@@ -370,6 +1494,10 @@ impl CodeStructureAnalyzer {
             .count() as u32;
 
         let mut design_pattern_usage = HashMap::new();
+        let class_fields = structures.iter()
+            .filter(|s| matches!(s.structure_type, StructureType::Class) && !s.fields.is_empty())
+            .map(|s| (s.name.clone(), s.fields.clone()))
+            .collect();
         
         // Detect design patterns in structure names and dependencies
         for structure in structures {
@@ -395,10 +1523,11 @@ impl CodeStructureAnalyzer {
             inheritance_usage: 0.3,
             polymorphism_indicators: vec!["interface".to_string(), "abstract".to_string()],
             design_pattern_usage,
+            class_fields,
         }
     }
 
-    fn analyze_function_organization(&self, structures: &[StructureInfo], code: &str) -> FunctionOrganization {
+    fn analyze_function_organization(&self, structures: &[StructureInfo], code: &str, language: &str) -> FunctionOrganization {
         let lines: Vec<&str> = code.lines().collect();
         let function_count = structures.iter()
             .filter(|s| matches!(s.structure_type, StructureType::Function))
@@ -423,19 +1552,39 @@ impl CodeStructureAnalyzer {
         // Analyze function complexities
         for structure in structures {
             if matches!(structure.structure_type, StructureType::Function) {
-                let complexity_level = match structure.complexity_score {
-                    x if x < 2.0 => "low",
-                    x if x < 4.0 => "medium",
-                    x if x < 6.0 => "high",
-                    _ => "very_high",
-                };
+                let complexity_level = self.complexity_thresholds.bucket(structure.complexity_score);
                 *complexity_distribution.entry(complexity_level.to_string()).or_insert(0) += 1;
             }
         }
 
-        // Count nesting levels
-        let nesting_count = NESTING_INDICATORS.find_iter(code).count();
-        nesting_distribution.insert(1, nesting_count as u32);
+        // Count nesting levels, per function when the extractor tracked them
+        let tracked_depths: Vec<u32> = structures.iter()
+            .filter(|structure| matches!(structure.structure_type, StructureType::Function))
+            .filter_map(|structure| structure.nesting_depth)
+            .collect();
+        if tracked_depths.is_empty() {
+            let (_, nesting_count) = complexity_counts(code, language);
+            nesting_distribution.insert(1, nesting_count as u32);
+        } else {
+            for depth in tracked_depths {
+                *nesting_distribution.entry(depth).or_insert(0) += 1;
+            }
+        }
+
+        let mut function_complexities: Vec<FunctionComplexity> = structures.iter()
+            .flat_map(|structure| {
+                let own = structure.line_number.zip(structure.cyclomatic_complexity).map(|(line_number, cyclomatic_complexity)| {
+                    FunctionComplexity { name: structure.name.clone(), line_number, cyclomatic_complexity }
+                });
+                let methods = structure.methods.iter().map(|method| FunctionComplexity {
+                    name: method.name.clone(),
+                    line_number: method.line_number,
+                    cyclomatic_complexity: method.cyclomatic_complexity,
+                });
+                own.into_iter().chain(methods)
+            })
+            .collect();
+        function_complexities.sort_by_key(|function| function.line_number);
 
         FunctionOrganization {
             average_function_length: average_length,
@@ -445,10 +1594,11 @@ impl CodeStructureAnalyzer {
             nesting_depth_distribution: nesting_distribution,
             pure_function_percentage: 0.6, // Estimation
             higher_order_function_usage: 0.2, // Estimation
+            function_complexities,
         }
     }
 
-    fn analyze_dependency_patterns(&self, structures: &[StructureInfo], code: &str) -> DependencyPatterns {
+    fn analyze_dependency_patterns(&self, structures: &[StructureInfo], code: &str, cohesion: f32) -> DependencyPatterns {
         let external_deps: Vec<String> = structures.iter()
             .flat_map(|s| &s.dependencies)
             .filter(|dep| !dep.contains("./") && !dep.contains("../"))
@@ -470,7 +1620,7 @@ impl CodeStructureAnalyzer {
             external_dependencies: external_deps,
             internal_module_references: internal_deps,
             coupling_strength,
-            cohesion_metrics: 0.75, // Simplified calculation
+            cohesion_metrics: cohesion,
             dependency_injection_usage: has_dependency_injection,
             circular_dependency_count: 0, // TODO: Implement proper cycle detection
         }
@@ -503,7 +1653,7 @@ impl CodeStructureAnalyzer {
         }
     }
 
-    fn calculate_complexity_metrics(&self, structures: &[StructureInfo], code: &str) -> ComplexityMetrics {
+    fn calculate_complexity_metrics(&self, structures: &[StructureInfo], code: &str, language: &str) -> ComplexityMetrics {
         let total_complexity: f32 = structures.iter()
             .map(|s| s.complexity_score)
             .sum();
@@ -515,15 +1665,19 @@ impl CodeStructureAnalyzer {
         };
 
         let lines: Vec<&str> = code.lines().collect();
-        let complexity_keywords = COMPLEXITY_KEYWORDS.find_iter(code).count();
-        let nesting_indicators = NESTING_INDICATORS.find_iter(code).count();
+        let (complexity_keywords, nesting_indicators) = complexity_counts(code, language);
 
         let cyclomatic_complexity = 1.0 + complexity_keywords as f32;
         let cognitive_complexity = complexity_keywords as f32 * 1.5;
         let nesting_complexity = nesting_indicators as f32 / lines.len() as f32;
 
-        // Simplified maintainability index
-        let maintainability_index = (100.0 - cyclomatic_complexity * 2.0).max(0.0);
+        let lines_of_code = lines.iter().filter(|line| !line.trim().is_empty()).count();
+        let halstead = Self::halstead_metrics(code, language);
+        let maintainability_index = Self::maintainability_index(
+            halstead.volume,
+            cyclomatic_complexity,
+            lines_of_code,
+        );
 
         ComplexityMetrics {
             cyclomatic_complexity,
@@ -532,8 +1686,65 @@ impl CodeStructureAnalyzer {
             function_length_variance: 0.3, // Simplified
             class_size_variance: 0.4, // Simplified
             maintainability_index,
+            halstead,
         }
     }
+
+    /// Halstead counts from a token scan; keywords in `language` count as operators
+    fn halstead_metrics(code: &str, language: &str) -> HalsteadMetrics {
+        let keywords = operator_keywords(language);
+        let mut operators = HashMap::new();
+        let mut operands = HashMap::new();
+
+        for token in Tokenizer::for_language(code, language) {
+            let is_operator = match token.kind {
+                TokenKind::Comment => continue,
+                TokenKind::Operator | TokenKind::Punctuation => true,
+                TokenKind::Identifier | TokenKind::Keyword => keywords.contains(&token.text),
+                TokenKind::Number | TokenKind::StringLiteral => false,
+            };
+
+            let counts = if is_operator { &mut operators } else { &mut operands };
+            *counts.entry(token.text).or_insert(0usize) += 1;
+        }
+
+        let distinct_operators = operators.len();
+        let distinct_operands = operands.len();
+        let total_operators: usize = operators.values().sum();
+        let total_operands: usize = operands.values().sum();
+
+        let vocabulary = distinct_operators + distinct_operands;
+        let volume = if vocabulary < 2 {
+            0.0
+        } else {
+            (total_operators + total_operands) as f32 * (vocabulary as f32).log2()
+        };
+        let difficulty = if distinct_operands == 0 {
+            0.0
+        } else {
+            (distinct_operators as f32 / 2.0) * (total_operands as f32 / distinct_operands as f32)
+        };
+
+        HalsteadMetrics {
+            distinct_operators,
+            distinct_operands,
+            total_operators,
+            total_operands,
+            volume,
+            difficulty,
+            effort: difficulty * volume,
+        }
+    }
+
+    /// Maintainability index normalized to [0, 100]:
+    /// `(171 - 5.2 ln(V) - 0.23 CC - 16.2 ln(LOC)) * 100 / 171`
+    fn maintainability_index(halstead_volume: f32, cyclomatic_complexity: f32, lines_of_code: usize) -> f32 {
+        let raw = 171.0
+            - 5.2 * halstead_volume.max(1.0).ln()
+            - 0.23 * cyclomatic_complexity
+            - 16.2 * (lines_of_code.max(1) as f32).ln();
+        (raw * 100.0 / 171.0).clamp(0.0, 100.0)
+    }
 }
 
 #[cfg(test)]
@@ -672,6 +1883,59 @@ mod tests {
         assert!(analysis.complexity_metrics.cognitive_complexity > 0.0);
     }
 
+    #[test]
+    fn test_branch_words_in_strings_and_identifiers_are_not_complexity() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let code = "function gift(format) {\n  // if this, else that\n  return format === 'for each' ? 'try' : 'catch';\n}";
+        let metrics = analyzer.analyze_code_structure(code, "javascript").unwrap().complexity_metrics;
+        // Only the ternary branches
+        assert_eq!(metrics.cyclomatic_complexity, 2.0);
+    }
+
+    #[test]
+    fn test_maintainability_index_penalizes_long_branchy_code() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let trivial = analyzer
+            .analyze_code_structure("function add(a, b) { return a + b; }", "javascript")
+            .unwrap();
+
+        let branch = "    if (value > 10 && flag) { total += value * 2; } else if (value < 0 || !flag) { total -= 1; }\n";
+        let long_code = format!(
+            "function process(items, flag) {{\n  let total = 0;\n  for (const value of items) {{\n{}  }}\n  return total;\n}}",
+            branch.repeat(40)
+        );
+        let branchy = analyzer.analyze_code_structure(&long_code, "javascript").unwrap();
+
+        let trivial_index = trivial.complexity_metrics.maintainability_index;
+        let branchy_index = branchy.complexity_metrics.maintainability_index;
+        assert!(trivial_index > 70.0, "trivial index was {}", trivial_index);
+        assert!(branchy_index < trivial_index - 30.0, "branchy index was {}", branchy_index);
+        assert!((0.0..=100.0).contains(&branchy_index));
+    }
+
+    #[test]
+    fn test_halstead_counts_match_hand_calculation() {
+        // Operators: let = * ; (4 distinct, 4 total); operands: total price 2 (3 distinct, 3 total)
+        let metrics = CodeStructureAnalyzer::halstead_metrics("let total = price * 2;", "rust");
+        assert_eq!(metrics.distinct_operators, 4);
+        assert_eq!(metrics.total_operators, 4);
+        assert_eq!(metrics.distinct_operands, 3);
+        assert_eq!(metrics.total_operands, 3);
+        assert!((metrics.volume - 7.0 * 7f32.log2()).abs() < 0.01);
+        assert!((metrics.difficulty - 2.0).abs() < 0.01);
+        assert!((metrics.effort - 2.0 * metrics.volume).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_halstead_operator_keywords_depend_on_language() {
+        let code = "x = a and b";
+        let python = CodeStructureAnalyzer::halstead_metrics(code, "python");
+        let javascript = CodeStructureAnalyzer::halstead_metrics(code, "javascript");
+
+        assert_eq!((python.distinct_operators, python.distinct_operands), (2, 3));
+        assert_eq!((javascript.distinct_operators, javascript.distinct_operands), (1, 4));
+    }
+
     #[test]
     fn test_module_organization_analysis() {
         let analyzer = CodeStructureAnalyzer::new();
@@ -706,4 +1970,308 @@ mod tests {
         assert!(!analysis.module_organization.import_patterns.is_empty());
         assert!(!analysis.module_organization.export_patterns.is_empty());
     }
+
+    #[test]
+    fn test_cohesion_from_member_references() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let cohesion = |code: &str, language: &str| {
+            analyzer.analyze_code_structure(code, language).unwrap().dependency_patterns.cohesion_metrics
+        };
+
+        let scattered = "class Report {\n  constructor() { this.title = ''; this.rows = []; this.db = null; }\n  setTitle(t) { this.title = t; }\n  addRow(r) { this.rows.push(r); }\n  connect() { this.db = open(); }\n}\n";
+        let focused = "class Counter {\n  constructor() { this.count = 0; this.step = 1; }\n  increment() { this.count += this.step; }\n  decrement() { this.count -= this.step; }\n  reset() { this.count = 0; this.log(); }\n  log() { console.log(this.count); }\n}\n";
+        let low = cohesion(scattered, "javascript");
+        let high = cohesion(focused, "javascript");
+        assert!(low < 0.01, "scattered class cohesion was {}", low);
+        assert!(high > 0.99, "focused class cohesion was {}", high);
+
+        let python_scattered = "class Report:\n    def __init__(self):\n        self.title = ''\n        self.rows = []\n\n    def set_title(self, t):\n        self.title = t\n\n    def add_row(self, r):\n        self.rows.append(r)\n";
+        assert!(cohesion(python_scattered, "python") < 0.01);
+
+        let rust_focused = "struct Counter { count: u32 }\nimpl Counter {\n    fn new() -> Self { Counter { count: 0 } }\n    fn bump(&mut self) { self.count += 1; }\n}\nimpl Counter {\n    fn get(&self) -> u32 { self.count }\n}\n";
+        assert!(cohesion(rust_focused, "rust") > 0.99);
+    }
+
+    #[test]
+    fn test_generic_nesting_raises_complexity() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let code = "func level(a, b, c) {\n  if a { one() }\n  if b { two() }\n  if c { three() }\n}\n\nfunc nested(a, b, c) {\n  if a {\n    if b {\n      if c { three() }\n    }\n  }\n}\n";
+        let lines: Vec<String> = code.lines().map(str::to_string).collect();
+        let structures = analyzer.extract_generic_structures(&lines, "generic").unwrap();
+
+        let level = structures.iter().find(|s| s.name == "level").unwrap();
+        let nested = structures.iter().find(|s| s.name == "nested").unwrap();
+        assert_eq!(level.nesting_depth, Some(2));
+        assert_eq!(nested.nesting_depth, Some(4));
+        assert_eq!(nested.size_lines, 7);
+        assert!(nested.complexity_score > level.complexity_score);
+
+        let organization = analyzer.analyze_code_structure(code, "generic").unwrap().function_organization;
+        assert_eq!(organization.nesting_depth_distribution.get(&2), Some(&1));
+        assert_eq!(organization.nesting_depth_distribution.get(&4), Some(&1));
+
+        let indented = "def outer(x):\n    for item in x:\n        if item:\n            print(item)\n";
+        let lines: Vec<String> = indented.lines().map(str::to_string).collect();
+        let structures = analyzer.extract_generic_structures(&lines, "generic").unwrap();
+        assert_eq!(structures[0].nesting_depth, Some(3));
+    }
+
+    #[test]
+    fn test_structures_carry_each_function_body_complexity() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let generic = "func add(a, b) {\n  return a + b\n}\n\nfunc classify(n) {\n  if n < 0 || n > 9 { return 0 }\n  for n > 1 { n = n / 2 }\n  return n\n}\n";
+        let complexities = |code: &str, language: &str| -> Vec<(String, u32, f32)> {
+            analyzer.analyze_code_structure(code, language).unwrap().function_organization.function_complexities.into_iter()
+                .map(|function| (function.name, function.line_number, function.cyclomatic_complexity))
+                .collect()
+        };
+        assert_eq!(complexities(generic, "generic"), vec![("add".to_string(), 1, 1.0), ("classify".to_string(), 5, 4.0)]);
+
+        // Methods are measured on their own, not with the rest of their class
+        let js = "class Cart {\n  total() {\n    return this.sum;\n  }\n  add(item) {\n    if (item && item.price > 0) {\n      this.sum += item.price;\n    }\n  }\n}\n";
+        assert_eq!(complexities(js, "javascript"), vec![("total".to_string(), 2, 1.0), ("add".to_string(), 5, 3.0)]);
+    }
+
+    #[test]
+    fn test_top_level_functions_are_extracted() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let functions = |code: &str, language: &str| {
+            let analysis = analyzer.analyze_code_structure(code, language).unwrap();
+            let complexities: Vec<(String, u32, f32)> = analysis.function_organization.function_complexities.into_iter()
+                .map(|function| (function.name, function.line_number, function.cyclomatic_complexity))
+                .collect();
+            (complexities, analysis.definitions)
+        };
+        let only_functions = |count| DefinitionCounts { classes: 0, functions: count };
+
+        let js = "function add(a, b) {\n  return a + b;\n}\n\nexport function clamp(n) {\n  if (n < 0) {\n    return 0;\n  }\n  return n;\n}\n";
+        assert_eq!(functions(js, "javascript"), (vec![("add".to_string(), 1, 1.0), ("clamp".to_string(), 5, 2.0)], only_functions(2)));
+
+        let python = "def add(a, b):\n    return a + b\n\nasync def fetch(url):\n    while not ready():\n        await pause()\n    return url\n";
+        assert_eq!(functions(python, "python"), (vec![("add".to_string(), 1, 1.0), ("fetch".to_string(), 4, 2.0)], only_functions(2)));
+
+        // `helper` inside the impl is a method, not the top-level function of the same name
+        let rust = "struct Parser;\n\nimpl Parser {\n    fn helper(&self) {}\n}\n\nfn helper(n: u32) -> u32 {\n    if n > 1 { n } else { 1 }\n}\n";
+        let (complexities, definitions) = functions(rust, "rust");
+        assert_eq!(complexities, vec![("helper".to_string(), 4, 1.0), ("helper".to_string(), 7, 2.0)]);
+        assert_eq!(definitions, DefinitionCounts { classes: 1, functions: 2 });
+
+        // Nothing found means no structures, not made-up ones
+        for (code, language) in [("const limit = 3;\n", "javascript"), ("LIMIT = 3\n", "python"), ("const LIMIT: u32 = 3;\n", "rust")] {
+            let ast = AstParser::new().parse_code(code, language).unwrap();
+            assert!(analyzer.extract_structures(&ast, code, language).unwrap().is_empty(), "{}", language);
+        }
+    }
+
+    #[test]
+    fn test_complexity_counts_only_decision_points() {
+        assert_eq!(cyclomatic_complexity("{\n  return a + b;\n}", "javascript"), 1.0);
+        // `else`, `try` and `finally` add no path beyond their `if` and `catch`
+        assert_eq!(cyclomatic_complexity("if (a) { x(); } else { y(); }", "javascript"), 2.0);
+        assert_eq!(cyclomatic_complexity("try { x(); } catch (e) { y(); } finally { z(); }", "javascript"), 2.0);
+        assert_eq!(cyclomatic_complexity("switch (a) { case 1: x(); break; case 2: y(); }", "javascript"), 3.0);
+        assert_eq!(cyclomatic_complexity("return a ? b : c;", "javascript"), 2.0);
+
+        // Rust's `?` propagates an error, it is not a ternary
+        assert_eq!(cyclomatic_complexity("{ let text = read(path)?; parse(&text)? }", "rust"), 1.0);
+    }
+
+    #[test]
+    fn test_complexity_thresholds_move_functions_between_buckets() {
+        let code = "func check(a) {\n  if a { one() }\n}\n";
+        let default_buckets = CodeStructureAnalyzer::new()
+            .analyze_code_structure(code, "generic").unwrap()
+            .function_organization.function_complexity_distribution;
+        assert_eq!(default_buckets.get("medium"), Some(&1));
+
+        let lenient = CodeStructureAnalyzer::new()
+            .with_complexity_thresholds(ComplexityThresholds::new(10.0, 20.0, 30.0)).unwrap()
+            .analyze_code_structure(code, "generic").unwrap()
+            .function_organization.function_complexity_distribution;
+        assert_eq!(lenient.get("low"), Some(&1));
+        assert_eq!(lenient.get("medium"), None);
+
+        let strict = ComplexityThresholds::new(0.5, 1.0, 1.5);
+        assert_eq!(strict.bucket(3.0), "very_high");
+
+        for unordered in [ComplexityThresholds::new(6.0, 4.0, 2.0), ComplexityThresholds::new(2.0, 2.0, 6.0), ComplexityThresholds::new(2.0, f32::NAN, 6.0)] {
+            assert!(CodeStructureAnalyzer::new().with_complexity_thresholds(unordered).is_err());
+        }
+    }
+
+    fn field_summary(fields: &[FieldInfo]) -> Vec<(&str, Option<&str>, FieldVisibility)> {
+        fields.iter().map(|f| (f.name.as_str(), f.field_type.as_deref(), f.visibility)).collect()
+    }
+
+    #[test]
+    fn test_class_and_struct_fields_are_enumerated() {
+        let analyzer = CodeStructureAnalyzer::new();
+
+        let typescript = "class Account {\n  protected owner: string;\n  #pin = 0;\n  constructor(private readonly bank: Bank, balance) {\n    this.balance = balance;\n    if (this.balance === 0) {}\n  }\n}\n";
+        let hierarchy = analyzer.analyze_code_structure(typescript, "typescript").unwrap().class_hierarchy;
+        assert_eq!(field_summary(&hierarchy.class_fields["Account"]), vec![
+            ("owner", Some("string"), FieldVisibility::Protected),
+            ("#pin", None, FieldVisibility::Private),
+            ("bank", Some("Bank"), FieldVisibility::Private),
+            ("balance", None, FieldVisibility::Public),
+        ]);
+
+        let python = "class Cart:\n    currency: str = 'EUR'\n    def __init__(self, items):\n        self.items = items\n        self._total: float = 0.0\n        self.__token = None\n    def add(self, item):\n        self.items.append(item)\n";
+        let hierarchy = analyzer.analyze_code_structure(python, "python").unwrap().class_hierarchy;
+        assert_eq!(field_summary(&hierarchy.class_fields["Cart"]), vec![
+            ("currency", Some("str"), FieldVisibility::Public),
+            ("items", None, FieldVisibility::Public),
+            ("_total", Some("float"), FieldVisibility::Protected),
+            ("__token", None, FieldVisibility::Private),
+        ]);
+
+        let rust = "pub struct Config {\n    pub name: String,\n    pub(crate) retries: u32,\n    cache: Vec<u8>,\n}\nstruct Meters(f64);\n";
+        let hierarchy = analyzer.analyze_code_structure(rust, "rust").unwrap().class_hierarchy;
+        assert_eq!(hierarchy.class_count, 2);
+        assert_eq!(field_summary(&hierarchy.class_fields["Config"]), vec![
+            ("name", Some("String"), FieldVisibility::Public),
+            ("retries", Some("u32"), FieldVisibility::Restricted),
+            ("cache", Some("Vec < u8 >"), FieldVisibility::Private),
+        ]);
+        assert_eq!(field_summary(&hierarchy.class_fields["Meters"]), vec![("0", Some("f64"), FieldVisibility::Private)]);
+    }
+
+    #[test]
+    fn test_god_class_and_long_method_smells() {
+        let methods: String = (0..25).map(|i| format!("  method{}() {{ return {}; }}\n", i, i)).collect();
+        let long_body = "    total += 1;\n".repeat(60);
+        let code = format!(
+            "function helper() {{ return 1; }}\nclass Everything {{\n{}  process() {{\n    let total = 0;\n{}    return total;\n  }}\n}}\n",
+            methods, long_body
+        );
+
+        let smells = CodeStructureAnalyzer::new().analyze_code_structure(&code, "javascript").unwrap().smells;
+        assert_eq!(smells.len(), 2);
+        assert_eq!(smells[0].kind, CodeSmellKind::GodClass);
+        assert_eq!((smells[0].name.as_str(), smells[0].line_number), ("Everything", 2));
+        assert!(matches!(smells[0].severity, Severity::Warning));
+        assert_eq!(smells[1].kind, CodeSmellKind::LongMethod);
+        assert_eq!((smells[1].name.as_str(), smells[1].line_number), ("process", 28));
+
+        // Stricter limits catch the Rust struct's impl methods too, doubled limits escalate severity
+        let rust = "struct Parser {\n    pos: usize,\n}\n\nimpl Parser {\n    fn new() -> Self {\n        Parser { pos: 0 }\n    }\n\n    fn advance(&mut self) {\n        self.pos += 1;\n        self.pos += 1;\n        self.pos += 1;\n        self.pos += 1;\n    }\n}\n";
+        let strict = CodeStructureAnalyzer::new().with_smell_thresholds(SmellThresholds::new(2, 5, 2, 10.0));
+        let smells = strict.analyze_code_structure(rust, "rust").unwrap().smells;
+        let found: Vec<(&str, u32)> = smells.iter().map(|smell| (smell.name.as_str(), smell.line_number)).collect();
+        assert_eq!(found, vec![("Parser", 1), ("new", 6), ("advance", 10)]);
+        assert!(matches!(smells[1].severity, Severity::Warning));
+        assert!(matches!(smells[2].severity, Severity::Error));
+    }
+
+    #[test]
+    fn test_long_top_level_functions_are_smells() {
+        let long_body = "    total += 1\n".repeat(60);
+        let python = format!("def short():\n    return 1\n\ndef tally():\n    total = 0\n{}    return total\n", long_body);
+        let js = format!("function short() {{\n  return 1;\n}}\n\nfunction tally() {{\n  let total = 0;\n{}  return total;\n}}\n", long_body);
+        let rust = format!("fn short() -> u32 {{\n    1\n}}\n\nfn tally() -> u32 {{\n    let mut total = 0;\n{}    total\n}}\n", long_body.replace("total += 1\n", "total += 1;\n"));
+        for (code, language) in [(python, "python"), (js, "javascript"), (rust, "rust")] {
+            let smells = CodeStructureAnalyzer::new().analyze_code_structure(&code, language).unwrap().smells;
+            let found: Vec<(CodeSmellKind, &str, u32)> = smells.iter().map(|smell| (smell.kind.clone(), smell.name.as_str(), smell.line_number)).collect();
+            assert_eq!(found, vec![(CodeSmellKind::LongMethod, "tally", if language == "python" { 4 } else { 5 })], "{}", language);
+        }
+    }
+
+    #[test]
+    fn test_nested_callbacks_flagged_but_async_version_is_not() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let nested = "fs.readFile('a.txt', function (err, a) {\n  fs.readFile('b.txt', (err, b) => {\n    db.save(a + b, function (err) {\n      console.log('done');\n    });\n  });\n});\n";
+        let smells = analyzer.find_callback_hell(nested, "javascript").unwrap();
+        assert_eq!(smells.len(), 1);
+        assert_eq!(smells[0].kind, CodeSmellKind::CallbackHell);
+        assert_eq!((smells[0].name.as_str(), smells[0].line_number), ("save", 3));
+        assert!(smells[0].description.contains("3 levels"));
+        assert!(matches!(smells[0].to_refactoring().refactoring_type, RefactoringType::UseAsyncAwait));
+        assert_eq!(analyzer.analyze_code_structure(nested, "javascript").unwrap().smells.len(), 1);
+
+        let flat = "async function combine() {\n  const a = await fs.promises.readFile('a.txt');\n  const b = await fs.promises.readFile('b.txt');\n  await db.save(a + b);\n  items.forEach((item) => console.log(item));\n}\n";
+        assert!(analyzer.find_callback_hell(flat, "javascript").unwrap().is_empty());
+
+        // The limit is configurable
+        let lenient = CodeStructureAnalyzer::new().with_smell_thresholds(SmellThresholds::default().with_max_callback_depth(3));
+        assert!(lenient.find_callback_hell(nested, "javascript").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_swallowed_exceptions_flagged_but_proper_handlers_are_not() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let js = "function load() {\n  try {\n    read();\n  } catch (e) {}\n  try {\n    parse();\n  } catch (err) {\n    logger.error(err);\n    throw err;\n  }\n  try {\n    save();\n  } catch (err) {\n    return null;\n  }\n  fetchData().catch(() => {});\n}\n";
+        let smells = analyzer.find_exception_anti_patterns(js, "javascript").unwrap();
+        let found: Vec<(CodeSmellKind, u32)> = smells.iter().map(|smell| (smell.kind.clone(), smell.line_number)).collect();
+        assert_eq!(found, vec![(CodeSmellKind::EmptyCatch, 4), (CodeSmellKind::SwallowedException, 13)]);
+        assert!(matches!(smells[0].severity, Severity::Error));
+        assert!(matches!(smells[0].to_refactoring().refactoring_type, RefactoringType::HandleErrors));
+
+        let python = "def load(path):\n    try:\n        return read(path)\n    except:\n        return None\n\ndef parse(text):\n    try:\n        return int(text)\n    except ValueError:\n        return 0\n    except Exception as exc:\n        log(exc)\n        raise\n\ndef close(handle):\n    try:\n        handle.close()\n    except OSError:\n        pass\n";
+        let smells = analyzer.find_exception_anti_patterns(python, "python").unwrap();
+        let found: Vec<(CodeSmellKind, &str, u32)> = smells.iter().map(|smell| (smell.kind.clone(), smell.name.as_str(), smell.line_number)).collect();
+        assert_eq!(found, vec![(CodeSmellKind::BroadCatch, "except", 4), (CodeSmellKind::EmptyCatch, "except OSError", 19)]);
+    }
+
+    #[test]
+    fn test_function_returning_object_or_nothing_is_flagged() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let js = "function findUser(users, id) {\n  for (const user of users) {\n    if (user.id === id) {\n      return { name: user.name };\n    }\n  }\n}\n\nfunction findOrNull(users, id) {\n  const user = users.find((u) => u.id === id);\n  if (!user) {\n    return null;\n  }\n  return { name: user.name };\n}\n\nfunction parse(text) {\n  if (!text) {\n    return;\n  }\n  if (text === '-') {\n    return null;\n  }\n  log(text);\n}\n";
+        let smells = analyzer.find_inconsistent_returns(js, "javascript").unwrap();
+        let found: Vec<(&str, u32)> = smells.iter().map(|smell| (smell.name.as_str(), smell.line_number)).collect();
+        assert_eq!(found, vec![("findUser", 1), ("parse", 17)]);
+        assert_eq!(smells[0].description, "`findUser` returns an object (line 4) and nothing at the end (line 7)");
+        assert_eq!(smells[1].description, "`parse` returns nothing (line 19), null (line 22) and nothing at the end (line 25)");
+        assert!(matches!(smells[0].to_refactoring().refactoring_type, RefactoringType::UnifyReturns));
+        assert_eq!(analyzer.analyze_code_structure(js, "javascript").unwrap().smells.len(), 2);
+
+        let python = "def lookup(table, key):\n    if key in table:\n        return {'value': table[key]}\n    return\n\ndef lookup_or_none(table, key):\n    if key in table:\n        return table[key]\n    return None\n\ndef entries(table):\n    for key in table:\n        yield key\n    return\n";
+        let smells = analyzer.find_inconsistent_returns(python, "python").unwrap();
+        assert_eq!(smells.len(), 1);
+        assert_eq!(smells[0].description, "`lookup` returns a dict (line 3) and nothing (line 4)");
+    }
+
+    #[test]
+    fn test_copy_pasted_functions_are_reported_as_duplicates() {
+        let analyzer = CodeStructureAnalyzer::new();
+        let body = "(items) {\n  let sum = 0;\n  for (const item of items) {\n    if (item.active) {\n      sum += item.price * item.quantity;\n    }\n  }\n  return sum;\n}\n";
+        let code = format!("function totalPrice{}\nfunction totalWeight{}\nfunction describe(item) {{\n  return `${{item.name}}`;\n}}\n", body, body.replace("price", "weight"));
+
+        let duplicates = analyzer.find_duplicates(&code, "javascript");
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].first_lines, (1, 9));
+        assert_eq!(duplicates[0].second_lines, (11, 19));
+
+        let refactoring = duplicates[0].to_refactoring();
+        assert_eq!(analyzer.suggest_refactorings(&code, "javascript").unwrap().len(), 1);
+        assert!(matches!(refactoring.refactoring_type, RefactoringType::RemoveDuplication));
+        assert_eq!(refactoring.target_location, "lines 11-19");
+
+        // Raising the minimum past the clone's length hides it
+        let strict = CodeStructureAnalyzer::new().with_min_duplicate_tokens(duplicates[0].token_count + 1);
+        assert!(strict.find_duplicates(&code, "javascript").is_empty());
+    }
+
+    #[test]
+    fn test_smells_become_refactoring_suggestions() {
+        let long_body = "    total += 1;\n".repeat(110);
+        let code = format!("class Report {{\n  title() {{\n    return 'r';\n  }}\n  process() {{\n    let total = 0;\n{}    return total;\n  }}\n}}\n", long_body);
+        let suggestions = CodeStructureAnalyzer::new().suggest_refactorings(&code, "javascript").unwrap();
+        let extract: Vec<_> = suggestions.iter()
+            .filter(|suggestion| matches!(suggestion.refactoring_type, RefactoringType::ExtractMethod))
+            .collect();
+        assert_eq!(extract.len(), 1);
+        assert_eq!(extract[0].target_location, "process (line 5)");
+        // Over twice the line limit, so a bigger job
+        assert!(matches!(extract[0].estimated_effort, Effort::Medium));
+        // The repeated statements are reported as duplication after the smells
+        assert!(matches!(suggestions.last().unwrap().refactoring_type, RefactoringType::RemoveDuplication));
+
+        let nested = "func nested(a, b, c) {\n  if a {\n    if b {\n      if c { three() }\n    }\n  }\n}\n";
+        let strict = CodeStructureAnalyzer::new().with_smell_thresholds(SmellThresholds::default().with_max_nesting_depth(3));
+        let suggestions = strict.suggest_refactorings(nested, "generic").unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert!(matches!(suggestions[0].refactoring_type, RefactoringType::SimplifyCondition));
+        assert_eq!(suggestions[0].target_location, "nested (line 1)");
+        assert!(CodeStructureAnalyzer::new().suggest_refactorings(nested, "generic").unwrap().is_empty());
+    }
 }
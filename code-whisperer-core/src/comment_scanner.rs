@@ -0,0 +1,154 @@
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+
+/// Comment and code line counts from a language-aware scan that skips string literals.
+/// A line holding both code and a trailing comment counts toward both
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommentStats {
+    pub comment_lines: usize,
+    pub code_lines: usize,
+}
+
+/// A string literal's contents, without quotes or escape processing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringLiteral {
+    pub line_number: u32,
+    pub column: usize, // chars before the opening quote on its line
+    pub text: String,
+}
+
+/// Per-line comment and code marks from one pass of the language's tokenizer
+struct Scan {
+    comment: Vec<bool>,
+    code: Vec<bool>,
+    comment_text: Vec<String>,
+    strings: Vec<StringLiteral>,
+}
+
+impl Scan {
+    fn run(code: &str, language: &str) -> Scan {
+        let line_count = code.lines().count().max(1);
+        let mut scan = Scan {
+            comment: vec![false; line_count],
+            code: vec![false; line_count],
+            comment_text: vec![String::new(); line_count],
+            strings: Vec::new(),
+        };
+
+        let docstrings = language.eq_ignore_ascii_case("python");
+        let mut previous_line = 0;
+        for token in Tokenizer::for_language(code, language) {
+            let line_start = token.line != previous_line;
+            previous_line = token.line + token.text.matches('\n').count() as u32;
+
+            // Python triple-quoted strings opening a line are docstrings
+            let is_docstring = docstrings
+                && line_start
+                && token.kind == TokenKind::StringLiteral
+                && (token.text.starts_with("\"\"\"") || token.text.starts_with("'''"));
+            let is_comment = token.kind == TokenKind::Comment || is_docstring;
+
+            for (index, piece) in token.text.split('\n').enumerate() {
+                let line = (token.line as usize - 1 + index).min(line_count - 1);
+                if is_comment {
+                    scan.comment_text[line].push_str(piece);
+                }
+                if !piece.trim().is_empty() {
+                    if is_comment {
+                        scan.comment[line] = true;
+                    } else {
+                        scan.code[line] = true;
+                    }
+                }
+            }
+
+            if token.kind == TokenKind::StringLiteral && !is_docstring {
+                scan.strings.push(string_literal(&token));
+            }
+        }
+
+        scan
+    }
+}
+
+/// The contents of a string token, between its opening quote and the closing one if present
+fn string_literal(token: &Token) -> StringLiteral {
+    let text = token.text;
+    let quote_start = text.find(['"', '\'', '`']).unwrap_or(0);
+    let (prefix, quoted) = text.split_at(quote_start);
+    let quote = quoted.chars().next().unwrap_or('"');
+
+    let triple = quote.to_string().repeat(3);
+    let (opening, closing) = if quoted.starts_with(&triple) {
+        (triple.clone(), triple)
+    } else {
+        // Rust raw strings close with as many `#`s as they opened with
+        let hashes = prefix.len() - prefix.trim_end_matches('#').len();
+        (quote.to_string(), format!("{}{}", quote, "#".repeat(hashes)))
+    };
+
+    let body = &quoted[opening.len().min(quoted.len())..];
+    let body = body.strip_suffix(closing.as_str()).unwrap_or(body);
+    StringLiteral {
+        line_number: token.line,
+        column: token.column as usize + prefix.chars().count(),
+        text: body.to_string(),
+    }
+}
+
+impl CommentStats {
+    pub fn scan(code: &str, language: &str) -> Self {
+        let scan = Scan::run(code, language);
+        CommentStats {
+            comment_lines: scan.comment.iter().filter(|&&line| line).count(),
+            code_lines: scan.code.iter().filter(|&&line| line).count(),
+        }
+    }
+
+    /// Comment lines per code line; a file of only comments counts as one code line
+    pub fn ratio(&self) -> f32 {
+        self.comment_lines as f32 / self.code_lines.max(1) as f32
+    }
+}
+
+/// The comment text on each line, markers such as `//` included. Code and string literals
+/// are left out, so lines without a comment are empty
+pub fn comment_text_by_line(code: &str, language: &str) -> Vec<String> {
+    Scan::run(code, language).comment_text
+}
+
+/// String literals outside comments, in source order; Python docstrings are comments, not literals
+pub fn string_literals(code: &str, language: &str) -> Vec<StringLiteral> {
+    Scan::run(code, language).strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_markers_inside_strings_are_ignored() {
+        let code = "const url = \"https://example.com\";\n// fetch the page\nfetch(url); /* inline */\n";
+        assert_eq!(CommentStats::scan(code, "javascript"), CommentStats { comment_lines: 2, code_lines: 2 });
+
+        let python = "def tag():\n    \"\"\"Return the\n    hash tag.\"\"\"\n    return '#rust'  # literal\n";
+        assert_eq!(CommentStats::scan(python, "python"), CommentStats { comment_lines: 3, code_lines: 2 });
+
+        let rust = "fn first<'a>(s: &'a str) -> char { '/' } // slash\n/* outer /* inner */ still comment */\n";
+        let stats = CommentStats::scan(rust, "rust");
+        assert_eq!(stats, CommentStats { comment_lines: 2, code_lines: 1 });
+        assert_eq!(stats.ratio(), 2.0);
+    }
+
+    #[test]
+    fn test_comment_text_by_line_skips_code_and_strings() {
+        let code = "let s = \"// not a comment\"; // real one\n/* two\n   lines */ done();\n";
+        assert_eq!(comment_text_by_line(code, "javascript"), vec!["// real one", "/* two", "   lines */"]);
+
+        let literals = string_literals(code, "javascript");
+        assert_eq!(literals, vec![StringLiteral { line_number: 1, column: 8, text: "// not a comment".to_string() }]);
+
+        let python = string_literals("def f():\n    \"\"\"Doc.\"\"\"\n    return '' + 'it\\'s'\n", "python");
+        let texts: Vec<&str> = python.iter().map(|literal| literal.text.as_str()).collect();
+        assert_eq!(texts, vec!["", "it\\'s"]);
+    }
+}
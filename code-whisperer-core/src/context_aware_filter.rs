@@ -2,13 +2,40 @@
 // This module implements intelligent filtering and prioritization of suggestions
 // based on coding context, user preferences, and project characteristics
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use std::time::Duration;
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 use crate::suggestion_generation_engine::{CodeSuggestion, SuggestionType};
-use crate::user_behavior_tracker::BehaviorAnalysis;
+use crate::user_behavior_tracker::{BehaviorAnalysis, Clock, RejectionReason, SystemClock};
+use crate::tokenizer::{Tokenizer, TokenKind};
+
+// Number of `SuggestionType` variants, the most types a suggestion set can spread over
+const SUGGESTION_TYPE_COUNT: usize = 8;
+
+// Baseline confidence required before any rejection history is known
+const DEFAULT_CONFIDENCE_THRESHOLD: f64 = 0.7;
+
+// Threshold increase per rejection of a suggestion as irrelevant or incorrect, and its ceiling
+const REJECTION_THRESHOLD_STEP: f64 = 0.02;
+const MAX_CONFIDENCE_THRESHOLD: f64 = 0.9;
+
+// Recently surfaced suggestions remembered so feedback by id can be traced to its type and context
+const MAX_TRACKED_SUGGESTIONS: usize = 256;
+
+// Keywords kept verbatim when identifiers are normalized for diversity hashing
+const NORMALIZATION_KEYWORDS: &[&str] = &[
+    "let", "const", "var", "mut", "fn", "function", "def", "class", "struct", "impl", "return", "if", "else",
+    "for", "while", "in", "of", "match", "new", "async", "await", "import", "from", "use", "pub", "self", "this",
+    "true", "false", "null", "None", "True", "False",
+];
+
+// Top-level `*-service` directories needed before a layout counts as microservices
+const MIN_MICROSERVICE_DIRECTORIES: usize = 3;
 
 // Main Context-Aware Filter Engine
 #[derive(Debug, Clone)]
@@ -18,6 +45,27 @@ pub struct ContextAwareFilter {
     project_analyzer: ProjectAnalyzer,
     filter_config: FilterConfiguration,
     suggestion_ranker: SuggestionRanker,
+    feedback_history: VecDeque<SuggestionFeedbackEvent>, // most recent last, capped at `feedback_window`
+    shown_suggestions: VecDeque<ShownSuggestion>, // recent results, most recent last, capped at `MAX_TRACKED_SUGGESTIONS`
+    cooldown: SuggestionCooldown,
+}
+
+// One accepted or rejected suggestion, remembered with the context it was shown in
+#[derive(Debug, Clone)]
+struct SuggestionFeedbackEvent {
+    suggestion_type: SuggestionType,
+    context_type: ContextType,
+    accepted: bool,
+    reason: Option<RejectionReason>,
+}
+
+// A suggestion this filter returned, remembered so feedback and dismissals can refer to it by id
+#[derive(Debug, Clone)]
+struct ShownSuggestion {
+    id: String,
+    suggestion_type: SuggestionType,
+    context_type: ContextType,
+    content_hash: u64,
 }
 
 impl ContextAwareFilter {
@@ -28,9 +76,94 @@ impl ContextAwareFilter {
             project_analyzer: ProjectAnalyzer::new(),
             filter_config: FilterConfiguration::default(),
             suggestion_ranker: SuggestionRanker::new(),
+            feedback_history: VecDeque::new(),
+            shown_suggestions: VecDeque::new(),
+            cooldown: SuggestionCooldown::new(FilterConfiguration::default().suggestion_cooldown),
+        }
+    }
+
+    // Remember how the user responded to a suggestion; only the last `feedback_window` responses count
+    pub fn record_feedback(&mut self, suggestion_type: SuggestionType, context_type: ContextType, accepted: bool) {
+        self.record_feedback_with_reason(suggestion_type, context_type, accepted, None);
+    }
+
+    // Like `record_feedback`, keeping why a rejected suggestion was turned down
+    pub fn record_feedback_with_reason(
+        &mut self,
+        suggestion_type: SuggestionType,
+        context_type: ContextType,
+        accepted: bool,
+        reason: Option<RejectionReason>,
+    ) {
+        self.feedback_history.push_back(SuggestionFeedbackEvent { suggestion_type, context_type, accepted, reason });
+        while self.feedback_history.len() > self.filter_config.feedback_window {
+            self.feedback_history.pop_front();
         }
     }
 
+    // Feedback on a suggestion this filter returned, looked up by id; false if it was never shown
+    // or has aged out of the tracked results
+    pub fn record_suggestion_feedback(&mut self, suggestion_id: &str, accepted: bool, reason: Option<RejectionReason>) -> bool {
+        let Some(shown) = self.find_shown(suggestion_id).cloned() else {
+            return false;
+        };
+        self.record_feedback_with_reason(shown.suggestion_type, shown.context_type, accepted, reason);
+        true
+    }
+
+    fn find_shown(&self, suggestion_id: &str) -> Option<&ShownSuggestion> {
+        self.shown_suggestions.iter().rev().find(|shown| shown.id == suggestion_id)
+    }
+
+    fn remember_shown(&mut self, suggestions: &[CodeSuggestion], context_type: &ContextType) {
+        for suggestion in suggestions {
+            self.shown_suggestions.push_back(ShownSuggestion {
+                id: suggestion.id.clone(),
+                suggestion_type: suggestion.suggestion_type.clone(),
+                context_type: context_type.clone(),
+                content_hash: suggestion_content_hash(suggestion),
+            });
+        }
+        while self.shown_suggestions.len() > MAX_TRACKED_SUGGESTIONS {
+            self.shown_suggestions.pop_front();
+        }
+    }
+
+    pub fn with_config(filter_config: FilterConfiguration) -> Self {
+        Self {
+            cooldown: SuggestionCooldown::new(filter_config.suggestion_cooldown),
+            filter_config,
+            ..Self::new()
+        }
+    }
+
+    // Keep a suggestion the user dismissed out of results until its cool-down passes
+    pub fn dismiss_suggestion(&mut self, suggestion: &CodeSuggestion) {
+        self.cooldown.start_cooldown(suggestion);
+    }
+
+    // Like `dismiss_suggestion`, for a suggestion this filter returned; false if it was never shown
+    // or has aged out of the tracked results
+    pub fn dismiss_suggestion_by_id(&mut self, suggestion_id: &str) -> bool {
+        let Some(content_hash) = self.find_shown(suggestion_id).map(|shown| shown.content_hash) else {
+            return false;
+        };
+        self.cooldown.start_cooldown_for(content_hash);
+        true
+    }
+
+    // Change how long shown and dismissed suggestions stay hidden; zero turns the cool-down off
+    pub fn set_suggestion_cooldown(&mut self, window: Duration) {
+        self.filter_config.suggestion_cooldown = window;
+        self.cooldown.window = window;
+    }
+
+    // Rank with custom weights, e.g. to favor recency over raw confidence; rejects negative weights
+    pub fn set_ranking_weights(&mut self, weights: RankingWeights) -> Result<(), String> {
+        self.suggestion_ranker = SuggestionRanker::with_weights(weights)?;
+        Ok(())
+    }
+
     // Main filtering function that applies context-aware filtering
     pub fn filter_suggestions(
         &mut self,
@@ -63,7 +196,52 @@ impl ContextAwareFilter {
             &preferences,
         )?;
         
-        Ok(ranked_suggestions)
+        // Hold back anything shown or dismissed moments ago
+        let surfaced = self.cooldown.surface(ranked_suggestions);
+        self.remember_shown(&surfaced, &context_analysis.current_context_type);
+        Ok(surfaced)
+    }
+
+    // Filter suggestions separately for every active cursor, primary cursor first
+    pub fn filter_suggestions_per_cursor(
+        &mut self,
+        suggestions: Vec<CodeSuggestion>,
+        context: &CodingContext,
+        user_behavior: &BehaviorAnalysis,
+        project_info: &ProjectInfo,
+    ) -> Result<Vec<CursorSuggestions>, String> {
+        let cursor_analyses = self.context_analyzer.analyze_cursors(context)?;
+        let preferences = self.preference_engine.derive_preferences(user_behavior)?;
+        let project_analysis = self.project_analyzer.analyze_project(project_info)?;
+        let suggestions: Vec<CodeSuggestion> = suggestions.into_iter()
+            .filter(|suggestion| !self.cooldown.is_cooling_down(suggestion))
+            .collect();
+
+        let per_cursor = context.cursors()
+            .into_iter()
+            .zip(cursor_analyses.iter())
+            .map(|(cursor, context_analysis)| {
+                let filtered = self.apply_filters(
+                    suggestions.clone(),
+                    context_analysis,
+                    &preferences,
+                    &project_analysis,
+                )?;
+                Ok(CursorSuggestions {
+                    cursor: cursor.clone(),
+                    suggestions: self.suggestion_ranker.rank_suggestions(filtered, context_analysis, &preferences)?,
+                })
+            })
+            .collect::<Result<Vec<CursorSuggestions>, String>>()?;
+
+        // One cool-down per suggestion, however many cursors show it
+        for suggestion in per_cursor.iter().flat_map(|cursor| &cursor.suggestions) {
+            self.cooldown.start_cooldown(suggestion);
+        }
+        for (cursor, context_analysis) in per_cursor.iter().zip(&cursor_analyses) {
+            self.remember_shown(&cursor.suggestions, &context_analysis.current_context_type);
+        }
+        Ok(per_cursor)
     }
 
     // Apply cascading filters to suggestions
@@ -79,6 +257,9 @@ impl ContextAwareFilter {
         // Filter by relevance to current context
         filtered = self.filter_by_context_relevance(filtered, context)?;
         
+        // Drop types the user keeps rejecting in this kind of context
+        filtered = self.filter_by_rejection_history(filtered, &context.current_context_type)?;
+
         // Filter by user preferences
         filtered = self.filter_by_preferences(filtered, preferences)?;
         
@@ -149,6 +330,31 @@ impl ContextAwareFilter {
         }
     }
 
+    // Filter out suggestion types repeatedly rejected in the current context within the feedback window.
+    // A `Duplicate` rejection objects to one repeated suggestion, not to its type, so it doesn't count
+    fn filter_by_rejection_history(
+        &self,
+        suggestions: Vec<CodeSuggestion>,
+        context_type: &ContextType,
+    ) -> Result<Vec<CodeSuggestion>, String> {
+        let threshold = self.filter_config.suppression_rejection_count;
+        if threshold == 0 {
+            return Ok(suggestions);
+        }
+
+        let mut rejections: HashMap<&SuggestionType, usize> = HashMap::new();
+        for event in &self.feedback_history {
+            if !event.accepted && &event.context_type == context_type && event.reason != Some(RejectionReason::Duplicate) {
+                *rejections.entry(&event.suggestion_type).or_insert(0) += 1;
+            }
+        }
+
+        Ok(suggestions
+            .into_iter()
+            .filter(|suggestion| rejections.get(&suggestion.suggestion_type).copied().unwrap_or(0) < threshold)
+            .collect())
+    }
+
     // Filter suggestions based on user preferences
     fn filter_by_preferences(
         &self,
@@ -296,24 +502,82 @@ impl ContextAwareFilter {
         &self,
         suggestions: Vec<CodeSuggestion>,
     ) -> Result<Vec<CodeSuggestion>, String> {
-        let mut diverse_suggestions = Vec::new();
+        let mut unique_suggestions = Vec::new();
         let mut seen_patterns = std::collections::HashSet::new();
         
         for suggestion in suggestions {
             let pattern_hash = self.calculate_suggestion_pattern_hash(&suggestion);
             
-            if !seen_patterns.contains(&pattern_hash) {
-                seen_patterns.insert(pattern_hash);
-                diverse_suggestions.push(suggestion);
-                
-                // Limit the number of suggestions to avoid overwhelming the user
-                if diverse_suggestions.len() >= self.filter_config.max_suggestions_per_context {
-                    break;
-                }
+            if seen_patterns.insert(pattern_hash) {
+                unique_suggestions.push(suggestion);
             }
         }
-        
-        Ok(diverse_suggestions)
+
+        // Limit the number of suggestions to avoid overwhelming the user
+        let cap = self.filter_config.max_suggestions_per_context;
+        if unique_suggestions.len() <= cap {
+            return Ok(unique_suggestions);
+        }
+        if self.filter_config.min_type_variety == 0 {
+            unique_suggestions.truncate(cap);
+            return Ok(unique_suggestions);
+        }
+
+        let mut ranked: Vec<usize> = (0..unique_suggestions.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            unique_suggestions[b].confidence_score
+                .partial_cmp(&unique_suggestions[a].confidence_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        // Keep the best suggestion of each type first, then fill with the highest-ranked rest
+        let variety = self.filter_config.min_type_variety.min(cap);
+        let mut kept_types = std::collections::HashSet::new();
+        let mut selected = std::collections::HashSet::new();
+        for &index in &ranked {
+            if kept_types.len() >= variety {
+                break;
+            }
+            if kept_types.insert(unique_suggestions[index].suggestion_type.clone()) {
+                selected.insert(index);
+            }
+        }
+        for &index in &ranked {
+            if selected.len() >= cap {
+                break;
+            }
+            selected.insert(index);
+        }
+
+        Ok(unique_suggestions
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| selected.contains(index))
+            .map(|(_, suggestion)| suggestion)
+            .collect())
+    }
+
+    // Normalized entropy of suggestion types: 0.0 for a single type, 1.0 for an even spread
+    pub fn diversity_score(suggestions: &[CodeSuggestion]) -> f64 {
+        let mut type_counts: HashMap<&SuggestionType, usize> = HashMap::new();
+        for suggestion in suggestions {
+            *type_counts.entry(&suggestion.suggestion_type).or_insert(0) += 1;
+        }
+
+        let max_types = suggestions.len().min(SUGGESTION_TYPE_COUNT);
+        if type_counts.len() < 2 || max_types < 2 {
+            return 0.0;
+        }
+
+        let total = suggestions.len() as f64;
+        let entropy: f64 = type_counts.values()
+            .map(|&count| {
+                let share = count as f64 / total;
+                -share * share.ln()
+            })
+            .sum();
+
+        entropy / (max_types as f64).ln()
     }
 
     // Helper methods for filtering logic
@@ -422,16 +686,95 @@ impl ContextAwareFilter {
     }
 
     fn calculate_suggestion_pattern_hash(&self, suggestion: &CodeSuggestion) -> u64 {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
         let mut hasher = DefaultHasher::new();
         suggestion.suggestion_type.hash(&mut hasher);
-        suggestion.suggested_code.chars().filter(|c| !c.is_whitespace()).collect::<String>().hash(&mut hasher);
+        match self.filter_config.diversity_hash_mode {
+            DiversityHashMode::Literal => {
+                suggestion.suggested_code.chars().filter(|c| !c.is_whitespace()).collect::<String>().hash(&mut hasher);
+            },
+            DiversityHashMode::NormalizeIdentifiers => {
+                normalize_identifiers(&suggestion.suggested_code).hash(&mut hasher);
+            },
+        }
         hasher.finish()
     }
 }
 
+// Remembers when each suggestion was last shown or dismissed, keyed by a hash of its type and
+// code, so one re-emitted on every keystroke doesn't flicker back into view. A zero window disables it
+#[derive(Debug, Clone)]
+pub struct SuggestionCooldown {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    started: HashMap<u64, DateTime<Utc>>, // content hash -> when its cool-down began
+}
+
+impl SuggestionCooldown {
+    pub fn new(window: Duration) -> Self {
+        Self::with_clock(window, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(window: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self { window, clock, started: HashMap::new() }
+    }
+
+    // Restart the cool-down for `suggestion`, e.g. because it was just shown or dismissed
+    pub fn start_cooldown(&mut self, suggestion: &CodeSuggestion) {
+        self.start_cooldown_for(suggestion_content_hash(suggestion));
+    }
+
+    fn start_cooldown_for(&mut self, content_hash: u64) {
+        if !self.window.is_zero() {
+            self.started.insert(content_hash, self.clock.now());
+        }
+    }
+
+    pub fn is_cooling_down(&self, suggestion: &CodeSuggestion) -> bool {
+        self.started.get(&suggestion_content_hash(suggestion))
+            .is_some_and(|&started| !cooldown_elapsed(&*self.clock, started, self.window))
+    }
+
+    // Drop suggestions still cooling down and start the cool-down for the rest, which are about to be shown
+    pub fn surface(&mut self, suggestions: Vec<CodeSuggestion>) -> Vec<CodeSuggestion> {
+        self.started.retain(|_, started| !cooldown_elapsed(&*self.clock, *started, self.window));
+        let shown: Vec<CodeSuggestion> = suggestions.into_iter()
+            .filter(|suggestion| !self.is_cooling_down(suggestion))
+            .collect();
+        for suggestion in &shown {
+            self.start_cooldown(suggestion);
+        }
+        shown
+    }
+}
+
+fn cooldown_elapsed(clock: &dyn Clock, started: DateTime<Utc>, window: Duration) -> bool {
+    chrono::Duration::from_std(window).is_ok_and(|window| clock.now() - started >= window)
+}
+
+// Whitespace-insensitive, so reformatted re-emissions still count as the same suggestion
+fn suggestion_content_hash(suggestion: &CodeSuggestion) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    suggestion.suggestion_type.hash(&mut hasher);
+    suggestion.suggested_code.chars().filter(|c| !c.is_whitespace()).collect::<String>().hash(&mut hasher);
+    hasher.finish()
+}
+
+// Token stream with each distinct identifier replaced by its first-seen position, so renames hash alike
+fn normalize_identifiers(code: &str) -> Vec<String> {
+    let mut placeholders: HashMap<&str, usize> = HashMap::new();
+    Tokenizer::new(code)
+        .filter(|token| token.kind != TokenKind::Comment)
+        .map(|token| {
+            if token.kind == TokenKind::Identifier && !NORMALIZATION_KEYWORDS.contains(&token.text) {
+                let next = placeholders.len();
+                format!("${}", placeholders.entry(token.text).or_insert(next))
+            } else {
+                token.text.to_string()
+            }
+        })
+        .collect()
+}
+
 // Context Analysis System
 #[derive(Debug, Clone)]
 pub struct ContextAnalyzer {
@@ -462,6 +805,14 @@ impl ContextAnalyzer {
         Ok(analysis)
     }
 
+    // Analyze the context around every active cursor, primary cursor first
+    pub fn analyze_cursors(&mut self, context: &CodingContext) -> Result<Vec<ContextAnalysis>, String> {
+        context.cursor_contexts()
+            .iter()
+            .map(|cursor_context| self.analyze_context(cursor_context))
+            .collect()
+    }
+
     fn perform_context_analysis(&self, context: &CodingContext) -> Result<ContextAnalysis, String> {
         let context_type = self.determine_context_type(context)?;
         let supported_languages = self.extract_supported_languages(context)?;
@@ -656,6 +1007,10 @@ impl ContextAnalyzer {
     }
 }
 
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
 // Preference Engine
 #[derive(Debug, Clone)]
 pub struct PreferenceEngine {
@@ -677,7 +1032,11 @@ impl PreferenceEngine {
         
         if let Some(cached_prefs) = self.preference_cache.get(user_key) {
             if !self.are_preferences_stale(cached_prefs) {
-                return Ok(cached_prefs.clone());
+                // Rejections since the cache was filled must raise the bar right away
+                return Ok(DerivedPreferences {
+                    min_confidence_threshold: self.derive_confidence_threshold(behavior),
+                    ..cached_prefs.clone()
+                });
             }
         }
         
@@ -700,10 +1059,16 @@ impl PreferenceEngine {
         })
     }
 
-    fn derive_confidence_threshold(&self, _behavior: &BehaviorAnalysis) -> f64 {
-        // Since BehaviorAnalysis doesn't have suggestion_acceptance_rate,
-        // we'll use a default threshold that can be adjusted based on other metrics
-        0.7 // Default threshold
+    fn derive_confidence_threshold(&self, behavior: &BehaviorAnalysis) -> f64 {
+        // Suggestions rejected as irrelevant or incorrect mean we should only show surer ones
+        let reasons = &behavior.suggestion_feedback.rejection_reasons;
+        let low_quality_rejections = [RejectionReason::Irrelevant, RejectionReason::Incorrect]
+            .iter()
+            .map(|reason| reasons.get(reason).copied().unwrap_or(0))
+            .sum::<u32>();
+
+        (DEFAULT_CONFIDENCE_THRESHOLD + low_quality_rejections as f64 * REJECTION_THRESHOLD_STEP)
+            .min(MAX_CONFIDENCE_THRESHOLD)
     }
 
     fn derive_preferred_types(&self, _behavior: &BehaviorAnalysis) -> Vec<SuggestionType> {
@@ -771,9 +1136,40 @@ impl ProjectAnalyzer {
         })
     }
 
-    fn detect_architectural_patterns(&self, _project_info: &ProjectInfo) -> Result<Vec<ArchitecturalPattern>, String> {
-        // Analyze project structure to detect patterns like MVC, MVP, MVVM, etc.
-        Ok(vec![ArchitecturalPattern::ModularArchitecture])
+    fn detect_architectural_patterns(&self, project_info: &ProjectInfo) -> Result<Vec<ArchitecturalPattern>, String> {
+        // Analyze the directory layout to detect patterns like MVC, MVP, MVVM, etc.
+        let directory_names: std::collections::HashSet<String> = project_info.directories.iter()
+            .map(|dir| file_name(dir.trim_end_matches(['/', '\\'])).to_lowercase())
+            .collect();
+        let has_dir = |names: &[&str]| names.iter().any(|name| directory_names.contains(*name));
+
+        let mut patterns = Vec::new();
+        if has_dir(&["controllers", "controller"]) && has_dir(&["models", "model"]) && has_dir(&["views", "view"]) {
+            patterns.push(ArchitecturalPattern::MVC);
+        }
+        if has_dir(&["presenters", "presenter"]) && has_dir(&["views", "view"]) {
+            patterns.push(ArchitecturalPattern::MVP);
+        }
+        if has_dir(&["viewmodels", "viewmodel"]) && has_dir(&["views", "view"]) {
+            patterns.push(ArchitecturalPattern::MVVM);
+        }
+        if has_dir(&["services", "service"]) && has_dir(&["repositories", "repository"]) {
+            patterns.push(ArchitecturalPattern::LayeredArchitecture);
+        }
+
+        let service_roots: std::collections::HashSet<String> = project_info.directories.iter()
+            .filter_map(|dir| dir.trim_start_matches("./").split(['/', '\\']).next())
+            .map(|root| root.to_lowercase())
+            .filter(|root| root.ends_with("-service") || root.ends_with("_service"))
+            .collect();
+        if service_roots.len() >= MIN_MICROSERVICE_DIRECTORIES {
+            patterns.push(ArchitecturalPattern::MicroserviceArchitecture);
+        }
+
+        if patterns.is_empty() {
+            patterns.push(ArchitecturalPattern::ModularArchitecture);
+        }
+        Ok(patterns)
     }
 
     fn extract_dependencies(&self, project_info: &ProjectInfo) -> Result<Vec<String>, String> {
@@ -795,18 +1191,68 @@ impl ProjectAnalyzer {
     }
 
     fn determine_project_type(&self, project_info: &ProjectInfo) -> Result<ProjectType, String> {
-        // Analyze project structure and files to determine type
-        if project_info.dependencies.iter().any(|dep| dep.contains("react")) {
+        // Exact package names, so `reactive-streams` isn't React; Maven coordinates are matched by artifact
+        let has_dependency = |names: &[&str]| {
+            project_info.dependencies.iter().any(|dep| {
+                let dep = dep.to_lowercase();
+                let artifact = dep.rsplit(':').next().unwrap_or(&dep);
+                names.contains(&artifact)
+            })
+        };
+        let has_file = |names: &[&str]| {
+            project_info.files.iter().any(|path| names.contains(&file_name(path)))
+        };
+        let has_extension = |extensions: &[&str]| {
+            project_info.files.iter().any(|path| {
+                file_name(path).rsplit_once('.').is_some_and(|(_, ext)| extensions.contains(&ext))
+            })
+        };
+
+        // Platform-specific markers are the most telling, so check them first
+        if has_file(&["AndroidManifest.xml", "pubspec.yaml", "Info.plist"])
+            || has_dependency(&["react-native", "flutter", "@ionic/core", "@ionic/angular", "@ionic/react"])
+        {
+            Ok(ProjectType::MobileApp)
+        } else if has_file(&["project.godot", "ProjectSettings.asset"])
+            || has_extension(&["unity", "gd", "uproject"])
+            || has_dependency(&["bevy", "pygame", "phaser", "godot", "macroquad", "ggez"])
+        {
+            Ok(ProjectType::GameDevelopment)
+        } else if has_file(&["tauri.conf.json"])
+            || has_extension(&["xaml"])
+            || has_dependency(&["electron", "tauri", "gtk", "gtk4", "pyqt5", "pyqt6", "tkinter", "egui", "iced", "winit"])
+        {
+            Ok(ProjectType::DesktopApp)
+        } else if has_dependency(&["react", "react-dom", "vue", "@angular/core", "svelte"]) {
             Ok(ProjectType::WebFrontend)
-        } else if project_info.dependencies.iter().any(|dep| dep.contains("express")) {
+        } else if has_dependency(&[
+            "express", "django", "flask", "fastapi", "spring-boot-starter-web", "actix-web", "axum", "rocket", "ktor-server-core", "vapor",
+        ]) || has_file(&["manage.py", "wsgi.py", "asgi.py"])
+        {
             Ok(ProjectType::WebBackend)
-        } else if project_info.dependencies.iter().any(|dep| dep.contains("tokio")) {
+        } else if has_extension(&["xcodeproj", "swift", "kt"]) {
+            // Kotlin and Swift without a server framework are most likely apps
+            Ok(ProjectType::MobileApp)
+        } else if has_file(&["index.html"]) {
+            Ok(ProjectType::WebFrontend)
+        } else if self.is_rust_library(project_info) {
+            Ok(ProjectType::Library)
+        } else if has_file(&["main.rs", "main.go"]) || has_dependency(&["tokio", "clap"]) {
             Ok(ProjectType::SystemTool)
         } else {
             Ok(ProjectType::Library)
         }
     }
 
+    fn is_rust_library(&self, project_info: &ProjectInfo) -> bool {
+        let declares_lib = project_info.manifests.iter()
+            .any(|(path, content)| file_name(path) == "Cargo.toml" && content.lines().any(|line| line.trim() == "[lib]"));
+        let has_lib_root = project_info.files.iter().any(|path| path.ends_with("src/lib.rs"));
+        let has_main = project_info.files.iter().any(|path| path.ends_with("src/main.rs"));
+
+        declares_lib || (has_lib_root && !has_main)
+    }
+
     fn is_project_analysis_stale(&self, analysis: &ProjectAnalysis) -> bool {
         let age = Utc::now().signed_duration_since(analysis.analysis_timestamp);
         age > chrono::Duration::hours(1) // Cache for 1 hour
@@ -826,6 +1272,11 @@ impl SuggestionRanker {
         }
     }
 
+    pub fn with_weights(ranking_weights: RankingWeights) -> Result<Self, String> {
+        ranking_weights.validate()?;
+        Ok(Self { ranking_weights })
+    }
+
     pub fn rank_suggestions(
         &self,
         suggestions: Vec<CodeSuggestion>,
@@ -937,15 +1388,50 @@ pub struct CodingContext {
     pub file_content: String,
     pub current_line: String,
     pub cursor_position: CursorPosition,
+    // Secondary cursors when multi-cursor editing; `cursor_position` stays the primary one
+    #[serde(default)]
+    pub additional_cursors: Vec<CursorPosition>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl CodingContext {
+    // All active cursors, primary cursor first
+    pub fn cursors(&self) -> Vec<&CursorPosition> {
+        std::iter::once(&self.cursor_position)
+            .chain(self.additional_cursors.iter())
+            .collect()
+    }
+
+    // One single-cursor context per active cursor, each with the line under that cursor
+    pub fn cursor_contexts(&self) -> Vec<CodingContext> {
+        let mut primary = self.clone();
+        primary.additional_cursors.clear();
+
+        let secondary = self.additional_cursors.iter().map(|cursor| CodingContext {
+            file_path: self.file_path.clone(),
+            file_language: self.file_language.clone(),
+            file_content: self.file_content.clone(),
+            current_line: self.file_content.lines().nth(cursor.row as usize).unwrap_or_default().to_string(),
+            cursor_position: cursor.clone(),
+            additional_cursors: Vec::new(),
+        });
+
+        std::iter::once(primary).chain(secondary).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CursorPosition {
     pub row: u32,
     pub column: u32,
     pub offset: u32,
 }
 
+#[derive(Debug, Clone)]
+pub struct CursorSuggestions {
+    pub cursor: CursorPosition,
+    pub suggestions: Vec<CodeSuggestion>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectInfo {
     pub project_path: String,
@@ -953,6 +1439,15 @@ pub struct ProjectInfo {
     pub dependencies: Vec<String>,
     pub file_count: u32,
     pub total_lines_of_code: u32,
+    // Project-relative file paths, e.g. `src/main.rs`
+    #[serde(default)]
+    pub files: Vec<String>,
+    // Contents of manifest files such as `Cargo.toml` or `package.json`, keyed by path
+    #[serde(default)]
+    pub manifests: HashMap<String, String>,
+    // Project-relative directory paths, e.g. `app/controllers`
+    #[serde(default)]
+    pub directories: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -1122,6 +1617,18 @@ pub struct FilterConfiguration {
     pub max_suggestions_per_context: usize,
     pub enable_diversity_filtering: bool,
     pub enable_project_compatibility_check: bool,
+    pub min_type_variety: usize, // distinct types kept when the cap is reached, 0 disables
+    pub feedback_window: usize, // recent accept/reject responses consulted for suppression
+    pub suppression_rejection_count: usize, // rejections of a type within the window that suppress it, 0 disables
+    pub diversity_hash_mode: DiversityHashMode,
+    pub suggestion_cooldown: Duration, // how long a shown or dismissed suggestion stays hidden, zero (the default) disables
+}
+
+/// How suggestions are compared when removing duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiversityHashMode {
+    Literal,              // same code, ignoring whitespace
+    NormalizeIdentifiers, // same structure, ignoring identifier names
 }
 
 impl Default for FilterConfiguration {
@@ -1131,6 +1638,11 @@ impl Default for FilterConfiguration {
             max_suggestions_per_context: 10,
             enable_diversity_filtering: true,
             enable_project_compatibility_check: true,
+            min_type_variety: 3,
+            feedback_window: 20,
+            suppression_rejection_count: 3,
+            diversity_hash_mode: DiversityHashMode::Literal,
+            suggestion_cooldown: Duration::ZERO,
         }
     }
 }
@@ -1160,6 +1672,28 @@ pub struct RankingWeights {
     pub recency_weight: f64,
 }
 
+impl Default for SuggestionRanker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RankingWeights {
+    // Every weight must be a finite, non-negative number
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            ("confidence_weight", self.confidence_weight),
+            ("context_weight", self.context_weight),
+            ("preference_weight", self.preference_weight),
+            ("recency_weight", self.recency_weight),
+        ];
+        match weights.iter().find(|(_, weight)| !weight.is_finite() || *weight < 0.0) {
+            Some((name, weight)) => Err(format!("Ranking weight {} must be non-negative, got {}", name, weight)),
+            None => Ok(()),
+        }
+    }
+}
+
 impl Default for RankingWeights {
     fn default() -> Self {
         Self {
@@ -1171,11 +1705,268 @@ impl Default for RankingWeights {
     }
 }
 
-// Tests - temporarily disabled for compilation
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::suggestion_generation_engine::CodePosition;
+
+    fn multi_cursor_context() -> CodingContext {
+        CodingContext {
+            file_path: "main.rs".to_string(),
+            file_language: "rust".to_string(),
+            file_content: "fn main() {\n    let total = 0;\n}\n".to_string(),
+            current_line: "fn main() {".to_string(),
+            cursor_position: CursorPosition { row: 0, column: 3, offset: 3 },
+            additional_cursors: vec![CursorPosition { row: 1, column: 8, offset: 20 }],
+        }
+    }
+
+    fn suggestion(id: usize, suggestion_type: SuggestionType, confidence_score: f32) -> CodeSuggestion {
+        CodeSuggestion {
+            id: format!("suggestion_{}", id),
+            suggestion_type,
+            title: String::new(),
+            description: String::new(),
+            suggested_code: format!("value_{}", id),
+            original_code: None,
+            confidence_score,
+            pattern_match_score: 0.0,
+            user_preference_score: 0.0,
+            context_relevance: 0.0,
+            position: CodePosition { line: 0, column: 0, start_offset: 0, end_offset: 0 },
+            preview_changes: Vec::new(),
+            reasoning: String::new(),
+            related_patterns: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_custom_ranking_weights_change_order() {
+        let context = ContextAnalyzer::new().analyze_context(&multi_cursor_context()).unwrap();
+        let preferences = DerivedPreferences {
+            min_confidence_threshold: 0.9,
+            preferred_suggestion_types: vec![SuggestionType::Documentation],
+            code_style_preferences: None,
+            last_updated: Utc::now(),
+        };
+        let suggestions = vec![
+            suggestion(0, SuggestionType::CodeCompletion, 0.8),
+            suggestion(1, SuggestionType::Documentation, 0.3),
+        ];
+        let ranked_ids = |weights: RankingWeights| -> Vec<String> {
+            SuggestionRanker::with_weights(weights).unwrap()
+                .rank_suggestions(suggestions.clone(), &context, &preferences).unwrap()
+                .into_iter().map(|s| s.id).collect()
+        };
+
+        let by_confidence = RankingWeights { confidence_weight: 10.0, context_weight: 0.0, preference_weight: 0.0, recency_weight: 0.0 };
+        let by_preference = RankingWeights { confidence_weight: 0.0, context_weight: 0.0, preference_weight: 10.0, recency_weight: 0.0 };
+        assert_eq!(ranked_ids(by_confidence), vec!["suggestion_0", "suggestion_1"]);
+        assert_eq!(ranked_ids(by_preference), vec!["suggestion_1", "suggestion_0"]);
+
+        let mut filter = ContextAwareFilter::new();
+        let negative = RankingWeights { recency_weight: -1.0, ..RankingWeights::default() };
+        assert!(filter.set_ranking_weights(negative).unwrap_err().contains("recency_weight"));
+        assert!(filter.set_ranking_weights(RankingWeights { recency_weight: f64::NAN, ..RankingWeights::default() }).is_err());
+        assert!(filter.set_ranking_weights(RankingWeights::default()).is_ok());
+    }
+
+    fn skewed_suggestions() -> Vec<CodeSuggestion> {
+        let mut suggestions: Vec<CodeSuggestion> = (0..12)
+            .map(|i| suggestion(i, SuggestionType::CodeCompletion, 0.95 - i as f32 * 0.01))
+            .collect();
+        suggestions.push(suggestion(12, SuggestionType::StructuralRefactoring, 0.7));
+        suggestions.push(suggestion(13, SuggestionType::Documentation, 0.65));
+        suggestions
+    }
+
+    #[test]
+    fn test_diversity_filter_keeps_minority_types() {
+        let filter = ContextAwareFilter::new();
+        let filtered = filter.filter_for_diversity(skewed_suggestions()).unwrap();
+
+        assert_eq!(filtered.len(), 10);
+        assert!(filtered.iter().any(|s| s.suggestion_type == SuggestionType::StructuralRefactoring));
+        assert!(filtered.iter().any(|s| s.suggestion_type == SuggestionType::Documentation));
+        // The remainder is the highest-ranked completions
+        assert!(filtered.iter().any(|s| s.id == "suggestion_0"));
+        assert!(!filtered.iter().any(|s| s.id == "suggestion_11"));
+    }
+
+    #[test]
+    fn test_repeated_suggestion_is_held_back_until_its_cooldown_passes() {
+        let clock = Arc::new(crate::user_behavior_tracker::MockClock::new(Utc::now()));
+        let mut cooldown = SuggestionCooldown::with_clock(Duration::from_secs(2), clock.clone());
+        let first = suggestion(0, SuggestionType::CodeCompletion, 0.9);
+        // The same code re-emitted with a new id and different spacing
+        let repeat = CodeSuggestion { id: "suggestion_again".to_string(), suggested_code: format!(" {} ", first.suggested_code), ..first.clone() };
+        let other = suggestion(1, SuggestionType::CodeCompletion, 0.9);
+
+        assert_eq!(cooldown.surface(vec![first.clone()]).len(), 1);
+        clock.advance(chrono::Duration::milliseconds(500));
+        let shown = cooldown.surface(vec![repeat.clone(), other.clone()]);
+        assert_eq!(shown.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec![other.id.as_str()]);
+
+        clock.advance(chrono::Duration::seconds(2));
+        assert_eq!(cooldown.surface(vec![repeat.clone()]).len(), 1);
+
+        // Dismissing restarts the cool-down too
+        clock.advance(chrono::Duration::seconds(3));
+        cooldown.start_cooldown(&other);
+        assert!(cooldown.is_cooling_down(&other));
+        // A zero window turns the cool-down off
+        assert_eq!(SuggestionCooldown::new(Duration::ZERO).surface(vec![first, repeat]).len(), 2);
+    }
+
+    #[test]
+    fn test_cooldown_is_opt_in_and_dismissals_refer_to_shown_ids() {
+        let behavior = crate::user_behavior_tracker::UserBehaviorTracker::new().analyze_behavior();
+        let shown_ids = |filter: &mut ContextAwareFilter| -> Vec<String> {
+            filter.filter_suggestions(camel_case_suggestions(), &multi_cursor_context(), &behavior, &project(&[], &[])).unwrap()
+                .into_iter().map(|suggestion| suggestion.id).collect()
+        };
+
+        let mut filter = ContextAwareFilter::new();
+        let shown = shown_ids(&mut filter);
+        assert_eq!(shown.len(), 2);
+        // Off by default, so repeats on the next keystroke come straight back
+        assert_eq!(shown_ids(&mut filter), shown);
+
+        filter.set_suggestion_cooldown(Duration::from_secs(60));
+        assert!(filter.dismiss_suggestion_by_id(&shown[0]));
+        assert!(!filter.dismiss_suggestion_by_id("never_shown"));
+        assert_eq!(shown_ids(&mut filter), vec![shown[1].clone()]);
+    }
+
+    #[test]
+    fn test_diversity_filter_can_be_disabled() {
+        let filter = ContextAwareFilter::with_config(FilterConfiguration {
+            min_type_variety: 0,
+            ..FilterConfiguration::default()
+        });
+        let filtered = filter.filter_for_diversity(skewed_suggestions()).unwrap();
+
+        assert_eq!(filtered.len(), 10);
+        assert!(filtered.iter().all(|s| s.suggestion_type == SuggestionType::CodeCompletion));
+    }
+
+    #[test]
+    fn test_diversity_score() {
+        let uniform: Vec<CodeSuggestion> = (0..4)
+            .map(|i| suggestion(i, SuggestionType::CodeCompletion, 0.9))
+            .collect();
+        assert_eq!(ContextAwareFilter::diversity_score(&uniform), 0.0);
+
+        let spread = vec![
+            suggestion(0, SuggestionType::CodeCompletion, 0.9),
+            suggestion(1, SuggestionType::Documentation, 0.9),
+            suggestion(2, SuggestionType::VariableNaming, 0.9),
+        ];
+        assert!((ContextAwareFilter::diversity_score(&spread) - 1.0).abs() < 1e-9);
+        assert!(ContextAwareFilter::diversity_score(&skewed_suggestions()) < 0.5);
+    }
+
+    fn project(files: &[&str], dependencies: &[&str]) -> ProjectInfo {
+        ProjectInfo {
+            project_path: "/projects/sample".to_string(),
+            project_name: "sample".to_string(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            file_count: files.len() as u32,
+            total_lines_of_code: 500,
+            files: files.iter().map(|f| f.to_string()).collect(),
+            manifests: HashMap::new(),
+            directories: Vec::new(),
+        }
+    }
+
+    fn with_directories(directories: &[&str]) -> ProjectInfo {
+        let mut info = project(&[], &[]);
+        info.directories = directories.iter().map(|d| d.to_string()).collect();
+        info
+    }
+
+    #[test]
+    fn test_detects_mvc_and_layered_architecture() {
+        let analyzer = ProjectAnalyzer::new();
+        let info = with_directories(&["app", "app/controllers", "app/models", "app/views/", "app/services", "app/repositories"]);
+        let patterns = analyzer.detect_architectural_patterns(&info).unwrap();
+
+        assert_eq!(patterns, vec![ArchitecturalPattern::MVC, ArchitecturalPattern::LayeredArchitecture]);
+    }
+
+    #[test]
+    fn test_detects_microservices_and_falls_back_to_modular() {
+        let analyzer = ProjectAnalyzer::new();
+        let services = with_directories(&["user-service", "user-service/src", "order-service", "billing_service", "docs"]);
+        assert_eq!(
+            analyzer.detect_architectural_patterns(&services).unwrap(),
+            vec![ArchitecturalPattern::MicroserviceArchitecture]
+        );
+
+        let two_services = with_directories(&["user-service", "order-service", "services/payment-service"]);
+        assert_eq!(
+            analyzer.detect_architectural_patterns(&two_services).unwrap(),
+            vec![ArchitecturalPattern::ModularArchitecture]
+        );
+    }
+
+    #[test]
+    fn test_detects_django_project() {
+        let analyzer = ProjectAnalyzer::new();
+        let info = project(&["manage.py", "blog/models.py", "blog/views.py", "requirements.txt"], &[]);
+        assert_eq!(analyzer.determine_project_type(&info).unwrap(), ProjectType::WebBackend);
+    }
+
+    #[test]
+    fn test_detects_rust_library() {
+        let analyzer = ProjectAnalyzer::new();
+        let mut info = project(&["Cargo.toml", "src/lib.rs", "src/bin/tool.rs", "src/main.rs"], &["serde"]);
+        info.manifests.insert("Cargo.toml".to_string(), "[package]\nname = \"sample\"\n\n[lib]\npath = \"src/lib.rs\"".to_string());
+        assert_eq!(analyzer.determine_project_type(&info).unwrap(), ProjectType::Library);
+
+        let binary = project(&["Cargo.toml", "src/main.rs"], &[]);
+        assert_eq!(analyzer.determine_project_type(&binary).unwrap(), ProjectType::SystemTool);
+    }
+
+    #[test]
+    fn test_detects_static_web_and_platform_projects() {
+        let analyzer = ProjectAnalyzer::new();
+        let site = project(&["index.html", "css/site.css", "js/app.js"], &[]);
+        assert_eq!(analyzer.determine_project_type(&site).unwrap(), ProjectType::WebFrontend);
+
+        let mobile = project(&["android/app/src/main/AndroidManifest.xml", "lib/main.dart"], &[]);
+        assert_eq!(analyzer.determine_project_type(&mobile).unwrap(), ProjectType::MobileApp);
+
+        let game = project(&["Cargo.toml", "src/main.rs"], &["bevy"]);
+        assert_eq!(analyzer.determine_project_type(&game).unwrap(), ProjectType::GameDevelopment);
+
+        let desktop = project(&["package.json", "main.js"], &["electron"]);
+        assert_eq!(analyzer.determine_project_type(&desktop).unwrap(), ProjectType::DesktopApp);
+    }
+
+    #[test]
+    fn test_server_frameworks_outrank_kotlin_and_swift_sources() {
+        let analyzer = ProjectAnalyzer::new();
+        let spring = project(&["build.gradle.kts", "src/main/kotlin/Application.kt"], &["org.springframework.boot:spring-boot-starter-web"]);
+        assert_eq!(analyzer.determine_project_type(&spring).unwrap(), ProjectType::WebBackend);
+
+        let vapor = project(&["Package.swift", "Sources/App/routes.swift"], &["vapor"]);
+        assert_eq!(analyzer.determine_project_type(&vapor).unwrap(), ProjectType::WebBackend);
+
+        let app = project(&["app/src/main/java/com/example/MainActivity.kt"], &["androidx.core:core-ktx"]);
+        assert_eq!(analyzer.determine_project_type(&app).unwrap(), ProjectType::MobileApp);
+    }
+
+    #[test]
+    fn test_dependencies_match_by_exact_name() {
+        let analyzer = ProjectAnalyzer::new();
+        // Neither is React or Express, so neither makes this a web project
+        let streams = project(&["Cargo.toml", "src/main.rs"], &["reactive-streams", "expressive"]);
+        assert_eq!(analyzer.determine_project_type(&streams).unwrap(), ProjectType::SystemTool);
+
+        let angular = project(&["package.json", "src/main.ts"], &["@angular/core"]);
+        assert_eq!(analyzer.determine_project_type(&angular).unwrap(), ProjectType::WebFrontend);
+    }
 
     #[test]
     fn test_context_aware_filter_creation() {
@@ -1183,6 +1974,160 @@ mod tests {
         assert!(filter.filter_config.min_quality_threshold > 0.0);
     }
 
-    // Other tests would go here with proper data structure initialization
+    #[test]
+    fn test_cursor_contexts_use_line_under_each_cursor() {
+        let context = multi_cursor_context();
+        assert_eq!(context.cursors().len(), 2);
+
+        let contexts = context.cursor_contexts();
+        assert_eq!(contexts.len(), 2);
+        assert_eq!(contexts[1].current_line, "    let total = 0;");
+        assert!(contexts.iter().all(|c| c.additional_cursors.is_empty()));
+    }
+
+    #[test]
+    fn test_context_analysis_per_cursor() {
+        let mut analyzer = ContextAnalyzer::new();
+        let analyses = analyzer.analyze_cursors(&multi_cursor_context()).unwrap();
+
+        assert_eq!(analyses.len(), 2);
+        assert_eq!(analyses[0].current_context_type, ContextType::FunctionDefinition);
+        assert_eq!(analyses[1].current_context_type, ContextType::VariableDeclaration);
+        assert_eq!(analyses[0].semantic_context.current_scope.scope_type, "global");
+        assert_eq!(analyses[1].semantic_context.current_scope.scope_type, "local");
+    }
+
+    #[test]
+    fn test_rejected_as_irrelevant_raises_confidence_threshold() {
+        let mut tracker = crate::user_behavior_tracker::UserBehaviorTracker::new();
+        let engine = PreferenceEngine::new();
+        assert_eq!(engine.derive_confidence_threshold(&tracker.analyze_behavior()), DEFAULT_CONFIDENCE_THRESHOLD);
+
+        for (i, reason) in [RejectionReason::Irrelevant, RejectionReason::Incorrect, RejectionReason::WrongStyle].iter().enumerate() {
+            tracker.track_suggestion_interaction_with_reason(&format!("s{}", i), false, None, Some(*reason));
+        }
+        let raised = engine.derive_confidence_threshold(&tracker.analyze_behavior());
+        assert!((raised - (DEFAULT_CONFIDENCE_THRESHOLD + 2.0 * REJECTION_THRESHOLD_STEP)).abs() < 1e-9);
+
+        for i in 0..50 {
+            tracker.track_suggestion_interaction_with_reason(&format!("r{}", i), false, None, Some(RejectionReason::Irrelevant));
+        }
+        assert_eq!(engine.derive_confidence_threshold(&tracker.analyze_behavior()), MAX_CONFIDENCE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_irrelevant_rejections_raise_the_bar_in_filtering() {
+        let mut filter = ContextAwareFilter::new();
+        let mut tracker = crate::user_behavior_tracker::UserBehaviorTracker::new();
+        let filter_once = |filter: &mut ContextAwareFilter, behavior: &BehaviorAnalysis| {
+            filter.filter_suggestions(camel_case_suggestions(), &multi_cursor_context(), behavior, &project(&[], &[])).unwrap().len()
+        };
+
+        assert_eq!(filter_once(&mut filter, &tracker.analyze_behavior()), 2);
+        for i in 0..3 {
+            tracker.track_suggestion_interaction_with_reason(&format!("s{}", i), false, None, Some(RejectionReason::Irrelevant));
+        }
+        // The cached preferences still pick up the higher threshold, so the 0.75 suggestion drops out
+        assert_eq!(filter_once(&mut filter, &tracker.analyze_behavior()), 1);
+    }
+
+    #[test]
+    fn test_feedback_by_id_feeds_rejection_history() {
+        let mut filter = ContextAwareFilter::new();
+        let behavior = crate::user_behavior_tracker::UserBehaviorTracker::new().analyze_behavior();
+        let shown = filter.filter_suggestions(camel_case_suggestions(), &multi_cursor_context(), &behavior, &project(&[], &[])).unwrap();
+        assert!(shown.iter().any(|suggestion| suggestion.id == "suggestion_0"));
+
+        for _ in 0..3 {
+            assert!(filter.record_suggestion_feedback("suggestion_0", false, None));
+        }
+        assert!(!filter.record_suggestion_feedback("never_shown", false, None));
+        let shown = filter.filter_suggestions(camel_case_suggestions(), &multi_cursor_context(), &behavior, &project(&[], &[])).unwrap();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].suggestion_type, SuggestionType::VariableNaming);
+    }
+
+    // Survives the default camelCase style preference, unlike `suggestion`'s `value_N` code
+    fn camel_case_suggestions() -> Vec<CodeSuggestion> {
+        vec![
+            CodeSuggestion { suggested_code: "totalValue".to_string(), ..suggestion(0, SuggestionType::CodeCompletion, 0.75) },
+            CodeSuggestion { suggested_code: "itemCount".to_string(), ..suggestion(1, SuggestionType::VariableNaming, 0.95) },
+        ]
+    }
+
+    fn mixed_suggestions() -> Vec<CodeSuggestion> {
+        vec![
+            suggestion(0, SuggestionType::Documentation, 0.9),
+            suggestion(1, SuggestionType::CodeCompletion, 0.9),
+        ]
+    }
+
+    #[test]
+    fn test_repeatedly_rejected_type_is_suppressed_in_context() {
+        let mut filter = ContextAwareFilter::new();
+        filter.record_feedback(SuggestionType::Documentation, ContextType::General, false);
+        filter.record_feedback(SuggestionType::Documentation, ContextType::General, false);
+        let kept = filter.filter_by_rejection_history(mixed_suggestions(), &ContextType::General).unwrap();
+        assert_eq!(kept.len(), 2);
+
+        filter.record_feedback(SuggestionType::Documentation, ContextType::General, false);
+        let kept = filter.filter_by_rejection_history(mixed_suggestions(), &ContextType::General).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].suggestion_type, SuggestionType::CodeCompletion);
+
+        // Rejections in another context do not carry over
+        let elsewhere = filter.filter_by_rejection_history(mixed_suggestions(), &ContextType::FunctionDefinition).unwrap();
+        assert_eq!(elsewhere.len(), 2);
+
+        // Nor do rejections of a suggestion as a duplicate
+        for _ in 0..3 {
+            filter.record_feedback_with_reason(SuggestionType::Documentation, ContextType::FunctionDefinition, false, Some(RejectionReason::Duplicate));
+        }
+        assert_eq!(filter.filter_by_rejection_history(mixed_suggestions(), &ContextType::FunctionDefinition).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_old_rejections_fall_out_of_the_feedback_window() {
+        let mut filter = ContextAwareFilter::with_config(FilterConfiguration {
+            feedback_window: 4,
+            ..FilterConfiguration::default()
+        });
+        for _ in 0..3 {
+            filter.record_feedback(SuggestionType::Documentation, ContextType::General, false);
+        }
+        assert_eq!(filter.filter_by_rejection_history(mixed_suggestions(), &ContextType::General).unwrap().len(), 1);
+
+        filter.record_feedback(SuggestionType::Documentation, ContextType::General, true);
+        filter.record_feedback(SuggestionType::CodeCompletion, ContextType::General, true);
+        assert_eq!(filter.feedback_history.len(), 4);
+        assert_eq!(filter.filter_by_rejection_history(mixed_suggestions(), &ContextType::General).unwrap().len(), 2);
+    }
+
+    fn coded_suggestion(id: usize, code: &str) -> CodeSuggestion {
+        CodeSuggestion {
+            suggested_code: code.to_string(),
+            ..suggestion(id, SuggestionType::CodeCompletion, 0.9)
+        }
+    }
+
+    #[test]
+    fn test_identifier_normalizing_hash_collapses_renamed_suggestions() {
+        let renamed = || vec![
+            coded_suggestion(0, "let total = items.len();"),
+            coded_suggestion(1, "let count = values.len();"),
+            coded_suggestion(2, "let count = count.len();"),
+        ];
+
+        let literal = ContextAwareFilter::new();
+        assert_eq!(literal.filter_for_diversity(renamed()).unwrap().len(), 3);
+
+        let normalizing = ContextAwareFilter::with_config(FilterConfiguration {
+            diversity_hash_mode: DiversityHashMode::NormalizeIdentifiers,
+            ..FilterConfiguration::default()
+        });
+        let kept = normalizing.filter_for_diversity(renamed()).unwrap();
+        // Reusing one name for both identifiers is a different structure
+        let ids: Vec<&str> = kept.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["suggestion_0", "suggestion_2"]);
+    }
 }
-*/
@@ -0,0 +1,53 @@
+use regex::Regex;
+
+/// How a custom detector recognizes its pattern in source code
+pub enum CustomMatcher {
+    Regex(Regex),
+    Predicate(Box<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+/// A team-specific pattern reported as `PatternType::Custom(name)`
+pub struct CustomDetector {
+    pub name: String,
+    matcher: CustomMatcher,
+}
+
+impl CustomDetector {
+    pub fn new(name: &str, matcher: CustomMatcher) -> Self {
+        Self { name: name.to_string(), matcher }
+    }
+
+    pub fn from_regex(name: &str, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self::new(name, CustomMatcher::Regex(Regex::new(pattern)?)))
+    }
+
+    /// Occurrences in `code` and the first matched text; `None` when the pattern is absent.
+    /// Predicates only say whether the pattern is present, so they count once with no text
+    pub fn detect(&self, code: &str) -> Option<(u32, String)> {
+        match &self.matcher {
+            CustomMatcher::Regex(regex) => {
+                let mut matches = regex.find_iter(code);
+                let first = matches.next()?.as_str().to_string();
+                Some((1 + matches.count() as u32, first))
+            }
+            CustomMatcher::Predicate(predicate) => predicate(code).then(|| (1, String::new())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_detector_counts_matches() {
+        let detector = CustomDetector::from_regex("todo_marker", r"TODO\(\w+\)").unwrap();
+        assert_eq!(detector.detect("// TODO(ana) one\n// TODO(raj) two"), Some((2, "TODO(ana)".to_string())));
+        assert_eq!(detector.detect("// nothing left"), None);
+        assert!(CustomDetector::from_regex("broken", "(unclosed").is_err());
+
+        let predicate = CustomDetector::new("long_file", CustomMatcher::Predicate(Box::new(|code| code.lines().count() > 2)));
+        assert_eq!(predicate.detect("a\nb\nc"), Some((1, String::new())));
+        assert_eq!(predicate.detect("a"), None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::CodingPattern;
+
+/// A top-level block of source and the patterns detected in it
+#[derive(Debug, Clone)]
+pub struct CachedRegion {
+    pub text: String,
+    pub patterns: Vec<CodingPattern>,
+}
+
+/// Patterns detected for the last version of a file analyzed in diff mode
+#[derive(Debug, Clone)]
+pub struct DiffCache {
+    pub language: String,
+    pub regions: Vec<CachedRegion>,
+}
+
+impl DiffCache {
+    /// Cached patterns for an unchanged region with identical text
+    pub fn find_unchanged(&self, text: &str) -> Option<&CachedRegion> {
+        self.regions.iter().find(|region| region.text == text)
+    }
+}
+
+/// Regions of a diff analysis, before the patterns found in its changed regions are recorded
+#[derive(Debug, Clone)]
+pub struct DetectedDiff {
+    pub language: String,
+    pub regions: Vec<CachedRegion>,
+    /// Patterns from the regions that had to be re-analyzed
+    pub detected: Vec<CodingPattern>,
+}
+
+/// Range of lines in `new_code` that differ from `old_code`, as `[start, end)`
+pub fn changed_line_range(old_code: &str, new_code: &str) -> Option<(usize, usize)> {
+    let old_lines: Vec<&str> = old_code.lines().collect();
+    let new_lines: Vec<&str> = new_code.lines().collect();
+
+    let common_prefix = old_lines.iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if common_prefix == old_lines.len() && common_prefix == new_lines.len() {
+        return None;
+    }
+
+    let max_suffix = old_lines.len().min(new_lines.len()) - common_prefix;
+    let common_suffix = old_lines.iter().rev()
+        .zip(new_lines.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    Some((common_prefix, new_lines.len() - common_suffix))
+}
+
+/// Split code into top-level regions, each starting at an unindented line after a blank line
+pub fn split_regions(code: &str) -> Vec<(usize, usize, String)> {
+    let lines: Vec<&str> = code.lines().collect();
+    let mut regions = Vec::new();
+    let mut start = 0;
+
+    for index in 1..lines.len() {
+        let line = lines[index];
+        let starts_region = !line.trim().is_empty()
+            && !line.starts_with(char::is_whitespace)
+            && lines[index - 1].trim().is_empty();
+
+        if starts_region {
+            regions.push((start, index, lines[start..index].join("\n")));
+            start = index;
+        }
+    }
+
+    if start < lines.len() {
+        regions.push((start, lines.len(), lines[start..].join("\n")));
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_line_range() {
+        let old = "a\nb\nc\nd";
+        assert_eq!(changed_line_range(old, old), None);
+        assert_eq!(changed_line_range(old, "a\nB\nc\nd"), Some((1, 2)));
+        assert_eq!(changed_line_range(old, "a\nb\nx\ny\nc\nd"), Some((2, 4)));
+        assert_eq!(changed_line_range(old, "a\nd"), Some((1, 1)));
+        assert_eq!(changed_line_range("", "a"), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_split_regions() {
+        let code = "fn a() {\n    1\n}\n\nfn b() {\n\n    2\n}\n\n\nfn c() {}";
+        let regions = split_regions(code);
+        assert_eq!(regions.len(), 3);
+        assert_eq!((regions[0].0, regions[0].1), (0, 4));
+        assert_eq!((regions[1].0, regions[1].1), (4, 10));
+        assert_eq!(regions[2].2, "fn c() {}");
+    }
+}
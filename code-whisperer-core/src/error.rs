@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Why an analysis could not produce a result; serialized with a `kind` tag for the wasm boundary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalysisError {
+    ParseFailed {
+        language: String,
+        message: String,
+        line: Option<u32>, // 1-based, when the parser reports a position
+    },
+    UnsupportedLanguage {
+        language: String,
+    },
+    EmptyInput,
+    InputTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    InvalidInput {
+        message: String, // what was wrong with the caller's JSON or arguments
+    },
+    Internal {
+        message: String,
+    },
+}
+
+impl AnalysisError {
+    pub fn parse_failed(language: &str, message: impl Into<String>, line: Option<u32>) -> Self {
+        AnalysisError::ParseFailed {
+            language: language.to_string(),
+            message: message.into(),
+            line,
+        }
+    }
+
+    /// Reject input over `limit` bytes before any parsing is attempted
+    pub fn check_input_size(code: &str, limit: usize) -> Result<(), AnalysisError> {
+        if code.len() > limit {
+            Err(AnalysisError::InputTooLarge { size: code.len(), limit })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// JSON form handed to JavaScript callers
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| format!("{{\"kind\":\"internal\",\"message\":{:?}}}", self.to_string()))
+    }
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::ParseFailed { language, message, line: Some(line) } => {
+                write!(f, "{} parsing error at line {}: {}", language, line, message)
+            },
+            AnalysisError::ParseFailed { language, message, line: None } => {
+                write!(f, "{} parsing error: {}", language, message)
+            },
+            AnalysisError::UnsupportedLanguage { language } => write!(f, "Unsupported language: {}", language),
+            AnalysisError::EmptyInput => write!(f, "Nothing to analyze"),
+            AnalysisError::InputTooLarge { size, limit } => {
+                write!(f, "Input of {} bytes exceeds the {} byte analysis limit", size, limit)
+            },
+            AnalysisError::InvalidInput { message } => write!(f, "Invalid input: {}", message),
+            AnalysisError::Internal { message } => write!(f, "Internal analysis error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_and_serde_round_trip() {
+        let error = AnalysisError::parse_failed("python", "unexpected indent", Some(3));
+        assert_eq!(error.to_string(), "python parsing error at line 3: unexpected indent");
+
+        let json = error.to_json();
+        assert!(json.contains("\"kind\":\"parse_failed\""));
+        assert_eq!(serde_json::from_str::<AnalysisError>(&json).unwrap(), error);
+
+        assert_eq!(AnalysisError::EmptyInput.to_json(), "{\"kind\":\"empty_input\"}");
+
+        let invalid = AnalysisError::InvalidInput { message: "expected an object".to_string() };
+        assert_eq!(invalid.to_string(), "Invalid input: expected an object");
+        assert!(invalid.to_json().contains("\"kind\":\"invalid_input\""));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::ast_parser::ParsedAst;
+use crate::code_structure_analyzer::CodeSmellKind;
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+use rustpython_parser::ast::{self as py_ast, Ranged};
+
+/// Exception types broad enough to hide bugs when caught without re-raising
+const BROAD_PYTHON_EXCEPTIONS: &[&str] = &["Exception", "BaseException"];
+
+/// An error handler that hides failures instead of dealing with them
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultyHandler {
+    pub kind: CodeSmellKind, // `EmptyCatch`, `BroadCatch` or `SwallowedException`
+    pub handler: String,     // the handler's header, e.g. `catch (err)` or `except Exception`
+    pub line_number: u32,
+}
+
+/// Empty handlers in JavaScript, TypeScript and Python, broad Python `except`s that never
+/// re-raise, and JavaScript `catch`es that neither rethrow nor look at the error. A handler
+/// holding only a comment is taken as deliberately ignoring the error
+pub fn faulty_handlers(ast: &ParsedAst, code: &str, language: &str) -> Vec<FaultyHandler> {
+    let mut handlers = match ast {
+        ParsedAst::JavaScript(_) => javascript_handlers(code, language),
+        ParsedAst::Python(suite) => {
+            let mut handlers = Vec::new();
+            python_handlers(suite, code, &mut handlers);
+            handlers
+        }
+        _ => Vec::new(),
+    };
+    handlers.sort_by_key(|handler| handler.line_number);
+    handlers
+}
+
+fn javascript_handlers(code: &str, language: &str) -> Vec<FaultyHandler> {
+    let tokens: Vec<Token> = Tokenizer::for_language(code, language).collect();
+    let mut handlers = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        // `.catch(...)` on a promise is a method call, not a handler
+        let is_method = index > 0 && tokens[index - 1].text == ".";
+        if token.kind != TokenKind::Keyword || token.text != "catch" || is_method {
+            continue;
+        }
+
+        let mut cursor = index + 1;
+        let mut bindings = Vec::new();
+        if tokens.get(cursor).is_some_and(|token| token.text == "(") {
+            while let Some(token) = tokens.get(cursor).filter(|token| token.text != ")") {
+                if token.kind == TokenKind::Identifier {
+                    bindings.push(token.text);
+                }
+                cursor += 1;
+            }
+            cursor += 1;
+        }
+        if tokens.get(cursor).is_none_or(|token| token.text != "{") {
+            continue;
+        }
+        let body = braced_body(&tokens[cursor..]);
+
+        let header = match bindings.as_slice() {
+            [] => "catch".to_string(),
+            _ => format!("catch ({})", bindings.join(", ")),
+        };
+        let kind = if body.is_empty() {
+            Some(CodeSmellKind::EmptyCatch)
+        } else if body.iter().all(|token| token.kind == TokenKind::Comment) {
+            None
+        } else {
+            let rethrows = body.iter().any(|token| token.kind == TokenKind::Keyword && token.text == "throw");
+            let inspects = body.iter().any(|token| token.kind == TokenKind::Identifier && bindings.contains(&token.text));
+            (!rethrows && !inspects).then_some(CodeSmellKind::SwallowedException)
+        };
+        if let Some(kind) = kind {
+            handlers.push(FaultyHandler { kind, handler: header, line_number: token.line });
+        }
+    }
+
+    handlers
+}
+
+/// Tokens between the `{` opening `tokens` and its matching `}`
+fn braced_body<'t, 'a>(tokens: &'t [Token<'a>]) -> &'t [Token<'a>] {
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match (token.kind, token.text) {
+            (TokenKind::Punctuation, "{") => depth += 1,
+            (TokenKind::Punctuation, "}") => {
+                depth -= 1;
+                if depth == 0 {
+                    return &tokens[1..index];
+                }
+            }
+            _ => {}
+        }
+    }
+    &tokens[1.min(tokens.len())..]
+}
+
+fn python_handlers(body: &[py_ast::Stmt], code: &str, handlers: &mut Vec<FaultyHandler>) {
+    for stmt in body {
+        if let py_ast::Stmt::Try(py_ast::StmtTry { handlers: excepts, .. }) | py_ast::Stmt::TryStar(py_ast::StmtTryStar { handlers: excepts, .. }) = stmt {
+            handlers.extend(excepts.iter().filter_map(|except| {
+                let py_ast::ExceptHandler::ExceptHandler(except) = except;
+                python_handler_fault(except, code)
+            }));
+        }
+        for block in python_child_blocks(stmt) {
+            python_handlers(block, code, handlers);
+        }
+    }
+}
+
+fn python_handler_fault(except: &py_ast::ExceptHandlerExceptHandler, code: &str) -> Option<FaultyHandler> {
+    let caught = except.type_.as_deref().map(|type_| {
+        code.get(type_.start().to_usize()..type_.end().to_usize()).unwrap_or_default().to_string()
+    });
+    let header = match &caught {
+        Some(caught) => format!("except {}", caught),
+        None => "except".to_string(),
+    };
+    let is_broad = caught.as_deref().is_none_or(|caught| BROAD_PYTHON_EXCEPTIONS.contains(&caught));
+    let is_empty = except.body.iter().all(|stmt| match stmt {
+        py_ast::Stmt::Pass(_) => true,
+        // A bare `...` placeholder
+        py_ast::Stmt::Expr(expr) => matches!(&*expr.value, py_ast::Expr::Constant(constant) if constant.value.is_ellipsis()),
+        _ => false,
+    });
+
+    let kind = if is_empty {
+        CodeSmellKind::EmptyCatch
+    } else if is_broad && !python_raises(&except.body) {
+        CodeSmellKind::BroadCatch
+    } else {
+        return None;
+    };
+    let line_number = code.get(..except.range.start().to_usize()).unwrap_or(code).matches('\n').count() as u32 + 1;
+    Some(FaultyHandler { kind, handler: header, line_number })
+}
+
+/// Whether a `raise` runs somewhere in `body`, outside nested functions and classes
+fn python_raises(body: &[py_ast::Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        py_ast::Stmt::Raise(_) => true,
+        py_ast::Stmt::FunctionDef(_) | py_ast::Stmt::AsyncFunctionDef(_) | py_ast::Stmt::ClassDef(_) => false,
+        _ => python_child_blocks(stmt).into_iter().any(python_raises),
+    })
+}
+
+/// Statement blocks directly inside `stmt`, `except` bodies included
+pub(crate) fn python_child_blocks(stmt: &py_ast::Stmt) -> Vec<&[py_ast::Stmt]> {
+    match stmt {
+        py_ast::Stmt::FunctionDef(def) => vec![&def.body],
+        py_ast::Stmt::AsyncFunctionDef(def) => vec![&def.body],
+        py_ast::Stmt::ClassDef(def) => vec![&def.body],
+        py_ast::Stmt::If(if_stmt) => vec![&if_stmt.body, &if_stmt.orelse],
+        py_ast::Stmt::For(for_stmt) => vec![&for_stmt.body, &for_stmt.orelse],
+        py_ast::Stmt::AsyncFor(for_stmt) => vec![&for_stmt.body, &for_stmt.orelse],
+        py_ast::Stmt::While(while_stmt) => vec![&while_stmt.body, &while_stmt.orelse],
+        py_ast::Stmt::With(with) => vec![&with.body],
+        py_ast::Stmt::AsyncWith(with) => vec![&with.body],
+        py_ast::Stmt::Match(match_stmt) => match_stmt.cases.iter().map(|case| case.body.as_slice()).collect(),
+        py_ast::Stmt::Try(py_ast::StmtTry { body, handlers, orelse, finalbody, .. })
+        | py_ast::Stmt::TryStar(py_ast::StmtTryStar { body, handlers, orelse, finalbody, .. }) => {
+            let mut blocks: Vec<&[py_ast::Stmt]> = vec![body, orelse, finalbody];
+            blocks.extend(handlers.iter().map(|handler| {
+                let py_ast::ExceptHandler::ExceptHandler(handler) = handler;
+                handler.body.as_slice()
+            }));
+            blocks
+        }
+        _ => Vec::new(),
+    }
+}
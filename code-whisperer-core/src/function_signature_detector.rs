@@ -1,7 +1,14 @@
 use crate::ast_parser::{AstParser, ParsedAst};
+use crate::code_structure_analyzer::cyclomatic_complexity;
+use crate::error::AnalysisError;
 use std::collections::HashMap;
 use regex::Regex;
 use lazy_static::lazy_static;
+use quote::ToTokens;
+use rustpython_parser::ast::{self as py_ast, Ranged};
+use swc_ecma_ast as swc_ast;
+use swc_common::Spanned;
+use wasm_bindgen::prelude::*;
 
 /// Function signature pattern detector that analyzes function definitions and usage patterns
 pub struct FunctionSignatureDetector {
@@ -15,6 +22,8 @@ pub struct FunctionSignatureAnalysis {
     pub return_type_patterns: ReturnTypePatterns,
     pub naming_conventions: FunctionNamingConventions,
     pub signature_complexity: SignatureComplexity,
+    pub accessor_analysis: AccessorAnalysis,
+    pub parameter_order: Vec<ParameterOrderInconsistency>,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +36,10 @@ pub struct FunctionPattern {
     pub documentation: Option<String>,
     pub language: String,
     pub line_number: u32,
+    pub is_void: Option<bool>, // inferred from the body or signature, None when unknown
+    pub owner: Option<String>, // enclosing class, struct or impl type
+    pub cyclomatic_complexity: Option<f32>, // of the body alone; None when there is no body to measure
+    pub decorators: Vec<String>, // Python decorators as written, without the `@`, e.g. `app.route("/")`
 }
 
 #[derive(Debug, Clone)]
@@ -120,6 +133,32 @@ pub struct SignatureComplexity {
     pub nesting_depth_tolerance: u32,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyAccessor {
+    pub owner: String,
+    pub property: String,
+    pub getter: Option<String>,
+    pub setter: Option<String>,
+}
+
+/// Two functions taking some of the same parameters in a different relative order,
+/// such as `(user, id)` and `(id, user)`, which invites swapped arguments at call sites
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterOrderInconsistency {
+    pub first: String,
+    pub first_line: u32,
+    pub second: String,
+    pub second_line: u32,
+    pub shared_parameters: Vec<String>, // in the order `first` takes them
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccessorAnalysis {
+    pub pairs: Vec<PropertyAccessor>,
+    pub orphan_getters: Vec<PropertyAccessor>,
+    pub orphan_setters: Vec<PropertyAccessor>,
+}
+
 lazy_static! {
     // Function naming pattern regexes
     static ref VERB_PATTERNS: Regex = Regex::new(r"^(get|set|is|has|can|should|will|create|make|build|add|remove|delete|update|modify|fetch|retrieve|find|search|calculate|compute|process|handle|execute|run|start|stop|parse|validate|format|convert|transform|render|draw|save|load|open|close|connect|disconnect|send|receive|emit|listen|subscribe|publish|register|unregister|enable|disable|activate|deactivate|initialize|destroy|reset|clear|copy|clone|merge|split|join|sort|filter|map|reduce|forEach|iterate|traverse|visit|compare|equals|contains|includes|indexOf|lastIndexOf|push|pop|shift|unshift|slice|splice|substring|replace|trim|padStart|padEnd|toUpperCase|toLowerCase|toString|valueOf|hasOwnProperty|isPrototypeOf|propertyIsEnumerable)").unwrap();
@@ -133,6 +172,58 @@ lazy_static! {
     static ref UTILITY_PATTERNS: Regex = Regex::new(r"^(parse|format|convert|transform|map|filter|reduce|sort|group|aggregate|summarize|normalize|sanitize|validate|verify|authenticate|authorize|encrypt|decrypt|encode|decode|compress|decompress|serialize|deserialize|stringify|jsonify|xmlify|htmlify|escape|unescape|trim|pad|truncate|wrap|unwrap|clone|copy|merge|extend|assign|pick|omit|pluck|flatten|unflatten|chunk|partition|zip|unzip|intersection|union|difference|unique|distinct|compact|reverse|shuffle|sample|range|sequence|generate|create|build|make|construct|instantiate|initialize|setup|configure|prepare|finalize|cleanup|destroy|dispose|release|free|clear|reset|restart|reload|refresh|sync|async|defer|delay|debounce|throttle|memoize|cache)").unwrap();
 }
 
+// Placeholder owner for method signatures that need a class or impl around them to parse
+const SIGNATURE_WRAPPER: &str = "__SignatureWrapper";
+
+/// Parse a single function signature and describe it as JSON
+#[wasm_bindgen]
+pub fn analyze_function_signature(signature: &str, language: &str) -> String {
+    let function = match FunctionSignatureDetector::new().analyze_signature_line(signature, language) {
+        Ok(function) => function,
+        Err(err) => return serde_json::json!({ "error": err }).to_string(),
+    };
+
+    let parameters: Vec<serde_json::Value> = function.parameters.iter()
+        .map(|param| serde_json::json!({
+            "name": param.name,
+            "type": param.param_type,
+            "default_value": param.default_value,
+            "is_optional": param.is_optional,
+            "is_rest_parameter": param.is_rest_parameter,
+        }))
+        .collect();
+
+    serde_json::json!({
+        "name": function.name,
+        "parameters": parameters,
+        "return_type": function.return_type,
+        "is_async": matches!(function.function_type, FunctionType::Async),
+        "function_type": format!("{:?}", function.function_type),
+        "visibility": format!("{:?}", function.visibility),
+        "verb_category": verb_category(&function.name),
+        "language": function.language,
+    })
+    .to_string()
+}
+
+/// Coarse intent of a function name, judged by its leading verb
+fn verb_category(name: &str) -> &'static str {
+    let lower_name = name.to_lowercase();
+    if BOOLEAN_PATTERNS.is_match(&lower_name) {
+        "boolean"
+    } else if GETTER_PATTERNS.is_match(&lower_name) {
+        "getter"
+    } else if SETTER_PATTERNS.is_match(&lower_name) {
+        "setter"
+    } else if UTILITY_PATTERNS.is_match(&lower_name) {
+        "utility"
+    } else if VERB_PATTERNS.is_match(&lower_name) {
+        "action"
+    } else {
+        "none"
+    }
+}
+
 impl FunctionSignatureDetector {
     pub fn new() -> Self {
         Self {
@@ -141,30 +232,80 @@ impl FunctionSignatureDetector {
     }
 
     /// Analyze function signatures in the given code
-    pub fn analyze_signatures(&self, code: &str, language: &str) -> Result<FunctionSignatureAnalysis, String> {
+    pub fn analyze_signatures(&self, code: &str, language: &str) -> Result<FunctionSignatureAnalysis, AnalysisError> {
         let ast = self.parser.parse_code(code, language)?;
         
-        let function_patterns = self.extract_function_patterns(&ast, language)?;
+        let function_patterns = self.extract_function_patterns(&ast, code, language)?;
         
         Ok(FunctionSignatureAnalysis {
             parameter_patterns: self.analyze_parameter_patterns(&function_patterns),
             return_type_patterns: self.analyze_return_type_patterns(&function_patterns),
             naming_conventions: self.analyze_naming_conventions(&function_patterns),
             signature_complexity: self.analyze_signature_complexity(&function_patterns),
+            accessor_analysis: self.analyze_accessor_pairs(&function_patterns),
+            parameter_order: self.analyze_parameter_order(&function_patterns),
             function_patterns,
         })
     }
 
-    fn extract_function_patterns(&self, ast: &ParsedAst, language: &str) -> Result<Vec<FunctionPattern>, String> {
+    /// Analyze a lone signature line, such as the one being typed, by completing it with an empty body
+    pub fn analyze_signature_line(&self, signature: &str, language: &str) -> Result<FunctionPattern, AnalysisError> {
+        let signature = signature.trim().trim_end_matches(['{', ';', ':']).trim_end();
+        if signature.is_empty() {
+            return Err(AnalysisError::EmptyInput);
+        }
+
+        let language = language.to_lowercase();
+        let candidates = match language.as_str() {
+            "python" => vec![format!("{}:\n    pass\n", signature)],
+            "javascript" | "typescript" => vec![
+                format!("{} {{}}", signature),
+                // Bare method signatures only parse inside a class body
+                format!("class {} {{\n{} {{}}\n}}", SIGNATURE_WRAPPER, signature),
+            ],
+            "rust" => vec![
+                format!("{} {{}}", signature),
+                format!("impl {} {{\n{} {{}}\n}}", SIGNATURE_WRAPPER, signature),
+            ],
+            _ => vec![signature.to_string()],
+        };
+
+        // The unwrapped attempt reports positions in the caller's own text
+        let mut first_error = None;
+        for candidate in candidates {
+            match self.parser.parse_code(&candidate, &language).and_then(|ast| self.extract_function_patterns(&ast, &candidate, &language)) {
+                Ok(functions) => {
+                    if let Some(mut function) = functions.into_iter().next() {
+                        if function.owner.as_deref() == Some(SIGNATURE_WRAPPER) {
+                            function.owner = None;
+                        }
+                        function.line_number = 1;
+                        // The body was made up to get the signature parsed
+                        function.cyclomatic_complexity = None;
+                        return Ok(function);
+                    }
+                },
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                },
+            }
+        }
+
+        Err(first_error.unwrap_or_else(|| {
+            AnalysisError::parse_failed(&language, format!("No function found in signature: {}", signature), None)
+        }))
+    }
+
+    fn extract_function_patterns(&self, ast: &ParsedAst, code: &str, language: &str) -> Result<Vec<FunctionPattern>, AnalysisError> {
         match ast {
             ParsedAst::JavaScript(module) => {
-                self.extract_javascript_functions(module, language)
+                self.extract_javascript_functions(module, code, language)
             },
             ParsedAst::Python(suite) => {
-                self.extract_python_functions(suite, language)
+                self.extract_python_functions(suite, code, language)
             },
             ParsedAst::Rust(items) => {
-                self.extract_rust_functions(items, language)
+                self.extract_rust_functions(items, code, language)
             },
             ParsedAst::Generic(lines) => {
                 self.extract_generic_functions(lines, language)
@@ -172,82 +313,331 @@ impl FunctionSignatureDetector {
         }
     }
 
-    fn extract_javascript_functions(&self, _module: &swc_ecma_ast::Module, language: &str) -> Result<Vec<FunctionPattern>, String> {
-        // TODO: Implement JavaScript function extraction using SWC AST
-        Ok(vec![
-            FunctionPattern {
-                name: "exampleFunction".to_string(),
-                parameters: vec![
-                    ParameterInfo {
-                        name: "param1".to_string(),
-                        param_type: Some("string".to_string()),
-                        default_value: None,
-                        is_optional: false,
-                        is_rest_parameter: false,
-                        annotation: None,
+    fn extract_javascript_functions(&self, module: &swc_ast::Module, code: &str, language: &str) -> Result<Vec<FunctionPattern>, AnalysisError> {
+        let mut functions = Vec::new();
+
+        for item in &module.body {
+            match item {
+                swc_ast::ModuleItem::Stmt(swc_ast::Stmt::Decl(decl)) => {
+                    self.collect_js_decl_functions(decl, Visibility::Private, code, language, &mut functions);
+                },
+                swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDecl(export_decl)) => {
+                    self.collect_js_decl_functions(&export_decl.decl, Visibility::Public, code, language, &mut functions);
+                },
+                swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDefaultDecl(export_default)) => {
+                    if let swc_ast::DefaultDecl::Fn(fn_expr) = &export_default.decl {
+                        let name = fn_expr.ident.as_ref().map(|i| i.sym.to_string()).unwrap_or_else(|| "default".to_string());
+                        functions.push(self.js_function_pattern(name, &fn_expr.function, FunctionType::Regular, Visibility::Public, code, language));
                     }
-                ],
-                return_type: Some("number".to_string()),
-                visibility: Visibility::Public,
-                function_type: FunctionType::Regular,
-                documentation: None,
-                language: language.to_string(),
-                line_number: 1,
+                },
+                _ => {}
             }
-        ])
+        }
+
+        Ok(functions)
     }
 
-    fn extract_python_functions(&self, _suite: &rustpython_parser::ast::Suite, language: &str) -> Result<Vec<FunctionPattern>, String> {
-        // TODO: Implement Python function extraction using rustpython AST
-        Ok(vec![
-            FunctionPattern {
-                name: "example_function".to_string(),
-                parameters: vec![
-                    ParameterInfo {
-                        name: "param1".to_string(),
-                        param_type: Some("str".to_string()),
-                        default_value: None,
-                        is_optional: false,
-                        is_rest_parameter: false,
-                        annotation: None,
+    fn collect_js_decl_functions(&self, decl: &swc_ast::Decl, visibility: Visibility, code: &str, language: &str, functions: &mut Vec<FunctionPattern>) {
+        match decl {
+            swc_ast::Decl::Fn(fn_decl) => {
+                functions.push(self.js_function_pattern(fn_decl.ident.sym.to_string(), &fn_decl.function, FunctionType::Regular, visibility, code, language));
+            },
+            swc_ast::Decl::Class(class_decl) => {
+                for member in &class_decl.class.body {
+                    match member {
+                        swc_ast::ClassMember::Method(method) => {
+                            let function_type = match method.kind {
+                                swc_ast::MethodKind::Getter => FunctionType::Getter,
+                                swc_ast::MethodKind::Setter => FunctionType::Setter,
+                                swc_ast::MethodKind::Method if method.is_static => FunctionType::StaticMethod,
+                                swc_ast::MethodKind::Method => FunctionType::Method,
+                            };
+                            let member_visibility = match method.accessibility {
+                                Some(swc_ast::Accessibility::Private) => Visibility::Private,
+                                Some(swc_ast::Accessibility::Protected) => Visibility::Protected,
+                                _ => Visibility::Public,
+                            };
+                            let mut pattern = self.js_function_pattern(js_prop_name(&method.key), &method.function, function_type, member_visibility, code, language);
+                            pattern.line_number = line_of_offset(code, js_offset(method.span));
+                            pattern.documentation = preceding_block_comment(code, pattern.line_number);
+                            pattern.owner = Some(class_decl.ident.sym.to_string());
+                            functions.push(pattern);
+                        },
+                        swc_ast::ClassMember::Constructor(constructor) => {
+                            let line_number = line_of_offset(code, js_offset(constructor.span));
+                            functions.push(FunctionPattern {
+                                name: "constructor".to_string(),
+                                parameters: constructor.params.iter().filter_map(|param| match param {
+                                    swc_ast::ParamOrTsParamProp::Param(param) => Some(js_parameter_info(&param.pat, code)),
+                                    swc_ast::ParamOrTsParamProp::TsParamProp(_) => None,
+                                }).collect(),
+                                return_type: None,
+                                visibility: Visibility::Public,
+                                function_type: FunctionType::Constructor,
+                                documentation: preceding_block_comment(code, line_number),
+                                language: language.to_string(),
+                                line_number,
+                                is_void: constructor.body.as_ref().map(|body| !js_stmts_return_value(&body.stmts)),
+                                owner: Some(class_decl.ident.sym.to_string()),
+                                cyclomatic_complexity: constructor.body.as_ref().map(|body| cyclomatic_complexity(js_source(code, body.span), language)),
+                                decorators: Vec::new(),
+                            });
+                        },
+                        _ => {}
                     }
-                ],
-                return_type: Some("int".to_string()),
-                visibility: Visibility::Public,
-                function_type: FunctionType::Regular,
-                documentation: None,
-                language: language.to_string(),
-                line_number: 1,
-            }
-        ])
+                }
+            },
+            swc_ast::Decl::Var(var_decl) => {
+                for declarator in &var_decl.decls {
+                    let name = match &declarator.name {
+                        swc_ast::Pat::Ident(ident) => ident.id.sym.to_string(),
+                        _ => continue,
+                    };
+                    let line_number = line_of_offset(code, js_offset(var_decl.span));
+                    match declarator.init.as_deref() {
+                        Some(swc_ast::Expr::Arrow(arrow)) => {
+                            functions.push(FunctionPattern {
+                                name,
+                                parameters: arrow.params.iter().map(|pat| js_parameter_info(pat, code)).collect(),
+                                return_type: arrow.return_type.as_deref().map(|ann| js_type_annotation(ann, code)),
+                                visibility: visibility.clone(),
+                                function_type: if arrow.is_async { FunctionType::Async } else { FunctionType::Arrow },
+                                documentation: preceding_block_comment(code, line_number),
+                                language: language.to_string(),
+                                line_number,
+                                is_void: Some(match &*arrow.body {
+                                    swc_ast::BlockStmtOrExpr::BlockStmt(block) => !js_stmts_return_value(&block.stmts),
+                                    swc_ast::BlockStmtOrExpr::Expr(_) => false,
+                                }),
+                                owner: None,
+                                cyclomatic_complexity: Some(cyclomatic_complexity(js_source(code, arrow.body.span()), language)),
+                                decorators: Vec::new(),
+                            });
+                        },
+                        Some(swc_ast::Expr::Fn(fn_expr)) => {
+                            let mut pattern = self.js_function_pattern(name, &fn_expr.function, FunctionType::Anonymous, visibility.clone(), code, language);
+                            pattern.line_number = line_number;
+                            pattern.documentation = preceding_block_comment(code, line_number);
+                            functions.push(pattern);
+                        },
+                        _ => {}
+                    }
+                }
+            },
+            _ => {}
+        }
     }
 
-    fn extract_rust_functions(&self, _items: &[syn::Item], language: &str) -> Result<Vec<FunctionPattern>, String> {
-        // TODO: Implement Rust function extraction using syn AST
-        Ok(vec![
-            FunctionPattern {
-                name: "example_function".to_string(),
-                parameters: vec![
-                    ParameterInfo {
-                        name: "param1".to_string(),
-                        param_type: Some("String".to_string()),
-                        default_value: None,
-                        is_optional: false,
-                        is_rest_parameter: false,
-                        annotation: None,
+    fn js_function_pattern(&self, name: String, function: &swc_ast::Function, default_type: FunctionType, visibility: Visibility, code: &str, language: &str) -> FunctionPattern {
+        let line_number = line_of_offset(code, js_offset(function.span));
+        let function_type = if function.is_async {
+            FunctionType::Async
+        } else if function.is_generator {
+            FunctionType::Generator
+        } else {
+            default_type
+        };
+
+        FunctionPattern {
+            name,
+            parameters: function.params.iter().map(|param| js_parameter_info(&param.pat, code)).collect(),
+            return_type: function.return_type.as_deref().map(|ann| js_type_annotation(ann, code)),
+            visibility,
+            function_type,
+            documentation: preceding_block_comment(code, line_number),
+            language: language.to_string(),
+            line_number,
+            is_void: function.body.as_ref().map(|body| !function.is_generator && !js_stmts_return_value(&body.stmts)),
+            owner: None,
+            cyclomatic_complexity: function.body.as_ref().map(|body| cyclomatic_complexity(js_source(code, body.span), language)),
+            decorators: Vec::new(),
+        }
+    }
+
+    fn extract_python_functions(&self, suite: &rustpython_parser::ast::Suite, code: &str, language: &str) -> Result<Vec<FunctionPattern>, AnalysisError> {
+        let mut functions = Vec::new();
+        self.collect_python_functions(suite, None, code, language, &mut functions);
+        Ok(functions)
+    }
+
+    fn collect_python_functions(&self, body: &[py_ast::Stmt], class_name: Option<&str>, code: &str, language: &str, functions: &mut Vec<FunctionPattern>) {
+        for stmt in body {
+            let mut pattern = match stmt {
+                py_ast::Stmt::FunctionDef(fn_def) => {
+                    let function_type = python_function_type(&fn_def.name, &fn_def.decorator_list, class_name.is_some(), false);
+                    self.python_function_pattern(&fn_def.name, &fn_def.args, &fn_def.body, fn_def.returns.as_deref(), &fn_def.decorator_list, fn_def.range, function_type, code, language)
+                },
+                py_ast::Stmt::AsyncFunctionDef(fn_def) => {
+                    let function_type = python_function_type(&fn_def.name, &fn_def.decorator_list, class_name.is_some(), true);
+                    self.python_function_pattern(&fn_def.name, &fn_def.args, &fn_def.body, fn_def.returns.as_deref(), &fn_def.decorator_list, fn_def.range, function_type, code, language)
+                },
+                py_ast::Stmt::ClassDef(class_def) => {
+                    self.collect_python_functions(&class_def.body, Some(class_def.name.as_str()), code, language, functions);
+                    continue;
+                },
+                _ => continue,
+            };
+            pattern.owner = class_name.map(str::to_string);
+            functions.push(pattern);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn python_function_pattern(
+        &self,
+        name: &str,
+        args: &py_ast::Arguments,
+        body: &[py_ast::Stmt],
+        returns: Option<&py_ast::Expr>,
+        decorators: &[py_ast::Expr],
+        range: py_ast::text_size::TextRange,
+        function_type: FunctionType,
+        code: &str,
+        language: &str,
+    ) -> FunctionPattern {
+        let mut parameters: Vec<ParameterInfo> = args.posonlyargs.iter()
+            .chain(args.args.iter())
+            .chain(args.kwonlyargs.iter())
+            .filter(|arg| arg.def.arg.as_str() != "self" && arg.def.arg.as_str() != "cls")
+            .map(|arg| ParameterInfo {
+                name: arg.def.arg.to_string(),
+                param_type: arg.def.annotation.as_deref().map(|a| python_source(code, a)),
+                default_value: arg.default.as_deref().map(|d| python_source(code, d)),
+                is_optional: arg.default.is_some(),
+                is_rest_parameter: false,
+                annotation: None,
+            })
+            .collect();
+
+        for rest in args.vararg.iter().chain(args.kwarg.iter()) {
+            parameters.push(ParameterInfo {
+                name: rest.arg.to_string(),
+                param_type: rest.annotation.as_deref().map(|a| python_source(code, a)),
+                default_value: None,
+                is_optional: true,
+                is_rest_parameter: true,
+                annotation: None,
+            });
+        }
+
+        let visibility = if name.starts_with("__") && name.ends_with("__") {
+            Visibility::Public
+        } else if name.starts_with('_') {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        };
+
+        FunctionPattern {
+            name: name.to_string(),
+            parameters,
+            return_type: returns.map(|r| python_source(code, r)),
+            visibility,
+            function_type,
+            documentation: python_docstring(body),
+            language: language.to_string(),
+            line_number: line_of_offset(code, range.start().to_usize()),
+            is_void: Some(!python_stmts_return_value(body)),
+            owner: None,
+            cyclomatic_complexity: Some(cyclomatic_complexity(python_body_source(code, body), language)),
+            decorators: decorators.iter().map(|decorator| python_source(code, decorator)).collect(),
+        }
+    }
+
+    fn extract_rust_functions(&self, items: &[syn::Item], code: &str, language: &str) -> Result<Vec<FunctionPattern>, AnalysisError> {
+        let mut functions = Vec::new();
+        let mut search_from = 0;
+
+        for item in items {
+            match item {
+                syn::Item::Fn(fn_item) => {
+                    functions.push(self.rust_function_pattern(&fn_item.attrs, &fn_item.vis, &fn_item.sig, &fn_item.block, false, code, &mut search_from, language));
+                },
+                syn::Item::Impl(impl_item) => {
+                    let owner = impl_item.self_ty.to_token_stream().to_string();
+                    for member in &impl_item.items {
+                        if let syn::ImplItem::Fn(method) = member {
+                            let mut pattern = self.rust_function_pattern(&method.attrs, &method.vis, &method.sig, &method.block, true, code, &mut search_from, language);
+                            pattern.owner = Some(owner.clone());
+                            functions.push(pattern);
+                        }
                     }
-                ],
-                return_type: Some("i32".to_string()),
-                visibility: Visibility::Public,
-                function_type: FunctionType::Regular,
-                documentation: None,
-                language: language.to_string(),
-                line_number: 1,
+                },
+                _ => {}
             }
-        ])
+        }
+
+        Ok(functions)
     }
 
-    fn extract_generic_functions(&self, lines: &[String], language: &str) -> Result<Vec<FunctionPattern>, String> {
+    #[allow(clippy::too_many_arguments)]
+    fn rust_function_pattern(
+        &self,
+        attrs: &[syn::Attribute],
+        vis: &syn::Visibility,
+        sig: &syn::Signature,
+        block: &syn::Block,
+        in_impl: bool,
+        code: &str,
+        search_from: &mut usize,
+        language: &str,
+    ) -> FunctionPattern {
+        let name = sig.ident.to_string();
+        let has_receiver = sig.inputs.iter().any(|arg| matches!(arg, syn::FnArg::Receiver(_)));
+
+        let parameters = sig.inputs.iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(pat_type) => Some(ParameterInfo {
+                    name: pat_type.pat.to_token_stream().to_string(),
+                    param_type: Some(pat_type.ty.to_token_stream().to_string()),
+                    default_value: None,
+                    is_optional: false,
+                    is_rest_parameter: false,
+                    annotation: None,
+                }),
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect();
+
+        let function_type = if sig.asyncness.is_some() {
+            FunctionType::Async
+        } else if in_impl && name == "new" && !has_receiver {
+            FunctionType::Constructor
+        } else if in_impl && has_receiver {
+            FunctionType::Method
+        } else if in_impl {
+            FunctionType::StaticMethod
+        } else {
+            FunctionType::Regular
+        };
+
+        let visibility = match vis {
+            syn::Visibility::Public(_) => Visibility::Public,
+            syn::Visibility::Restricted(_) => Visibility::Internal,
+            syn::Visibility::Inherited => Visibility::Private,
+        };
+
+        FunctionPattern {
+            name: name.clone(),
+            parameters,
+            return_type: match &sig.output {
+                syn::ReturnType::Default => None,
+                syn::ReturnType::Type(_, ty) => Some(ty.to_token_stream().to_string()),
+            },
+            visibility,
+            function_type,
+            documentation: rust_doc_comment(attrs),
+            language: language.to_string(),
+            // syn spans carry no line info without proc-macro2's span-locations, so locate by text
+            line_number: find_rust_fn_line(code, &name, search_from),
+            is_void: Some(rust_returns_unit(&sig.output)),
+            owner: None,
+            cyclomatic_complexity: Some(cyclomatic_complexity(&block.to_token_stream().to_string(), language)),
+            decorators: Vec::new(),
+        }
+    }
+
+    fn extract_generic_functions(&self, lines: &[String], language: &str) -> Result<Vec<FunctionPattern>, AnalysisError> {
         let mut functions = Vec::new();
         
         for (line_num, line) in lines.iter().enumerate() {
@@ -278,6 +668,10 @@ impl FunctionSignatureDetector {
                 documentation: None,
                 language: language.to_string(),
                 line_number,
+                is_void: None,
+                owner: None,
+                cyclomatic_complexity: None,
+                decorators: Vec::new(),
             })
         } else {
             None
@@ -383,9 +777,11 @@ impl FunctionSignatureDetector {
                 *return_types.entry(return_type.clone()).or_insert(0) += 1;
                 explicit_types += 1;
 
-                if return_type.to_lowercase().contains("void") || return_type.to_lowercase().contains("none") {
+                if return_type.to_lowercase().contains("void") || return_type.to_lowercase().contains("none") || return_type == "()" {
                     void_functions += 1;
                 }
+            } else if function.is_void == Some(true) {
+                void_functions += 1;
             }
 
             if matches!(function.function_type, FunctionType::Async) {
@@ -531,6 +927,90 @@ impl FunctionSignatureDetector {
         }
     }
 
+    /// Pair `getFoo`/`setFoo`, `is_foo`/`set_foo` and native accessors within each class
+    pub fn analyze_accessor_pairs(&self, functions: &[FunctionPattern]) -> AccessorAnalysis {
+        let mut accessors: Vec<PropertyAccessor> = Vec::new();
+        let mut index: HashMap<(String, String), usize> = HashMap::new();
+        let mut plain_methods: HashMap<(String, String), String> = HashMap::new();
+
+        for function in functions {
+            let Some(owner) = &function.owner else { continue };
+            let role = match function.function_type {
+                FunctionType::Getter => Some((true, function.name.clone())),
+                FunctionType::Setter => Some((false, function.name.clone())),
+                _ => accessor_property(&function.name),
+            };
+
+            let Some((is_getter, property)) = role else {
+                if function.parameters.is_empty() {
+                    plain_methods.insert((owner.clone(), property_key(&function.name)), function.name.clone());
+                }
+                continue;
+            };
+
+            let key = (owner.clone(), property_key(&property));
+            let slot = *index.entry(key).or_insert_with(|| {
+                accessors.push(PropertyAccessor {
+                    owner: owner.clone(),
+                    property: property.clone(),
+                    getter: None,
+                    setter: None,
+                });
+                accessors.len() - 1
+            });
+            let accessor = &mut accessors[slot];
+            if is_getter {
+                accessor.getter.get_or_insert_with(|| function.name.clone());
+            } else {
+                accessor.setter.get_or_insert_with(|| function.name.clone());
+            }
+        }
+
+        let mut analysis = AccessorAnalysis::default();
+        for mut accessor in accessors {
+            // Rust-style `name()` getters pair with `set_name()` without a prefix
+            if accessor.getter.is_none() {
+                accessor.getter = plain_methods.get(&(accessor.owner.clone(), property_key(&accessor.property))).cloned();
+            }
+            match (&accessor.getter, &accessor.setter) {
+                (Some(_), Some(_)) => analysis.pairs.push(accessor),
+                (Some(_), None) => analysis.orphan_getters.push(accessor),
+                _ => analysis.orphan_setters.push(accessor),
+            }
+        }
+        analysis
+    }
+
+    /// Pairs of functions sharing two or more parameter names (case-insensitively) in orders that disagree
+    pub fn analyze_parameter_order(&self, functions: &[FunctionPattern]) -> Vec<ParameterOrderInconsistency> {
+        let names: Vec<Vec<String>> = functions.iter()
+            .map(|function| function.parameters.iter().map(|param| param.name.to_lowercase()).collect())
+            .collect();
+
+        let mut inconsistencies = Vec::new();
+        for (i, first) in functions.iter().enumerate() {
+            for (j, second) in functions.iter().enumerate().skip(i + 1) {
+                let shared: Vec<usize> = (0..names[i].len()).filter(|&k| names[j].contains(&names[i][k])).collect();
+                if shared.len() < 2 {
+                    continue;
+                }
+                let positions: Vec<usize> = shared.iter()
+                    .filter_map(|&k| names[j].iter().position(|name| *name == names[i][k]))
+                    .collect();
+                if positions.windows(2).any(|pair| pair[0] > pair[1]) {
+                    inconsistencies.push(ParameterOrderInconsistency {
+                        first: first.name.clone(),
+                        first_line: first.line_number,
+                        second: second.name.clone(),
+                        second_line: second.line_number,
+                        shared_parameters: shared.iter().map(|&k| first.parameters[k].name.clone()).collect(),
+                    });
+                }
+            }
+        }
+        inconsistencies
+    }
+
     fn detect_naming_style(&self, names: &[String]) -> crate::pattern_extractor::NamingStyle {
         if names.is_empty() {
             return crate::pattern_extractor::NamingStyle::Unknown;
@@ -566,6 +1046,254 @@ impl FunctionSignatureDetector {
     }
 }
 
+// swc source maps start the first file at BytePos(1)
+fn js_offset(span: swc_common::Span) -> usize {
+    span.lo.0.saturating_sub(1) as usize
+}
+
+/// Source text covered by an swc span
+fn js_source(code: &str, span: swc_common::Span) -> &str {
+    code.get(js_offset(span)..span.hi.0.saturating_sub(1) as usize).unwrap_or_default()
+}
+
+/// Source text from the first statement of a Python body to the end of the last
+fn python_body_source<'a>(code: &'a str, body: &[py_ast::Stmt]) -> &'a str {
+    match (body.first(), body.last()) {
+        (Some(first), Some(last)) => code.get(first.start().to_usize()..last.end().to_usize()).unwrap_or_default(),
+        _ => "",
+    }
+}
+
+/// 1-based line number of a byte offset
+fn line_of_offset(code: &str, offset: usize) -> u32 {
+    let offset = offset.min(code.len());
+    code.as_bytes()[..offset].iter().filter(|&&b| b == b'\n').count() as u32 + 1
+}
+
+/// Text of a `/** ... */` block ending on the line right above `line_number`
+fn preceding_block_comment(code: &str, line_number: u32) -> Option<String> {
+    let lines: Vec<&str> = code.lines().collect();
+    let mut index = (line_number as usize).checked_sub(2)?;
+    if !lines.get(index)?.trim().ends_with("*/") {
+        return None;
+    }
+
+    let end = index;
+    while !lines[index].trim_start().starts_with("/**") {
+        index = index.checked_sub(1)?;
+    }
+
+    let text: Vec<String> = lines[index..=end]
+        .iter()
+        .map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("/**").unwrap_or(line);
+            let line = line.strip_suffix("*/").unwrap_or(line);
+            let line = line.trim();
+            line.strip_prefix('*').unwrap_or(line).trim().to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if text.is_empty() { None } else { Some(text.join("\n")) }
+}
+
+fn js_prop_name(key: &swc_ast::PropName) -> String {
+    match key {
+        swc_ast::PropName::Ident(ident) => ident.sym.to_string(),
+        swc_ast::PropName::Str(s) => s.value.to_string(),
+        swc_ast::PropName::Num(n) => n.value.to_string(),
+        _ => "computed".to_string(),
+    }
+}
+
+// TypeScript annotations, rendered from source so generics survive intact
+fn js_type_annotation(annotation: &swc_ast::TsTypeAnn, code: &str) -> String {
+    js_source(code, annotation.type_ann.span()).trim().to_string()
+}
+
+fn js_parameter_info(pat: &swc_ast::Pat, code: &str) -> ParameterInfo {
+    match pat {
+        swc_ast::Pat::Ident(ident) => ParameterInfo {
+            name: ident.id.sym.to_string(),
+            param_type: ident.type_ann.as_deref().map(|ann| js_type_annotation(ann, code)),
+            default_value: None,
+            is_optional: ident.id.optional,
+            is_rest_parameter: false,
+            annotation: None,
+        },
+        swc_ast::Pat::Assign(assign) => ParameterInfo {
+            default_value: Some(js_source(code, assign.right.span()).trim().to_string()),
+            is_optional: true,
+            ..js_parameter_info(&assign.left, code)
+        },
+        swc_ast::Pat::Rest(rest) => {
+            let inner = js_parameter_info(&rest.arg, code);
+            ParameterInfo {
+                param_type: rest.type_ann.as_deref().map(|ann| js_type_annotation(ann, code)).or(inner.param_type),
+                is_rest_parameter: true,
+                is_optional: true,
+                ..inner
+            }
+        },
+        _ => ParameterInfo {
+            name: "destructured".to_string(),
+            param_type: None,
+            default_value: None,
+            is_optional: false,
+            is_rest_parameter: false,
+            annotation: None,
+        },
+    }
+}
+
+// Whether any reachable `return` yields a value; nested functions and classes are skipped
+fn js_stmts_return_value(stmts: &[swc_ast::Stmt]) -> bool {
+    stmts.iter().any(js_stmt_returns_value)
+}
+
+fn js_stmt_returns_value(stmt: &swc_ast::Stmt) -> bool {
+    match stmt {
+        swc_ast::Stmt::Return(ret) => ret.arg.is_some(),
+        swc_ast::Stmt::Block(block) => js_stmts_return_value(&block.stmts),
+        swc_ast::Stmt::If(if_stmt) => {
+            js_stmt_returns_value(&if_stmt.cons) || if_stmt.alt.as_deref().is_some_and(js_stmt_returns_value)
+        },
+        swc_ast::Stmt::For(for_stmt) => js_stmt_returns_value(&for_stmt.body),
+        swc_ast::Stmt::ForIn(for_in) => js_stmt_returns_value(&for_in.body),
+        swc_ast::Stmt::ForOf(for_of) => js_stmt_returns_value(&for_of.body),
+        swc_ast::Stmt::While(while_stmt) => js_stmt_returns_value(&while_stmt.body),
+        swc_ast::Stmt::DoWhile(do_while) => js_stmt_returns_value(&do_while.body),
+        swc_ast::Stmt::Labeled(labeled) => js_stmt_returns_value(&labeled.body),
+        swc_ast::Stmt::With(with) => js_stmt_returns_value(&with.body),
+        swc_ast::Stmt::Switch(switch) => switch.cases.iter().any(|case| js_stmts_return_value(&case.cons)),
+        swc_ast::Stmt::Try(try_stmt) => {
+            js_stmts_return_value(&try_stmt.block.stmts)
+                || try_stmt.handler.as_ref().is_some_and(|handler| js_stmts_return_value(&handler.body.stmts))
+                || try_stmt.finalizer.as_ref().is_some_and(|finalizer| js_stmts_return_value(&finalizer.stmts))
+        },
+        _ => false,
+    }
+}
+
+// Like the JavaScript walk, but a bare `yield` also hands values back to the caller
+fn python_stmts_return_value(body: &[py_ast::Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        py_ast::Stmt::Return(ret) => ret.value.is_some(),
+        py_ast::Stmt::Expr(expr) => matches!(*expr.value, py_ast::Expr::Yield(_) | py_ast::Expr::YieldFrom(_)),
+        py_ast::Stmt::If(s) => python_stmts_return_value(&s.body) || python_stmts_return_value(&s.orelse),
+        py_ast::Stmt::For(s) => python_stmts_return_value(&s.body) || python_stmts_return_value(&s.orelse),
+        py_ast::Stmt::AsyncFor(s) => python_stmts_return_value(&s.body) || python_stmts_return_value(&s.orelse),
+        py_ast::Stmt::While(s) => python_stmts_return_value(&s.body) || python_stmts_return_value(&s.orelse),
+        py_ast::Stmt::With(s) => python_stmts_return_value(&s.body),
+        py_ast::Stmt::AsyncWith(s) => python_stmts_return_value(&s.body),
+        py_ast::Stmt::Match(s) => s.cases.iter().any(|case| python_stmts_return_value(&case.body)),
+        py_ast::Stmt::Try(s) => {
+            python_stmts_return_value(&s.body)
+                || python_stmts_return_value(&s.orelse)
+                || python_stmts_return_value(&s.finalbody)
+                || s.handlers.iter().any(|py_ast::ExceptHandler::ExceptHandler(handler)| python_stmts_return_value(&handler.body))
+        },
+        _ => false,
+    })
+}
+
+fn rust_returns_unit(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Default => true,
+        syn::ReturnType::Type(_, ty) => matches!(&**ty, syn::Type::Tuple(tuple) if tuple.elems.is_empty()),
+    }
+}
+
+fn python_source(code: &str, expr: &py_ast::Expr) -> String {
+    let range = expr.range();
+    code.get(range.start().to_usize()..range.end().to_usize())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn python_docstring(body: &[py_ast::Stmt]) -> Option<String> {
+    match body.first()? {
+        py_ast::Stmt::Expr(expr_stmt) => match expr_stmt.value.as_ref() {
+            py_ast::Expr::Constant(constant) => match &constant.value {
+                py_ast::Constant::Str(text) => Some(text.trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Split an accessor-style name into (is_getter, property), e.g. `getUserName` -> (true, "UserName")
+fn accessor_property(name: &str) -> Option<(bool, String)> {
+    ["get", "is", "has", "set"].iter().find_map(|&prefix| {
+        let rest = name.strip_prefix(prefix)?;
+        let rest = rest.strip_prefix('_').unwrap_or_else(|| if rest.starts_with(char::is_uppercase) { rest } else { "" });
+        if rest.is_empty() {
+            return None;
+        }
+        Some((prefix != "set", rest.to_string()))
+    })
+}
+
+// `UserName`, `userName` and `user_name` all name the same property
+fn property_key(property: &str) -> String {
+    property.chars().filter(|c| *c != '_').flat_map(char::to_lowercase).collect()
+}
+
+// Inside a class, decorators such as `@staticmethod` or `@property` outrank `async def`, which
+// outranks a plain method; module-level functions are only ever regular or async
+fn python_function_type(name: &str, decorators: &[py_ast::Expr], in_class: bool, is_async: bool) -> FunctionType {
+    let has_decorator = |wanted: &str| decorators.iter().any(|d| match d {
+        py_ast::Expr::Name(n) => n.id.as_str() == wanted,
+        py_ast::Expr::Attribute(a) => a.attr.as_str() == wanted,
+        _ => false,
+    });
+
+    if !in_class {
+        if is_async { FunctionType::Async } else { FunctionType::Regular }
+    } else if name == "__init__" {
+        FunctionType::Constructor
+    } else if has_decorator("staticmethod") || has_decorator("classmethod") {
+        FunctionType::StaticMethod
+    } else if has_decorator("property") || has_decorator("cached_property") {
+        FunctionType::Getter
+    } else if has_decorator("setter") {
+        FunctionType::Setter
+    } else if is_async {
+        FunctionType::Async
+    } else {
+        FunctionType::Method
+    }
+}
+
+fn rust_doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs.iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }), .. }) => {
+                Some(s.value().trim().to_string())
+            },
+            _ => None,
+        })
+        .collect();
+
+    if lines.is_empty() { None } else { Some(lines.join("\n")) }
+}
+
+fn find_rust_fn_line(code: &str, name: &str, search_from: &mut usize) -> u32 {
+    let fn_regex = Regex::new(&format!(r"\bfn\s+{}\b", regex::escape(name))).unwrap();
+    let start = (*search_from).min(code.len());
+    match fn_regex.find(&code[start..]) {
+        Some(found) => {
+            *search_from = start + found.end();
+            line_of_offset(code, start + found.start())
+        },
+        None => 0,
+    }
+}
+
 impl Default for FunctionSignatureDetector {
     fn default() -> Self {
         Self::new()
@@ -627,6 +1355,10 @@ mod tests {
                 documentation: None,
                 language: "javascript".to_string(),
                 line_number: 1,
+                is_void: None,
+                owner: None,
+                cyclomatic_complexity: None,
+                decorators: Vec::new(),
             }
         ];
 
@@ -692,6 +1424,10 @@ mod tests {
                 documentation: None,
                 language: "javascript".to_string(),
                 line_number: 1,
+                is_void: None,
+                owner: None,
+                cyclomatic_complexity: None,
+                decorators: Vec::new(),
             },
             FunctionPattern {
                 name: "complexFunc".to_string(),
@@ -706,6 +1442,10 @@ mod tests {
                 documentation: None,
                 language: "javascript".to_string(),
                 line_number: 5,
+                is_void: None,
+                owner: None,
+                cyclomatic_complexity: None,
+                decorators: Vec::new(),
             }
         ];
 
@@ -713,4 +1453,208 @@ mod tests {
         assert_eq!(complexity.average_parameters, 2.0);
         assert_eq!(complexity.max_parameters_seen, 3);
     }
+
+    #[test]
+    fn test_accessor_pairs_and_orphans() {
+        let detector = FunctionSignatureDetector::new();
+        let code = r#"
+            class Account {
+                getBalance() { return this.balance; }
+                setBalance(value) { this.balance = value; }
+                isActive() { return this.active; }
+                setOwner(owner) { this.owner = owner; }
+            }
+            function getConfig() { return {}; }
+        "#;
+        let accessors = detector.analyze_signatures(code, "javascript").unwrap().accessor_analysis;
+
+        assert_eq!(accessors.pairs.len(), 1);
+        assert_eq!(accessors.pairs[0].owner, "Account");
+        assert_eq!(accessors.pairs[0].getter.as_deref(), Some("getBalance"));
+        assert_eq!(accessors.pairs[0].setter.as_deref(), Some("setBalance"));
+        assert_eq!(accessors.orphan_getters.len(), 1);
+        assert_eq!(accessors.orphan_getters[0].property, "Active");
+        assert_eq!(accessors.orphan_setters.len(), 1);
+        assert_eq!(accessors.orphan_setters[0].setter.as_deref(), Some("setOwner"));
+    }
+
+    #[test]
+    fn test_parameter_order_inconsistency_is_flagged() {
+        let detector = FunctionSignatureDetector::new();
+        let code = "function grantRole(user, role, expiresAt) {}\nfunction revokeRole(role, user) {}\nfunction auditRole(user, role) {}\nfunction notify(user) {}\n";
+        let order = detector.analyze_signatures(code, "javascript").unwrap().parameter_order;
+
+        assert_eq!(order.len(), 2);
+        assert_eq!((order[0].first.as_str(), order[0].first_line), ("grantRole", 1));
+        assert_eq!((order[0].second.as_str(), order[0].second_line), ("revokeRole", 2));
+        assert_eq!(order[0].shared_parameters, vec!["user", "role"]);
+        assert_eq!((order[1].first.as_str(), order[1].second.as_str()), ("revokeRole", "auditRole"));
+
+        // `self` is not a parameter, so the methods agree on `(key, value)`
+        let rust = "struct Cache;\nimpl Cache {\n    fn put(&mut self, key: u32, value: u32) {}\n    fn replace(&mut self, key: u32, value: u32, ttl: u64) {}\n}\n";
+        assert!(detector.analyze_signatures(rust, "rust").unwrap().parameter_order.is_empty());
+    }
+
+    #[test]
+    fn test_rust_and_python_accessor_styles() {
+        let detector = FunctionSignatureDetector::new();
+        let rust = "struct User { name: String }\nimpl User {\n    pub fn name(&self) -> &str { &self.name }\n    pub fn set_name(&mut self, name: String) { self.name = name; }\n    pub fn set_email(&mut self, email: String) {}\n}\n";
+        let accessors = detector.analyze_signatures(rust, "rust").unwrap().accessor_analysis;
+        assert_eq!(accessors.pairs.len(), 1);
+        assert_eq!(accessors.pairs[0].getter.as_deref(), Some("name"));
+        assert_eq!(accessors.orphan_setters[0].property, "email");
+
+        let python = "class Point:\n    @property\n    def x(self):\n        return self._x\n\n    @x.setter\n    def x(self, value):\n        self._x = value\n";
+        let accessors = detector.analyze_signatures(python, "python").unwrap().accessor_analysis;
+        assert_eq!(accessors.pairs.len(), 1);
+        assert_eq!(accessors.pairs[0].owner, "Point");
+    }
+
+    #[test]
+    fn test_python_decorators_and_async_defs_set_function_type() {
+        let detector = FunctionSignatureDetector::new();
+        let code = "class Account:\n    @property\n    def balance(self):\n        return self._balance\n\n    @staticmethod\n    async def fetch(account_id):\n        return await load(account_id)\n\n    async def refresh(self):\n        await self.sync()\n\n@app.route('/accounts')\n@login_required\nasync def list_accounts():\n    return []\n";
+        let functions = detector.analyze_signatures(code, "python").unwrap().function_patterns;
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["balance", "fetch", "refresh", "list_accounts"]);
+
+        let types: Vec<&FunctionType> = functions.iter().map(|f| &f.function_type).collect();
+        assert!(matches!(types.as_slice(), [FunctionType::Getter, FunctionType::StaticMethod, FunctionType::Async, FunctionType::Async]));
+        let decorators: Vec<&[String]> = functions.iter().map(|f| f.decorators.as_slice()).collect();
+        assert_eq!(decorators, vec![&["property".to_string()][..], &["staticmethod".to_string()], &[], &["app.route('/accounts')".to_string(), "login_required".to_string()]]);
+    }
+
+    #[test]
+    fn test_javascript_jsdoc_is_captured() {
+        let detector = FunctionSignatureDetector::new();
+        let code = r#"
+/**
+ * Adds two numbers.
+ * @param {number} a
+ */
+function add(a, b = 2) {
+    return a + b;
+}
+
+function undocumented(...rest) {}
+"#;
+
+        let result = detector.analyze_signatures(code, "javascript").unwrap();
+        assert_eq!(result.function_patterns.len(), 2);
+
+        let add = &result.function_patterns[0];
+        assert_eq!(add.name, "add");
+        assert_eq!(add.line_number, 6);
+        assert_eq!(add.documentation.as_deref(), Some("Adds two numbers.\n@param {number} a"));
+        assert_eq!(add.parameters.len(), 2);
+        assert_eq!(add.parameters[1].default_value.as_deref(), Some("2"));
+
+        let undocumented = &result.function_patterns[1];
+        assert!(undocumented.documentation.is_none());
+        assert!(undocumented.parameters[0].is_rest_parameter);
+    }
+
+    #[test]
+    fn test_python_docstring_is_captured() {
+        let detector = FunctionSignatureDetector::new();
+        let code = "def greet(name: str, punctuation='!') -> str:\n    \"\"\"Return a friendly greeting.\"\"\"\n    return 'Hi ' + name + punctuation\n\nasync def fetch():\n    pass\n";
+
+        let result = detector.analyze_signatures(code, "python").unwrap();
+        assert_eq!(result.function_patterns.len(), 2);
+
+        let greet = &result.function_patterns[0];
+        assert_eq!(greet.documentation.as_deref(), Some("Return a friendly greeting."));
+        assert_eq!(greet.return_type.as_deref(), Some("str"));
+        assert_eq!(greet.parameters[0].param_type.as_deref(), Some("str"));
+        assert!(greet.parameters[1].is_optional);
+
+        let fetch = &result.function_patterns[1];
+        assert!(fetch.documentation.is_none());
+        assert!(matches!(fetch.function_type, FunctionType::Async));
+        assert_eq!(fetch.line_number, 5);
+    }
+
+    #[test]
+    fn test_rust_doc_comment_is_captured() {
+        let detector = FunctionSignatureDetector::new();
+        let code = "/// Computes the answer.\n/// Always 42.\npub fn answer(scale: u32) -> u32 {\n    42 * scale\n}\n";
+
+        let result = detector.analyze_signatures(code, "rust").unwrap();
+        assert_eq!(result.function_patterns.len(), 1);
+
+        let answer = &result.function_patterns[0];
+        assert_eq!(answer.documentation.as_deref(), Some("Computes the answer.\nAlways 42."));
+        assert_eq!(answer.line_number, 3);
+        assert!(matches!(answer.visibility, Visibility::Public));
+        assert_eq!(answer.return_type.as_deref(), Some("u32"));
+    }
+
+    #[test]
+    fn test_analyze_typescript_signature_without_body() {
+        let json = analyze_function_signature("async function fetchUser(id: string): Promise<User>", "typescript");
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["name"], "fetchUser");
+        assert_eq!(value["is_async"], true);
+        assert_eq!(value["parameters"][0]["name"], "id");
+        assert_eq!(value["parameters"][0]["type"], "string");
+        assert_eq!(value["return_type"], "Promise<User>");
+        assert_eq!(value["verb_category"], "getter");
+    }
+
+    #[test]
+    fn test_analyze_method_and_python_signatures() {
+        let detector = FunctionSignatureDetector::new();
+
+        let method = detector.analyze_signature_line("pub fn set_name(&mut self, name: String)", "rust").unwrap();
+        assert_eq!(method.name, "set_name");
+        assert!(method.owner.is_none());
+        assert_eq!(method.parameters[0].param_type.as_deref(), Some("String"));
+
+        let python = detector.analyze_signature_line("def is_valid(value: int) -> bool:", "python").unwrap();
+        assert_eq!(python.return_type.as_deref(), Some("bool"));
+        assert_eq!(verb_category(&python.name), "boolean");
+
+        let error: serde_json::Value = serde_json::from_str(&analyze_function_signature("   ", "javascript")).unwrap();
+        assert_eq!(error["error"]["kind"], "empty_input");
+    }
+
+    #[test]
+    fn test_void_functions_inferred_without_annotations() {
+        let detector = FunctionSignatureDetector::new();
+
+        let js = "function logUser(user) {\n  console.log(user);\n  if (!user) { return; }\n}\nfunction getName(user) {\n  const inner = () => { return 1; };\n  return user.name;\n}\nconst notify = (msg) => { alert(msg); };\n";
+        let result = detector.analyze_signatures(js, "javascript").unwrap();
+        let voids: Vec<Option<bool>> = result.function_patterns.iter().map(|f| f.is_void).collect();
+        assert_eq!(voids, vec![Some(true), Some(false), Some(true)]);
+        assert!((result.return_type_patterns.void_function_percentage - 2.0 / 3.0).abs() < 1e-6);
+
+        let python = "def save(item):\n    if item:\n        store(item)\n\ndef load():\n    try:\n        return read()\n    except IOError:\n        pass\n";
+        let result = detector.analyze_signatures(python, "python").unwrap();
+        assert_eq!(result.return_type_patterns.void_function_percentage, 0.5);
+
+        let rust = "fn reset(&mut self) {}\nfn clear() -> () {}\nfn size() -> usize { 0 }\n";
+        let result = detector.analyze_signatures(rust, "rust").unwrap();
+        assert!((result.return_type_patterns.void_function_percentage - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_each_function_gets_its_own_cyclomatic_complexity() {
+        let detector = FunctionSignatureDetector::new();
+        let complexities = |code: &str, language: &str| -> Vec<Option<f32>> {
+            detector.analyze_signatures(code, language).unwrap().function_patterns.iter().map(|f| f.cyclomatic_complexity).collect()
+        };
+
+        let js = "function add(a, b) {\n  return a + b;\n}\nfunction classify(n) {\n  if (n < 0 && n > -10) { return 'small'; }\n  while (n > 100) { n = n / 2; }\n  return n > 5 ? 'big' : 'mid';\n}\n";
+        assert_eq!(complexities(js, "javascript"), vec![Some(1.0), Some(5.0)]);
+
+        let python = "def add(a, b):\n    return a + b\n\ndef classify(n):\n    if n < 0:\n        return 'negative'\n    for step in range(n):\n        n -= step\n    return n\n";
+        assert_eq!(complexities(python, "python"), vec![Some(1.0), Some(3.0)]);
+
+        let rust = "fn add(a: u32, b: u32) -> u32 { a + b }\nfn classify(n: i32) -> i32 {\n    if n < 0 || n > 9 { return 0; }\n    n\n}\n";
+        assert_eq!(complexities(rust, "rust"), vec![Some(1.0), Some(3.0)]);
+
+        // A bare signature has no body to measure
+        assert_eq!(detector.analyze_signature_line("function classify(n)", "javascript").unwrap().cyclomatic_complexity, None);
+    }
 }
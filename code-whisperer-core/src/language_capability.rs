@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::ast_parser::GENERIC_LANGUAGES;
+
+/// Languages with a real parser behind them
+const AST_LANGUAGES: &[&str] = &["javascript", "typescript", "python", "rust"];
+
+/// Which analyses a language gets; everything else falls back to regex pattern detection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageCapability {
+    pub name: String,
+    pub ast_supported: bool,
+    pub style_supported: bool,
+    pub structure_supported: bool,
+}
+
+/// Capabilities for `language`. Generic names get line-based style and structure analysis
+pub fn language_capability(language: &str) -> LanguageCapability {
+    let name = language.to_lowercase();
+    let ast_supported = AST_LANGUAGES.contains(&name.as_str());
+    let line_based = GENERIC_LANGUAGES.contains(&name.as_str());
+
+    LanguageCapability {
+        ast_supported,
+        style_supported: ast_supported || line_based,
+        structure_supported: ast_supported || line_based,
+        name,
+    }
+}
+
+/// Every language with more than regex-only support
+pub fn supported_languages() -> Vec<LanguageCapability> {
+    AST_LANGUAGES.iter()
+        .chain(std::iter::once(&"generic"))
+        .map(|language| language_capability(language))
+        .collect()
+}
+
+/// `supported_languages` as a JSON array, for graying out features in the editor
+#[wasm_bindgen]
+pub fn supported_languages_json() -> String {
+    serde_json::to_string(&supported_languages()).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsed_languages_report_ast_support() {
+        let languages = supported_languages();
+        for name in ["rust", "javascript", "python"] {
+            let capability = languages.iter().find(|capability| capability.name == name).unwrap();
+            assert!(capability.ast_supported && capability.style_supported && capability.structure_supported);
+        }
+
+        assert_eq!(language_capability("Kotlin"), LanguageCapability {
+            name: "kotlin".to_string(),
+            ast_supported: false,
+            style_supported: false,
+            structure_supported: false,
+        });
+
+        let json: serde_json::Value = serde_json::from_str(&supported_languages_json()).unwrap();
+        assert_eq!(json[0]["name"], "javascript");
+        assert_eq!(json[0]["ast_supported"], true);
+    }
+}
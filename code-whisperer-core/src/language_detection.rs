@@ -0,0 +1,121 @@
+use wasm_bindgen::prelude::*;
+use regex::Regex;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref SHEBANG: Regex = Regex::new(r"^#!\s*\S*?(?:/env\s+)?(?:.*/)?(python\d*|node|deno|bash|sh|zsh)\b").unwrap();
+
+    static ref RUST_SIGNALS: Regex = Regex::new(r"(?m)(\bfn\s+\w+\s*(<[^>]*>)?\s*\([^)]*\)\s*->|^\s*(pub\s+)?(fn|struct|enum|impl|trait|mod)\s|\blet\s+mut\s|#\[derive|\buse\s+(std|crate|super)::|&mut\s|\w+::\w+!?\()").unwrap();
+    static ref PYTHON_SIGNALS: Regex = Regex::new(r"(?m)(^\s*def\s+\w+\s*\(.*\)\s*(->\s*[^:]+)?:\s*$|^\s*(el)?if\s.*:\s*$|^\s*class\s+\w+(\(.*\))?:\s*$|^\s*from\s+\S+\s+import\s|\bself\.|__\w+__|^\s*import\s+\w+\s*$)").unwrap();
+    static ref JAVASCRIPT_SIGNALS: Regex = Regex::new(r"(?m)(\bfunction\s*\w*\s*\(|=>|^\s*(const|let|var)\s+\w+\s*=|\bconsole\.\w+\(|\brequire\(|===|!==|^\s*export\s+(default\s+)?|^\s*import\s+.*\s+from\s+['\x22])").unwrap();
+    static ref TYPESCRIPT_SIGNALS: Regex = Regex::new(r"(?m)(^\s*(export\s+)?interface\s+\w+|:\s*(string|number|boolean|void|any)\b|^\s*type\s+\w+\s*=|<\w+>\(|\bas\s+(string|number|const)\b)").unwrap();
+    static ref JAVA_SIGNALS: Regex = Regex::new(r"(?m)(^\s*package\s+[\w.]+;|^\s*import\s+[\w.]+(\.\*)?;|\bpublic\s+(static\s+)?(class|void|final)\b|\bSystem\.out\.|@Override)").unwrap();
+    static ref GO_SIGNALS: Regex = Regex::new(r"(?m)(^\s*package\s+\w+\s*$|^\s*func\s+(\(\w+\s+\*?\w+\)\s*)?\w+\(|:=|^\s*import\s+\(|\bfmt\.\w+\()").unwrap();
+}
+
+/// Guess the language of a snippet from shebangs and characteristic syntax
+#[wasm_bindgen]
+pub fn detect_language(code: &str) -> Option<String> {
+    if let Some(language) = detect_from_shebang(code) {
+        return Some(language.to_string());
+    }
+
+    let count = |regex: &Regex| regex.find_iter(code).count();
+    let javascript = count(&JAVASCRIPT_SIGNALS);
+    let typescript = count(&TYPESCRIPT_SIGNALS);
+
+    let mut scores = [
+        ("rust", count(&RUST_SIGNALS)),
+        ("python", count(&PYTHON_SIGNALS)),
+        ("javascript", javascript),
+        // Type annotations only mean TypeScript when the rest looks like JavaScript
+        ("typescript", if javascript > 0 || typescript > 1 { javascript + typescript * 2 } else { 0 }),
+        ("java", count(&JAVA_SIGNALS) * 2),
+        ("go", count(&GO_SIGNALS) * 2),
+    ];
+
+    // Stable sort keeps the listed order as the tie-breaker
+    scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    match scores[0] {
+        (_, 0) => None,
+        (language, _) => Some(language.to_string()),
+    }
+}
+
+fn detect_from_shebang(code: &str) -> Option<&'static str> {
+    let first_line = code.lines().next()?;
+    let captures = SHEBANG.captures(first_line.trim())?;
+    match captures.get(1)?.as_str() {
+        interpreter if interpreter.starts_with("python") => Some("python"),
+        "node" | "deno" => Some("javascript"),
+        _ => Some("shell"),
+    }
+}
+
+/// Common alternative names, mapped to the canonical names the analyzers match on
+pub const DEFAULT_LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("py", "python"),
+    ("python3", "python"),
+    ("rs", "rust"),
+    ("c++", "cpp"),
+    ("cxx", "cpp"),
+    ("golang", "go"),
+];
+
+/// Whether `language` asks for auto-detection
+pub fn wants_detection(language: &str) -> bool {
+    let language = language.trim();
+    language.is_empty() || language.eq_ignore_ascii_case("auto")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_rust() {
+        let code = "use std::collections::HashMap;\n\npub fn count(words: &[&str]) -> HashMap<String, usize> {\n    let mut map = HashMap::new();\n    map\n}";
+        assert_eq!(detect_language(code).as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_detects_python() {
+        let code = "from typing import List\n\nclass Stack:\n    def push(self, item):\n        self.items.append(item)\n";
+        assert_eq!(detect_language(code).as_deref(), Some("python"));
+        assert_eq!(detect_language("#!/usr/bin/env python3\nprint('hi')").as_deref(), Some("python"));
+    }
+
+    #[test]
+    fn test_detects_javascript_and_typescript() {
+        let js = "const add = (a, b) => a + b;\nfunction log(x) {\n  console.log(x);\n}";
+        assert_eq!(detect_language(js).as_deref(), Some("javascript"));
+
+        let ts = "export interface User {\n  name: string;\n  age: number;\n}\nconst greet = (u: User): string => u.name;";
+        assert_eq!(detect_language(ts).as_deref(), Some("typescript"));
+    }
+
+    #[test]
+    fn test_detects_java_and_go() {
+        let java = "package com.example;\n\nimport java.util.List;\n\npublic class App {\n    public static void main(String[] args) {}\n}";
+        assert_eq!(detect_language(java).as_deref(), Some("java"));
+
+        let go = "package main\n\nimport \"fmt\"\n\nfunc main() {\n    x := 1\n    fmt.Println(x)\n}";
+        assert_eq!(detect_language(go).as_deref(), Some("go"));
+    }
+
+    #[test]
+    fn test_unrecognized_code() {
+        assert_eq!(detect_language("hello world"), None);
+        assert!(wants_detection(""));
+        assert!(wants_detection("AUTO"));
+        assert!(!wants_detection("rust"));
+    }
+}
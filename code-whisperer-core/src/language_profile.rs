@@ -0,0 +1,84 @@
+use crate::abort::AbortFlag;
+use crate::pattern_id::first_line_where;
+use serde::{Deserialize, Serialize};
+
+/// Keyword heuristics for languages without a dedicated parser
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LanguageProfile {
+    pub function_keywords: Vec<String>,
+    pub class_keywords: Vec<String>,
+    pub variable_keywords: Vec<String>,
+    pub comment_tokens: Vec<String>,
+}
+
+impl LanguageProfile {
+    pub fn new(
+        function_keywords: Vec<String>,
+        class_keywords: Vec<String>,
+        variable_keywords: Vec<String>,
+        comment_tokens: Vec<String>,
+    ) -> Self {
+        Self {
+            function_keywords,
+            class_keywords,
+            variable_keywords,
+            comment_tokens,
+        }
+    }
+
+    /// Check whether a line only contains a comment
+    pub fn is_comment(&self, line: &str) -> bool {
+        let trimmed = line.trim_start();
+        self.comment_tokens.iter().any(|token| !token.is_empty() && trimmed.starts_with(token.as_str()))
+    }
+
+    /// Count lines declaring something with one of the given keywords
+    pub fn count_declarations(&self, code: &str, keywords: &[String]) -> usize {
+        code.lines().filter(|line| self.declares(line, keywords)).count()
+    }
+
+    /// Byte offset and text of the first line declaring one of `keywords`, or `None` once `abort` is set
+    pub fn first_declaration<'c>(&self, code: &'c str, keywords: &[String], abort: &AbortFlag) -> Option<(usize, &'c str)> {
+        first_line_where(code, abort, |line| self.declares(line, keywords))
+    }
+
+    fn declares(&self, line: &str, keywords: &[String]) -> bool {
+        !self.is_comment(line) && line.split_whitespace().any(|token| keywords.iter().any(|k| k == token))
+    }
+}
+
+impl Default for LanguageProfile {
+    fn default() -> Self {
+        Self {
+            function_keywords: vec!["function".to_string(), "def".to_string(), "fn".to_string()],
+            class_keywords: Vec::new(),
+            variable_keywords: Vec::new(),
+            comment_tokens: vec!["//".to_string(), "#".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_profile_detects_common_function_keywords() {
+        let profile = LanguageProfile::default();
+        let code = "def foo():\n    pass\nfn bar() {}\n// function commented()";
+        assert_eq!(profile.count_declarations(code, &profile.function_keywords), 2);
+    }
+
+    #[test]
+    fn test_comment_lines_are_ignored() {
+        let profile = LanguageProfile::new(
+            vec!["func".to_string()],
+            vec!["struct".to_string()],
+            vec!["var".to_string(), "let".to_string()],
+            vec!["//".to_string(), "/*".to_string()],
+        );
+        assert!(profile.is_comment("   // func notReal()"));
+        assert!(!profile.is_comment("func real() {}"));
+        assert_eq!(profile.count_declarations("/* struct A */\nstruct B {}", &profile.class_keywords), 1);
+    }
+}
@@ -1,6 +1,10 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+use diff_analysis::{CachedRegion, DetectedDiff, DiffCache};
+use pattern_id::{first_line_where, pattern_id, structural_path};
+use custom_detector::{CustomDetector, CustomMatcher};
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -16,12 +20,32 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     fn log(s: &str);
 }
 
+// Native builds (tests, tooling) have no browser console
+#[cfg(not(target_arch = "wasm32"))]
+fn log(_s: &str) {}
+
+/// Milliseconds since the Unix epoch, from `js_sys::Date` under WASM
+fn now_millis() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now() as u64
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64
+    }
+}
+
 // Core analysis modules
 mod ast_parser;
 mod pattern_extractor;
@@ -29,6 +53,26 @@ mod style_analyzer;
 mod function_signature_detector; // Ganti dari function_signature_analyzer
 mod variable_naming_recognizer;
 mod code_structure_analyzer;
+mod callback_nesting;
+mod exception_handling;
+mod resource_management;
+mod return_consistency;
+mod selection_analysis;
+mod normalized_ast;
+mod module_graph;
+mod language_profile;
+mod comment_scanner;
+mod custom_detector;
+mod diff_analysis;
+mod notebook;
+mod tokenizer;
+mod language_detection;
+mod language_capability;
+mod pattern_id;
+mod abort;
+mod error;
+#[cfg(not(target_arch = "wasm32"))]
+mod shared_analyzer;
 
 // User behavior and storage
 mod user_behavior_tracker;
@@ -42,21 +86,35 @@ mod context_aware_filter;
 // WASM serialization for browser integration
 pub mod wasm_serializer;
 pub mod wasm_interface;
+pub mod wasm_cache;
 
 // Re-export main components
 pub use ast_parser::AstParser;
-pub use pattern_extractor::{PatternExtractor, PatternAnalysis};
-pub use style_analyzer::{CodingStyleAnalyzer, DetailedStyleAnalysis}; // Perbaiki nama
-pub use function_signature_detector::{FunctionSignatureDetector, FunctionSignatureAnalysis}; // Perbaiki nama
-pub use variable_naming_recognizer::{VariableNamingRecognizer, VariableNamingAnalysis};
-pub use code_structure_analyzer::{CodeStructureAnalyzer, CodeStructureAnalysis};
-pub use user_behavior_tracker::{UserBehaviorTracker, BehaviorAnalysis}; // Perbaiki nama
-pub use local_storage_manager::{LocalStorageManager, UserPatternData}; // Perbaiki nama
+pub use pattern_extractor::{PatternExtractor, PatternAnalysis, AnalysisDiff, diff_analyses_json};
+pub use style_analyzer::{CodingStyleAnalyzer, DetailedStyleAnalysis, DebtMarkers, DebtMarker, HardcodedString, MagicNumber, UnusedImport, LineEnding, normalize_line_endings}; // Perbaiki nama
+pub use function_signature_detector::{FunctionSignatureDetector, FunctionSignatureAnalysis, analyze_function_signature}; // Perbaiki nama
+pub use variable_naming_recognizer::{VariableNamingRecognizer, VariableNamingAnalysis, NamingSmell, CanonicalStyleSuggestion, NameRename, SemanticCategory, SemanticPatternConfig, Reliability};
+pub use code_structure_analyzer::{CodeStructureAnalyzer, CodeStructureAnalysis, ComplexityThresholds, FunctionComplexity, SmellThresholds, CodeSmell, CodeSmellKind, FieldInfo, FieldVisibility, DuplicateBlock, DefinitionCounts};
+pub use module_graph::ModuleDependencyGraph;
+pub use language_profile::LanguageProfile;
+pub use normalized_ast::to_normalized_ast;
+pub use comment_scanner::CommentStats;
+pub use notebook::{analyze_notebook, analyze_notebook_cells, NotebookAnalysis, NotebookCellAnalysis, NotebookAggregate, CrossCellReference};
+pub use tokenizer::{tokenize, Token, TokenKind, Tokenizer};
+pub use language_detection::detect_language;
+pub use language_capability::{LanguageCapability, language_capability, supported_languages, supported_languages_json};
+pub use abort::{AbortFlag, AbortableAnalysis};
+pub use error::AnalysisError;
+#[cfg(not(target_arch = "wasm32"))]
+pub use shared_analyzer::SharedPatternAnalyzer;
+pub use user_behavior_tracker::{UserBehaviorTracker, BehaviorAnalysis, Clock, SystemClock, MockClock, WasmBehaviorTracker, RejectionReason}; // Perbaiki nama
+pub use local_storage_manager::{LocalStorageManager, UserPatternData, StyleViolation, Severity, KeyDerivationMethod, KeyDerivationParams, StorageBackend, InMemoryBackend}; // Perbaiki nama
 pub use pattern_scoring_engine::{PatternScoringEngine, ScoringResult};
 pub use suggestion_generation_engine::{SuggestionGenerationEngine, SuggestionResult};
-pub use context_aware_filter::{ContextAwareFilter, CodingContext}; // Perbaiki nama
-pub use wasm_serializer::{WasmSerializer, SerializableAnalysisResult, ComprehensiveAnalysis};
-pub use wasm_interface::{CodeWhispererEngine, EngineConfig, EditorContext};
+pub use context_aware_filter::{ContextAwareFilter, CodingContext, CursorPosition, CursorSuggestions, FilterConfiguration, DiversityHashMode, RankingWeights, SuggestionRanker}; // Perbaiki nama
+pub use wasm_serializer::{WasmSerializer, SerializableAnalysisResult, ComprehensiveAnalysis, OutputVerbosity};
+pub use wasm_interface::{CodeWhispererEngine, EngineConfig, EngineConfigBuilder, EditorContext, configure_engine};
+pub use wasm_cache::WasmCache;
 
 // Core data structures for Code Whisperer
 
@@ -145,10 +203,16 @@ pub struct CodingPattern {
     language: String,
     content: PatternContent,
     confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_confidence: Option<f64>, // confidence before any feedback; unset in patterns saved before recalibration
+    #[serde(default = "no_decay")]
+    decay_factor: f64, // staleness multiplier applied on top of `confidence` when ranking
     frequency: u32,
     last_seen: String,
     source_files: Vec<String>,
     user_feedback: Vec<PatternFeedback>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<SourceLocation>, // where the pattern sits in the analyzed source
 }
 
 #[wasm_bindgen]
@@ -173,23 +237,13 @@ impl CodingPattern {
                 placeholders: vec![],
             },
             confidence,
+            base_confidence: Some(confidence),
+            decay_factor: no_decay(),
             frequency: 1,
-            last_seen: {
-                #[cfg(target_arch = "wasm32")]
-                {
-                    js_sys::Date::now().to_string()
-                }
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_millis()
-                        .to_string()
-                }
-            },
+            last_seen: now_millis().to_string(),
             source_files: vec![],
             user_feedback: vec![],
+            location: None,
         }
     }
 
@@ -221,6 +275,12 @@ impl CodingPattern {
         self.confidence
     }
 
+    /// Stored confidence scaled down by how long ago the pattern was last seen
+    #[wasm_bindgen(getter)]
+    pub fn effective_confidence(&self) -> f64 {
+        self.confidence * self.decay_factor
+    }
+
     #[wasm_bindgen(getter)]
     pub fn frequency(&self) -> u32 {
         self.frequency
@@ -248,7 +308,7 @@ impl CodingPattern {
         };
 
         self.user_feedback.push(PatternFeedback {
-            timestamp: js_sys::Date::now().to_string(),
+            timestamp: now_millis().to_string(),
             feedback_type,
             comments,
         });
@@ -258,6 +318,113 @@ impl CodingPattern {
     pub fn get_feedback_count(&self) -> usize {
         self.user_feedback.len()
     }
+
+    /// Adjust confidence from the recorded feedback, within a bounded range of the initial value
+    #[wasm_bindgen]
+    pub fn recalibrate_confidence(&mut self) -> f64 {
+        let rated: Vec<f64> = self.user_feedback
+            .iter()
+            .filter_map(|feedback| match feedback.feedback_type {
+                FeedbackType::Accepted => Some(1.0),
+                FeedbackType::Modified => Some(0.5),
+                FeedbackType::Rejected => Some(-1.0),
+                FeedbackType::Ignored => None,
+            })
+            .collect();
+        // Patterns saved before recalibration existed still carry their initial confidence
+        let base_confidence = *self.base_confidence.get_or_insert(self.confidence);
+
+        if rated.is_empty() {
+            self.confidence = base_confidence;
+            return self.confidence;
+        }
+
+        let samples = rated.len() as f64;
+        let sentiment = rated.iter().sum::<f64>() / samples;
+        // Few samples only nudge the confidence; it takes consistent feedback to move it far
+        let evidence = samples / (samples + CALIBRATION_PRIOR_SAMPLES);
+
+        self.confidence = (base_confidence + MAX_CALIBRATION_ADJUSTMENT * sentiment * evidence).clamp(0.0, 1.0);
+        self.confidence
+    }
+}
+
+impl CodingPattern {
+    pub fn content(&self) -> &PatternContent {
+        &self.content
+    }
+
+    pub fn location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    pub(crate) fn with_location(mut self, location: SourceLocation) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Drop the detail `verbosity` leaves out: feedback history below `Full`, and the content,
+    /// source files and location in a `Summary`
+    fn trim(&mut self, verbosity: OutputVerbosity) {
+        if verbosity == OutputVerbosity::Full {
+            return;
+        }
+        self.user_feedback.clear();
+        if verbosity == OutputVerbosity::Summary {
+            self.content = PatternContent::String { pattern: String::new(), placeholders: Vec::new() };
+            self.source_files.clear();
+            self.location = None;
+        }
+    }
+
+    fn last_seen_millis(&self) -> u64 {
+        self.last_seen.parse().unwrap_or(0)
+    }
+}
+
+fn no_decay() -> f64 {
+    1.0
+}
+
+const MAX_CALIBRATION_ADJUSTMENT: f64 = 0.3;
+const CALIBRATION_PRIOR_SAMPLES: f64 = 4.0;
+
+/// Days after which a pattern that has not been seen again ranks at half its confidence
+const CONFIDENCE_HALF_LIFE_DAYS: f64 = 14.0;
+const MILLIS_PER_DAY: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Upper bound on tokens kept for a single fallback pattern
+const MAX_FALLBACK_TOKENS: usize = 2000;
+
+/// Patterns kept once the host reports high memory pressure, unless configured otherwise
+const DEFAULT_PRESSURE_PATTERN_CAP: usize = 500;
+
+/// Confidence given to patterns found by user-registered detectors
+const CUSTOM_PATTERN_CONFIDENCE: f64 = 0.6;
+
+/// Most patterns handed to a streaming callback at once
+pub const STREAM_BATCH_SIZE: usize = 32;
+
+/// Latency and filtering controls for a single analysis
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AnalysisOptions {
+    /// Skip AST parsing entirely and use regex detection
+    pub force_regex: bool,
+    /// Files larger than this many bytes go straight to regex detection
+    pub max_ast_size: Option<usize>,
+    /// Files larger than this many bytes are not analyzed at all
+    pub max_input_bytes: Option<usize>,
+    /// Patterns below this confidence are dropped before they are stored
+    pub min_confidence: Option<f64>,
+    /// File the code came from, recorded on every detected pattern for `get_pattern_heatmap`
+    pub file_path: Option<String>,
+}
+
+impl AnalysisOptions {
+    fn skips_ast(&self, code: &str) -> bool {
+        self.force_regex || self.max_ast_size.is_some_and(|max| code.len() > max)
+    }
 }
 
 #[wasm_bindgen]
@@ -265,6 +432,14 @@ pub struct PatternAnalyzer {
     patterns: Vec<CodingPattern>,
     language_stats: HashMap<String, u32>,
     ast_parser: AstParser,
+    language_profiles: HashMap<String, LanguageProfile>,
+    diff_cache: Option<DiffCache>,
+    parse_count: AtomicU32,
+    pressure_pattern_cap: usize,
+    under_memory_pressure: bool,
+    custom_detectors: Vec<CustomDetector>,
+    language_aliases: HashMap<String, String>,
+    verbosity: OutputVerbosity,
 }
 
 #[wasm_bindgen]
@@ -276,69 +451,256 @@ impl PatternAnalyzer {
             patterns: Vec::new(),
             language_stats: HashMap::new(),
             ast_parser: AstParser::new(),
+            language_profiles: HashMap::new(),
+            diff_cache: None,
+            parse_count: AtomicU32::new(0),
+            pressure_pattern_cap: DEFAULT_PRESSURE_PATTERN_CAP,
+            under_memory_pressure: false,
+            custom_detectors: Vec::new(),
+            language_aliases: language_detection::DEFAULT_LANGUAGE_ALIASES.iter()
+                .map(|&(alias, canonical)| (alias.to_string(), canonical.to_string()))
+                .collect(),
+            verbosity: OutputVerbosity::Full,
         }
     }
 
     #[wasm_bindgen]
     pub fn analyze_code(&mut self, code: &str, language: &str) -> Vec<CodingPattern> {
-        console_log!("Analyzing {} characters of {} code using AST parser", code.len(), language);
+        self.analyze_code_with_options(code, language, &AnalysisOptions::default())
+    }
 
-        let mut detected_patterns = Vec::new();
+    /// Analyze the contents of the file at `file_path`, attributing the detected patterns to it
+    #[wasm_bindgen]
+    pub fn analyze_file(&mut self, file_path: &str, code: &str, language: &str) -> Vec<CodingPattern> {
+        let options = AnalysisOptions { file_path: Some(file_path.to_string()), ..AnalysisOptions::default() };
+        self.analyze_code_with_options(code, language, &options)
+    }
 
-        // Update language statistics
-        *self.language_stats.entry(language.to_string()).or_insert(0) += 1;
+    /// Analyze code with `AnalysisOptions` given as JSON, e.g. `{"force_regex": true}`
+    #[wasm_bindgen]
+    pub fn analyze_code_with_options_json(&mut self, code: &str, language: &str, options_json: &str) -> Vec<CodingPattern> {
+        let options = serde_json::from_str::<AnalysisOptions>(options_json).unwrap_or_else(|err| {
+            console_log!("Invalid analysis options, using defaults: {}", err);
+            AnalysisOptions::default()
+        });
+        self.analyze_code_with_options(code, language, &options)
+    }
+
+    /// Analyze code, returning `{"patterns": [...]}` JSON, or `{"error": AnalysisError}` if the
+    /// input is refused or an extractor fails
+    #[wasm_bindgen]
+    pub fn analyze_code_json(&mut self, code: &str, language: &str) -> String {
+        let result = match self.try_analyze_code(code, language, &AnalysisOptions::default(), &AbortFlag::default()) {
+            Ok(analysis) => serde_json::json!({ "patterns": analysis.patterns }),
+            Err(error) => serde_json::json!({ "error": error }),
+        };
+        serde_json::to_string(&result).unwrap_or("{}".to_string())
+    }
+
+    /// Analyze the `start_offset..end_offset` byte range of `full_code`, returning `{"patterns": [...],
+    /// "outer_references": [...]}` JSON: the patterns declared in the selection, and the names it uses
+    /// that are declared elsewhere in the file. Pattern locations are positions in the whole file,
+    /// not the selection. The language is resolved from the whole file
+    #[wasm_bindgen]
+    pub fn analyze_selection(&mut self, full_code: &str, start_offset: usize, end_offset: usize, language: &str) -> String {
+        let language = self.resolve_language(full_code, language);
+        let (start, end) = selection_analysis::clamp_selection(full_code, start_offset, end_offset);
+        let selection = selection_analysis::Selection::new(full_code, start, end, &language);
+
+        let result = match self.try_analyze_code(&selection.source, &language, &AnalysisOptions::default(), &AbortFlag::default()) {
+            Ok(mut analysis) => {
+                for location in analysis.patterns.iter_mut().filter_map(|pattern| pattern.location.as_mut()) {
+                    selection.relocate(location);
+                }
+                serde_json::json!({
+                    "patterns": analysis.patterns,
+                    "outer_references": selection_analysis::outer_references(full_code, start, end, &language),
+                })
+            }
+            Err(error) => serde_json::json!({ "error": error }),
+        };
+        serde_json::to_string(&result).unwrap_or("{}".to_string())
+    }
+
+    /// Analyze code until `abort` is set, returning `AbortableAnalysis` JSON with the patterns found
+    /// so far, or `{"error": AnalysisError}` when the input is refused
+    #[wasm_bindgen]
+    pub fn analyze_code_abortable_json(&mut self, code: &str, language: &str, abort: &AbortFlag) -> String {
+        let result = match self.analyze_code_abortable(code, language, &AnalysisOptions::default(), abort) {
+            Ok(analysis) => serde_json::to_value(&analysis).unwrap_or_default(),
+            Err(error) => serde_json::json!({ "error": error }),
+        };
+        serde_json::to_string(&result).unwrap_or("{}".to_string())
+    }
+
+    /// Analyze code, calling `callback(batch, done)` with arrays of at most `STREAM_BATCH_SIZE`
+    /// patterns as each detection stage finishes; the final call has an empty batch and `done` set
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen]
+    pub fn analyze_code_streaming(&mut self, code: &str, language: &str, callback: &js_sys::Function) {
+        self.analyze_code_streaming_with(code, language, |batch, done| {
+            let batch: js_sys::Array = batch.iter().cloned().map(JsValue::from).collect();
+            if let Err(err) = callback.call2(&JsValue::NULL, &batch, &JsValue::from_bool(done)) {
+                console_log!("Streaming callback failed: {:?}", err);
+            }
+        });
+    }
+
+    /// Re-analyze only the regions of `new_code` that changed since `old_code`
+    #[wasm_bindgen]
+    pub fn analyze_diff(&mut self, old_code: &str, new_code: &str, language: &str) -> Vec<CodingPattern> {
+        let diff = self.detect_diff(old_code, new_code, language);
+        self.record_diff(diff)
+    }
+
+    /// Analyze `alias` code as `canonical`, e.g. `("mjs", "javascript")`. Aliases are
+    /// case-insensitive; registering one again replaces it
+    #[wasm_bindgen]
+    pub fn register_language_alias(&mut self, alias: &str, canonical: &str) {
+        self.language_aliases.insert(alias.trim().to_lowercase(), canonical.to_lowercase());
+    }
+
+    /// The canonical name for `language`, or `language` itself when it is not an alias
+    #[wasm_bindgen]
+    pub fn canonical_language(&self, language: &str) -> String {
+        self.language_aliases.get(&language.trim().to_lowercase()).cloned().unwrap_or_else(|| language.to_string())
+    }
+
+    // Empty or "auto" languages are guessed from the code, falling back to generic analysis
+    fn resolve_language(&self, code: &str, language: &str) -> String {
+        if !language_detection::wants_detection(language) {
+            return self.canonical_language(language);
+        }
+        let detected = detect_language(code).unwrap_or_else(|| "generic".to_string());
+        console_log!("Auto-detected language: {}", detected);
+        detected
+    }
+
+    /// Number of times source has been handed to the AST parser
+    #[wasm_bindgen(getter)]
+    pub fn parse_count(&self) -> u32 {
+        self.parse_count.load(Ordering::Relaxed)
+    }
+
+    fn detect_patterns(&self, code: &str, language: &str, options: &AnalysisOptions, abort: &AbortFlag) -> AbortableAnalysis {
+        let mut analysis = self.detect_builtin_patterns(code, language, options, abort);
+        if !analysis.aborted {
+            analysis.patterns.extend(self.detect_custom_patterns(code, language));
+        }
+        analysis
+    }
+
+    fn detect_custom_patterns(&self, code: &str, language: &str) -> Vec<CodingPattern> {
+        let language = language.to_lowercase();
+        self.custom_detectors.iter().filter_map(|detector| {
+            let (frequency, matched) = detector.detect(code)?;
+            let path = structural_path(code, code.find(matched.as_str()).unwrap_or(0));
+            let mut pattern = CodingPattern::new(
+                pattern_id(&format!("custom_{}", detector.name), &detector.name, &language, &matched, &path),
+                detector.name.clone(),
+                language.clone(),
+                CUSTOM_PATTERN_CONFIDENCE,
+            );
+            // Even names like "function_definition" stay custom
+            pattern.pattern_type = PatternType::Custom(detector.name.clone());
+            pattern.frequency = frequency;
+            pattern.content = PatternContent::String { pattern: matched, placeholders: vec![] };
+            Some(pattern)
+        }).collect()
+    }
+
+    fn detect_builtin_patterns(&self, code: &str, language: &str, options: &AnalysisOptions, abort: &AbortFlag) -> AbortableAnalysis {
+        if abort.should_stop(0) {
+            return AbortableAnalysis { patterns: Vec::new(), aborted: true };
+        }
+
+        // User-registered profiles take precedence for languages without a parser
+        if let Some(profile) = self.language_profiles.get(&language.to_lowercase()) {
+            console_log!("Using registered profile for {}", language);
+            let patterns = self.analyze_profile_regex(code, language, profile, abort);
+            return AbortableAnalysis { patterns, aborted: abort.aborted() };
+        }
+
+        if options.skips_ast(code) {
+            console_log!("Skipping AST parsing for {} bytes of {} code", code.len(), language);
+            return self.analyze_with_regex(code, language, abort);
+        }
 
         // Try AST-based parsing first
+        self.parse_count.fetch_add(1, Ordering::Relaxed);
         match self.ast_parser.parse_code(code, language) {
             Ok(ast) => {
                 console_log!("Successfully parsed {} code with AST", language);
-                let ast_patterns = self.ast_parser.extract_patterns(&ast, language);
-                detected_patterns.extend(ast_patterns);
+                self.ast_parser.extract_patterns_abortable(&ast, code, language, abort)
             }
             Err(err) => {
                 console_log!("AST parsing failed for {}: {}, falling back to regex", language, err);
-                // Fallback to regex-based parsing
-                detected_patterns.extend(self.analyze_with_regex(code, language));
+                // Fallback to regex-based parsing, keeping the token stream for similarity scoring
+                let mut analysis = self.analyze_with_regex(code, language, abort);
+                if !analysis.aborted {
+                    analysis.patterns.extend(self.token_sequence_pattern(code, language, abort));
+                    analysis.aborted = abort.aborted();
+                }
+                analysis
             }
         }
+    }
 
-        // Add detected patterns to our collection
-        for pattern in &detected_patterns {
-            self.patterns.push(pattern.clone());
+    fn token_sequence_pattern(&self, code: &str, language: &str, abort: &AbortFlag) -> Option<CodingPattern> {
+        let mut tokens = Vec::new();
+        let mut metadata = HashMap::new();
+
+        for (index, token) in tokenizer::Tokenizer::new(code).take(MAX_FALLBACK_TOKENS).enumerate() {
+            if abort.tick() {
+                return None;
+            }
+            tokens.push(token.text.to_string());
+            metadata.insert(index, token.kind.as_str().to_string());
+        }
+
+        if tokens.is_empty() {
+            return None;
         }
 
-        console_log!("Detected {} patterns", detected_patterns.len());
-        detected_patterns
+        let mut pattern = CodingPattern::new(
+            pattern_id(&format!("{}_tokens", language.to_lowercase()), "token_sequence", language, &tokens.join(" "), ""),
+            "token_sequence".to_string(),
+            language.to_lowercase(),
+            0.3,
+        );
+        pattern.content = PatternContent::Tokens { tokens, metadata };
+        Some(pattern)
     }
 
-    fn analyze_with_regex(&self, code: &str, language: &str) -> Vec<CodingPattern> {
+    /// Line-based fallback analysis, polling `abort` once per line scanned
+    fn analyze_with_regex(&self, code: &str, language: &str, abort: &AbortFlag) -> AbortableAnalysis {
         let mut patterns = Vec::new();
 
         match language.to_lowercase().as_str() {
             "javascript" | "typescript" => {
-                patterns.extend(self.analyze_javascript_regex(code));
+                patterns.extend(self.analyze_javascript_regex(code, abort));
             },
             "python" => {
-                patterns.extend(self.analyze_python_regex(code));
+                patterns.extend(self.analyze_python_regex(code, abort));
             },
             "rust" => {
-                patterns.extend(self.analyze_rust_regex(code));
+                patterns.extend(self.analyze_rust_regex(code, abort));
             },
             _ => {
-                patterns.extend(self.analyze_generic_regex(code));
+                patterns.extend(self.analyze_generic_regex(code, abort));
             }
         }
 
-        patterns
+        AbortableAnalysis { patterns, aborted: abort.aborted() }
     }
 
-    fn analyze_javascript_regex(&self, code: &str) -> Vec<CodingPattern> {
+    fn analyze_javascript_regex(&self, code: &str, abort: &AbortFlag) -> Vec<CodingPattern> {
         let mut patterns = Vec::new();
 
         // Function patterns
-        if code.contains("function") || code.contains("=>") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| ["function", "=>"].iter().any(|needle| line.contains(needle))) {
             let pattern = CodingPattern::new(
-                format!("js_func_{}", js_sys::Date::now() as u64),
+                pattern_id("js_func", "function_definition", "javascript", line, &structural_path(code, offset)),
                 "function_definition".to_string(),
                 "javascript".to_string(),
                 0.8,
@@ -347,9 +709,9 @@ impl PatternAnalyzer {
         }
 
         // Variable patterns
-        if code.contains("const ") || code.contains("let ") || code.contains("var ") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| ["const ", "let ", "var "].iter().any(|needle| line.contains(needle))) {
             let pattern = CodingPattern::new(
-                format!("js_var_{}", js_sys::Date::now() as u64),
+                pattern_id("js_var", "variable_declaration", "javascript", line, &structural_path(code, offset)),
                 "variable_declaration".to_string(),
                 "javascript".to_string(),
                 0.6,
@@ -358,9 +720,9 @@ impl PatternAnalyzer {
         }
 
         // Class patterns
-        if code.contains("class ") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| line.contains("class ")) {
             let pattern = CodingPattern::new(
-                format!("js_class_{}", js_sys::Date::now() as u64),
+                pattern_id("js_class", "class_definition", "javascript", line, &structural_path(code, offset)),
                 "class_definition".to_string(),
                 "javascript".to_string(),
                 0.7,
@@ -371,12 +733,12 @@ impl PatternAnalyzer {
         patterns
     }
 
-    fn analyze_python_regex(&self, code: &str) -> Vec<CodingPattern> {
+    fn analyze_python_regex(&self, code: &str, abort: &AbortFlag) -> Vec<CodingPattern> {
         let mut patterns = Vec::new();
 
-        if code.contains("def ") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| line.contains("def ")) {
             let pattern = CodingPattern::new(
-                format!("py_func_{}", js_sys::Date::now() as u64),
+                pattern_id("py_func", "function_definition", "python", line, &structural_path(code, offset)),
                 "function_definition".to_string(),
                 "python".to_string(),
                 0.8,
@@ -384,9 +746,9 @@ impl PatternAnalyzer {
             patterns.push(pattern);
         }
 
-        if code.contains("class ") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| line.contains("class ")) {
             let pattern = CodingPattern::new(
-                format!("py_class_{}", js_sys::Date::now() as u64),
+                pattern_id("py_class", "class_definition", "python", line, &structural_path(code, offset)),
                 "class_definition".to_string(),
                 "python".to_string(),
                 0.7,
@@ -397,12 +759,12 @@ impl PatternAnalyzer {
         patterns
     }
 
-    fn analyze_rust_regex(&self, code: &str) -> Vec<CodingPattern> {
+    fn analyze_rust_regex(&self, code: &str, abort: &AbortFlag) -> Vec<CodingPattern> {
         let mut patterns = Vec::new();
 
-        if code.contains("fn ") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| line.contains("fn ")) {
             let pattern = CodingPattern::new(
-                format!("rs_func_{}", js_sys::Date::now() as u64),
+                pattern_id("rs_func", "function_definition", "rust", line, &structural_path(code, offset)),
                 "function_definition".to_string(),
                 "rust".to_string(),
                 0.8,
@@ -410,9 +772,9 @@ impl PatternAnalyzer {
             patterns.push(pattern);
         }
 
-        if code.contains("struct ") {
+        if let Some((offset, line)) = first_line_where(code, abort, |line| line.contains("struct ")) {
             let pattern = CodingPattern::new(
-                format!("rs_struct_{}", js_sys::Date::now() as u64),
+                pattern_id("rs_struct", "class_definition", "rust", line, &structural_path(code, offset)),
                 "class_definition".to_string(),
                 "rust".to_string(),
                 0.7,
@@ -423,18 +785,29 @@ impl PatternAnalyzer {
         patterns
     }
 
-    fn analyze_generic_regex(&self, code: &str) -> Vec<CodingPattern> {
+    fn analyze_generic_regex(&self, code: &str, abort: &AbortFlag) -> Vec<CodingPattern> {
+        // Generic pattern detection
+        self.analyze_profile_regex(code, "generic", &LanguageProfile::default(), abort)
+    }
+
+    fn analyze_profile_regex(&self, code: &str, language: &str, profile: &LanguageProfile, abort: &AbortFlag) -> Vec<CodingPattern> {
         let mut patterns = Vec::new();
+        let language = language.to_lowercase();
+        let checks = [
+            (&profile.function_keywords, "func", "function_definition", 0.5),
+            (&profile.class_keywords, "class", "class_definition", 0.45),
+            (&profile.variable_keywords, "var", "variable_declaration", 0.4),
+        ];
 
-        // Generic pattern detection
-        if code.contains("function") || code.contains("def ") || code.contains("fn ") {
-            let pattern = CodingPattern::new(
-                format!("gen_func_{}", js_sys::Date::now() as u64),
-                "function_definition".to_string(),
-                "generic".to_string(),
-                0.5,
-            );
-            patterns.push(pattern);
+        for (keywords, id_prefix, pattern_type, confidence) in checks {
+            if let Some((offset, line)) = profile.first_declaration(code, keywords, abort) {
+                patterns.push(CodingPattern::new(
+                    pattern_id(&format!("gen_{}", id_prefix), pattern_type, &language, line, &structural_path(code, offset)),
+                    pattern_type.to_string(),
+                    language.clone(),
+                    confidence,
+                ));
+            }
         }
 
         patterns
@@ -455,6 +828,13 @@ impl PatternAnalyzer {
         serde_json::to_string(&self.language_stats).unwrap_or("{}".to_string())
     }
 
+    /// Analyses run per language, as a plain `{ language: count }` object
+    #[wasm_bindgen(getter = language_stats)]
+    pub fn language_stats_js(&self) -> JsValue {
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        self.language_stats.serialize(&serializer).unwrap_or(JsValue::NULL)
+    }
+
     #[wasm_bindgen]
     pub fn get_patterns_by_language(&self, language: &str) -> Vec<CodingPattern> {
         self.patterns
@@ -473,10 +853,1001 @@ impl PatternAnalyzer {
             .collect()
     }
 
+    /// JSON map of source file -> pattern type -> number of patterns seen in that file
+    #[wasm_bindgen]
+    pub fn get_pattern_heatmap(&self) -> String {
+        serde_json::to_string(&self.pattern_heatmap()).unwrap_or("{}".to_string())
+    }
+
     #[wasm_bindgen]
     pub fn get_top_patterns(&self, limit: usize) -> Vec<CodingPattern> {
         let mut sorted_patterns = self.patterns.clone();
-        sorted_patterns.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+        sorted_patterns.sort_by(|a, b| b.effective_confidence().total_cmp(&a.effective_confidence()));
         sorted_patterns.into_iter().take(limit).collect()
     }
+
+    /// Drop accumulated patterns, language statistics, the diff cache and the recorded memory
+    /// pressure, e.g. when the editor switches projects. Registered profiles and custom detectors
+    /// are kept
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.patterns.clear();
+        self.language_stats.clear();
+        self.diff_cache = None;
+        self.under_memory_pressure = false;
+    }
+
+    /// Drop accumulated state for one language, leaving other languages untouched
+    #[wasm_bindgen]
+    pub fn clear_language(&mut self, language: &str) {
+        self.patterns.retain(|pattern| !pattern.language.eq_ignore_ascii_case(language));
+        self.language_stats.retain(|name, _| !name.eq_ignore_ascii_case(language));
+        if self.diff_cache.as_ref().is_some_and(|cache| cache.language.eq_ignore_ascii_case(language)) {
+            self.diff_cache = None;
+        }
+    }
+
+    /// Recompute every pattern's staleness decay as of `now` (milliseconds since the epoch,
+    /// e.g. `Date.now()`), halving effective confidence every `CONFIDENCE_HALF_LIFE_DAYS`.
+    /// Stored confidence is left untouched
+    #[wasm_bindgen]
+    pub fn age_confidence(&mut self, now: f64) {
+        for pattern in &mut self.patterns {
+            let age_days = (now - pattern.last_seen_millis() as f64).max(0.0) / MILLIS_PER_DAY;
+            pattern.decay_factor = 0.5f64.powf(age_days / CONFIDENCE_HALF_LIFE_DAYS);
+        }
+    }
+
+    /// Maximum number of patterns retained while memory pressure is high
+    #[wasm_bindgen(getter)]
+    pub fn pressure_pattern_cap(&self) -> usize {
+        self.pressure_pattern_cap
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_pressure_pattern_cap(&mut self, cap: usize) {
+        self.pressure_pattern_cap = cap;
+        if self.under_memory_pressure {
+            self.trim_to_pressure_cap();
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn verbosity(&self) -> OutputVerbosity {
+        self.verbosity
+    }
+
+    /// How much of each pattern the analysis entry points return; stored patterns keep everything
+    #[wasm_bindgen(setter)]
+    pub fn set_verbosity(&mut self, verbosity: OutputVerbosity) {
+        self.verbosity = verbosity;
+    }
+
+    /// Record the host's memory pressure level (`low`, `medium` or `high`, as reported by
+    /// `WasmMemoryOptimizer::get_memory_pressure`). While it is `high`, retained patterns are
+    /// capped at `pressure_pattern_cap`. Returns the number of patterns evicted.
+    #[wasm_bindgen]
+    pub fn set_memory_pressure(&mut self, pressure_level: &str) -> usize {
+        self.under_memory_pressure = pressure_level.eq_ignore_ascii_case("high");
+        if self.under_memory_pressure {
+            self.trim_to_pressure_cap()
+        } else {
+            0
+        }
+    }
+
+    /// Report `Custom(name)` patterns wherever `pattern` (a regex) matches analyzed code.
+    /// Returns false when the regex does not compile
+    #[wasm_bindgen]
+    pub fn register_custom_detector(&mut self, name: &str, pattern: &str) -> bool {
+        match CustomDetector::from_regex(name, pattern) {
+            Ok(detector) => {
+                self.add_custom_detector(detector);
+                true
+            }
+            Err(err) => {
+                console_log!("Invalid custom detector {}: {}", name, err);
+                false
+            }
+        }
+    }
+
+    /// Register keyword heuristics for a language, given as a JSON `LanguageProfile`
+    #[wasm_bindgen]
+    pub fn register_language_profile_json(&mut self, name: &str, profile_json: &str) -> bool {
+        match serde_json::from_str::<LanguageProfile>(profile_json) {
+            Ok(profile) => {
+                self.register_language_profile(name, profile);
+                true
+            }
+            Err(err) => {
+                console_log!("Invalid language profile for {}: {}", name, err);
+                false
+            }
+        }
+    }
+}
+
+impl PatternAnalyzer {
+    /// Register keyword heuristics used when analyzing `name` code
+    pub fn register_language_profile(&mut self, name: &str, profile: LanguageProfile) {
+        self.language_profiles.insert(name.to_lowercase(), profile);
+    }
+
+    /// Report `Custom(name)` patterns for code where `predicate` holds
+    pub fn register_custom_predicate(&mut self, name: &str, predicate: impl Fn(&str) -> bool + Send + Sync + 'static) {
+        self.add_custom_detector(CustomDetector::new(name, CustomMatcher::Predicate(Box::new(predicate))));
+    }
+
+    // Registering a name again replaces its detector
+    fn add_custom_detector(&mut self, detector: CustomDetector) {
+        self.custom_detectors.retain(|existing| existing.name != detector.name);
+        self.custom_detectors.push(detector);
+    }
+
+    /// Analyses run per language, keyed by canonical language name
+    pub fn language_stats(&self) -> &HashMap<String, u32> {
+        &self.language_stats
+    }
+
+    /// Analyze code and record the detected patterns, honouring the latency options
+    pub fn analyze_code_with_options(&mut self, code: &str, language: &str, options: &AnalysisOptions) -> Vec<CodingPattern> {
+        match self.analyze_code_abortable(code, language, options, &AbortFlag::default()) {
+            Ok(analysis) => analysis.patterns,
+            Err(error) => {
+                console_log!("{}", error);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Analyze code, stopping early once `abort` is set; patterns found before that are still
+    /// recorded. Input over `options.max_input_bytes` is refused with `InputTooLarge`
+    pub fn analyze_code_abortable(&mut self, code: &str, language: &str, options: &AnalysisOptions, abort: &AbortFlag) -> Result<AbortableAnalysis, AnalysisError> {
+        self.try_analyze_code(code, language, options, abort)
+    }
+
+    /// Analyze code, handing each detection stage's patterns to `on_batch` in groups of at most
+    /// `STREAM_BATCH_SIZE` as soon as that stage finishes, so an editor can render the built-in
+    /// patterns while custom detectors still run. A final empty batch with `done` set signals
+    /// completion, even when the input is refused
+    pub fn analyze_code_streaming_with(&mut self, code: &str, language: &str, mut on_batch: impl FnMut(&[CodingPattern], bool)) {
+        let mut on_stage = |patterns: &[CodingPattern]| {
+            for batch in patterns.chunks(STREAM_BATCH_SIZE) {
+                on_batch(batch, false);
+            }
+        };
+        if let Err(error) = self.try_analyze_code_in_stages(code, language, &AnalysisOptions::default(), &AbortFlag::default(), &mut on_stage) {
+            console_log!("{}", error);
+        }
+        on_batch(&[], true);
+    }
+
+    /// Detect patterns without recording them, so a shared analyzer can parse under a read lock.
+    /// Returns the language the code was analyzed as, for `record_detected`
+    pub(crate) fn detect_unrecorded(&self, code: &str, language: &str, options: &AnalysisOptions, abort: &AbortFlag) -> Result<(String, AbortableAnalysis), AnalysisError> {
+        let language = self.analysis_language(code, language, options)?;
+        let analysis = self.detect_patterns(code, &language, options, abort);
+        Ok((language, analysis))
+    }
+
+    /// Record the patterns from `detect_unrecorded` as `analyze_code` would have
+    pub(crate) fn record_detected(&mut self, language: &str, analysis: &mut AbortableAnalysis, options: &AnalysisOptions) {
+        *self.language_stats.entry(language.to_string()).or_insert(0) += 1;
+        self.record_patterns(&mut analysis.patterns, options);
+        self.finish_analysis(analysis);
+    }
+
+    /// Work out which regions of `new_code` changed since `old_code` and detect patterns in
+    /// those, reusing the diff cache for the rest, without recording anything
+    pub(crate) fn detect_diff(&self, old_code: &str, new_code: &str, language: &str) -> DetectedDiff {
+        let language = self.canonical_language(language);
+        let language_key = language.to_lowercase();
+        let changed = diff_analysis::changed_line_range(old_code, new_code);
+        let cache = self.diff_cache.as_ref().filter(|cache| cache.language == language_key);
+
+        let mut regions = Vec::new();
+        let mut detected = Vec::new();
+        let mut reanalyzed = 0;
+
+        for (start_line, end_line, text) in diff_analysis::split_regions(new_code) {
+            let touched = match changed {
+                // An empty range (pure deletion) still touches the region around it
+                Some((change_start, change_end)) => start_line < change_end.max(change_start + 1) && change_start < end_line,
+                None => false,
+            };
+
+            let cached = if touched {
+                None
+            } else {
+                cache.and_then(|cache| cache.find_unchanged(&text))
+            };
+
+            let patterns = match cached {
+                Some(region) => region.patterns.clone(),
+                None => {
+                    reanalyzed += 1;
+                    let patterns = self.detect_patterns(&text, &language, &AnalysisOptions::default(), &AbortFlag::default()).patterns;
+                    detected.extend(patterns.iter().cloned());
+                    patterns
+                }
+            };
+            regions.push(CachedRegion { text, patterns });
+        }
+
+        console_log!("Diff analysis re-examined {} of {} regions", reanalyzed, regions.len());
+        DetectedDiff { language, regions, detected }
+    }
+
+    /// Record the patterns `detect_diff` found and cache its regions for the next diff, returning
+    /// the patterns of every region
+    pub(crate) fn record_diff(&mut self, diff: DetectedDiff) -> Vec<CodingPattern> {
+        *self.language_stats.entry(diff.language.clone()).or_insert(0) += 1;
+        self.patterns.extend(diff.detected);
+
+        let merged_patterns = diff.regions.iter().flat_map(|region| region.patterns.iter().cloned()).collect();
+        self.diff_cache = Some(DiffCache { language: diff.language.to_lowercase(), regions: diff.regions });
+        merged_patterns
+    }
+
+    /// Check `code` against the input limit and resolve the language it is analyzed as
+    fn analysis_language(&self, code: &str, language: &str, options: &AnalysisOptions) -> Result<String, AnalysisError> {
+        if let Some(limit) = options.max_input_bytes {
+            AnalysisError::check_input_size(code, limit)?;
+        }
+
+        let language = self.resolve_language(code, language);
+        console_log!("Analyzing {} characters of {} code using AST parser", code.len(), language);
+        Ok(language)
+    }
+
+    fn try_analyze_code(&mut self, code: &str, language: &str, options: &AnalysisOptions, abort: &AbortFlag) -> Result<AbortableAnalysis, AnalysisError> {
+        self.try_analyze_code_in_stages(code, language, options, abort, &mut |_| {})
+    }
+
+    /// Run the built-in detectors, then the custom ones, recording each stage's patterns and
+    /// handing them to `on_stage` before the next stage starts. Refused input is an error here;
+    /// extractors must not panic at all, since wasm32 builds abort
+    fn try_analyze_code_in_stages(
+        &mut self,
+        code: &str,
+        language: &str,
+        options: &AnalysisOptions,
+        abort: &AbortFlag,
+        on_stage: &mut dyn FnMut(&[CodingPattern]),
+    ) -> Result<AbortableAnalysis, AnalysisError> {
+        let detected_language = self.analysis_language(code, language, options)?;
+        let language = detected_language.as_str();
+
+        // Update language statistics
+        *self.language_stats.entry(language.to_string()).or_insert(0) += 1;
+
+        let mut analysis = self.detect_builtin_patterns(code, language, options, abort);
+        self.record_patterns(&mut analysis.patterns, options);
+        on_stage(&analysis.patterns);
+        if !analysis.aborted {
+            let mut custom = self.detect_custom_patterns(code, language);
+            self.record_patterns(&mut custom, options);
+            on_stage(&custom);
+            analysis.patterns.extend(custom);
+        }
+        self.finish_analysis(&analysis);
+        Ok(analysis)
+    }
+
+    fn finish_analysis(&mut self, analysis: &AbortableAnalysis) {
+        if self.under_memory_pressure {
+            self.trim_to_pressure_cap();
+        }
+
+        if analysis.aborted {
+            console_log!("Analysis aborted after {} patterns", analysis.patterns.len());
+        } else {
+            console_log!("Detected {} patterns", analysis.patterns.len());
+        }
+    }
+
+    /// Apply the confidence floor and source file of `options` to `patterns`, then store them and
+    /// trim what is handed back to the configured verbosity
+    fn record_patterns(&mut self, patterns: &mut Vec<CodingPattern>, options: &AnalysisOptions) {
+        if let Some(min_confidence) = options.min_confidence {
+            patterns.retain(|pattern| pattern.confidence >= min_confidence);
+        }
+        if let Some(file_path) = &options.file_path {
+            for pattern in patterns.iter_mut() {
+                pattern.add_source_file(file_path.clone());
+            }
+        }
+        self.patterns.extend(patterns.iter().cloned());
+        for pattern in patterns.iter_mut() {
+            pattern.trim(self.verbosity);
+        }
+    }
+
+    /// Source file -> pattern type -> number of stored patterns seen in that file
+    pub fn pattern_heatmap(&self) -> std::collections::BTreeMap<&str, std::collections::BTreeMap<String, u32>> {
+        let mut heatmap: std::collections::BTreeMap<&str, std::collections::BTreeMap<String, u32>> = std::collections::BTreeMap::new();
+        for pattern in &self.patterns {
+            for file in &pattern.source_files {
+                *heatmap.entry(file).or_default().entry(pattern.pattern_type()).or_insert(0) += 1;
+            }
+        }
+        heatmap
+    }
+
+    /// Run only the regex-based detection, without recording the results
+    pub fn analyze_with_regex_only(&self, code: &str, language: &str) -> Vec<CodingPattern> {
+        self.analyze_with_regex(code, language, &AbortFlag::default()).patterns
+    }
+
+    /// Evict the lowest-confidence patterns, oldest first among equals, until at most
+    /// `pressure_pattern_cap` remain. Survivors keep their original order.
+    fn trim_to_pressure_cap(&mut self) -> usize {
+        let excess = self.patterns.len().saturating_sub(self.pressure_pattern_cap);
+        if excess == 0 {
+            return 0;
+        }
+
+        let mut ranked: Vec<usize> = (0..self.patterns.len()).collect();
+        ranked.sort_by(|&a, &b| {
+            let (a, b) = (&self.patterns[a], &self.patterns[b]);
+            a.confidence.partial_cmp(&b.confidence).unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.last_seen_millis().cmp(&b.last_seen_millis()))
+        });
+        let evicted: HashSet<usize> = ranked.into_iter().take(excess).collect();
+
+        let mut index = 0;
+        self.patterns.retain(|_| {
+            let keep = !evicted.contains(&index);
+            index += 1;
+            keep
+        });
+        console_log!("Memory pressure: evicted {} patterns", excess);
+        excess
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kotlin_profile() -> LanguageProfile {
+        LanguageProfile::new(
+            vec!["fun".to_string()],
+            vec!["class".to_string(), "object".to_string()],
+            vec!["val".to_string(), "var".to_string()],
+            vec!["//".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_registered_profile_detects_kotlin_function() {
+        let mut analyzer = PatternAnalyzer::new();
+        analyzer.register_language_profile("Kotlin", kotlin_profile());
+
+        let code = "fun greet(name: String): String {\n    val greeting = \"Hello\"\n    return greeting + name\n}";
+        let patterns = analyzer.analyze_code(code, "kotlin");
+
+        assert!(patterns.iter().any(|p| p.pattern_type() == "function_definition"));
+        assert!(patterns.iter().any(|p| p.pattern_type() == "variable_declaration"));
+        assert!(!patterns.iter().any(|p| p.pattern_type() == "class_definition"));
+        assert!(patterns.iter().all(|p| p.language() == "kotlin"));
+        assert_eq!(analyzer.get_pattern_count(), patterns.len());
+    }
+
+    #[test]
+    fn test_pattern_heatmap_buckets_by_file_and_type() {
+        let mut analyzer = PatternAnalyzer::new();
+        analyzer.analyze_file("src/lib.rs", "fn alpha() {}\n\nfn beta() {}\n", "rust");
+        let options = AnalysisOptions { file_path: Some("tools/gen.py".to_string()), ..AnalysisOptions::default() };
+        analyzer.analyze_code_with_options_json("def gamma():\n    return 1\n", "python", &serde_json::to_string(&options).unwrap());
+        // Code with no file attached stays off the map
+        analyzer.analyze_code("fn delta() {}\n", "rust");
+
+        let heatmap: serde_json::Value = serde_json::from_str(&analyzer.get_pattern_heatmap()).unwrap();
+        assert_eq!(heatmap["src/lib.rs"]["function_definition"], 2);
+        assert_eq!(heatmap["tools/gen.py"]["function_definition"], 1);
+        assert_eq!(heatmap.as_object().unwrap().len(), 2);
+        assert_eq!(PatternAnalyzer::new().get_pattern_heatmap(), "{}");
+    }
+
+    #[test]
+    fn test_min_confidence_drops_patterns_before_storing() {
+        let code = "function load(url) {\n  for (let i = 0; i < 3; i++) {\n    if (url) { fetch(url); }\n  }\n}\nclass Cache {}\n";
+        let all = PatternAnalyzer::new().analyze_code(code, "javascript");
+        let mut confidences: Vec<f64> = all.iter().map(|p| p.confidence()).collect();
+        confidences.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let (lowest, highest) = (confidences[0], confidences[confidences.len() - 1]);
+        assert!(lowest < highest);
+
+        let mut analyzer = PatternAnalyzer::new();
+        let threshold = (lowest + highest) / 2.0;
+        let kept = analyzer.analyze_code_with_options_json(code, "javascript", &format!("{{\"min_confidence\": {}}}", threshold));
+        assert!(!kept.is_empty());
+        assert!(kept.len() < all.len());
+        assert!(kept.iter().all(|p| p.confidence() >= threshold));
+        assert_eq!(analyzer.get_pattern_count(), kept.len());
+        assert!(analyzer.get_patterns().iter().all(|p| p.confidence() >= threshold));
+    }
+
+    #[test]
+    fn test_unregistered_language_finds_no_kotlin_function() {
+        let mut analyzer = PatternAnalyzer::new();
+        let patterns = analyzer.analyze_code("fun greet() {}", "kotlin");
+        assert!(patterns.is_empty());
+    }
+
+    #[test]
+    fn test_register_language_profile_json() {
+        let mut analyzer = PatternAnalyzer::new();
+        let json = serde_json::to_string(&kotlin_profile()).unwrap();
+        assert!(analyzer.register_language_profile_json("kotlin", &json));
+        assert!(!analyzer.register_language_profile_json("swift", "not json"));
+
+        let patterns = analyzer.analyze_code("class Greeter", "kotlin");
+        assert!(patterns.iter().any(|p| p.pattern_type() == "class_definition"));
+        assert!(analyzer.analyze_code("class Greeter", "swift").is_empty());
+    }
+
+    #[test]
+    fn test_analyze_diff_reuses_untouched_regions() {
+        let mut analyzer = PatternAnalyzer::new();
+        let old_code = "fn alpha() -> u32 {\n    1\n}\n\nfn beta() -> u32 {\n    2\n}\n\nfn gamma() -> u32 {\n    3\n}\n";
+        let new_code = old_code.replace("    2", "    let two = 2;\n    two");
+
+        let initial = analyzer.analyze_diff("", old_code, "rust");
+        assert_eq!(initial.len(), 3);
+        assert_eq!(analyzer.parse_count(), 3);
+
+        let updated = analyzer.analyze_diff(old_code, &new_code, "rust");
+        assert_eq!(analyzer.parse_count(), 4); // only beta was re-parsed
+        let ids: Vec<String> = updated.iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec![
+            "rust_function_definition_alpha",
+            "rust_function_definition_beta",
+            "rust_function_definition_gamma",
+        ]);
+
+        // No changes means nothing is re-parsed
+        analyzer.analyze_diff(&new_code, &new_code, "rust");
+        assert_eq!(analyzer.parse_count(), 4);
+    }
+
+    #[test]
+    fn test_analyze_diff_language_change_invalidates_cache() {
+        let mut analyzer = PatternAnalyzer::new();
+        let code = "def one():\n    return 1\n\ndef two():\n    return 2\n";
+        analyzer.analyze_diff("", code, "python");
+        assert_eq!(analyzer.parse_count(), 2);
+
+        analyzer.analyze_diff(code, code, "rust");
+        assert_eq!(analyzer.parse_count(), 4);
+    }
+
+    #[test]
+    fn test_recalibrate_confidence_single_rejection_is_bounded() {
+        let mut pattern = CodingPattern::new("p".to_string(), "function_definition".to_string(), "rust".to_string(), 0.9);
+        pattern.add_feedback("rejected".to_string(), None);
+
+        let confidence = pattern.recalibrate_confidence();
+        assert!(confidence < 0.9);
+        assert!(confidence > 0.8);
+        // Recalibrating again without new feedback is stable
+        assert_eq!(pattern.recalibrate_confidence(), confidence);
+    }
+
+    #[test]
+    fn test_recalibrate_confidence_moves_with_feedback() {
+        let mut accepted = CodingPattern::new("a".to_string(), "loop_construct".to_string(), "python".to_string(), 0.8);
+        for _ in 0..20 {
+            accepted.add_feedback("accepted".to_string(), None);
+        }
+        let raised = accepted.recalibrate_confidence();
+        assert!(raised > 0.8);
+        assert!(raised <= 1.0);
+
+        let mut rejected = CodingPattern::new("r".to_string(), "loop_construct".to_string(), "python".to_string(), 0.1);
+        for _ in 0..20 {
+            rejected.add_feedback("rejected".to_string(), Some("noise".to_string()));
+        }
+        rejected.add_feedback("ignored".to_string(), None);
+        let lowered = rejected.recalibrate_confidence();
+        assert!(lowered < 0.1);
+        assert!(lowered >= 0.0);
+        assert_eq!(rejected.confidence(), lowered);
+    }
+
+    #[test]
+    fn test_patterns_saved_without_base_confidence_recalibrate_from_their_confidence() {
+        let mut saved = serde_json::to_value(CodingPattern::new("p".to_string(), "function_definition".to_string(), "rust".to_string(), 0.7)).unwrap();
+        saved.as_object_mut().unwrap().remove("base_confidence");
+        let mut pattern: CodingPattern = serde_json::from_value(saved).unwrap();
+
+        pattern.add_feedback("rejected".to_string(), None);
+        let lowered = pattern.recalibrate_confidence();
+        assert!(lowered < 0.7 && lowered > 0.6);
+        // The base stays at the saved confidence rather than following the recalibrated one
+        pattern.user_feedback.clear();
+        assert_eq!(pattern.recalibrate_confidence(), 0.7);
+    }
+
+    #[test]
+    fn test_broken_code_produces_token_pattern() {
+        let mut analyzer = PatternAnalyzer::new();
+        let patterns = analyzer.analyze_code("function broken( {\n  const x = ;", "javascript");
+
+        let token_pattern = patterns.iter()
+            .find(|p| p.pattern_type() == "token_sequence")
+            .expect("fallback should keep a token stream");
+        match token_pattern.content() {
+            PatternContent::Tokens { tokens, metadata } => {
+                assert!(!tokens.is_empty());
+                assert_eq!(tokens[0], "function");
+                assert_eq!(metadata.len(), tokens.len());
+                assert_eq!(metadata[&0], "identifier");
+            }
+            other => panic!("expected Tokens content, got {:?}", other),
+        }
+
+        // Regex fallback patterns are still produced alongside it
+        assert!(patterns.iter().any(|p| p.pattern_type() == "function_definition"));
+    }
+
+    #[test]
+    fn test_valid_code_has_no_token_pattern() {
+        let mut analyzer = PatternAnalyzer::new();
+        let patterns = analyzer.analyze_code("fn ok() {}", "rust");
+        assert!(!patterns.iter().any(|p| matches!(p.content(), PatternContent::Tokens { .. })));
+    }
+
+    #[test]
+    fn test_auto_language_is_detected_before_analysis() {
+        let mut analyzer = PatternAnalyzer::new();
+        let patterns = analyzer.analyze_code("def greet(name):\n    return name\n", "auto");
+
+        assert!(!patterns.is_empty());
+        assert!(patterns.iter().all(|p| p.language() == "python"));
+        assert_eq!(analyzer.language_stats.get("python"), Some(&1));
+
+        analyzer.analyze_code("pub fn one() -> u32 { 1 }", "");
+        assert_eq!(analyzer.language_stats.get("rust"), Some(&1));
+    }
+
+    #[test]
+    fn test_large_file_skips_ast_parser() {
+        let mut analyzer = PatternAnalyzer::new();
+        let options = AnalysisOptions { max_ast_size: Some(64), ..Default::default() };
+
+        let small = "fn small() {}";
+        analyzer.analyze_code_with_options(small, "rust", &options);
+        assert_eq!(analyzer.parse_count(), 1);
+
+        let large = "fn large() {\n    let value = 1;\n}\n".repeat(10);
+        let patterns = analyzer.analyze_code_with_options(&large, "rust", &options);
+        assert_eq!(analyzer.parse_count(), 1);
+        assert!(patterns.iter().any(|p| p.pattern_type() == "function_definition"));
+    }
+
+    #[test]
+    fn test_max_input_bytes_skips_analysis() {
+        let mut analyzer = PatternAnalyzer::new();
+        let options = AnalysisOptions { max_input_bytes: Some(32), ..Default::default() };
+
+        let patterns = analyzer.analyze_code_with_options(&"const value = 1;\n".repeat(10), "javascript", &options);
+        assert!(patterns.is_empty());
+        assert_eq!(analyzer.parse_count(), 0);
+        assert!(analyzer.get_language_stats().contains("{}"));
+    }
+
+    #[test]
+    fn test_force_regex_from_json_options() {
+        let mut analyzer = PatternAnalyzer::new();
+        let patterns = analyzer.analyze_code_with_options_json("def f():\n    return 1\n", "python", r#"{"force_regex": true}"#);
+        assert_eq!(analyzer.parse_count(), 0);
+        assert!(!patterns.is_empty());
+
+        // Malformed options fall back to the default AST path
+        analyzer.analyze_code_with_options_json("def f():\n    return 1\n", "python", "not json");
+        assert_eq!(analyzer.parse_count(), 1);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_abort_returns_partial_patterns() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let total = 5000;
+        let code: String = (0..total).map(|i| format!("function handler{}() {{ return {}; }}\n", i, i)).collect();
+
+        // Trip the flag a few polls into extraction, as a newer keystroke would
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&polls);
+        let abort = AbortFlag::with_check(move || counter.fetch_add(1, Ordering::Relaxed) >= 3);
+
+        let mut analyzer = PatternAnalyzer::new();
+        let analysis = analyzer.analyze_code_abortable(&code, "javascript", &AnalysisOptions::default(), &abort).unwrap();
+        assert!(analysis.aborted);
+        assert!(!analysis.patterns.is_empty());
+        assert!(analysis.patterns.len() < total / 10);
+        assert_eq!(analyzer.get_pattern_count(), analysis.patterns.len());
+
+        // An already-aborted flag skips the work entirely
+        let json = analyzer.analyze_code_abortable_json(&code, "javascript", &abort);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["aborted"], true);
+        assert_eq!(value["patterns"].as_array().unwrap().len(), 0);
+
+        abort.reset();
+        let complete = analyzer.analyze_code_abortable(&code, "javascript", &AnalysisOptions::default(), &AbortFlag::new()).unwrap();
+        assert!(!complete.aborted);
+        assert_eq!(complete.patterns.len(), total);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_abort_stops_the_line_based_paths() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // Nothing to find until the last line, so only per-line polling can stop the scan
+        let tripping_flag = || {
+            let polls = Arc::new(AtomicUsize::new(0));
+            let counter = Arc::clone(&polls);
+            (polls, AbortFlag::with_check(move || counter.fetch_add(1, Ordering::Relaxed) >= 3))
+        };
+        let filler = "x = 1\n".repeat(5000);
+        let mut analyzer = PatternAnalyzer::new();
+        analyzer.register_language_profile("kotlin", kotlin_profile());
+
+        let regex_only = AnalysisOptions { force_regex: true, ..AnalysisOptions::default() };
+        let cases = [
+            (format!("{}function last() {{}}\n", filler), "javascript", &regex_only),
+            (format!("{}fun last() {{}}\n", filler), "kotlin", &AnalysisOptions::default()),
+        ];
+        for (code, language, options) in cases {
+            let (polls, abort) = tripping_flag();
+            let analysis = analyzer.analyze_code_abortable(&code, language, options, &abort).unwrap();
+            assert!(analysis.aborted, "{} was not aborted", language);
+            assert!(analysis.patterns.is_empty());
+            assert_eq!(polls.load(Ordering::Relaxed), 4);
+
+            let complete = analyzer.analyze_code_abortable(&code, language, options, &AbortFlag::new()).unwrap();
+            assert!(!complete.aborted);
+            assert!(!complete.patterns.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_abortable_analysis_reports_oversized_input() {
+        let code = "function handler() { return 1; }\n".repeat(4);
+        let options = AnalysisOptions { max_input_bytes: Some(32), ..AnalysisOptions::default() };
+        let mut analyzer = PatternAnalyzer::new();
+
+        let error = analyzer.analyze_code_abortable(&code, "javascript", &options, &AbortFlag::new()).unwrap_err();
+        assert!(matches!(error, AnalysisError::InputTooLarge { size, limit: 32 } if size == code.len()));
+        assert_eq!(analyzer.parse_count(), 0);
+        assert_eq!(analyzer.get_pattern_count(), 0);
+    }
+
+    #[test]
+    fn test_language_stats_count_analyses_per_language() {
+        let mut analyzer = PatternAnalyzer::new();
+        assert!(analyzer.language_stats().is_empty());
+
+        analyzer.analyze_code("function a() {}", "javascript");
+        analyzer.analyze_code("const b = 1;", "javascript");
+        analyzer.analyze_code("fn main() {}", "rust");
+
+        let stats = analyzer.language_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["javascript"], 2);
+        assert_eq!(stats["rust"], 1);
+    }
+
+    #[test]
+    fn test_language_aliases_route_to_canonical_analysis() {
+        let code = "def f():\n    return 1\n";
+        let mut analyzer = PatternAnalyzer::new();
+        let via_alias = analyzer.analyze_code(code, "py");
+        assert_eq!(analyzer.parse_count(), 1);
+        assert!(!via_alias.is_empty());
+        assert!(via_alias.iter().all(|pattern| pattern.language == "python"));
+        assert_eq!(analyzer.get_language_stats(), r#"{"python":1}"#);
+
+        // Unknown names stay generic until registered
+        assert!(analyzer.analyze_code(code, "snake").is_empty());
+        analyzer.register_language_alias("Snake", "python");
+        assert_eq!(analyzer.canonical_language("SNAKE"), "python");
+        assert_eq!(analyzer.analyze_code(code, "snake").len(), via_alias.len());
+    }
+
+    #[test]
+    fn test_clear_drops_accumulated_state() {
+        let mut analyzer = PatternAnalyzer::new();
+        analyzer.analyze_code("function a() { return 1; }", "javascript");
+        analyzer.analyze_code("def f():\n    return 1\n", "python");
+
+        analyzer.clear_language("JavaScript");
+        assert!(analyzer.get_patterns_by_language("javascript").is_empty());
+        assert!(!analyzer.get_patterns_by_language("python").is_empty());
+        assert_eq!(analyzer.get_language_stats(), r#"{"python":1}"#);
+
+        analyzer.clear();
+        assert_eq!(analyzer.get_pattern_count(), 0);
+        assert_eq!(analyzer.get_language_stats(), "{}");
+
+        // Still usable afterwards
+        assert!(!analyzer.analyze_code("function b() { return 2; }", "javascript").is_empty());
+        assert_eq!(analyzer.get_language_stats(), r#"{"javascript":1}"#);
+
+        // Memory pressure recorded before a clear no longer caps what is retained
+        analyzer.set_pressure_pattern_cap(1);
+        analyzer.set_memory_pressure("high");
+        analyzer.clear();
+        analyzer.analyze_code("function c() {}\nfunction d() {}", "javascript");
+        assert_eq!(analyzer.get_pattern_count(), 2);
+    }
+
+    #[test]
+    fn test_streamed_batches_add_up_to_synchronous_result() {
+        let code: String = (0..100).map(|i| format!("function handler{}() {{ return {}; }}\n", i, i)).collect();
+        let expected: Vec<String> = PatternAnalyzer::new().analyze_code(&code, "javascript")
+            .iter().map(|p| p.id()).collect();
+
+        let mut analyzer = PatternAnalyzer::new();
+        let mut streamed = Vec::new();
+        let mut completions = 0;
+        analyzer.analyze_code_streaming_with(&code, "javascript", |batch, done| {
+            assert!(batch.len() <= STREAM_BATCH_SIZE);
+            assert_eq!(completions, 0, "no batches after completion");
+            if done {
+                assert!(batch.is_empty());
+                completions += 1;
+            }
+            streamed.extend(batch.iter().map(|p| p.id()));
+        });
+
+        assert_eq!(completions, 1);
+        assert!(expected.len() > STREAM_BATCH_SIZE);
+        assert_eq!(streamed, expected);
+        assert_eq!(analyzer.get_pattern_count(), expected.len());
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn test_js_streaming_callback_batches_add_up_to_synchronous_result() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use wasm_bindgen::closure::Closure;
+        use wasm_bindgen::JsCast;
+
+        let code: String = (0..100).map(|i| format!("function handler{}() {{ return {}; }}\n", i, i)).collect();
+        let expected: Vec<String> = PatternAnalyzer::new().analyze_code(&code, "javascript")
+            .iter().map(|p| p.id()).collect();
+
+        let streamed = Rc::new(RefCell::new(Vec::new()));
+        let completions = Rc::new(RefCell::new(0));
+        let (ids, done_calls) = (streamed.clone(), completions.clone());
+        let callback = Closure::<dyn FnMut(js_sys::Array, bool)>::new(move |batch: js_sys::Array, done: bool| {
+            assert!(batch.length() as usize <= STREAM_BATCH_SIZE);
+            for pattern in batch.iter() {
+                let id = js_sys::Reflect::get(&pattern, &JsValue::from_str("id")).unwrap();
+                ids.borrow_mut().push(id.as_string().unwrap());
+            }
+            if done {
+                *done_calls.borrow_mut() += 1;
+            }
+        });
+
+        PatternAnalyzer::new().analyze_code_streaming(&code, "javascript", callback.as_ref().unchecked_ref());
+        assert_eq!(*completions.borrow(), 1);
+        assert_eq!(*streamed.borrow(), expected);
+    }
+
+    #[test]
+    fn test_builtin_batches_stream_before_custom_detectors_run() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let streamed_batches = Arc::new(AtomicUsize::new(0));
+        let seen_by_detector = Arc::new(AtomicUsize::new(usize::MAX));
+        let mut analyzer = PatternAnalyzer::new();
+        let (streamed, seen) = (streamed_batches.clone(), seen_by_detector.clone());
+        analyzer.register_custom_predicate("todo_marker", move |code| {
+            seen.store(streamed.load(Ordering::SeqCst), Ordering::SeqCst);
+            code.contains("TODO")
+        });
+
+        let mut custom_batch = None;
+        analyzer.analyze_code_streaming_with("// TODO\nfunction load() { return 1; }\n", "javascript", |batch, done| {
+            if done {
+                return;
+            }
+            if batch.iter().any(|pattern| pattern.pattern_type == PatternType::Custom("todo_marker".to_string())) {
+                custom_batch = Some(streamed_batches.load(Ordering::SeqCst));
+            }
+            streamed_batches.fetch_add(1, Ordering::SeqCst);
+        });
+
+        // The function pattern was delivered before the custom detector ran, in an earlier batch
+        assert_eq!(seen_by_detector.load(Ordering::SeqCst), 1);
+        assert_eq!(custom_batch, Some(1));
+    }
+
+    #[test]
+    fn test_regex_pattern_ids_are_unique_and_reproducible() {
+        let code = "function a() {}\nconst b = 1;\nclass C {}";
+        let options = AnalysisOptions { force_regex: true, ..Default::default() };
+
+        let mut analyzer = PatternAnalyzer::new();
+        let first: Vec<String> = analyzer.analyze_code_with_options(code, "javascript", &options)
+            .iter().map(|p| p.id()).collect();
+        let unique: std::collections::HashSet<&String> = first.iter().collect();
+        assert_eq!(unique.len(), first.len());
+
+        // Ids come from each pattern's own line, so unrelated edits elsewhere leave them alone
+        let edited = format!("// header\n{}\nconst later = 2;", code);
+        for _ in 0..20 {
+            let repeated: Vec<String> = analyzer.analyze_code_with_options(&edited, "javascript", &options)
+                .iter().map(|p| p.id()).collect();
+            assert_eq!(first, repeated);
+        }
+
+        // The same line inside a different block is a different pattern
+        let nested = "if (ready) {\n  function a() {}\n}";
+        let nested_ids: Vec<String> = analyzer.analyze_code_with_options(nested, "javascript", &options)
+            .iter().map(|p| p.id()).collect();
+        assert_ne!(first[0], nested_ids[0]);
+    }
+
+    #[test]
+    fn test_high_memory_pressure_caps_retained_patterns() {
+        let mut analyzer = PatternAnalyzer::new();
+        analyzer.set_pressure_pattern_cap(3);
+        for (index, confidence) in [0.4, 0.9, 0.2, 0.8, 0.6, 0.9].iter().enumerate() {
+            let mut pattern = CodingPattern::new(format!("p{}", index), "function_definition".to_string(), "javascript".to_string(), *confidence);
+            pattern.last_seen = index.to_string();
+            analyzer.patterns.push(pattern);
+        }
+
+        // Below the high threshold nothing is evicted
+        assert_eq!(analyzer.set_memory_pressure("medium"), 0);
+        assert_eq!(analyzer.get_pattern_count(), 6);
+
+        assert_eq!(analyzer.set_memory_pressure("high"), 3);
+        let ids: Vec<String> = analyzer.get_patterns().iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec!["p1", "p3", "p5"]);
+
+        // Shrinking the cap under pressure prefers the newer of two equal-confidence patterns
+        analyzer.set_pressure_pattern_cap(1);
+        assert_eq!(analyzer.get_patterns()[0].id(), "p5");
+
+        // New analyses stay capped until the pressure drops
+        analyzer.analyze_code("function a() {}\nfunction b() {}", "javascript");
+        assert_eq!(analyzer.get_pattern_count(), 1);
+        analyzer.set_memory_pressure("low");
+        analyzer.analyze_code("function a() {}\nfunction b() {}", "javascript");
+        assert!(analyzer.get_pattern_count() > 1);
+    }
+
+    #[test]
+    fn test_custom_detector_emits_custom_patterns() {
+        let mut analyzer = PatternAnalyzer::new();
+        assert!(analyzer.register_custom_detector("audit_log", r"audit\.log\(\s*'[\w.]+'"));
+        assert!(!analyzer.register_custom_detector("broken", "audit.log("));
+
+        let code = "function pay(order) {\n  audit.log('payment.start', order);\n  charge(order);\n  audit.log('payment.done', order);\n}\n";
+        let patterns = analyzer.analyze_code(code, "javascript");
+        let custom: Vec<&CodingPattern> = patterns.iter().filter(|p| p.pattern_type() == "audit_log").collect();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].pattern_type, PatternType::Custom("audit_log".to_string()));
+        assert_eq!(custom[0].frequency(), 2);
+        assert!(matches!(custom[0].content(), PatternContent::String { pattern, .. } if pattern == "audit.log('payment.start'"));
+
+        analyzer.register_custom_predicate("audit_log", |code| code.contains("console.log"));
+        assert!(analyzer.analyze_code(code, "javascript").iter().all(|p| p.pattern_type() != "audit_log"));
+        assert_eq!(analyzer.analyze_code("console.log(1);", "javascript").iter().filter(|p| p.pattern_type() == "audit_log").count(), 1);
+    }
+
+    #[test]
+    fn test_malformed_and_non_ascii_input_returns_json() {
+        let mut analyzer = PatternAnalyzer::new();
+        for (code, language) in [
+            ("let is日x = true;\nconst s = \"\n", "javascript"),
+            ("is日x = 1\nhasé = 'a\ndef f(\n", "python"),
+            ("fn main() { let sé = r#\"x; }\nimpl A { fn f(&self) {} }\n", "rust"),
+        ] {
+            let json: serde_json::Value = serde_json::from_str(&analyzer.analyze_code_json(code, language)).unwrap();
+            assert!(json["patterns"].is_array(), "{}: {}", code, json);
+
+            let style: serde_json::Value = serde_json::from_str(&wasm_interface::analyze_style(code, language)).unwrap();
+            assert!(style["consistency_scores"].is_object() || style["error"]["kind"] == "parse_failed", "{}: {}", code, style);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&analyzer.analyze_code_json("function f() {}", "javascript")).unwrap();
+        assert!(json["patterns"].as_array().is_some_and(|patterns| !patterns.is_empty()));
+    }
+
+    #[test]
+    fn test_analyze_selection_returns_only_selected_patterns_with_outer_context() {
+        let code = "const TAX_RATE = 0.2;\nlet discount = 5;\n\nfunction subtotal(items) {\n  return items.reduce((sum, item) => sum + item.price, 0);\n}\n\nfunction total(items) {\n  const net = subtotal(items) - discount;\n  return net * (1 + TAX_RATE);\n}\n\nfunction formatPrice(value) {\n  return '$' + value.toFixed(2);\n}\n";
+        let start = code.find("function total").unwrap();
+        let end = code.find("\n\nfunction formatPrice").unwrap();
+
+        let mut analyzer = PatternAnalyzer::new();
+        let json: serde_json::Value = serde_json::from_str(&analyzer.analyze_selection(code, start, end, "javascript")).unwrap();
+        let ids: Vec<&str> = json["patterns"].as_array().unwrap().iter().filter_map(|pattern| pattern["id"].as_str()).collect();
+        assert!(ids.iter().any(|id| id.ends_with("function_definition_total")));
+        assert!(ids.iter().all(|id| !id.contains("subtotal") && !id.contains("formatPrice") && !id.contains("TAX_RATE")));
+        let located = |json: &serde_json::Value, suffix: &str| {
+            let pattern = json["patterns"].as_array().unwrap().iter()
+                .find(|pattern| pattern["id"].as_str().unwrap().ends_with(suffix))
+                .unwrap();
+            (pattern["location"]["start_line"].as_u64().unwrap(), pattern["location"]["start_column"].as_u64().unwrap())
+        };
+        assert_eq!(located(&json, "function_definition_total"), (8, 0));
+
+        // `items` is the selection's own parameter; `item.price` style properties are not names
+        assert_eq!(json["outer_references"], serde_json::json!(["subtotal", "discount", "TAX_RATE"]));
+
+        // An indented Python method still parses once selected
+        let python = "LIMIT = 3\n\nclass Queue:\n    def push(self, item):\n        return len(self.items) < LIMIT\n\n    def pop(self):\n        return self.items.pop()\n";
+        let start = python.find("    def push").unwrap();
+        let end = python.find("\n\n    def pop").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&analyzer.analyze_selection(python, start, end, "python")).unwrap();
+        let ids: Vec<&str> = json["patterns"].as_array().unwrap().iter().filter_map(|pattern| pattern["id"].as_str()).collect();
+        assert!(ids.iter().any(|id| id.ends_with("function_definition_push")));
+        assert!(ids.iter().all(|id| !id.contains("pop") && !id.contains("Queue")));
+        assert_eq!(json["outer_references"], serde_json::json!(["LIMIT"]));
+        assert_eq!(located(&json, "function_definition_push"), (4, 4));
+
+        // A selection starting mid-line is shifted along that line
+        let inline = "let ready = false; function start() {}\n";
+        let start = inline.find("function start").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&analyzer.analyze_selection(inline, start, inline.len(), "javascript")).unwrap();
+        assert_eq!(located(&json, "function_definition_start"), (1, 19));
+    }
+
+    #[test]
+    fn test_summary_verbosity_trims_returned_patterns_but_not_stored_ones() {
+        let code = "function add(a, b) {\n  return a + b;\n}\n";
+        let options = AnalysisOptions { file_path: Some("src/math.js".to_string()), ..AnalysisOptions::default() };
+        let mut analyzer = PatternAnalyzer::new();
+        let full = analyzer.analyze_code_with_options(code, "javascript", &options);
+
+        analyzer.set_verbosity(OutputVerbosity::Summary);
+        let summary = analyzer.analyze_code_with_options(code, "javascript", &options);
+        assert_eq!(summary.len(), full.len());
+        let size = |patterns: &[CodingPattern]| serde_json::to_string(patterns).unwrap().len();
+        assert!(size(&summary) < size(&full));
+        assert!(summary.iter().all(|pattern| pattern.location().is_none() && pattern.get_source_files().is_empty()));
+
+        // The analyzer still remembers where each pattern came from
+        assert!(analyzer.get_patterns().iter().all(|pattern| pattern.get_source_files() == ["src/math.js"]));
+    }
+
+    #[test]
+    fn test_stale_patterns_lose_effective_confidence() {
+        let now = 100.0 * MILLIS_PER_DAY;
+        let mut analyzer = PatternAnalyzer::new();
+        for (id, days_ago) in [("old", 28.0), ("new", 0.5)] {
+            let mut pattern = CodingPattern::new(id.to_string(), "function_definition".to_string(), "rust".to_string(), 0.8);
+            pattern.last_seen = ((now - days_ago * MILLIS_PER_DAY) as u64).to_string();
+            analyzer.patterns.push(pattern);
+        }
+        assert_eq!(analyzer.get_top_patterns(1)[0].id(), "old");
+
+        analyzer.age_confidence(now);
+        let patterns = analyzer.get_patterns();
+        let (old, new) = (&patterns[0], &patterns[1]);
+        assert!(old.effective_confidence() < new.effective_confidence());
+        assert!((old.effective_confidence() - 0.2).abs() < 1e-9); // two half-lives
+        assert_eq!(old.confidence(), 0.8);
+        assert_eq!(analyzer.get_top_patterns(1)[0].id(), "new");
+
+        // A NaN confidence sorts instead of panicking
+        analyzer.patterns[1].decay_factor = f64::NAN;
+        assert_eq!(analyzer.get_top_patterns(2).len(), 2);
+    }
 }
@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc, Duration};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 
 /// Secure local storage system for user patterns, learning history, and cached analysis results
 pub struct LocalStorageManager {
-    storage_backend: StorageBackend,
+    storage_settings: StorageSettings,
+    backend: Box<dyn StorageBackend>,
     encryption_manager: EncryptionManager,
     cache_manager: CacheManager,
     data_validation: DataValidation,
+    user_cache: HashMap<String, UserPatternData>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,20 +79,100 @@ pub struct StorageMetadata {
 }
 
 // Storage backend implementation
+/// Where serialized user data lives; implement this to plug in e.g. a SQLite or S3 store.
+/// Backends must be `Send` so a `LocalStorageManager` can move to a worker thread
+pub trait StorageBackend: Send {
+    fn read(&self, key: &str) -> Result<Vec<u8>, String>;
+    fn write(&mut self, key: &str, data: &[u8]) -> Result<(), String>;
+    fn delete(&mut self, key: &str) -> Result<(), String>;
+    fn list(&self) -> Result<Vec<String>, String>;
+}
+
 #[derive(Debug, Clone)]
-pub struct StorageBackend {
+pub struct StorageSettings {
     storage_type: StorageType,
     base_path: String,
     compression_enabled: bool,
     backup_enabled: bool,
 }
 
+/// Backend for `StorageType::InMemory`
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBackend {
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl StorageBackend for InMemoryBackend {
+    fn read(&self, key: &str) -> Result<Vec<u8>, String> {
+        self.entries.get(key)
+            .cloned()
+            .ok_or_else(|| format!("No stored data for {}", key))
+    }
+
+    fn write(&mut self, key: &str, data: &[u8]) -> Result<(), String> {
+        self.entries.insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<(), String> {
+        self.entries.remove(key);
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        let mut keys: Vec<String> = self.entries.keys().cloned().collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Stand-in for the file and browser backends until they are wired to their host APIs:
+/// writes are accepted and dropped, reads find nothing
+#[derive(Debug, Clone)]
+struct UnavailableBackend;
+
+impl StorageBackend for UnavailableBackend {
+    fn read(&self, _key: &str) -> Result<Vec<u8>, String> {
+        Err("Storage not implemented".to_string())
+    }
+
+    fn write(&mut self, _key: &str, _data: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn delete(&mut self, _key: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+}
+
+/// Schema version written by this build; older stored data is migrated on load
+pub const CURRENT_DATA_VERSION: u32 = 2;
+
+/// Identifies an `export_user_data` envelope
+pub const EXPORT_FORMAT: &str = "code-whisperer-export";
+/// Newest envelope version `import_user_data` understands
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Portable export: the stored blob, base64-encoded so any stored bytes survive as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportEnvelope {
+    pub format: String,
+    pub version: u32,
+    pub encrypted: bool, // the payload is a passphrase-protected blob rather than plain JSON
+    pub payload: String,
+}
+
 #[derive(Debug, Clone)]
 pub enum StorageType {
     LocalFile,      // Local filesystem storage
     IndexedDB,      // Browser IndexedDB storage
     LocalStorage,   // Browser localStorage
     InMemory,       // In-memory storage (testing)
+    Custom,         // Injected with `LocalStorageManager::with_backend`
 }
 
 // Encryption and security
@@ -97,9 +181,11 @@ pub struct EncryptionManager {
     encryption_enabled: bool,
     key_derivation_method: KeyDerivationMethod,
     cipher_algorithm: CipherAlgorithm,
+    passphrase: Option<String>, // without one, data is stored as plain JSON
+    kdf_params: Option<KeyDerivationParams>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum KeyDerivationMethod {
     PBKDF2,
     Argon2,
@@ -107,6 +193,92 @@ pub enum KeyDerivationMethod {
     None,
 }
 
+const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+const MAX_ARGON2_ITERATIONS: u32 = 64;
+const MAX_KDF_PARALLELISM: u32 = 16;
+const MAX_KDF_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_BLOCK_SIZE: u32 = 32;
+
+/// A key derivation method with its cost parameters, stored alongside the protected data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum KeyDerivationParams {
+    Pbkdf2 { iterations: u32 },
+    Argon2 { memory_kib: u32, iterations: u32, parallelism: u32 },
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl KeyDerivationParams {
+    /// OWASP-recommended costs; `KeyDerivationMethod::None` has none
+    pub fn recommended(method: &KeyDerivationMethod) -> Option<Self> {
+        match method {
+            KeyDerivationMethod::PBKDF2 => Some(KeyDerivationParams::Pbkdf2 { iterations: 600_000 }),
+            KeyDerivationMethod::Argon2 => Some(KeyDerivationParams::Argon2 { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }),
+            KeyDerivationMethod::Scrypt => Some(KeyDerivationParams::Scrypt { log_n: 17, r: 8, p: 1 }),
+            KeyDerivationMethod::None => None,
+        }
+    }
+
+    pub fn method(&self) -> KeyDerivationMethod {
+        match self {
+            KeyDerivationParams::Pbkdf2 { .. } => KeyDerivationMethod::PBKDF2,
+            KeyDerivationParams::Argon2 { .. } => KeyDerivationMethod::Argon2,
+            KeyDerivationParams::Scrypt { .. } => KeyDerivationMethod::Scrypt,
+        }
+    }
+
+    /// Rejects costs outside what a browser tab can afford. Parameters come back from storage, so a
+    /// tampered blob must not be able to overflow the scrypt cost or allocate gigabytes
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = match *self {
+            KeyDerivationParams::Pbkdf2 { iterations } => (1..=MAX_KDF_ITERATIONS).contains(&iterations),
+            KeyDerivationParams::Argon2 { memory_kib, iterations, parallelism } => {
+                (1..=MAX_KDF_PARALLELISM).contains(&parallelism)
+                    && (8 * parallelism..=MAX_KDF_MEMORY_KIB).contains(&memory_kib)
+                    && (1..=MAX_ARGON2_ITERATIONS).contains(&iterations)
+            },
+            KeyDerivationParams::Scrypt { log_n, r, p } => {
+                // scrypt holds 128 * r * N bytes
+                (1..=MAX_SCRYPT_LOG_N).contains(&log_n)
+                    && (1..=MAX_SCRYPT_BLOCK_SIZE).contains(&r)
+                    && (1..=MAX_KDF_PARALLELISM).contains(&p)
+                    && (128u64 * r as u64) << log_n <= MAX_KDF_MEMORY_KIB as u64 * 1024
+            },
+        };
+        if in_range {
+            Ok(())
+        } else {
+            Err(format!("Key derivation parameters out of bounds: {:?}", self))
+        }
+    }
+
+    /// 256-bit key from `passphrase`, once the parameters pass `validate`
+    pub fn derive_key(&self, passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+        self.validate()?;
+        let mut key = [0u8; 32];
+        match *self {
+            KeyDerivationParams::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key)
+            },
+            KeyDerivationParams::Argon2 { memory_kib, iterations, parallelism } => {
+                let params = argon2::Params::new(memory_kib, iterations, parallelism, Some(key.len()))
+                    .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|e| format!("Argon2 key derivation failed: {}", e))?
+            },
+            KeyDerivationParams::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, key.len())
+                    .map_err(|e| format!("Invalid scrypt parameters: {}", e))?;
+                scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+                    .map_err(|e| format!("scrypt key derivation failed: {}", e))?
+            },
+        }
+        Ok(key)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum CipherAlgorithm {
     AES256GCM,
@@ -114,6 +286,27 @@ pub enum CipherAlgorithm {
     None,
 }
 
+/// Stored form of passphrase-protected user data: the payload in the clear, an HMAC-SHA256 tag, and
+/// everything needed to re-derive the tag key except the passphrase. No cipher is wired in yet, so
+/// this detects tampering and wrong passphrases but does not hide the data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuthenticatedBlob {
+    kdf: KeyDerivationParams,
+    salt: String,
+    tag: String,
+    payload: String,
+}
+
+impl AuthenticatedBlob {
+    fn mac(&self, key: &[u8; 32]) -> Hmac<Sha256> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        // Covers the key parameters too so they can't be swapped undetected
+        mac.update(format!("{}:{}:", serde_json::to_string(&self.kdf).unwrap_or_default(), self.salt).as_bytes());
+        mac.update(self.payload.as_bytes());
+        mac
+    }
+}
+
 // Cache management
 #[derive(Debug, Clone)]
 pub struct CacheManager {
@@ -203,6 +396,55 @@ pub struct UsageStatistics {
     pub context_usage: HashMap<String, u32>,
 }
 
+/// Maximum number of timestamps kept in `UsageStatistics::recent_usage`
+const MAX_RECENT_USAGE: usize = 50;
+
+impl UsageStatistics {
+    pub fn new() -> Self {
+        Self {
+            total_uses: 0,
+            successful_uses: 0,
+            recent_usage: Vec::new(),
+            average_time_saved: Duration::zero(),
+            context_usage: HashMap::new(),
+        }
+    }
+
+    /// Record a single use of the pattern
+    pub fn record_use(&mut self, success: bool, time_saved: Duration, timestamp: DateTime<Utc>) {
+        let previous_uses = self.total_uses as i64;
+        self.total_uses += 1;
+        if success {
+            self.successful_uses += 1;
+        }
+
+        // Running average over all recorded uses
+        let total_ms = self.average_time_saved.num_milliseconds() * previous_uses + time_saved.num_milliseconds();
+        self.average_time_saved = Duration::milliseconds(total_ms / self.total_uses as i64);
+
+        self.recent_usage.push(timestamp);
+        if self.recent_usage.len() > MAX_RECENT_USAGE {
+            let overflow = self.recent_usage.len() - MAX_RECENT_USAGE;
+            self.recent_usage.drain(..overflow);
+        }
+    }
+
+    /// Fraction of uses that were successful
+    pub fn success_rate(&self) -> f32 {
+        if self.total_uses == 0 {
+            0.0
+        } else {
+            self.successful_uses as f32 / self.total_uses as f32
+        }
+    }
+}
+
+impl Default for UsageStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LearningSession {
     pub session_id: String,
@@ -354,6 +596,17 @@ pub struct StyleSuggestion {
     pub description: String,
     pub confidence: f32,
     pub auto_applicable: bool,
+    #[serde(default)]
+    pub fix: Option<TextEdit>,
+}
+
+/// Replacement of whole lines `start_line..=end_line` (1-based)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub original: String,
+    pub replacement: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -392,6 +645,10 @@ pub enum RefactoringType {
     MoveMethod,
     SimplifyCondition,
     RemoveDuplication,
+    UseAsyncAwait,
+    HandleErrors,
+    CloseResource,
+    UnifyReturns,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -532,17 +789,33 @@ pub struct NotificationSettings {
 
 impl LocalStorageManager {
     pub fn new(storage_type: StorageType, base_path: String) -> Self {
+        let backend: Box<dyn StorageBackend> = match storage_type {
+            StorageType::InMemory => Box::new(InMemoryBackend::default()),
+            _ => Box::new(UnavailableBackend),
+        };
+        Self::build(storage_type, base_path, backend)
+    }
+
+    /// Use a custom backend for all reads and writes
+    pub fn with_backend(backend: Box<dyn StorageBackend>) -> Self {
+        Self::build(StorageType::Custom, String::new(), backend)
+    }
+
+    fn build(storage_type: StorageType, base_path: String, backend: Box<dyn StorageBackend>) -> Self {
         Self {
-            storage_backend: StorageBackend {
+            storage_settings: StorageSettings {
                 storage_type,
                 base_path,
                 compression_enabled: true,
                 backup_enabled: true,
             },
+            backend,
             encryption_manager: EncryptionManager {
                 encryption_enabled: true,
                 key_derivation_method: KeyDerivationMethod::PBKDF2,
-                cipher_algorithm: CipherAlgorithm::AES256GCM,
+                cipher_algorithm: CipherAlgorithm::None, // no cipher is implemented yet
+                passphrase: None,
+                kdf_params: None,
             },
             cache_manager: CacheManager {
                 max_cache_size: 100 * 1024 * 1024, // 100MB
@@ -555,17 +828,30 @@ impl LocalStorageManager {
                 integrity_checks: true,
                 version_compatibility: true,
             },
+            user_cache: HashMap::new(),
         }
     }
 
+    /// Authenticate stored data with a key derived from `passphrase`, so tampering or a wrong
+    /// passphrase is detected on load. The data itself is still stored readable
+    pub fn with_passphrase(mut self, passphrase: &str, params: KeyDerivationParams) -> Self {
+        self.encryption_manager = EncryptionManager {
+            encryption_enabled: true,
+            key_derivation_method: params.method(),
+            cipher_algorithm: self.encryption_manager.cipher_algorithm,
+            passphrase: Some(passphrase.to_string()),
+            kdf_params: Some(params),
+        };
+        self
+    }
+
     /// Store user pattern data
     pub fn store_user_data(&mut self, user_id: &str, data: &UserPatternData) -> StorageResult<()> {
         match self.validate_data(data) {
             Ok(_) => {
-                let encrypted_data = self.encrypt_data(data);
-                match encrypted_data {
-                    Ok(encrypted) => {
-                        let storage_result = self.write_to_storage(user_id, &encrypted);
+                match self.authenticate_data(data) {
+                    Ok(sealed) => {
+                        let storage_result = self.write_to_storage(user_id, &sealed);
                         self.update_cache(user_id, data.clone());
                         
                         StorageResult {
@@ -578,7 +864,7 @@ impl LocalStorageManager {
                     Err(e) => StorageResult {
                         data: None,
                         success: false,
-                        error_message: Some(format!("Encryption failed: {}", e)),
+                        error_message: Some(format!("Sealing failed: {}", e)),
                         timestamp: Utc::now(),
                     }
                 }
@@ -606,8 +892,8 @@ impl LocalStorageManager {
 
         // Load from storage
         match self.read_from_storage(user_id) {
-            Ok(encrypted_data) => {
-                match self.decrypt_data(&encrypted_data) {
+            Ok(stored) => {
+                match self.verify_data(&stored) {
                     Ok(data) => {
                         self.update_cache(user_id, data.clone());
                         StorageResult {
@@ -620,7 +906,7 @@ impl LocalStorageManager {
                     Err(e) => StorageResult {
                         data: None,
                         success: false,
-                        error_message: Some(format!("Decryption failed: {}", e)),
+                        error_message: Some(format!("Verification failed: {}", e)),
                         timestamp: Utc::now(),
                     }
                 }
@@ -693,6 +979,35 @@ impl LocalStorageManager {
         }
     }
 
+    /// Record that a pattern was applied, updating its usage statistics
+    pub fn record_pattern_use(&mut self, user_id: &str, pattern_id: &str, success: bool, time_saved: Duration) -> StorageResult<()> {
+        match self.load_user_data(user_id) {
+            StorageResult { data: Some(mut user_data), success: true, .. } => {
+                let now = Utc::now();
+                let stats = user_data.pattern_library.pattern_usage_stats
+                    .entry(pattern_id.to_string())
+                    .or_insert_with(UsageStatistics::new);
+                stats.record_use(success, time_saved, now);
+                let success_rate = stats.success_rate();
+
+                if let Some(pattern) = user_data.pattern_library.personal_patterns.get_mut(pattern_id) {
+                    pattern.usage_frequency += 1;
+                    pattern.last_used = now;
+                    pattern.success_rate = success_rate;
+                }
+
+                user_data.metadata.last_updated = now;
+                self.store_user_data(user_id, &user_data)
+            },
+            result => StorageResult {
+                data: None,
+                success: false,
+                error_message: Some("Failed to load user data".to_string()),
+                timestamp: result.timestamp,
+            }
+        }
+    }
+
     /// Clean up expired cache entries
     pub fn cleanup_expired_cache(&mut self) -> u32 {
         let now = Utc::now();
@@ -709,7 +1024,13 @@ impl LocalStorageManager {
     pub fn export_user_data(&self, user_id: &str) -> StorageResult<String> {
         match self.read_from_storage(user_id) {
             Ok(data) => {
-                match serde_json::to_string_pretty(&data) {
+                let envelope = ExportEnvelope {
+                    format: EXPORT_FORMAT.to_string(),
+                    version: EXPORT_VERSION,
+                    encrypted: serde_json::from_slice::<AuthenticatedBlob>(&data).is_ok(),
+                    payload: base64_encode(&data),
+                };
+                match serde_json::to_string_pretty(&envelope) {
                     Ok(json_data) => StorageResult {
                         data: Some(json_data),
                         success: true,
@@ -733,16 +1054,22 @@ impl LocalStorageManager {
         }
     }
 
-    /// Import user data from backup
+    /// Import user data from backup: an export envelope, or plain `UserPatternData` JSON
     pub fn import_user_data(&mut self, user_id: &str, json_data: &str) -> StorageResult<()> {
-        match serde_json::from_str::<UserPatternData>(json_data) {
+        let imported = match serde_json::from_str::<ExportEnvelope>(json_data) {
+            Ok(envelope) if envelope.format == EXPORT_FORMAT => self.open_envelope(&envelope),
+            _ => serde_json::from_str::<UserPatternData>(json_data)
+                .map_err(|e| format!("Import deserialization failed: {}", e)),
+        };
+
+        match imported {
             Ok(user_data) => {
                 self.store_user_data(user_id, &user_data)
             },
             Err(e) => StorageResult {
                 data: None,
                 success: false,
-                error_message: Some(format!("Import deserialization failed: {}", e)),
+                error_message: Some(e),
                 timestamp: Utc::now(),
             }
         }
@@ -770,6 +1097,15 @@ impl LocalStorageManager {
         }
     }
 
+    pub fn storage_type(&self) -> &StorageType {
+        &self.storage_settings.storage_type
+    }
+
+    /// Users with data in the backend
+    pub fn stored_user_ids(&self) -> Result<Vec<String>, String> {
+        self.backend.list()
+    }
+
     /// Get storage statistics
     pub fn get_storage_stats(&self) -> StorageStats {
         StorageStats {
@@ -800,62 +1136,174 @@ impl LocalStorageManager {
         Ok(())
     }
 
-    fn encrypt_data(&self, data: &UserPatternData) -> Result<Vec<u8>, String> {
+    /// Upgrade stored data written at `from_version`, one schema step at a time
+    pub fn migrate(&self, data: serde_json::Value, from_version: u32) -> Result<UserPatternData, String> {
+        let mut data = data;
+        let mut version = from_version;
+        while version < CURRENT_DATA_VERSION {
+            data = match version {
+                1 => migrate_v1_to_v2(data)?,
+                _ => return Err(format!("No migration from data version {}", version)),
+            };
+            version += 1;
+        }
+
+        data["metadata"]["data_version"] = version.into();
+        serde_json::from_value(data).map_err(|e| format!("Migrated data is invalid: {}", e))
+    }
+
+    fn deserialize_user_data(&self, bytes: &[u8]) -> Result<UserPatternData, String> {
+        let value: serde_json::Value = serde_json::from_slice(bytes)
+            .map_err(|e| format!("Deserialization failed: {}", e))?;
+        let version = value["metadata"]["data_version"].as_u64().unwrap_or(0) as u32;
+
+        if version > CURRENT_DATA_VERSION {
+            Err(format!("Data version {} is newer than supported version {}", version, CURRENT_DATA_VERSION))
+        } else if version < CURRENT_DATA_VERSION {
+            self.migrate(value, version)
+        } else {
+            serde_json::from_value(value).map_err(|e| format!("Deserialization failed: {}", e))
+        }
+    }
+
+    fn open_envelope(&self, envelope: &ExportEnvelope) -> Result<UserPatternData, String> {
+        if envelope.version == 0 || envelope.version > EXPORT_VERSION {
+            return Err(format!("Unsupported export version: {}", envelope.version));
+        }
+
+        let blob = base64_decode(&envelope.payload)?;
+        if envelope.encrypted {
+            self.verify_data(&blob)
+        } else {
+            self.deserialize_user_data(&blob)
+        }
+    }
+
+    /// Serialized `data`, wrapped in an `AuthenticatedBlob` when a passphrase is configured
+    fn authenticate_data(&self, data: &UserPatternData) -> Result<Vec<u8>, String> {
+        let serialized = serde_json::to_vec(data)
+            .map_err(|e| format!("Serialization failed: {}", e))?;
         if !self.encryption_manager.encryption_enabled {
-            // Return serialized data without encryption
-            return serde_json::to_vec(data)
-                .map_err(|e| format!("Serialization failed: {}", e));
+            return Ok(serialized);
         }
+        let (Some(passphrase), Some(kdf)) = (&self.encryption_manager.passphrase, &self.encryption_manager.kdf_params) else {
+            return Ok(serialized);
+        };
 
-        // In a real implementation, this would use actual encryption
-        // For now, just serialize the data
-        serde_json::to_vec(data)
-            .map_err(|e| format!("Encryption serialization failed: {}", e))
+        // v4 UUIDs carry 122 random bits, enough for a per-record salt
+        let salt = uuid::Uuid::new_v4().into_bytes();
+        let key = kdf.derive_key(passphrase, &salt)?;
+        let mut blob = AuthenticatedBlob {
+            kdf: kdf.clone(),
+            salt: base64_encode(&salt),
+            tag: String::new(),
+            payload: base64_encode(&serialized),
+        };
+        blob.tag = base64_encode(&blob.mac(&key).finalize().into_bytes());
+
+        serde_json::to_vec(&blob).map_err(|e| format!("Serialization failed: {}", e))
     }
 
-    fn decrypt_data(&self, encrypted_data: &[u8]) -> Result<UserPatternData, String> {
+    /// Inverse of `authenticate_data`; a blob whose tag does not match is an error
+    fn verify_data(&self, stored: &[u8]) -> Result<UserPatternData, String> {
         if !self.encryption_manager.encryption_enabled {
-            // Deserialize without decryption
-            return serde_json::from_slice(encrypted_data)
-                .map_err(|e| format!("Deserialization failed: {}", e));
+            return self.deserialize_user_data(stored);
         }
 
-        // In a real implementation, this would use actual decryption
-        // For now, just deserialize the data
-        serde_json::from_slice(encrypted_data)
-            .map_err(|e| format!("Decryption deserialization failed: {}", e))
+        let Some(passphrase) = self.encryption_manager.passphrase.as_ref() else {
+            if serde_json::from_slice::<AuthenticatedBlob>(stored).is_ok() {
+                return Err("Data is passphrase-protected but no passphrase is configured".to_string());
+            }
+            return self.deserialize_user_data(stored);
+        };
+        let blob = serde_json::from_slice::<AuthenticatedBlob>(stored)
+            .map_err(|e| format!("Stored data is not a valid authenticated blob: {}", e))?;
+
+        let key = blob.kdf.derive_key(passphrase, &base64_decode(&blob.salt)?)?;
+        // `verify_slice` compares in constant time
+        blob.mac(&key)
+            .verify_slice(&base64_decode(&blob.tag)?)
+            .map_err(|_| "Authentication failed: wrong passphrase or corrupted data".to_string())?;
+        self.deserialize_user_data(&base64_decode(&blob.payload)?)
     }
 
-    fn write_to_storage(&self, user_id: &str, data: &[u8]) -> Result<(), String> {
-        // Simplified storage implementation
-        // In a real implementation, this would write to the actual storage backend
-        Ok(())
+    fn write_to_storage(&mut self, user_id: &str, data: &[u8]) -> Result<(), String> {
+        self.backend.write(user_id, data)
     }
 
     fn read_from_storage(&self, user_id: &str) -> Result<Vec<u8>, String> {
-        // Simplified storage implementation
-        // In a real implementation, this would read from the actual storage backend
-        Err("Storage not implemented".to_string())
+        self.backend.read(user_id)
     }
 
-    fn delete_from_storage(&self, user_id: &str) -> Result<(), String> {
-        // Simplified storage implementation
-        Ok(())
+    fn delete_from_storage(&mut self, user_id: &str) -> Result<(), String> {
+        self.backend.delete(user_id)
     }
 
     fn update_cache(&mut self, user_id: &str, data: UserPatternData) {
-        // Simplified cache implementation
-        // In a real implementation, this would update the actual cache
+        self.user_cache.insert(user_id.to_string(), data);
     }
 
     fn get_from_cache(&self, user_id: &str) -> Option<UserPatternData> {
-        // Simplified cache implementation
-        None
+        self.user_cache.get(user_id).cloned()
     }
 
     fn clear_user_cache(&mut self, user_id: &str) {
-        // Simplified cache implementation
+        self.user_cache.remove(user_id);
+    }
+}
+
+/// v1 payloads predate per-pattern usage statistics
+fn migrate_v1_to_v2(mut data: serde_json::Value) -> Result<serde_json::Value, String> {
+    let library = data.get_mut("pattern_library")
+        .and_then(|library| library.as_object_mut())
+        .ok_or("v1 data has no pattern_library")?;
+    library.entry("pattern_usage_stats").or_insert_with(|| serde_json::json!({}));
+    Ok(data)
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard padded base64
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(triple >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn base64_decode(encoded: &str) -> Result<Vec<u8>, String> {
+    let symbols: Vec<u8> = encoded.bytes().filter(|byte| !byte.is_ascii_whitespace()).collect();
+    if !symbols.len().is_multiple_of(4) {
+        return Err("Invalid base64 payload length".to_string());
+    }
+
+    let chunk_count = symbols.len() / 4;
+    let mut decoded = Vec::with_capacity(chunk_count * 3);
+    for (index, chunk) in symbols.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&byte| byte == b'=').count();
+        if padding > 2 || (padding > 0 && index + 1 < chunk_count) {
+            return Err("Invalid base64 padding".to_string());
+        }
+        let mut triple = 0u32;
+        for &byte in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|&symbol| symbol == byte)
+                .ok_or_else(|| format!("Invalid base64 character: {}", byte as char))?;
+            triple = triple << 6 | value as u32;
+        }
+        triple <<= 6 * padding as u32;
+        decoded.extend_from_slice(&triple.to_be_bytes()[1..4 - padding]);
     }
+    Ok(decoded)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -931,7 +1379,7 @@ impl Default for UserPatternData {
                 created_at: now,
                 last_updated: now,
                 last_accessed: now,
-                data_version: 1,
+                data_version: CURRENT_DATA_VERSION,
                 backup_count: 0,
                 total_storage_size: 0,
             },
@@ -1024,6 +1472,8 @@ mod tests {
             encryption_enabled: true,
             key_derivation_method: KeyDerivationMethod::Argon2,
             cipher_algorithm: CipherAlgorithm::ChaCha20Poly1305,
+            passphrase: None,
+            kdf_params: None,
         };
         
         assert!(encryption.encryption_enabled);
@@ -1082,4 +1532,295 @@ mod tests {
         assert_eq!(skill.current_level, 6.5);
         assert_eq!(skill.progression_history.len(), 2);
     }
+
+    #[test]
+    fn test_record_pattern_use() {
+        let mut storage = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string());
+        let mut user_data = UserPatternData { user_id: "user_1".to_string(), ..Default::default() };
+        user_data.pattern_library.personal_patterns.insert("p1".to_string(), PersonalPattern {
+            pattern_id: "p1".to_string(),
+            name: "Early return".to_string(),
+            description: "Guard clause".to_string(),
+            code_template: "if (!x) return;".to_string(),
+            language: "javascript".to_string(),
+            category: PatternCategory::BestPractice,
+            usage_frequency: 0,
+            success_rate: 0.0,
+            created_at: Utc::now() - Duration::days(1),
+            last_used: Utc::now() - Duration::days(1),
+            tags: Vec::new(),
+        });
+        assert!(storage.store_user_data("user_1", &user_data).success);
+
+        assert!(storage.record_pattern_use("user_1", "p1", true, Duration::seconds(10)).success);
+        assert!(storage.record_pattern_use("user_1", "p1", true, Duration::seconds(20)).success);
+        assert!(storage.record_pattern_use("user_1", "p1", false, Duration::seconds(0)).success);
+        assert!(storage.record_pattern_use("user_1", "p2", true, Duration::seconds(5)).success);
+
+        let loaded = storage.load_user_data("user_1").data.unwrap();
+        let stats = &loaded.pattern_library.pattern_usage_stats["p1"];
+        assert_eq!(stats.total_uses, 3);
+        assert_eq!(stats.successful_uses, 2);
+        assert!((stats.success_rate() - 2.0 / 3.0).abs() < 0.001);
+        assert_eq!(stats.recent_usage.len(), 3);
+        assert_eq!(stats.average_time_saved, Duration::seconds(10));
+
+        let pattern = &loaded.pattern_library.personal_patterns["p1"];
+        assert_eq!(pattern.usage_frequency, 3);
+        assert!(pattern.last_used > pattern.created_at);
+        assert!((pattern.success_rate - 2.0 / 3.0).abs() < 0.001);
+
+        // Patterns outside the personal library still get stats
+        assert_eq!(loaded.pattern_library.pattern_usage_stats["p2"].total_uses, 1);
+    }
+
+    #[test]
+    fn test_recent_usage_is_bounded() {
+        let mut stats = UsageStatistics::new();
+        for _ in 0..(MAX_RECENT_USAGE + 10) {
+            stats.record_use(true, Duration::seconds(1), Utc::now());
+        }
+        assert_eq!(stats.total_uses as usize, MAX_RECENT_USAGE + 10);
+        assert_eq!(stats.recent_usage.len(), MAX_RECENT_USAGE);
+    }
+
+    #[test]
+    fn test_record_pattern_use_unknown_user() {
+        let mut storage = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string());
+        let result = storage.record_pattern_use("missing", "p1", true, Duration::seconds(1));
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for input in [&b""[..], b"f", b"fo", b"foo", b"foob", &[0xff, 0x00, 0xfe, 0x80]] {
+            assert_eq!(base64_decode(&base64_encode(input)).unwrap(), input);
+        }
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+        assert!(base64_decode("Zm9").is_err());
+
+        // Untrusted input: too much padding, padding mid-stream, `=` inside a chunk
+        for malformed in ["====", "Z===", "Zg==Zm9v", "Zm=v"] {
+            assert!(base64_decode(malformed).is_err(), "{}", malformed);
+        }
+        let mut storage = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string());
+        let envelope = r#"{"format":"code-whisperer-export","version":1,"encrypted":false,"payload":"===="}"#;
+        assert!(!storage.import_user_data("u", envelope).success);
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let mut source = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string());
+        let mut user_data = UserPatternData {
+            user_id: "user_1".to_string(),
+            ..UserPatternData::default()
+        };
+        user_data.pattern_library.favorite_patterns.push("p1".to_string());
+        assert!(source.store_user_data("user_1", &user_data).success);
+
+        let exported = source.export_user_data("user_1").data.unwrap();
+        let envelope: ExportEnvelope = serde_json::from_str(&exported).unwrap();
+        assert_eq!(envelope.format, EXPORT_FORMAT);
+        assert_eq!(envelope.version, EXPORT_VERSION);
+        // Nothing was sealed without a passphrase
+        assert!(!envelope.encrypted);
+
+        let mut target = LocalStorageManager::new(StorageType::InMemory, "/tmp/other".to_string());
+        assert!(target.import_user_data("user_1", &exported).success);
+        let imported = target.load_user_data("user_1").data.unwrap();
+        assert_eq!(serde_json::to_value(&imported).unwrap(), serde_json::to_value(&user_data).unwrap());
+
+        let future = exported.replace("\"version\": 1", "\"version\": 99");
+        assert!(!target.import_user_data("user_1", &future).success);
+
+        let mut protected = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string())
+            .with_passphrase("correct horse", KeyDerivationParams::Pbkdf2 { iterations: 1 });
+        assert!(protected.store_user_data("user_1", &user_data).success);
+        let sealed = protected.export_user_data("user_1").data.unwrap();
+        assert!(serde_json::from_str::<ExportEnvelope>(&sealed).unwrap().encrypted);
+        assert!(protected.import_user_data("user_2", &sealed).success);
+    }
+
+    #[test]
+    fn test_v1_data_is_migrated_on_load() {
+        let mut storage = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string());
+        let mut v1 = serde_json::to_value(UserPatternData {
+            user_id: "user_1".to_string(),
+            ..UserPatternData::default()
+        }).unwrap();
+        v1["metadata"]["data_version"] = 1.into();
+        v1["pattern_library"].as_object_mut().unwrap().remove("pattern_usage_stats");
+        storage.write_to_storage("user_1", &serde_json::to_vec(&v1).unwrap()).unwrap();
+
+        let result = storage.load_user_data("user_1");
+        assert!(result.success, "{:?}", result.error_message);
+        let migrated = result.data.unwrap();
+        assert_eq!(migrated.metadata.data_version, CURRENT_DATA_VERSION);
+        assert!(migrated.pattern_library.pattern_usage_stats.is_empty());
+
+        assert!(storage.migrate(v1, 0).is_err());
+    }
+
+    fn fast_kdf_params() -> [KeyDerivationParams; 3] {
+        [
+            KeyDerivationParams::Pbkdf2 { iterations: 1000 },
+            KeyDerivationParams::Argon2 { memory_kib: 64, iterations: 2, parallelism: 1 },
+            KeyDerivationParams::Scrypt { log_n: 6, r: 8, p: 1 },
+        ]
+    }
+
+    #[test]
+    fn test_key_derivation_is_deterministic_and_method_distinct() {
+        let salt = b"0123456789abcdef";
+        let keys: Vec<[u8; 32]> = fast_kdf_params().iter()
+            .map(|params| {
+                let key = params.derive_key("correct horse", salt).unwrap();
+                assert_eq!(key, params.derive_key("correct horse", salt).unwrap());
+                assert_ne!(key, params.derive_key("correct horse", b"fedcba9876543210").unwrap());
+                key
+            })
+            .collect();
+
+        assert_ne!(keys[0], keys[1]);
+        assert_ne!(keys[1], keys[2]);
+        assert_ne!(keys[0], keys[2]);
+        assert!(KeyDerivationParams::recommended(&KeyDerivationMethod::None).is_none());
+
+        // RFC 7914 sections 11 and 12, truncated to the 32-byte key
+        let hex = |key: [u8; 32]| key.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        let pbkdf2 = KeyDerivationParams::Pbkdf2 { iterations: 1 }.derive_key("passwd", b"salt").unwrap();
+        assert_eq!(hex(pbkdf2), "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc");
+        let scrypt = KeyDerivationParams::Scrypt { log_n: 4, r: 1, p: 1 }.derive_key("", b"").unwrap();
+        assert_eq!(hex(scrypt), "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442");
+    }
+
+    #[test]
+    fn test_authenticated_round_trip_per_method() {
+        let user_data = UserPatternData {
+            user_id: "user_1".to_string(),
+            ..UserPatternData::default()
+        };
+
+        for params in fast_kdf_params() {
+            let mut storage = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string())
+                .with_passphrase("correct horse", params.clone());
+            assert!(storage.store_user_data("user_1", &user_data).success);
+
+            let stored = storage.read_from_storage("user_1").unwrap();
+            let blob: AuthenticatedBlob = serde_json::from_slice(&stored).unwrap();
+            assert_eq!(blob.kdf, params);
+
+            // A fresh manager has nothing cached, so it must derive the key from the stored parameters
+            let mut reader = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string())
+                .with_passphrase("correct horse", KeyDerivationParams::Pbkdf2 { iterations: 1 });
+            reader.write_to_storage("user_1", &stored).unwrap();
+            let loaded = reader.load_user_data("user_1");
+            assert!(loaded.success, "{:?}", loaded.error_message);
+            assert_eq!(serde_json::to_value(loaded.data.unwrap()).unwrap(), serde_json::to_value(&user_data).unwrap());
+
+            let mut wrong = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string())
+                .with_passphrase("wrong horse", params);
+            wrong.write_to_storage("user_1", &stored).unwrap();
+            assert!(!wrong.load_user_data("user_1").success);
+        }
+    }
+
+    #[test]
+    fn test_out_of_bounds_kdf_parameters_are_rejected() {
+        for params in [
+            KeyDerivationParams::Pbkdf2 { iterations: 0 },
+            KeyDerivationParams::Argon2 { memory_kib: u32::MAX, iterations: 2, parallelism: 1 },
+            KeyDerivationParams::Argon2 { memory_kib: 64, iterations: 2, parallelism: 0 },
+            KeyDerivationParams::Scrypt { log_n: 64, r: 8, p: 1 },
+            KeyDerivationParams::Scrypt { log_n: 20, r: 32, p: 1 },
+            KeyDerivationParams::Scrypt { log_n: 10, r: 8, p: u32::MAX },
+        ] {
+            assert!(params.derive_key("correct horse", b"salt").is_err(), "{:?}", params);
+        }
+        for params in fast_kdf_params() {
+            assert!(params.validate().is_ok());
+        }
+
+        // A stored blob whose parameters were tampered with fails to load instead of deriving
+        let mut storage = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string())
+            .with_passphrase("correct horse", KeyDerivationParams::Pbkdf2 { iterations: 1 });
+        let user_data = UserPatternData { user_id: "user_1".to_string(), ..UserPatternData::default() };
+        assert!(storage.store_user_data("user_1", &user_data).success);
+        let mut blob: AuthenticatedBlob = serde_json::from_slice(&storage.read_from_storage("user_1").unwrap()).unwrap();
+        blob.kdf = KeyDerivationParams::Scrypt { log_n: 64, r: 8, p: 1 };
+        storage.write_to_storage("user_1", &serde_json::to_vec(&blob).unwrap()).unwrap();
+        storage.user_cache.clear();
+        assert!(!storage.load_user_data("user_1").success);
+    }
+
+    #[test]
+    fn test_unparseable_blob_is_an_error_once_a_passphrase_is_set() {
+        let plain = serde_json::to_vec(&UserPatternData { user_id: "user_1".to_string(), ..UserPatternData::default() }).unwrap();
+
+        let mut protected = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string())
+            .with_passphrase("correct horse", KeyDerivationParams::Pbkdf2 { iterations: 1 });
+        protected.write_to_storage("user_1", &plain).unwrap();
+        let loaded = protected.load_user_data("user_1");
+        assert!(!loaded.success);
+        assert!(loaded.error_message.unwrap().contains("not a valid authenticated blob"));
+
+        // Without a passphrase, plain JSON is still how data is stored
+        let mut unprotected = LocalStorageManager::new(StorageType::InMemory, "/tmp/test".to_string());
+        unprotected.write_to_storage("user_1", &plain).unwrap();
+        assert!(unprotected.load_user_data("user_1").success);
+    }
+
+    #[derive(Default)]
+    struct MockBackend {
+        calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+        entries: HashMap<String, Vec<u8>>,
+    }
+
+    impl StorageBackend for MockBackend {
+        fn read(&self, key: &str) -> Result<Vec<u8>, String> {
+            self.calls.lock().unwrap().push(format!("read {}", key));
+            self.entries.get(key).cloned().ok_or_else(|| "missing".to_string())
+        }
+
+        fn write(&mut self, key: &str, data: &[u8]) -> Result<(), String> {
+            self.calls.lock().unwrap().push(format!("write {}", key));
+            self.entries.insert(key.to_string(), data.to_vec());
+            Ok(())
+        }
+
+        fn delete(&mut self, key: &str) -> Result<(), String> {
+            self.calls.lock().unwrap().push(format!("delete {}", key));
+            self.entries.remove(key);
+            Ok(())
+        }
+
+        fn list(&self) -> Result<Vec<String>, String> {
+            self.calls.lock().unwrap().push("list".to_string());
+            Ok(self.entries.keys().cloned().collect())
+        }
+    }
+
+    #[test]
+    fn test_custom_backend_receives_storage_calls() {
+        let backend = MockBackend::default();
+        let calls = std::sync::Arc::clone(&backend.calls);
+        let mut storage = LocalStorageManager::with_backend(Box::new(backend));
+        assert!(matches!(storage.storage_type(), StorageType::Custom));
+        let user_data = UserPatternData {
+            user_id: "user_1".to_string(),
+            ..UserPatternData::default()
+        };
+
+        assert!(storage.store_user_data("user_1", &user_data).success);
+        assert!(storage.export_user_data("user_1").success);
+        assert_eq!(storage.stored_user_ids().unwrap(), vec!["user_1".to_string()]);
+        assert!(storage.delete_user_data("user_1").success);
+        assert!(!storage.load_user_data("user_1").success);
+
+        assert_eq!(*calls.lock().unwrap(), vec!["write user_1", "read user_1", "list", "delete user_1", "read user_1"]);
+
+        // A manager with a custom backend can still be handed to another thread
+        let data = std::thread::spawn(move || storage.store_user_data("user_2", &user_data).success).join().unwrap();
+        assert!(data);
+    }
 }
@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, BTreeSet};
+use regex::Regex;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    // The imported path of a JS/TS, Python or Rust import line
+    static ref MODULE_IMPORT: Regex = Regex::new(
+        r#"^\s*from\s+([\w.]+)\s+import\b|^\s*import\s+([\w.]+)\s*$|from\s+['"]([^'"]+)['"]|require\(\s*['"]([^'"]+)['"]\s*\)|^\s*import\s+['"]([^'"]+)['"]|^\s*(?:pub\s+)?use\s+([\w:]+)"#
+    ).unwrap();
+}
+
+/// Import edges between a set of analyzed modules; an edge `a -> b` means `a` imports `b`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleDependencyGraph {
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ModuleDependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the graph from `(module path, source)` pairs, keeping only imports of other listed
+    /// modules. Relative JS/TS imports resolve against the importing file's directory; Python and
+    /// Rust paths match the module whose path ends with them, nearest to the importer first
+    pub fn from_sources(modules: &[(String, String)]) -> Self {
+        let mut graph = Self::new();
+        let keys: BTreeMap<String, &String> = modules.iter().map(|(name, _)| (module_key(name), name)).collect();
+
+        for (name, code) in modules {
+            graph.add_module(name);
+            let importer = module_key(name);
+            for line in code.lines() {
+                let Some(import) = MODULE_IMPORT.captures(line).and_then(|c| import_path(&importer, &c)) else {
+                    continue;
+                };
+                let imported = resolve(&keys, &importer, &import).filter(|candidate| *candidate != name);
+                if let Some(imported) = imported {
+                    graph.add_dependency(name, imported);
+                }
+            }
+        }
+        graph
+    }
+
+    pub fn add_module(&mut self, name: &str) {
+        self.dependencies.entry(name.to_string()).or_default();
+    }
+
+    pub fn add_dependency(&mut self, from: &str, to: &str) {
+        self.add_module(to);
+        self.dependencies.entry(from.to_string()).or_default().insert(to.to_string());
+    }
+
+    pub fn dependencies_of(&self, module: &str) -> Vec<String> {
+        self.dependencies.get(module).map(|deps| deps.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Modules ordered so each comes after everything it imports, ties broken by name.
+    /// Fails with the edges that lie on an import cycle.
+    pub fn topological_order(&self) -> Result<Vec<String>, Vec<(String, String)>> {
+        let mut remaining: BTreeMap<&str, usize> = self.dependencies
+            .iter()
+            .map(|(module, deps)| (module.as_str(), deps.len()))
+            .collect();
+        let mut dependents: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (module, deps) in &self.dependencies {
+            for dep in deps {
+                dependents.entry(dep.as_str()).or_default().push(module.as_str());
+            }
+        }
+
+        let mut ready: BTreeSet<&str> = remaining.iter().filter(|(_, count)| **count == 0).map(|(m, _)| *m).collect();
+        let mut order = Vec::new();
+        while let Some(module) = ready.pop_first() {
+            remaining.remove(module);
+            order.push(module.to_string());
+            for dependent in dependents.get(module).into_iter().flatten() {
+                if let Some(count) = remaining.get_mut(dependent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert(dependent);
+                    }
+                }
+            }
+        }
+
+        if remaining.is_empty() {
+            Ok(order)
+        } else {
+            Err(self.cycle_edges())
+        }
+    }
+
+    /// Edges whose endpoints share a strongly connected component, i.e. that sit on some cycle
+    fn cycle_edges(&self) -> Vec<(String, String)> {
+        let components = self.strongly_connected_components();
+        self.dependencies
+            .iter()
+            .flat_map(|(from, deps)| deps.iter().map(move |to| (from, to)))
+            .filter(|(from, to)| components[from.as_str()] == components[to.as_str()])
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect()
+    }
+
+    // Tarjan's algorithm; returns the component index of every module
+    fn strongly_connected_components(&self) -> BTreeMap<&str, usize> {
+        struct Tarjan<'a> {
+            graph: &'a BTreeMap<String, BTreeSet<String>>,
+            index: BTreeMap<&'a str, usize>,
+            low_link: BTreeMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            on_stack: BTreeSet<&'a str>,
+            component: BTreeMap<&'a str, usize>,
+            next_component: usize,
+        }
+
+        impl<'a> Tarjan<'a> {
+            fn visit(&mut self, module: &'a str) {
+                let index = self.index.len();
+                self.index.insert(module, index);
+                self.low_link.insert(module, index);
+                self.stack.push(module);
+                self.on_stack.insert(module);
+
+                for dep in self.graph.get(module).into_iter().flatten() {
+                    let dep = dep.as_str();
+                    if !self.index.contains_key(dep) {
+                        self.visit(dep);
+                        let low = self.low_link[module].min(self.low_link[dep]);
+                        self.low_link.insert(module, low);
+                    } else if self.on_stack.contains(dep) {
+                        let low = self.low_link[module].min(self.index[dep]);
+                        self.low_link.insert(module, low);
+                    }
+                }
+
+                if self.low_link[module] == self.index[module] {
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack.remove(member);
+                        self.component.insert(member, self.next_component);
+                        if member == module {
+                            break;
+                        }
+                    }
+                    self.next_component += 1;
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph: &self.dependencies,
+            index: BTreeMap::new(),
+            low_link: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            component: BTreeMap::new(),
+            next_component: 0,
+        };
+        for module in self.dependencies.keys() {
+            if !tarjan.index.contains_key(module.as_str()) {
+                tarjan.visit(module);
+            }
+        }
+        tarjan.component
+    }
+}
+
+/// Where an import points, in the form of `module_key`
+enum ImportPath {
+    Exact(String),           // a relative JS/TS import, resolved against the importer
+    Suffix(Vec<String>),     // a Python or Rust path; its longest prefix naming a module wins
+}
+
+/// Module path without extension or package entry file, with `/` separators:
+/// `src\utils\format.ts` -> `src/utils/format`, `pkg/__init__.py` -> `pkg`, `src/lexer/mod.rs` -> `src/lexer`
+fn module_key(name: &str) -> String {
+    let path = normalize_path(&name.replace('\\', "/"));
+    let file_start = path.rfind('/').map_or(0, |slash| slash + 1);
+    let stem = match path[file_start..].find('.') {
+        Some(dot) if dot > 0 => &path[..file_start + dot],
+        _ => &path[..],
+    };
+    ["/index", "/__init__", "/mod"].iter()
+        .find_map(|entry| stem.strip_suffix(entry))
+        .unwrap_or(stem)
+        .to_string()
+}
+
+/// `path` with `.` and `..` segments folded away
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// The directory holding the module `key`, `""` at the root
+fn parent(key: &str) -> &str {
+    key.rfind('/').map_or("", |slash| &key[..slash])
+}
+
+/// What the import matched by `MODULE_IMPORT` refers to, or `None` for a bare JS package import
+fn import_path(importer: &str, captures: &regex::Captures) -> Option<ImportPath> {
+    let (group, target) = captures.iter().enumerate().skip(1).find_map(|(group, m)| Some((group, m?.as_str())))?;
+    match group {
+        // Python `from .models import User` climbs one package per extra dot
+        1 | 2 => {
+            let dots = target.len() - target.trim_start_matches('.').len();
+            let segments: Vec<String> = target[dots..].split('.').filter(|s| !s.is_empty()).map(str::to_string).collect();
+            if dots == 0 {
+                return Some(ImportPath::Suffix(segments));
+            }
+            let package = (1..dots).fold(parent(importer), |package, _| parent(package));
+            Some(ImportPath::Exact(normalize_path(&format!("{}/{}", package, segments.join("/")))))
+        }
+        3..=5 if target.starts_with('.') => Some(ImportPath::Exact(module_key(&format!("{}/{}", parent(importer), target)))),
+        3..=5 => None,
+        _ => {
+            let segments = target.split("::")
+                .filter(|segment| !matches!(*segment, "" | "crate" | "self" | "super"))
+                .map(str::to_string)
+                .collect();
+            Some(ImportPath::Suffix(segments))
+        }
+    }
+}
+
+/// The listed module `import` names; among several with the same path suffix, the one sharing
+/// the most leading directories with `importer`
+fn resolve<'k>(keys: &BTreeMap<String, &'k String>, importer: &str, import: &ImportPath) -> Option<&'k String> {
+    let segments = match import {
+        ImportPath::Exact(key) => return keys.get(key).copied(),
+        ImportPath::Suffix(segments) => segments,
+    };
+    let shared_directories = |key: &str| key.split('/').zip(importer.split('/')).take_while(|(a, b)| a == b).count();
+    (1..=segments.len()).rev().find_map(|len| {
+        let suffix = segments[..len].join("/");
+        keys.iter()
+            .filter(|(key, _)| **key == suffix || key.ends_with(&format!("/{}", suffix)))
+            .filter(|(key, _)| key.as_str() != importer)
+            .max_by_key(|(key, _)| (shared_directories(key), std::cmp::Reverse(key.len())))
+            .map(|(_, name)| *name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules(sources: &[(&str, &str)]) -> Vec<(String, String)> {
+        sources.iter().map(|(name, code)| (name.to_string(), code.to_string())).collect()
+    }
+
+    #[test]
+    fn test_acyclic_modules_get_dependency_first_order() {
+        let graph = ModuleDependencyGraph::from_sources(&modules(&[
+            ("src/app.ts", "import { render } from './view';\nimport { load } from './store';\n"),
+            ("src/view.ts", "import { load } from './store';\nimport React from 'react';\n"),
+            ("src/store.ts", "export const load = () => [];\n"),
+            ("tools/report.py", "from store import load\nimport os\n"),
+        ]));
+        assert_eq!(graph.dependencies_of("src/app.ts"), vec!["src/store.ts", "src/view.ts"]);
+
+        let order = graph.topological_order().unwrap();
+        assert_eq!(order, vec!["src/store.ts", "src/view.ts", "src/app.ts", "tools/report.py"]);
+        let position = |name: &str| order.iter().position(|m| m == name).unwrap();
+        for module in &order {
+            for dep in graph.dependencies_of(module) {
+                assert!(position(&dep) < position(module));
+            }
+        }
+    }
+
+    #[test]
+    fn test_modules_with_the_same_file_name_stay_apart() {
+        let graph = ModuleDependencyGraph::from_sources(&modules(&[
+            ("src/a/utils.ts", "export const a = 1;\n"),
+            ("src/b/utils.ts", "export const b = 2;\n"),
+            ("src/a/main.ts", "import { a } from './utils';\nimport { b } from '../b/utils.ts';\n"),
+            ("pkg/__init__.py", ""),
+            ("pkg/models.py", "from . import db\n"),
+            ("pkg/views.py", "from .models import User\nimport pkg\n"),
+            ("src/lexer/mod.rs", ""),
+            ("src/main.rs", "use crate::lexer::Token;\n"),
+        ]));
+        assert_eq!(graph.dependencies_of("src/a/main.ts"), vec!["src/a/utils.ts", "src/b/utils.ts"]);
+        assert!(graph.dependencies_of("src/a/utils.ts").is_empty());
+        assert_eq!(graph.dependencies_of("pkg/views.py"), vec!["pkg/__init__.py", "pkg/models.py"]);
+        assert_eq!(graph.dependencies_of("src/main.rs"), vec!["src/lexer/mod.rs"]);
+
+        assert_eq!(module_key("src\\utils\\index.ts"), "src/utils");
+        assert_eq!(module_key("./lib/.hidden"), "lib/.hidden");
+    }
+
+    #[test]
+    fn test_cycle_reports_only_the_edges_on_it() {
+        let graph = ModuleDependencyGraph::from_sources(&modules(&[
+            ("parser.rs", "use crate::lexer::Token;\n"),
+            ("lexer.rs", "use crate::parser::Span;\n"),
+            ("main.rs", "use crate::parser::parse;\n"),
+        ]));
+
+        let cycle = graph.topological_order().unwrap_err();
+        assert_eq!(cycle, vec![
+            ("lexer.rs".to_string(), "parser.rs".to_string()),
+            ("parser.rs".to_string(), "lexer.rs".to_string()),
+        ]);
+    }
+}
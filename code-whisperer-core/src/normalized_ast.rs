@@ -0,0 +1,481 @@
+use crate::ast_parser::ParsedAst;
+use crate::exception_handling::python_child_blocks;
+use crate::{AstNode, SourceLocation};
+use quote::ToTokens;
+use regex::Regex;
+use rustpython_parser::ast::{self as py_ast, Ranged};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use swc_common::{Span, Spanned};
+use swc_ecma_ast as swc_ast;
+
+/// Flatten a language-specific AST into the crate's common `AstNode` form, one node per
+/// top-level item. Every language shares the same `node_type`s:
+///
+/// - `function_declaration` and `method_declaration`: `name`, `is_async`; children are the
+///   `parameter`s followed by the body's statements
+/// - `class_declaration`, `struct_declaration`, `enum_declaration`, `trait_declaration`,
+///   `impl_block` and `module_declaration`: `name`; children are the members, fields or variants
+/// - `variable_declaration` (`name`), `import_declaration` (`source`) and `type_declaration`
+/// - `if_statement`, `loop_statement`, `try_statement`, `switch_statement` and
+///   `with_statement`, whose children are the statements of every branch in order
+/// - `return_statement`, `throw_statement`, `expression_statement`, and `statement` for the rest
+///
+/// Generic sources become one `line` node per non-blank line. Rust nodes are located only
+/// when they are named items, by their first line, since syn spans carry no positions here
+pub fn to_normalized_ast(ast: &ParsedAst, code: &str) -> Vec<AstNode> {
+    match ast {
+        ParsedAst::JavaScript(module) => {
+            let normalizer = JsNormalizer { code };
+            module.body.iter().flat_map(|item| normalizer.module_item(item)).collect()
+        }
+        ParsedAst::Python(suite) => PyNormalizer { code }.stmts(suite, false),
+        ParsedAst::Rust(items) => {
+            let mut normalizer = RustNormalizer { code, search_from: 0 };
+            items.iter().map(|item| normalizer.item(item, false)).collect()
+        }
+        ParsedAst::Generic(lines) => lines.iter().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                let line_number = index as u32 + 1;
+                let location = SourceLocation {
+                    file: String::new(),
+                    start_line: line_number,
+                    start_column: 0,
+                    end_line: line_number,
+                    end_column: line.chars().count() as u32,
+                };
+                node("line", [("text", json!(line.trim()))], Vec::new(), location)
+            })
+            .collect(),
+    }
+}
+
+fn node<const N: usize>(node_type: &str, properties: [(&str, Value); N], children: Vec<AstNode>, location: SourceLocation) -> AstNode {
+    AstNode {
+        node_type: node_type.to_string(),
+        properties: properties.into_iter().map(|(key, value)| (key.to_string(), value)).collect::<HashMap<_, _>>(),
+        children,
+        location,
+    }
+}
+
+/// Lines are 1-based; columns count the chars before the position on its line
+pub(crate) fn location(code: &str, start: usize, end: usize) -> SourceLocation {
+    let position = |offset: usize| {
+        let before = code.get(..offset.min(code.len())).unwrap_or(code);
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        (before.matches('\n').count() as u32 + 1, before[line_start..].chars().count() as u32)
+    };
+    let (start_line, start_column) = position(start);
+    let (end_line, end_column) = position(end);
+    SourceLocation { file: String::new(), start_line, start_column, end_line, end_column }
+}
+
+struct JsNormalizer<'a> {
+    code: &'a str,
+}
+
+impl JsNormalizer<'_> {
+    fn location(&self, span: Span) -> SourceLocation {
+        location(self.code, span.lo.0.saturating_sub(1) as usize, span.hi.0.saturating_sub(1) as usize)
+    }
+
+    fn source(&self, span: Span) -> &str {
+        self.code.get(span.lo.0.saturating_sub(1) as usize..span.hi.0.saturating_sub(1) as usize).unwrap_or_default()
+    }
+
+    fn module_item(&self, item: &swc_ast::ModuleItem) -> Vec<AstNode> {
+        let decl = match item {
+            swc_ast::ModuleItem::Stmt(stmt) => return self.stmt(stmt),
+            swc_ast::ModuleItem::ModuleDecl(decl) => decl,
+        };
+        let exported = |mut nodes: Vec<AstNode>| {
+            for node in &mut nodes {
+                node.properties.insert("exported".to_string(), json!(true));
+            }
+            nodes
+        };
+        match decl {
+            swc_ast::ModuleDecl::Import(import) => {
+                vec![node("import_declaration", [("source", json!(import.src.value.to_string()))], Vec::new(), self.location(import.span))]
+            }
+            swc_ast::ModuleDecl::ExportDecl(export) => exported(self.decl(&export.decl)),
+            swc_ast::ModuleDecl::ExportDefaultDecl(export) => exported(match &export.decl {
+                swc_ast::DefaultDecl::Fn(fn_expr) => {
+                    let name = fn_expr.ident.as_ref().map_or("default".to_string(), |ident| ident.sym.to_string());
+                    vec![self.function("function_declaration", &name, &fn_expr.function)]
+                }
+                swc_ast::DefaultDecl::Class(class_expr) => {
+                    let name = class_expr.ident.as_ref().map_or("default".to_string(), |ident| ident.sym.to_string());
+                    vec![self.class(&name, &class_expr.class)]
+                }
+                swc_ast::DefaultDecl::TsInterfaceDecl(interface) => {
+                    vec![node("type_declaration", [("name", json!(interface.id.sym.to_string()))], Vec::new(), self.location(interface.span))]
+                }
+            }),
+            other => vec![node("statement", [], Vec::new(), self.location(other.span()))],
+        }
+    }
+
+    fn stmts(&self, stmts: &[swc_ast::Stmt]) -> Vec<AstNode> {
+        stmts.iter().flat_map(|stmt| self.stmt(stmt)).collect()
+    }
+
+    /// Blocks are flattened into their statements, and each declarator of a `var` is its own node
+    fn stmt(&self, stmt: &swc_ast::Stmt) -> Vec<AstNode> {
+        use swc_ast::Stmt;
+        let (node_type, children) = match stmt {
+            Stmt::Block(block) => return self.stmts(&block.stmts),
+            Stmt::Decl(decl) => return self.decl(decl),
+            Stmt::Return(_) => ("return_statement", Vec::new()),
+            Stmt::Throw(_) => ("throw_statement", Vec::new()),
+            Stmt::Expr(_) => ("expression_statement", Vec::new()),
+            Stmt::If(if_stmt) => {
+                let mut children = self.stmt(&if_stmt.cons);
+                children.extend(if_stmt.alt.iter().flat_map(|alt| self.stmt(alt)));
+                ("if_statement", children)
+            }
+            Stmt::For(for_stmt) => ("loop_statement", self.stmt(&for_stmt.body)),
+            Stmt::ForIn(for_in) => ("loop_statement", self.stmt(&for_in.body)),
+            Stmt::ForOf(for_of) => ("loop_statement", self.stmt(&for_of.body)),
+            Stmt::While(while_stmt) => ("loop_statement", self.stmt(&while_stmt.body)),
+            Stmt::DoWhile(do_while) => ("loop_statement", self.stmt(&do_while.body)),
+            Stmt::Labeled(labeled) => return self.stmt(&labeled.body),
+            Stmt::Switch(switch) => ("switch_statement", switch.cases.iter().flat_map(|case| self.stmts(&case.cons)).collect()),
+            Stmt::Try(try_stmt) => {
+                let mut children = self.stmts(&try_stmt.block.stmts);
+                children.extend(try_stmt.handler.iter().flat_map(|handler| self.stmts(&handler.body.stmts)));
+                children.extend(try_stmt.finalizer.iter().flat_map(|finalizer| self.stmts(&finalizer.stmts)));
+                ("try_statement", children)
+            }
+            _ => ("statement", Vec::new()),
+        };
+        vec![node(node_type, [], children, self.location(stmt.span()))]
+    }
+
+    fn decl(&self, decl: &swc_ast::Decl) -> Vec<AstNode> {
+        match decl {
+            swc_ast::Decl::Fn(fn_decl) => vec![self.function("function_declaration", &fn_decl.ident.sym, &fn_decl.function)],
+            swc_ast::Decl::Class(class_decl) => vec![self.class(&class_decl.ident.sym, &class_decl.class)],
+            swc_ast::Decl::Var(var) => {
+                let kind = format!("{:?}", var.kind).to_lowercase();
+                var.decls.iter().map(|declarator| {
+                    // `const handler = () => {...}` keeps the function it holds
+                    let children = match declarator.init.as_deref() {
+                        Some(swc_ast::Expr::Fn(fn_expr)) => vec![self.function("function_declaration", "anonymous", &fn_expr.function)],
+                        Some(swc_ast::Expr::Arrow(arrow)) => vec![self.arrow(arrow)],
+                        _ => Vec::new(),
+                    };
+                    let name = self.source(declarator.name.span());
+                    node("variable_declaration", [("name", json!(name)), ("kind", json!(kind))], children, self.location(declarator.span))
+                }).collect()
+            }
+            swc_ast::Decl::TsInterface(interface) => {
+                vec![node("type_declaration", [("name", json!(interface.id.sym.to_string()))], Vec::new(), self.location(interface.span))]
+            }
+            swc_ast::Decl::TsTypeAlias(alias) => {
+                vec![node("type_declaration", [("name", json!(alias.id.sym.to_string()))], Vec::new(), self.location(alias.span))]
+            }
+            swc_ast::Decl::TsEnum(ts_enum) => {
+                vec![node("enum_declaration", [("name", json!(ts_enum.id.sym.to_string()))], Vec::new(), self.location(ts_enum.span))]
+            }
+            other => vec![node("statement", [], Vec::new(), self.location(other.span()))],
+        }
+    }
+
+    fn function(&self, node_type: &str, name: &str, function: &swc_ast::Function) -> AstNode {
+        let mut children: Vec<AstNode> = function.params.iter().map(|param| self.parameter(param.pat.span())).collect();
+        children.extend(function.body.iter().flat_map(|body| self.stmts(&body.stmts)));
+        node(node_type, [("name", json!(name)), ("is_async", json!(function.is_async))], children, self.location(function.span))
+    }
+
+    fn arrow(&self, arrow: &swc_ast::ArrowExpr) -> AstNode {
+        let mut children: Vec<AstNode> = arrow.params.iter().map(|param| self.parameter(param.span())).collect();
+        if let swc_ast::BlockStmtOrExpr::BlockStmt(body) = &*arrow.body {
+            children.extend(self.stmts(&body.stmts));
+        }
+        node("function_declaration", [("name", json!("anonymous")), ("is_async", json!(arrow.is_async))], children, self.location(arrow.span))
+    }
+
+    fn parameter(&self, span: Span) -> AstNode {
+        node("parameter", [("name", json!(self.source(span)))], Vec::new(), self.location(span))
+    }
+
+    fn class(&self, name: &str, class: &swc_ast::Class) -> AstNode {
+        let members = class.body.iter().filter_map(|member| match member {
+            swc_ast::ClassMember::Constructor(constructor) => {
+                let mut children: Vec<AstNode> = constructor.params.iter().map(|param| self.parameter(param.span())).collect();
+                children.extend(constructor.body.iter().flat_map(|body| self.stmts(&body.stmts)));
+                Some(node("method_declaration", [("name", json!("constructor")), ("is_async", json!(false))], children, self.location(constructor.span)))
+            }
+            swc_ast::ClassMember::Method(method) => Some(self.function("method_declaration", self.source(method.key.span()), &method.function)),
+            swc_ast::ClassMember::PrivateMethod(method) => Some(self.function("method_declaration", self.source(method.key.span), &method.function)),
+            swc_ast::ClassMember::ClassProp(prop) => {
+                Some(node("variable_declaration", [("name", json!(self.source(prop.key.span())))], Vec::new(), self.location(prop.span)))
+            }
+            swc_ast::ClassMember::PrivateProp(prop) => {
+                Some(node("variable_declaration", [("name", json!(self.source(prop.key.span)))], Vec::new(), self.location(prop.span)))
+            }
+            _ => None,
+        }).collect();
+        let superclass = class.super_class.as_ref().map(|superclass| self.source(superclass.span()));
+        node("class_declaration", [("name", json!(name)), ("superclass", json!(superclass))], members, self.location(class.span))
+    }
+}
+
+struct PyNormalizer<'a> {
+    code: &'a str,
+}
+
+impl PyNormalizer<'_> {
+    fn location(&self, range: py_ast::text_size::TextRange) -> SourceLocation {
+        location(self.code, range.start().to_usize(), range.end().to_usize())
+    }
+
+    fn source(&self, expr: &py_ast::Expr) -> &str {
+        self.code.get(expr.start().to_usize()..expr.end().to_usize()).unwrap_or_default()
+    }
+
+    fn stmts(&self, body: &[py_ast::Stmt], in_class: bool) -> Vec<AstNode> {
+        body.iter().flat_map(|stmt| self.stmt(stmt, in_class)).collect()
+    }
+
+    fn stmt(&self, stmt: &py_ast::Stmt, in_class: bool) -> Vec<AstNode> {
+        use py_ast::Stmt;
+        let location = self.location(stmt.range());
+        let blocks = || python_child_blocks(stmt).into_iter().flat_map(|block| self.stmts(block, false)).collect();
+        let function_type = if in_class { "method_declaration" } else { "function_declaration" };
+        let single = match stmt {
+            Stmt::FunctionDef(def) => self.function(function_type, &def.name, &def.args, &def.body, false, location),
+            Stmt::AsyncFunctionDef(def) => self.function(function_type, &def.name, &def.args, &def.body, true, location),
+            Stmt::ClassDef(def) => {
+                let bases: Vec<&str> = def.bases.iter().map(|base| self.source(base)).collect();
+                node("class_declaration", [("name", json!(def.name.as_str())), ("bases", json!(bases))], self.stmts(&def.body, true), location)
+            }
+            Stmt::Assign(assign) => {
+                return assign.targets.iter()
+                    .filter(|target| matches!(target, py_ast::Expr::Name(_)))
+                    .map(|target| node("variable_declaration", [("name", json!(self.source(target)))], Vec::new(), location.clone()))
+                    .collect();
+            }
+            Stmt::AnnAssign(assign) => {
+                let properties = [("name", json!(self.source(&assign.target))), ("annotation", json!(self.source(&assign.annotation)))];
+                node("variable_declaration", properties, Vec::new(), location)
+            }
+            Stmt::Import(import) => {
+                let modules: Vec<&str> = import.names.iter().map(|alias| alias.name.as_str()).collect();
+                node("import_declaration", [("source", json!(modules.join(", ")))], Vec::new(), location)
+            }
+            Stmt::ImportFrom(import) => {
+                let module = format!("{}{}", ".".repeat(import.level.map_or(0, |level| level.to_u32() as usize)), import.module.as_deref().unwrap_or_default());
+                node("import_declaration", [("source", json!(module))], Vec::new(), location)
+            }
+            Stmt::If(_) => node("if_statement", [], blocks(), location),
+            Stmt::For(_) | Stmt::AsyncFor(_) | Stmt::While(_) => node("loop_statement", [], blocks(), location),
+            Stmt::Try(_) | Stmt::TryStar(_) => node("try_statement", [], blocks(), location),
+            Stmt::With(_) | Stmt::AsyncWith(_) => node("with_statement", [], blocks(), location),
+            Stmt::Match(_) => node("switch_statement", [], blocks(), location),
+            Stmt::Return(_) => node("return_statement", [], Vec::new(), location),
+            Stmt::Raise(_) => node("throw_statement", [], Vec::new(), location),
+            Stmt::Expr(_) => node("expression_statement", [], Vec::new(), location),
+            _ => node("statement", [], Vec::new(), location),
+        };
+        vec![single]
+    }
+
+    fn function(&self, node_type: &str, name: &str, args: &py_ast::Arguments, body: &[py_ast::Stmt], is_async: bool, location: SourceLocation) -> AstNode {
+        let mut parameters: Vec<&py_ast::Arg> = args.posonlyargs.iter().chain(args.args.iter()).map(|arg| &arg.def).collect();
+        parameters.extend(args.vararg.as_deref());
+        parameters.extend(args.kwonlyargs.iter().map(|arg| &arg.def));
+        parameters.extend(args.kwarg.as_deref());
+
+        let mut children: Vec<AstNode> = parameters.into_iter()
+            .map(|arg| node("parameter", [("name", json!(arg.arg.as_str()))], Vec::new(), self.location(arg.range)))
+            .collect();
+        children.extend(self.stmts(body, false));
+        node(node_type, [("name", json!(name)), ("is_async", json!(is_async))], children, location)
+    }
+}
+
+struct RustNormalizer<'a> {
+    code: &'a str,
+    search_from: usize, // named items are found in source order
+}
+
+impl RustNormalizer<'_> {
+    /// The first line of the next `keyword name` in the source, or an empty location
+    fn locate(&mut self, keyword: &str, name: &str) -> SourceLocation {
+        let pattern = Regex::new(&format!(r"\b{}\s+{}\b", keyword, regex::escape(name))).unwrap();
+        let start = self.search_from.min(self.code.len());
+        match pattern.find(&self.code[start..]) {
+            Some(found) => {
+                self.search_from = start + found.end();
+                location(self.code, start + found.start(), start + found.end())
+            }
+            None => Self::unlocated(),
+        }
+    }
+
+    fn unlocated() -> SourceLocation {
+        SourceLocation { file: String::new(), start_line: 0, start_column: 0, end_line: 0, end_column: 0 }
+    }
+
+    fn item(&mut self, item: &syn::Item, in_impl: bool) -> AstNode {
+        match item {
+            syn::Item::Fn(function) => self.function(&function.sig, Some(&function.block), in_impl),
+            syn::Item::Struct(item) => {
+                let location = self.locate("struct", &item.ident.to_string());
+                let fields = item.fields.iter().enumerate().map(|(index, field)| {
+                    let name = field.ident.as_ref().map_or(index.to_string(), ToString::to_string);
+                    node("variable_declaration", [("name", json!(name))], Vec::new(), Self::unlocated())
+                }).collect();
+                node("struct_declaration", [("name", json!(item.ident.to_string()))], fields, location)
+            }
+            syn::Item::Enum(item) => {
+                let location = self.locate("enum", &item.ident.to_string());
+                let variants = item.variants.iter()
+                    .map(|variant| node("variant", [("name", json!(variant.ident.to_string()))], Vec::new(), Self::unlocated()))
+                    .collect();
+                node("enum_declaration", [("name", json!(item.ident.to_string()))], variants, location)
+            }
+            syn::Item::Trait(item) => {
+                let location = self.locate("trait", &item.ident.to_string());
+                let methods = item.items.iter().filter_map(|member| match member {
+                    syn::TraitItem::Fn(method) => Some(self.function(&method.sig, method.default.as_ref(), true)),
+                    _ => None,
+                }).collect();
+                node("trait_declaration", [("name", json!(item.ident.to_string()))], methods, location)
+            }
+            syn::Item::Impl(item) => {
+                let self_type = item.self_ty.to_token_stream().to_string();
+                let trait_name = item.trait_.as_ref().map(|(_, path, _)| path.to_token_stream().to_string());
+                let methods = item.items.iter().filter_map(|member| match member {
+                    syn::ImplItem::Fn(method) => Some(self.function(&method.sig, Some(&method.block), true)),
+                    _ => None,
+                }).collect();
+                node("impl_block", [("name", json!(self_type)), ("trait", json!(trait_name))], methods, Self::unlocated())
+            }
+            syn::Item::Mod(item) => {
+                let location = self.locate("mod", &item.ident.to_string());
+                let items = item.content.iter().flat_map(|(_, items)| items).map(|inner| self.item(inner, false)).collect();
+                node("module_declaration", [("name", json!(item.ident.to_string()))], items, location)
+            }
+            syn::Item::Use(item) => {
+                node("import_declaration", [("source", json!(item.tree.to_token_stream().to_string()))], Vec::new(), Self::unlocated())
+            }
+            syn::Item::Const(item) => {
+                let location = self.locate("const", &item.ident.to_string());
+                node("variable_declaration", [("name", json!(item.ident.to_string()))], Vec::new(), location)
+            }
+            syn::Item::Static(item) => {
+                let location = self.locate(r"static(?:\s+mut)?", &item.ident.to_string());
+                node("variable_declaration", [("name", json!(item.ident.to_string()))], Vec::new(), location)
+            }
+            syn::Item::Type(item) => {
+                let location = self.locate("type", &item.ident.to_string());
+                node("type_declaration", [("name", json!(item.ident.to_string()))], Vec::new(), location)
+            }
+            _ => node("statement", [], Vec::new(), Self::unlocated()),
+        }
+    }
+
+    fn function(&mut self, sig: &syn::Signature, body: Option<&syn::Block>, in_impl: bool) -> AstNode {
+        let name = sig.ident.to_string();
+        let location = self.locate("fn", &name);
+        let mut children: Vec<AstNode> = sig.inputs.iter().map(|input| {
+            let name = match input {
+                syn::FnArg::Receiver(_) => "self".to_string(),
+                syn::FnArg::Typed(typed) => typed.pat.to_token_stream().to_string(),
+            };
+            node("parameter", [("name", json!(name))], Vec::new(), Self::unlocated())
+        }).collect();
+        children.extend(body.iter().flat_map(|block| self.stmts(&block.stmts)));
+
+        let node_type = if in_impl { "method_declaration" } else { "function_declaration" };
+        node(node_type, [("name", json!(name)), ("is_async", json!(sig.asyncness.is_some()))], children, location)
+    }
+
+    fn stmts(&mut self, stmts: &[syn::Stmt]) -> Vec<AstNode> {
+        stmts.iter().map(|stmt| match stmt {
+            syn::Stmt::Local(local) => {
+                node("variable_declaration", [("name", json!(local.pat.to_token_stream().to_string()))], Vec::new(), Self::unlocated())
+            }
+            syn::Stmt::Item(item) => self.item(item, false),
+            syn::Stmt::Expr(expr, _) => self.expr(expr),
+            syn::Stmt::Macro(_) => node("expression_statement", [], Vec::new(), Self::unlocated()),
+        }).collect()
+    }
+
+    fn expr(&mut self, expr: &syn::Expr) -> AstNode {
+        let (node_type, children) = match expr {
+            syn::Expr::Return(_) => ("return_statement", Vec::new()),
+            syn::Expr::If(if_expr) => {
+                let mut children = self.stmts(&if_expr.then_branch.stmts);
+                match if_expr.else_branch.as_ref().map(|(_, otherwise)| &**otherwise) {
+                    Some(syn::Expr::Block(block)) => children.extend(self.stmts(&block.block.stmts)),
+                    Some(otherwise) => children.push(self.expr(otherwise)),
+                    None => {}
+                }
+                ("if_statement", children)
+            }
+            syn::Expr::ForLoop(for_loop) => ("loop_statement", self.stmts(&for_loop.body.stmts)),
+            syn::Expr::While(while_loop) => ("loop_statement", self.stmts(&while_loop.body.stmts)),
+            syn::Expr::Loop(loop_expr) => ("loop_statement", self.stmts(&loop_expr.body.stmts)),
+            syn::Expr::Match(match_expr) => {
+                let arms = match_expr.arms.iter().map(|arm| match &*arm.body {
+                    syn::Expr::Block(block) => self.stmts(&block.block.stmts),
+                    body => vec![self.expr(body)],
+                }).collect::<Vec<_>>();
+                ("switch_statement", arms.into_iter().flatten().collect())
+            }
+            _ => ("expression_statement", Vec::new()),
+        };
+        node(node_type, [], children, Self::unlocated())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_parser::AstParser;
+
+    fn normalize(code: &str, language: &str) -> Vec<AstNode> {
+        let ast = AstParser::new().parse_code(code, language).unwrap();
+        to_normalized_ast(&ast, code)
+    }
+
+    fn child_types(node: &AstNode) -> Vec<&str> {
+        node.children.iter().map(|child| child.node_type.as_str()).collect()
+    }
+
+    #[test]
+    fn test_function_declarations_share_one_shape_across_languages() {
+        let js = normalize("import fs from 'fs';\n\nfunction add(a, b) {\n  const sum = a + b;\n  return sum;\n}\n", "javascript");
+        assert_eq!(js.iter().map(|node| node.node_type.as_str()).collect::<Vec<_>>(), ["import_declaration", "function_declaration"]);
+        let add = &js[1];
+        assert_eq!(add.properties["name"], "add");
+        assert_eq!(child_types(add), ["parameter", "parameter", "variable_declaration", "return_statement"]);
+        assert_eq!(add.children[1].properties["name"], "b");
+        assert_eq!((add.location.start_line, add.location.end_line), (3, 6));
+
+        let python = normalize("class Cart:\n    async def add(self, item):\n        if item:\n            self.items.append(item)\n        return self\n", "python");
+        let method = &python[0].children[0];
+        assert_eq!(python[0].node_type, "class_declaration");
+        assert_eq!((method.node_type.as_str(), method.properties["is_async"].as_bool()), ("method_declaration", Some(true)));
+        assert_eq!(child_types(method), ["parameter", "parameter", "if_statement", "return_statement"]);
+        assert_eq!(child_types(&method.children[2]), ["expression_statement"]);
+        assert_eq!(method.location.start_line, 2);
+
+        let rust = normalize("fn add(a: i32, b: i32) -> i32 {\n    let sum = a + b;\n    sum\n}\n", "rust");
+        assert_eq!(rust[0].node_type, "function_declaration");
+        assert_eq!(child_types(&rust[0]), ["parameter", "parameter", "variable_declaration", "expression_statement"]);
+        assert_eq!(rust[0].location.start_line, 1);
+
+        // A comment between keyword and name hides the item from the source search
+        let hidden = normalize("struct /* 2D */ Point { x: i32 }
+", "rust");
+        assert_eq!(hidden[0].properties["name"], "Point");
+        assert_eq!(hidden[0].location.start_line, 0);
+    }
+}
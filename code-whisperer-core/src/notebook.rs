@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use regex::Regex;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use crate::error::AnalysisError;
+use crate::tokenizer::{Tokenizer, TokenKind};
+use crate::{CodingPattern, PatternAnalyzer};
+
+lazy_static! {
+    static ref ASSIGNMENT: Regex = Regex::new(r"^\s*(?:(?:let|const|var)\s+)?([A-Za-z_]\w*(?:\s*,\s*[A-Za-z_]\w*)*)\s*(?::[^=]+)?=(?:[^=]|$)").unwrap();
+    static ref DEFINITION: Regex = Regex::new(r"^\s*(?:async\s+)?(?:def|function|class)\s+([A-Za-z_]\w*)").unwrap();
+    static ref LOOP_VARIABLE: Regex = Regex::new(r"^\s*for\s*\(?\s*(?:(?:let|const|var)\s+)?([A-Za-z_]\w*)\s+(?:in|of)\b").unwrap();
+    static ref IMPORT: Regex = Regex::new(r"^\s*import\s+([\w.]+)(?:\s+as\s+(\w+))?\s*$").unwrap();
+    static ref FROM_IMPORT: Regex = Regex::new(r"^\s*from\s+[\w.]+\s+import\s+(.+)$").unwrap();
+}
+
+/// A notebook cell as sent by the editor: plain source, or a Jupyter cell whose `source` may be split into lines
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NotebookCell {
+    Source(String),
+    Jupyter { source: CellSource },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CellSource {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl NotebookCell {
+    fn into_source(self) -> String {
+        match self {
+            NotebookCell::Source(source) | NotebookCell::Jupyter { source: CellSource::Text(source) } => source,
+            NotebookCell::Jupyter { source: CellSource::Lines(lines) } => lines.concat(),
+        }
+    }
+}
+
+/// A name used in a cell that an earlier cell defined
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrossCellReference {
+    pub name: String,
+    pub line_number: u32, // within the using cell
+    pub defined_in_cell: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotebookCellAnalysis {
+    pub index: usize,
+    pub patterns: Vec<CodingPattern>,
+    pub defined_names: Vec<String>, // in definition order, first definition only
+    pub cross_cell_references: Vec<CrossCellReference>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotebookAggregate {
+    pub cell_count: usize,
+    pub pattern_counts: BTreeMap<String, u32>, // pattern type -> occurrences over all cells
+    pub scope: BTreeMap<String, usize>,        // name -> cell holding its latest definition
+    pub shared_names: Vec<String>,             // names defined in one cell and used in a later one
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotebookAnalysis {
+    pub cells: Vec<NotebookCellAnalysis>,
+    pub aggregate: NotebookAggregate,
+}
+
+/// Analyze notebook cells in order as one shared scope: names defined by a cell stay visible to
+/// every later cell, so uses of them are reported as cross-cell references instead of unknown names
+pub fn analyze_notebook_cells(cells: &[String], language: &str) -> NotebookAnalysis {
+    let mut analyzer = PatternAnalyzer::new();
+    let mut aggregate = NotebookAggregate { cell_count: cells.len(), ..NotebookAggregate::default() };
+    let mut analyses = Vec::new();
+
+    for (index, code) in cells.iter().enumerate() {
+        let patterns = analyzer.analyze_code(code, language);
+        for pattern in &patterns {
+            *aggregate.pattern_counts.entry(pattern.pattern_type()).or_insert(0) += 1;
+        }
+
+        let tokens: Vec<_> = Tokenizer::for_language(code, language).filter(|token| token.kind != TokenKind::Comment).collect();
+        let line_starts: Vec<usize> = std::iter::once(0).chain(code.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        let mut defined_names: Vec<String> = Vec::new();
+        let mut cross_cell_references: Vec<CrossCellReference> = Vec::new();
+        let mut next_token = 0;
+
+        for (line_index, line) in code.lines().enumerate() {
+            // Uses come before the line's own definitions, so `total = total + 1` reads the earlier cell's total
+            let line_end = line_starts[line_index] + line.len();
+            while next_token < tokens.len() && tokens[next_token].offset < line_end {
+                let token = &tokens[next_token];
+                let is_attribute = next_token > 0 && tokens[next_token - 1].text == ".";
+                next_token += 1;
+                if token.kind != TokenKind::Identifier || is_attribute {
+                    continue;
+                }
+                let Some(&defined_in_cell) = aggregate.scope.get(token.text) else { continue };
+                let already_reported = cross_cell_references.iter().any(|reference| reference.name == token.text);
+                if defined_in_cell < index && !already_reported {
+                    cross_cell_references.push(CrossCellReference {
+                        name: token.text.to_string(),
+                        line_number: line_index as u32 + 1,
+                        defined_in_cell,
+                    });
+                }
+            }
+
+            for name in defined_names_on(line) {
+                aggregate.scope.insert(name.clone(), index);
+                if !defined_names.contains(&name) {
+                    defined_names.push(name);
+                }
+            }
+        }
+
+        for reference in &cross_cell_references {
+            if !aggregate.shared_names.contains(&reference.name) {
+                aggregate.shared_names.push(reference.name.clone());
+            }
+        }
+        analyses.push(NotebookCellAnalysis { index, patterns, defined_names, cross_cell_references });
+    }
+
+    aggregate.shared_names.sort();
+    NotebookAnalysis { cells: analyses, aggregate }
+}
+
+/// Analyze notebook cells given as a JSON array of source strings or Jupyter cells (`{"source": ...}`),
+/// returning `NotebookAnalysis` JSON, or `{"error": AnalysisError}` for malformed input
+#[wasm_bindgen]
+pub fn analyze_notebook(cells_json: &str, language: &str) -> String {
+    let result = serde_json::from_str::<Vec<NotebookCell>>(cells_json)
+        .map_err(|err| AnalysisError::InvalidInput { message: format!("Invalid notebook cells: {}", err) })
+        .map(|cells| {
+            let sources: Vec<String> = cells.into_iter().map(NotebookCell::into_source).collect();
+            analyze_notebook_cells(&sources, language)
+        });
+    let json = match result {
+        Ok(analysis) => serde_json::to_value(analysis).unwrap_or_default(),
+        Err(error) => serde_json::json!({ "error": error }),
+    };
+    serde_json::to_string(&json).unwrap_or_default()
+}
+
+/// Names a line binds: assignments (tuple targets included), `def`/`function`/`class`,
+/// loop variables and imports
+fn defined_names_on(line: &str) -> Vec<String> {
+    if let Some(captures) = IMPORT.captures(line) {
+        let name = captures.get(2).map_or_else(|| captures[1].split('.').next().unwrap_or_default(), |alias| alias.as_str());
+        return vec![name.to_string()];
+    }
+    if let Some(captures) = FROM_IMPORT.captures(line) {
+        return captures[1]
+            .trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+            .split(',')
+            .filter_map(|item| item.split_whitespace().last())
+            .filter(|name| *name != "*")
+            .map(str::to_string)
+            .collect();
+    }
+    [&*DEFINITION, &*LOOP_VARIABLE, &*ASSIGNMENT]
+        .iter()
+        .find_map(|regex| regex.captures(line))
+        .map(|captures| captures[1].split(',').map(|name| name.trim().to_string()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_later_cells_see_names_from_earlier_cells() {
+        let cells = serde_json::json!([
+            "import pandas as pd\nprices = pd.read_csv('prices.csv')\nrate = 0.2\n",
+            { "source": ["total = prices.sum()\n", "total = total * rate\n", "print(total, prices.mean())\n"] },
+        ]);
+        let analysis: serde_json::Value = serde_json::from_str(&analyze_notebook(&cells.to_string(), "python")).unwrap();
+
+        assert_eq!(analysis["cells"][0]["defined_names"], serde_json::json!(["pd", "prices", "rate"]));
+        assert_eq!(analysis["cells"][0]["cross_cell_references"], serde_json::json!([]));
+        assert_eq!(analysis["cells"][1]["defined_names"], serde_json::json!(["total"]));
+        assert_eq!(analysis["cells"][1]["cross_cell_references"], serde_json::json!([
+            { "name": "prices", "line_number": 1, "defined_in_cell": 0 },
+            { "name": "rate", "line_number": 2, "defined_in_cell": 0 },
+        ]));
+
+        // `total` is defined in cell 1 before its own uses there, so it is not cross-cell
+        assert_eq!(analysis["aggregate"]["shared_names"], serde_json::json!(["prices", "rate"]));
+        assert_eq!(analysis["aggregate"]["scope"]["total"], 1);
+        assert_eq!(analysis["aggregate"]["cell_count"], 2);
+
+        // Python's `//` is floor division, not a comment hiding the reference
+        let floor_division = analyze_notebook_cells(&["total = 10\n".to_string(), "half = 5 // total\n".to_string()], "python");
+        assert_eq!(floor_division.cells[1].cross_cell_references[0].name, "total");
+
+        let invalid: serde_json::Value = serde_json::from_str(&analyze_notebook("{\"cells\": 1}", "python")).unwrap();
+        assert_eq!(invalid["error"]["kind"], "invalid_input");
+    }
+
+    #[test]
+    fn test_redefinition_moves_a_name_to_the_later_cell() {
+        let cells = vec!["let count = 1;\n".to_string(), "count = count + 1;\n".to_string(), "show(count);\n".to_string()];
+        let analysis = analyze_notebook_cells(&cells, "javascript");
+
+        assert_eq!(analysis.cells[1].cross_cell_references[0].defined_in_cell, 0);
+        assert_eq!(analysis.cells[2].cross_cell_references, vec![CrossCellReference {
+            name: "count".to_string(),
+            line_number: 1,
+            defined_in_cell: 1,
+        }]);
+    }
+}
@@ -1,5 +1,9 @@
 use crate::{CodingPattern, ast_parser::{AstParser, ParsedAst}};
-use std::collections::HashMap;
+use crate::error::AnalysisError;
+use crate::comment_scanner::CommentStats;
+use crate::tokenizer::{SpacedToken, TokenKind, spaced_tokens};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use wasm_bindgen::prelude::*;
 use regex::Regex;
 use lazy_static::lazy_static;
 use serde::{Serialize, Deserialize}; // Tambah ini
@@ -17,6 +21,92 @@ pub struct PatternAnalysis {
     pub structure_patterns: StructurePatterns,
 }
 
+/// What changed between two analyses of the same code base, e.g. across commits
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisDiff {
+    pub pattern_type_deltas: BTreeMap<String, i64>, // pattern type -> after count minus before count, non-zero only
+    pub increased_pattern_types: Vec<String>,
+    pub decreased_pattern_types: Vec<String>,
+    pub consistency_before: f32,
+    pub consistency_after: f32,
+    pub consistency_delta: f32,
+    pub new_naming_styles: Vec<String>,
+}
+
+impl PatternAnalysis {
+    /// Share of style and naming dimensions with one settled convention; dimensions without data are skipped
+    pub fn consistency_score(&self) -> f32 {
+        let naming = &self.naming_conventions;
+        let mut settled = Vec::new();
+        settled.push(!matches!(self.style_metrics.indentation_type, IndentationType::Mixed));
+        settled.push(!matches!(self.style_metrics.brace_style, BraceStyle::Mixed));
+        for style in [&naming.function_naming, &naming.variable_naming, &naming.class_naming, &naming.constant_naming, &naming.file_naming] {
+            if !matches!(style, NamingStyle::Unknown) {
+                settled.push(!matches!(style, NamingStyle::Mixed));
+            }
+        }
+
+        settled.iter().filter(|s| **s).count() as f32 / settled.len() as f32
+    }
+
+    /// Drift from `self` (before) to `other` (after)
+    pub fn diff(&self, other: &PatternAnalysis) -> AnalysisDiff {
+        let count_types = |analysis: &PatternAnalysis| {
+            let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+            for pattern in &analysis.patterns {
+                *counts.entry(pattern.pattern_type()).or_insert(0) += 1;
+            }
+            counts
+        };
+        let before = count_types(self);
+        let after = count_types(other);
+
+        let mut diff = AnalysisDiff {
+            consistency_before: self.consistency_score(),
+            consistency_after: other.consistency_score(),
+            ..Default::default()
+        };
+        diff.consistency_delta = diff.consistency_after - diff.consistency_before;
+
+        for pattern_type in before.keys().chain(after.keys()).collect::<BTreeSet<_>>() {
+            let delta = after.get(pattern_type).unwrap_or(&0) - before.get(pattern_type).unwrap_or(&0);
+            if delta > 0 {
+                diff.increased_pattern_types.push(pattern_type.clone());
+            } else if delta < 0 {
+                diff.decreased_pattern_types.push(pattern_type.clone());
+            } else {
+                continue;
+            }
+            diff.pattern_type_deltas.insert(pattern_type.clone(), delta);
+        }
+
+        let known_styles = self.naming_conventions.styles();
+        diff.new_naming_styles = other.naming_conventions.styles().difference(&known_styles).cloned().collect();
+        diff
+    }
+}
+
+impl NamingConventions {
+    // Distinct styles in use, by variant name
+    fn styles(&self) -> BTreeSet<String> {
+        [&self.function_naming, &self.variable_naming, &self.class_naming, &self.constant_naming, &self.file_naming]
+            .into_iter()
+            .filter(|style| !matches!(style, NamingStyle::Unknown))
+            .map(|style| format!("{:?}", style))
+            .collect()
+    }
+}
+
+/// `AnalysisDiff` JSON between two serialized `PatternAnalysis` values, before then after
+#[wasm_bindgen]
+pub fn diff_analyses_json(before: &str, after: &str) -> String {
+    let parse = |json: &str| serde_json::from_str::<PatternAnalysis>(json);
+    match (parse(before), parse(after)) {
+        (Ok(before), Ok(after)) => serde_json::to_string(&before.diff(&after)).unwrap_or_else(|_| "{}".to_string()),
+        (Err(e), _) | (_, Err(e)) => AnalysisError::InvalidInput { message: format!("Invalid analysis JSON: {}", e) }.to_json(),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)] // Tambah Serialize, Deserialize
 pub struct StyleMetrics {
     pub indentation_type: IndentationType,
@@ -25,6 +115,8 @@ pub struct StyleMetrics {
     pub line_length_preference: u32,
     pub space_around_operators: bool,
     pub trailing_commas: bool,
+    #[serde(default)]
+    pub comment_density: f32, // comment lines per code line
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,12 +212,12 @@ impl PatternExtractor {
     }
 
     /// Extract comprehensive patterns from source code
-    pub fn extract_patterns(&self, code: &str, language: &str) -> Result<PatternAnalysis, String> {
+    pub fn extract_patterns(&self, code: &str, language: &str) -> Result<PatternAnalysis, AnalysisError> {
         let ast = self.parser.parse_code(code, language)?;
         
         let mut analysis = PatternAnalysis {
             patterns: Vec::new(),
-            style_metrics: self.analyze_style_metrics(code),
+            style_metrics: self.analyze_style_metrics(code, language),
             naming_conventions: self.analyze_naming_conventions(&ast, language),
             structure_patterns: self.analyze_structure_patterns(&ast, code, language),
         };
@@ -140,14 +232,14 @@ impl PatternExtractor {
         Ok(analysis)
     }
 
-    /// Analyze coding style metrics from raw source code
-    fn analyze_style_metrics(&self, code: &str) -> StyleMetrics {
+    /// Analyze coding style metrics from raw source code; needs no parse
+    pub(crate) fn analyze_style_metrics(&self, code: &str, language: &str) -> StyleMetrics {
         let lines: Vec<&str> = code.lines().collect();
         
         let indentation = self.detect_indentation(&lines);
         let brace_style = self.detect_brace_style(&lines);
         let avg_line_length = self.calculate_average_line_length(&lines);
-        let space_around_operators = self.detect_space_around_operators(code);
+        let space_around_operators = self.detect_space_around_operators(code, language);
         let trailing_commas = self.detect_trailing_commas(code);
 
         StyleMetrics {
@@ -157,6 +249,7 @@ impl PatternExtractor {
             line_length_preference: avg_line_length,
             space_around_operators,
             trailing_commas,
+            comment_density: CommentStats::scan(code, language).ratio(),
         }
     }
 
@@ -234,15 +327,10 @@ impl PatternExtractor {
         (total_length / lines.len()) as u32
     }
 
-    /// Detect space around operators preference
-    fn detect_space_around_operators(&self, code: &str) -> bool {
-        let spaced_operators = Regex::new(r"\s[+\-*/=<>!]+\s").unwrap();
-        let unspaced_operators = Regex::new(r"[^\s][+\-*/=<>!]+[^\s]").unwrap();
-
-        let spaced_count = spaced_operators.find_iter(code).count();
-        let unspaced_count = unspaced_operators.find_iter(code).count();
-
-        spaced_count > unspaced_count
+    /// Detect space around operators preference, from code tokens only so operators inside
+    /// strings and comments don't count
+    fn detect_space_around_operators(&self, code: &str, language: &str) -> bool {
+        prefers_spaced_operators(&spaced_tokens(code, language))
     }
 
     /// Detect trailing comma preference
@@ -352,31 +440,31 @@ impl PatternExtractor {
     }
 
     /// Extract function-related patterns
-    fn extract_function_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, String> {
+    fn extract_function_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, AnalysisError> {
         // TODO: Implement function pattern extraction
         Ok(Vec::new())
     }
 
     /// Extract class-related patterns
-    fn extract_class_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, String> {
+    fn extract_class_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, AnalysisError> {
         // TODO: Implement class pattern extraction
         Ok(Vec::new())
     }
 
     /// Extract variable-related patterns
-    fn extract_variable_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, String> {
+    fn extract_variable_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, AnalysisError> {
         // TODO: Implement variable pattern extraction
         Ok(Vec::new())
     }
 
     /// Extract control flow patterns
-    fn extract_control_flow_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, String> {
+    fn extract_control_flow_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, AnalysisError> {
         // TODO: Implement control flow pattern extraction
         Ok(Vec::new())
     }
 
     /// Extract error handling patterns
-    fn extract_error_handling_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, String> {
+    fn extract_error_handling_patterns(&self, _ast: &ParsedAst, _language: &str) -> Result<Vec<CodingPattern>, AnalysisError> {
         // TODO: Implement error handling pattern extraction
         Ok(Vec::new())
     }
@@ -388,6 +476,25 @@ impl Default for PatternExtractor {
     }
 }
 
+/// Whether arithmetic, comparison and assignment operators are more often spaced on both
+/// sides than written flush against both neighbours; one-sided spacing counts for neither
+pub(crate) fn prefers_spaced_operators(tokens: &[SpacedToken]) -> bool {
+    let operators = tokens.iter()
+        .filter(|spaced| spaced.token.kind == TokenKind::Operator)
+        .filter(|spaced| spaced.token.text.chars().all(|c| "+-*/=<>!".contains(c)));
+
+    let (mut spaced_count, mut unspaced_count) = (0, 0);
+    for operator in operators {
+        match (operator.space_before, operator.space_after) {
+            (true, true) => spaced_count += 1,
+            (false, false) => unspaced_count += 1,
+            _ => {},
+        }
+    }
+
+    spaced_count > unspaced_count
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -479,10 +586,24 @@ mod tests {
         let extractor = PatternExtractor::new();
         
         let spaced_code = "let x = a + b;\nlet y = c * d;";
-        assert!(extractor.detect_space_around_operators(spaced_code));
+        assert!(extractor.detect_space_around_operators(spaced_code, "javascript"));
         
         let unspaced_code = "let x=a+b;\nlet y=c*d;";
-        assert!(!extractor.detect_space_around_operators(unspaced_code));
+        assert!(!extractor.detect_space_around_operators(unspaced_code, "javascript"));
+    }
+
+    #[test]
+    fn test_operators_inside_strings_do_not_sway_spacing() {
+        let extractor = PatternExtractor::new();
+
+        // Unspaced operators only inside literals and comments
+        let spaced_code = "let x = a + b;\nlet label = \"a+b=c\" + 'x*y-z';\n// i=i+1, j=j-1\n";
+        assert!(extractor.detect_space_around_operators(spaced_code, "javascript"));
+        assert!(extractor.analyze_style_metrics(spaced_code, "javascript").space_around_operators);
+
+        // Spaced operators only inside literals
+        let unspaced_code = "x=a+b\nlabel=\"a + b = c\"+'x * y'\n";
+        assert!(!extractor.detect_space_around_operators(unspaced_code, "python"));
     }
 
     #[test]
@@ -540,4 +661,64 @@ const result = myProcessor.processData();
         assert_eq!(pattern_analysis.style_metrics.indentation_size, 4); // Default
         assert!(pattern_analysis.patterns.is_empty());
     }
+
+    fn snapshot(pattern_types: &[&str], variable_naming: NamingStyle, brace_style: BraceStyle) -> PatternAnalysis {
+        PatternAnalysis {
+            patterns: pattern_types
+                .iter()
+                .enumerate()
+                .map(|(i, pattern_type)| CodingPattern::new(format!("p{}", i), pattern_type.to_string(), "javascript".to_string(), 0.8))
+                .collect(),
+            style_metrics: StyleMetrics {
+                indentation_type: IndentationType::Spaces,
+                indentation_size: 2,
+                brace_style,
+                line_length_preference: 80,
+                space_around_operators: true,
+                trailing_commas: false,
+                comment_density: 0.0,
+            },
+            naming_conventions: NamingConventions {
+                function_naming: NamingStyle::CamelCase,
+                variable_naming,
+                class_naming: NamingStyle::Unknown,
+                constant_naming: NamingStyle::Unknown,
+                file_naming: NamingStyle::Unknown,
+            },
+            structure_patterns: StructurePatterns {
+                preferred_file_organization: FileOrganization::ImportsFirst,
+                function_length_preference: 20,
+                class_structure_preference: ClassStructure::Mixed,
+                import_organization: ImportStyle::Grouped,
+            },
+        }
+    }
+
+    #[test]
+    fn test_analysis_diff_reports_new_class_and_consistency_drop() {
+        let before = snapshot(&["function_definition", "function_definition", "loop_construct"], NamingStyle::CamelCase, BraceStyle::SameLine);
+        let after = snapshot(&["function_definition", "class_definition"], NamingStyle::SnakeCase, BraceStyle::Mixed);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.increased_pattern_types, vec!["class_definition"]);
+        assert_eq!(diff.decreased_pattern_types, vec!["function_definition", "loop_construct"]);
+        assert_eq!(diff.pattern_type_deltas.get("class_definition"), Some(&1));
+        assert_eq!(diff.pattern_type_deltas.get("function_definition"), Some(&-1));
+        assert_eq!(diff.consistency_before, 1.0);
+        assert_eq!(diff.consistency_after, 0.75);
+        assert!(diff.consistency_delta < 0.0);
+        assert_eq!(diff.new_naming_styles, vec!["SnakeCase"]);
+
+        assert_eq!(before.diff(&before), AnalysisDiff { consistency_before: 1.0, consistency_after: 1.0, ..Default::default() });
+    }
+
+    #[test]
+    fn test_diff_analyses_json() {
+        let before = serde_json::to_string(&snapshot(&["function_definition"], NamingStyle::CamelCase, BraceStyle::SameLine)).unwrap();
+        let after = serde_json::to_string(&snapshot(&["function_definition", "class_definition"], NamingStyle::CamelCase, BraceStyle::SameLine)).unwrap();
+
+        let diff: AnalysisDiff = serde_json::from_str(&diff_analyses_json(&before, &after)).unwrap();
+        assert_eq!(diff.increased_pattern_types, vec!["class_definition"]);
+        assert!(diff_analyses_json(&before, "{").contains("\"kind\":\"invalid_input\""));
+    }
 }
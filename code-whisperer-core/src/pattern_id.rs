@@ -0,0 +1,95 @@
+use crate::abort::AbortFlag;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Stable FNV-1a hash of pattern type, language, whitespace-normalized content and structural path
+pub fn content_hash(pattern_type: &str, language: &str, content: &str, path: &str) -> u64 {
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    // Unit separators keep ("ab", "c") and ("a", "bc") apart
+    [pattern_type, "\u{1f}", &language.to_lowercase(), "\u{1f}", &normalized, "\u{1f}", path]
+        .iter()
+        .flat_map(|part| part.bytes())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Id for a pattern derived from its own content and where it sits, so the same pattern gets the
+/// same id every time it is analyzed, and identical content in different places does not collide.
+/// `path` is the pattern's `structural_path`
+pub fn pattern_id(prefix: &str, pattern_type: &str, language: &str, content: &str, path: &str) -> String {
+    format!("{}_{:016x}", prefix, content_hash(pattern_type, language, content, path))
+}
+
+/// The headers of the blocks enclosing the line at `offset`, outermost first and joined by ` > `,
+/// e.g. `class Queue: > def push(self, item):`. Blocks are found by indentation, so this needs no
+/// parser and works for the regex fallbacks too
+pub fn structural_path(code: &str, offset: usize) -> String {
+    let offset = offset.min(code.len());
+    let line_start = code[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let indent = |line: &str| line.len() - line.trim_start().len();
+
+    let mut depth = indent(code[line_start..].lines().next().unwrap_or_default());
+    let mut headers = Vec::new();
+    for line in code[..line_start].lines().rev().filter(|line| !line.trim().is_empty()) {
+        if indent(line) < depth {
+            depth = indent(line);
+            headers.push(line.trim());
+        }
+    }
+    headers.reverse();
+    headers.join(" > ")
+}
+
+/// Byte offset and text of the first line of `code` satisfying `matches`. Each line is a tick of
+/// `abort`; an aborted search finds nothing
+pub fn first_line_where<'c>(code: &'c str, abort: &AbortFlag, matches: impl Fn(&str) -> bool) -> Option<(usize, &'c str)> {
+    let mut offset = 0;
+    for line in code.split_inclusive('\n') {
+        if abort.tick() {
+            return None;
+        }
+        let text = line.trim_end_matches(['\n', '\r']);
+        if matches(text) {
+            return Some((offset, text));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_unique_for_distinct_content_in_a_tight_loop() {
+        let ids: std::collections::HashSet<String> = (0..1000)
+            .map(|index| pattern_id("js_func", "function_definition", "javascript", &format!("function f{}() {{}}", index), ""))
+            .collect();
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn test_ids_are_reproducible_for_identical_content() {
+        let id_a = pattern_id("rs_func", "function_definition", "rust", "fn a() {}", "impl Point {");
+        let id_b = pattern_id("rs_func", "function_definition", "Rust", "fn  a()\n{}", "impl Point {");
+        assert_eq!(id_a, id_b);
+
+        assert_ne!(
+            content_hash("function_definition", "rust", "fn a() {}", ""),
+            content_hash("function_definition", "rust", "fn b() {}", "")
+        );
+        // The same method in two impls is two patterns
+        assert_ne!(id_a, pattern_id("rs_func", "function_definition", "rust", "fn a() {}", "impl Line {"));
+    }
+
+    #[test]
+    fn test_structural_path_lists_enclosing_blocks() {
+        let code = "class Queue:\n    def push(self, item):\n        self.items.append(item)\n\n    def pop(self):\n        return self.items.pop()\n";
+        let offset = code.find("return").unwrap();
+        assert_eq!(structural_path(code, offset), "class Queue: > def pop(self):");
+        assert_eq!(structural_path(code, code.find("def push").unwrap()), "class Queue:");
+        assert_eq!(structural_path(code, 0), "");
+    }
+}
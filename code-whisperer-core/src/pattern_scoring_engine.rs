@@ -126,7 +126,7 @@ impl PatternScoringEngine {
         self.calculate_composite_scores(&mut scored_patterns);
 
         // Sort by composite score (descending)
-        scored_patterns.sort_by(|a, b| b.composite_score.partial_cmp(&a.composite_score).unwrap());
+        scored_patterns.sort_by(|a, b| b.composite_score.total_cmp(&a.composite_score));
 
         // Update historical patterns
         self.update_historical_patterns(&scored_patterns);
@@ -469,8 +469,6 @@ impl PatternScoringEngine {
 mod tests {
     use super::*;
     use crate::pattern_extractor::{NamingConventions, StructurePatterns};
-    use crate::style_analyzer::{CodingStyleAnalysis, IndentationStyle, BraceStyleAnalysis};
-    use crate::user_behavior_tracker::{BehaviorAnalysis, SessionData, PatternPreferences};
 
     #[test]
     fn test_pattern_scoring_engine_creation() {
@@ -0,0 +1,270 @@
+use std::ops::Range;
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+
+/// Calls that hand back something to close, as `(qualifier, function)`; a `None` qualifier
+/// matches the function however it is reached
+const PYTHON_ACQUISITIONS: &[(Option<&str>, &str)] = &[
+    (None, "open"),
+    (None, "urlopen"),
+    (Some("sqlite3"), "connect"),
+    (Some("psycopg2"), "connect"),
+    (Some("pymysql"), "connect"),
+    (Some("socket"), "socket"),
+    (Some("socket"), "create_connection"),
+];
+const JAVASCRIPT_ACQUISITIONS: &[(Option<&str>, &str)] = &[
+    (Some("fs"), "openSync"),
+    (Some("fs"), "createReadStream"),
+    (Some("fs"), "createWriteStream"),
+    (Some("net"), "connect"),
+    (Some("net"), "createConnection"),
+    (None, "createConnection"),
+    (None, "WebSocket"),
+];
+const GO_ACQUISITIONS: &[(Option<&str>, &str)] = &[
+    (Some("os"), "Open"),
+    (Some("os"), "Create"),
+    (Some("os"), "OpenFile"),
+    (Some("sql"), "Open"),
+    (Some("net"), "Dial"),
+];
+const RUST_ACQUISITIONS: &[(Option<&str>, &str)] = &[
+    (Some("File"), "open"),
+    (Some("File"), "create"),
+    (Some("TcpStream"), "connect"),
+];
+
+/// `resource.close()` and friends
+const RELEASE_METHODS: &[&str] = &["close", "Close", "end", "destroy", "release", "disconnect", "shutdown", "terminate"];
+/// `closeSync(fd)` and friends, taking the resource first
+const RELEASE_FUNCTIONS: &[&str] = &["close", "closeSync", "fclose"];
+/// Rust values dropped by these never run their destructor
+const RUST_LEAKS: &[&str] = &["forget", "leak"];
+/// Keywords opening a braced block that is not a function body
+const CONTROL_KEYWORDS: &[&str] = &["if", "else", "for", "while", "do", "switch", "catch", "try", "finally", "with", "loop", "match"];
+
+/// A file, socket or connection opened without a matching close
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnclosedResource {
+    pub acquisition: String,      // the opening call, e.g. `open` or `fs.openSync`
+    pub variable: Option<String>, // where the handle is kept; None when it is used and dropped inline
+    pub line_number: u32,
+}
+
+/// Resources opened in Python, JavaScript, TypeScript, Go and Rust that are never released.
+/// A handle is safe once the function opening it calls `close()` (or `end()`, `destroy()`...)
+/// on it, as a `finally` block or Go `defer` would; returning it hands the job to the caller. Python
+/// opens in a `with` header are always safe, and Rust handles are closed on drop unless they
+/// are passed to `mem::forget` or `Box::leak`
+pub fn find_unclosed_resources(code: &str, language: &str) -> Vec<UnclosedResource> {
+    let language = language.to_lowercase();
+    let acquisitions = match language.as_str() {
+        "python" => PYTHON_ACQUISITIONS,
+        "javascript" | "typescript" => JAVASCRIPT_ACQUISITIONS,
+        "go" => GO_ACQUISITIONS,
+        "rust" => RUST_ACQUISITIONS,
+        _ => return Vec::new(),
+    };
+    let tokens: Vec<Token> = Tokenizer::for_language(code, &language)
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect();
+
+    let mut unclosed = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let Some(acquisition) = acquisition_at(&tokens, index, acquisitions) else { continue };
+        let line: Vec<&Token> = tokens.iter().filter(|other| other.line == token.line).collect();
+        let opens_line = |words: &[&str]| line.first().is_some_and(|first| words.contains(&first.text));
+
+        // `with open(...) as f:` closes itself; returned handles are the caller's to close
+        let managed = language == "python" && (opens_line(&["with"]) || (opens_line(&["async"]) && line.get(1).is_some_and(|second| second.text == "with")));
+        if managed || opens_line(&["return", "yield"]) {
+            continue;
+        }
+
+        let variable = assigned_variable(&tokens, index, token.line);
+        // A same-named handle closed in another function says nothing about this one
+        let scope = &tokens[function_scope(&tokens, index, &language)];
+        let leaked = match (&variable, language.as_str()) {
+            (Some(variable), "rust") => is_passed_to(scope, variable, RUST_LEAKS),
+            (Some(variable), _) => !is_released(scope, variable) && !is_returned(scope, variable),
+            // Only Python drops a handle used inline without closing it
+            (None, language) => language == "python",
+        };
+        if leaked {
+            unclosed.push(UnclosedResource { acquisition, variable, line_number: token.line });
+        }
+    }
+
+    unclosed
+}
+
+/// The acquisition called at `index`, spelled as written
+fn acquisition_at(tokens: &[Token], index: usize, acquisitions: &[(Option<&str>, &str)]) -> Option<String> {
+    let token = &tokens[index];
+    if token.kind != TokenKind::Identifier || tokens.get(index + 1).is_none_or(|next| next.text != "(") {
+        return None;
+    }
+    // `fs.openSync` or `File::open`
+    let qualifier = match index.checked_sub(1).map(|before| tokens[before].text) {
+        Some(".") => index.checked_sub(2).map(|at| tokens[at].text),
+        Some(":") if index >= 3 && tokens[index - 2].text == ":" => Some(tokens[index - 3].text),
+        _ => None,
+    };
+
+    acquisitions.iter()
+        .find(|(wanted, function)| *function == token.text && wanted.is_none_or(|wanted| qualifier == Some(wanted)))
+        .map(|_| match qualifier {
+            Some(qualifier) => format!("{}.{}", qualifier, token.text),
+            None => token.text.to_string(),
+        })
+}
+
+/// The variable a call at `index` is stored in: the last name before `=` in the first
+/// comma-separated target, so `f, err := os.Open(...)` keeps `f` and `self.file = open(...)`
+/// keeps `file`. None when the call's result is chained on, as in `open(p).read()`
+fn assigned_variable(tokens: &[Token], index: usize, line: u32) -> Option<String> {
+    let mut depth = 0;
+    let call_end = tokens[index + 1..].iter().position(|token| {
+        match token.text {
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    })? + index + 1;
+    if tokens.get(call_end + 1).is_some_and(|next| next.text == "." || next.text == "?" && tokens.get(call_end + 2).is_some_and(|after| after.text == ".")) {
+        return None;
+    }
+
+    let line_start = tokens[..index].iter().rposition(|token| token.line != line).map_or(0, |before| before + 1);
+    let assign = tokens[line_start..index].iter().position(|token| token.kind == TokenKind::Operator && token.text == "=")? + line_start;
+    let first_target = tokens[line_start..assign].split(|token| token.text == ",").next()?;
+    first_target.iter().rev().find(|token| token.kind == TokenKind::Identifier).map(|token| token.text.to_string())
+}
+
+/// Token range of the body of the innermost function enclosing `index`, or every token for
+/// top-level code
+fn function_scope(tokens: &[Token], index: usize, language: &str) -> Range<usize> {
+    let scope = if language == "python" { python_function_scope(tokens, index) } else { braced_function_scope(tokens, index) };
+    scope.unwrap_or(0..tokens.len())
+}
+
+/// The indented block under the nearest `def` that `index`'s line is nested in
+fn python_function_scope(tokens: &[Token], index: usize) -> Option<Range<usize>> {
+    let starts_line = |at: usize| at == 0 || tokens[at - 1].line != tokens[at].line;
+    let line_start = (0..=index).rev().find(|&at| starts_line(at)).unwrap_or(0);
+    let mut indent = tokens[line_start].column;
+
+    let def = (0..line_start).rev().filter(|&at| starts_line(at)).find(|&at| {
+        if tokens[at].column >= indent {
+            return false;
+        }
+        indent = tokens[at].column;
+        tokens[at].text == "def" || tokens[at].text == "async" && tokens.get(at + 1).is_some_and(|next| next.text == "def")
+    })?;
+    let end = (def + 1..tokens.len())
+        .find(|&at| starts_line(at) && tokens[at].column <= tokens[def].column)
+        .unwrap_or(tokens.len());
+    Some(def..end)
+}
+
+/// The `{ ... }` body of the nearest function, method, closure or arrow function around `index`
+fn braced_function_scope(tokens: &[Token], index: usize) -> Option<Range<usize>> {
+    let mut depth = 0;
+    let open = (0..index).rev().find(|&at| {
+        match tokens[at].text {
+            "}" => depth += 1,
+            "{" if depth > 0 => depth -= 1,
+            "{" => return opens_function_body(tokens, at),
+            _ => {}
+        }
+        false
+    })?;
+
+    let mut depth = 0;
+    let close = tokens[open..].iter().position(|token| {
+        match token.text {
+            "{" => depth += 1,
+            "}" => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    });
+    Some(open..close.map_or(tokens.len(), |close| open + close + 1))
+}
+
+/// Whether the `{` at `open` starts a function body: its header since the previous statement or
+/// block names a function keyword, or is a method shorthand like `load(path) {`
+fn opens_function_body(tokens: &[Token], open: usize) -> bool {
+    let header_start = tokens[..open].iter().rposition(|token| matches!(token.text, ";" | "{" | "}")).map_or(0, |end| end + 1);
+    let header = &tokens[header_start..open];
+    let declares_function = header.iter().any(|token| matches!(token.text, "function" | "func" | "fn" | "=>"));
+    let is_method = header.last().is_some_and(|last| last.text == ")")
+        && header.first().is_some_and(|first| !CONTROL_KEYWORDS.contains(&first.text));
+    declares_function || is_method
+}
+
+/// Whether `variable` itself is returned, as in `return handle`, rather than something read from it
+fn is_returned(tokens: &[Token], variable: &str) -> bool {
+    tokens.windows(2).enumerate().any(|(at, window)| {
+        window[0].text == "return" && window[1].text == variable
+            && tokens.get(at + 2).is_none_or(|next| !matches!(next.text, "." | "(" | "["))
+    })
+}
+
+/// Whether anything calls a release method on `variable` or passes it to a release function
+fn is_released(tokens: &[Token], variable: &str) -> bool {
+    tokens.windows(3).any(|window| {
+        window[0].text == variable && window[1].text == "." && RELEASE_METHODS.contains(&window[2].text)
+    }) || is_passed_to(tokens, variable, RELEASE_FUNCTIONS)
+}
+
+/// Whether `variable` is the first argument of a call to one of `functions`
+fn is_passed_to(tokens: &[Token], variable: &str, functions: &[&str]) -> bool {
+    tokens.windows(3).any(|window| {
+        functions.contains(&window[0].text) && window[1].text == "(" && window[2].text == variable
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flagged(code: &str, language: &str) -> Vec<(Option<String>, u32)> {
+        find_unclosed_resources(code, language).into_iter().map(|resource| (resource.variable, resource.line_number)).collect()
+    }
+
+    #[test]
+    fn test_only_the_unclosed_python_open_is_flagged() {
+        let code = "def read_config(path):\n    handle = open(path)\n    return handle.read()\n\ndef read_data(path):\n    with open(path) as handle:\n        return handle.read()\n\ndef read_log(path):\n    log = open(path)\n    try:\n        return log.read()\n    finally:\n        log.close()\n";
+        assert_eq!(flagged(code, "python"), vec![(Some("handle".to_string()), 2)]);
+        assert_eq!(find_unclosed_resources(code, "python")[0].acquisition, "open");
+
+        // Used inline, the handle is never closed
+        assert_eq!(flagged("text = open('notes.txt').read()\n", "python"), vec![(None, 1)]);
+    }
+
+    #[test]
+    fn test_try_finally_defer_and_raii_count_as_closed() {
+        let js = "const fd = fs.openSync(path, 'r');\ntry {\n  read(fd);\n} finally {\n  fs.closeSync(fd);\n}\nconst stream = fs.createWriteStream(out);\nstream.write(data);\n";
+        assert_eq!(flagged(js, "javascript"), vec![(Some("stream".to_string()), 7)]);
+
+        let go = "func load(path string) error {\n\tf, err := os.Open(path)\n\tif err != nil {\n\t\treturn err\n\t}\n\tdefer f.Close()\n\treturn nil\n}\n";
+        assert!(flagged(go, "go").is_empty());
+
+        let rust = "fn load() -> std::io::Result<()> {\n    let file = File::open(\"a.txt\")?;\n    let leaked = File::create(\"b.txt\")?;\n    std::mem::forget(leaked);\n    Ok(())\n}\n";
+        assert_eq!(flagged(rust, "rust"), vec![(Some("leaked".to_string()), 3)]);
+    }
+
+    #[test]
+    fn test_releases_only_count_in_the_opening_function() {
+        let python = "def read(path):\n    handle = open(path)\n    return handle.read()\n\ndef cleanup(handle):\n    handle.close()\n\ndef reopen(path):\n    handle = open(path)\n    log(handle)\n    return handle\n";
+        assert_eq!(flagged(python, "python"), vec![(Some("handle".to_string()), 2)]);
+
+        let js = "class Store {\n  load(path) {\n    const fd = fs.openSync(path, 'r');\n    if (fd) {\n      parse(fd);\n    }\n  }\n  close(fd) {\n    fs.closeSync(fd);\n  }\n}\n";
+        assert_eq!(flagged(js, "javascript"), vec![(Some("fd".to_string()), 3)]);
+
+        // Top-level code is one scope
+        assert!(flagged("const fd = fs.openSync(path);\nfs.closeSync(fd);\n", "javascript").is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::ast_parser::ParsedAst;
+use crate::exception_handling::python_child_blocks;
+use rustpython_parser::ast::{self as py_ast, Ranged};
+use swc_ecma_ast as swc_ast;
+
+/// What one path out of a function hands back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReturnShape {
+    Object,   // an object or dict literal
+    Array,    // an array or list literal
+    Value,    // any other expression
+    Null,     // `null` or `None`
+    Nothing,  // a bare `return`, or `return undefined`
+    FallsOff, // the end of the body, reached without returning
+}
+
+impl ReturnShape {
+    fn is_value(self) -> bool {
+        matches!(self, ReturnShape::Object | ReturnShape::Array | ReturnShape::Value)
+    }
+
+    fn is_undefined(self) -> bool {
+        matches!(self, ReturnShape::Nothing | ReturnShape::FallsOff)
+    }
+
+    fn describe(self, python: bool) -> &'static str {
+        match self {
+            ReturnShape::Object if python => "a dict",
+            ReturnShape::Object => "an object",
+            ReturnShape::Array if python => "a list",
+            ReturnShape::Array => "an array",
+            ReturnShape::Value => "a value",
+            ReturnShape::Null if python => "None",
+            ReturnShape::Null => "null",
+            ReturnShape::Nothing => "nothing",
+            ReturnShape::FallsOff => "nothing at the end",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnSite {
+    pub shape: ReturnShape,
+    pub line_number: u32,
+}
+
+/// A function whose paths disagree on what they return
+#[derive(Debug, Clone, PartialEq)]
+pub struct InconsistentReturn {
+    pub function: String,
+    pub line_number: u32,
+    pub returns: Vec<ReturnSite>, // every way out of the function, in source order
+}
+
+impl InconsistentReturn {
+    /// e.g. "returns an object (line 3) and nothing at the end (line 6)"
+    pub fn summary(&self, python: bool) -> String {
+        let parts: Vec<String> = self.returns.iter()
+            .map(|site| format!("{} (line {})", site.shape.describe(python), site.line_number))
+            .collect();
+        match parts.as_slice() {
+            [] => "returns nothing".to_string(),
+            [only] => format!("returns {}", only),
+            [rest @ .., last] => format!("returns {} and {}", rest.join(", "), last),
+        }
+    }
+}
+
+/// JavaScript, TypeScript and Python functions that return a value on some paths but nothing
+/// on others, mix `null` with `undefined` (`None` with a bare `return`), or hand back an
+/// object on one path and an array on another. Returning either a value or `null`/`None`
+/// is a deliberate "maybe" and is not flagged; generators are skipped
+pub fn inconsistent_returns(ast: &ParsedAst, code: &str) -> Vec<InconsistentReturn> {
+    let mut found = Vec::new();
+    match ast {
+        ParsedAst::JavaScript(module) => {
+            let mut functions = Vec::new();
+            for item in &module.body {
+                match item {
+                    swc_ast::ModuleItem::Stmt(stmt) => js_collect_stmt(stmt, &mut functions),
+                    swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDecl(export)) => js_collect_decl(&export.decl, &mut functions),
+                    swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDefaultDecl(export)) => {
+                        if let swc_ast::DefaultDecl::Fn(fn_expr) = &export.decl {
+                            let name = fn_expr.ident.as_ref().map_or_else(|| "default".to_string(), |ident| ident.sym.to_string());
+                            js_collect_function(name, &fn_expr.function, &mut functions);
+                        }
+                    }
+                    swc_ast::ModuleItem::ModuleDecl(_) => {}
+                }
+            }
+            found.extend(functions.into_iter().filter_map(|function| js_inconsistency(function, code)));
+        }
+        ParsedAst::Python(suite) => python_collect(suite, code, &mut found),
+        _ => {}
+    }
+    found.sort_by_key(|function| function.line_number);
+    found
+}
+
+/// Whether `returns` disagree with each other
+fn conflicting(returns: &[ReturnSite]) -> bool {
+    let has = |wanted: fn(ReturnShape) -> bool| returns.iter().any(|site| wanted(site.shape));
+    let undefined = has(ReturnShape::is_undefined);
+    (undefined && has(|shape| shape.is_value() || shape == ReturnShape::Null))
+        || (has(|shape| shape == ReturnShape::Object) && has(|shape| shape == ReturnShape::Array))
+}
+
+fn line_at(code: &str, offset: usize) -> u32 {
+    code.get(..offset).unwrap_or(code).matches('\n').count() as u32 + 1
+}
+
+/// A named function body found in a module, nested ones included
+struct JsFunction<'a> {
+    name: String,
+    body: &'a [swc_ast::Stmt],
+    span: swc_common::Span, // the body block, braces included
+}
+
+fn js_collect_stmt<'a>(stmt: &'a swc_ast::Stmt, functions: &mut Vec<JsFunction<'a>>) {
+    if let swc_ast::Stmt::Decl(decl) = stmt {
+        js_collect_decl(decl, functions);
+    }
+}
+
+fn js_collect_decl<'a>(decl: &'a swc_ast::Decl, functions: &mut Vec<JsFunction<'a>>) {
+    match decl {
+        swc_ast::Decl::Fn(fn_decl) => js_collect_function(fn_decl.ident.sym.to_string(), &fn_decl.function, functions),
+        swc_ast::Decl::Var(var) => {
+            for declarator in &var.decls {
+                let (swc_ast::Pat::Ident(binding), Some(init)) = (&declarator.name, declarator.init.as_deref()) else { continue };
+                let name = binding.id.sym.to_string();
+                match init {
+                    swc_ast::Expr::Fn(fn_expr) => js_collect_function(name, &fn_expr.function, functions),
+                    swc_ast::Expr::Arrow(arrow) if !arrow.is_generator => {
+                        if let swc_ast::BlockStmtOrExpr::BlockStmt(block) = &*arrow.body {
+                            functions.push(JsFunction { name, body: &block.stmts, span: block.span });
+                            block.stmts.iter().for_each(|stmt| js_collect_stmt(stmt, functions));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        swc_ast::Decl::Class(class) => {
+            for member in &class.class.body {
+                if let swc_ast::ClassMember::Method(method) = member {
+                    let name = match &method.key {
+                        swc_ast::PropName::Ident(ident) => ident.sym.to_string(),
+                        _ => continue,
+                    };
+                    js_collect_function(name, &method.function, functions);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn js_collect_function<'a>(name: String, function: &'a swc_ast::Function, functions: &mut Vec<JsFunction<'a>>) {
+    let Some(body) = function.body.as_ref().filter(|_| !function.is_generator) else { return };
+    functions.push(JsFunction { name, body: &body.stmts, span: body.span });
+    body.stmts.iter().for_each(|stmt| js_collect_stmt(stmt, functions));
+}
+
+fn js_inconsistency(function: JsFunction, code: &str) -> Option<InconsistentReturn> {
+    let mut returns = Vec::new();
+    js_returns(function.body, code, &mut returns);
+    if !js_always_exits(function.body) {
+        // The closing brace is the last byte of the block
+        let closing = function.span.hi.0.saturating_sub(2) as usize;
+        returns.push(ReturnSite { shape: ReturnShape::FallsOff, line_number: line_at(code, closing) });
+    }
+    conflicting(&returns).then(|| InconsistentReturn {
+        function: function.name,
+        line_number: line_at(code, function.span.lo.0.saturating_sub(1) as usize),
+        returns,
+    })
+}
+
+/// Every `return` in `stmts`, outside nested functions
+fn js_returns(stmts: &[swc_ast::Stmt], code: &str, returns: &mut Vec<ReturnSite>) {
+    for stmt in stmts {
+        js_stmt_returns(stmt, code, returns);
+    }
+}
+
+fn js_stmt_returns(stmt: &swc_ast::Stmt, code: &str, returns: &mut Vec<ReturnSite>) {
+    use swc_ast::Stmt;
+    match stmt {
+        Stmt::Return(ret) => returns.push(ReturnSite {
+            shape: ret.arg.as_deref().map_or(ReturnShape::Nothing, js_shape),
+            line_number: line_at(code, ret.span.lo.0.saturating_sub(1) as usize),
+        }),
+        Stmt::Block(block) => js_returns(&block.stmts, code, returns),
+        Stmt::If(if_stmt) => {
+            js_stmt_returns(&if_stmt.cons, code, returns);
+            if let Some(alt) = &if_stmt.alt {
+                js_stmt_returns(alt, code, returns);
+            }
+        }
+        Stmt::For(for_stmt) => js_stmt_returns(&for_stmt.body, code, returns),
+        Stmt::ForIn(for_in) => js_stmt_returns(&for_in.body, code, returns),
+        Stmt::ForOf(for_of) => js_stmt_returns(&for_of.body, code, returns),
+        Stmt::While(while_stmt) => js_stmt_returns(&while_stmt.body, code, returns),
+        Stmt::DoWhile(do_while) => js_stmt_returns(&do_while.body, code, returns),
+        Stmt::Labeled(labeled) => js_stmt_returns(&labeled.body, code, returns),
+        Stmt::Switch(switch) => switch.cases.iter().for_each(|case| js_returns(&case.cons, code, returns)),
+        Stmt::Try(try_stmt) => {
+            js_returns(&try_stmt.block.stmts, code, returns);
+            if let Some(handler) = &try_stmt.handler {
+                js_returns(&handler.body.stmts, code, returns);
+            }
+            if let Some(finalizer) = &try_stmt.finalizer {
+                js_returns(&finalizer.stmts, code, returns);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn js_shape(expr: &swc_ast::Expr) -> ReturnShape {
+    match expr {
+        swc_ast::Expr::Paren(paren) => js_shape(&paren.expr),
+        swc_ast::Expr::Object(_) => ReturnShape::Object,
+        swc_ast::Expr::Array(_) => ReturnShape::Array,
+        swc_ast::Expr::Lit(swc_ast::Lit::Null(_)) => ReturnShape::Null,
+        swc_ast::Expr::Ident(ident) if &*ident.sym == "undefined" => ReturnShape::Nothing,
+        swc_ast::Expr::Unary(unary) if unary.op == swc_ast::UnaryOp::Void => ReturnShape::Nothing,
+        _ => ReturnShape::Value,
+    }
+}
+
+/// Whether running `stmts` always ends in a `return` or `throw`
+fn js_always_exits(stmts: &[swc_ast::Stmt]) -> bool {
+    stmts.iter().any(js_stmt_exits)
+}
+
+fn js_stmt_exits(stmt: &swc_ast::Stmt) -> bool {
+    use swc_ast::Stmt;
+    match stmt {
+        Stmt::Return(_) | Stmt::Throw(_) => true,
+        Stmt::Block(block) => js_always_exits(&block.stmts),
+        Stmt::If(if_stmt) => js_stmt_exits(&if_stmt.cons) && if_stmt.alt.as_deref().is_some_and(js_stmt_exits),
+        Stmt::Try(try_stmt) => {
+            let finalizer_exits = try_stmt.finalizer.as_ref().is_some_and(|finalizer| js_always_exits(&finalizer.stmts));
+            let handler_exits = try_stmt.handler.as_ref().is_none_or(|handler| js_always_exits(&handler.body.stmts));
+            finalizer_exits || (js_always_exits(&try_stmt.block.stmts) && handler_exits)
+        }
+        // Every case falls through to one that exits, and `default` leaves no way around them
+        Stmt::Switch(switch) => {
+            switch.cases.iter().any(|case| case.test.is_none())
+                && switch.cases.last().is_some_and(|case| js_always_exits(&case.cons))
+                && switch.cases.iter().all(|case| case.cons.is_empty() || js_always_exits(&case.cons))
+        }
+        // `while (true)` only leaves through its body
+        Stmt::While(while_stmt) => matches!(&*while_stmt.test, swc_ast::Expr::Lit(swc_ast::Lit::Bool(value)) if value.value),
+        Stmt::For(for_stmt) => for_stmt.test.is_none(),
+        Stmt::Labeled(labeled) => js_stmt_exits(&labeled.body),
+        _ => false,
+    }
+}
+
+fn python_collect(body: &[py_ast::Stmt], code: &str, found: &mut Vec<InconsistentReturn>) {
+    for stmt in body {
+        let function = match stmt {
+            py_ast::Stmt::FunctionDef(def) => Some((def.name.as_str(), def.body.as_slice(), def.range)),
+            py_ast::Stmt::AsyncFunctionDef(def) => Some((def.name.as_str(), def.body.as_slice(), def.range)),
+            _ => None,
+        };
+        if let Some((name, function_body, range)) = function {
+            found.extend(python_inconsistency(name, function_body, range, code));
+        }
+        for block in python_child_blocks(stmt) {
+            python_collect(block, code, found);
+        }
+    }
+}
+
+fn python_inconsistency(name: &str, body: &[py_ast::Stmt], range: rustpython_parser::text_size::TextRange, code: &str) -> Option<InconsistentReturn> {
+    if python_yields(body) {
+        return None;
+    }
+    let mut returns = Vec::new();
+    python_returns(body, code, &mut returns);
+    if !python_always_exits(body) {
+        let last = body.last().map_or(range.end(), |stmt| stmt.end());
+        returns.push(ReturnSite { shape: ReturnShape::FallsOff, line_number: line_at(code, last.to_usize().saturating_sub(1)) });
+    }
+    conflicting(&returns).then(|| InconsistentReturn {
+        function: name.to_string(),
+        line_number: line_at(code, range.start().to_usize()),
+        returns,
+    })
+}
+
+/// Statement blocks whose `return`s belong to the enclosing function
+fn python_own_blocks(stmt: &py_ast::Stmt) -> Vec<&[py_ast::Stmt]> {
+    match stmt {
+        py_ast::Stmt::FunctionDef(_) | py_ast::Stmt::AsyncFunctionDef(_) | py_ast::Stmt::ClassDef(_) => Vec::new(),
+        _ => python_child_blocks(stmt),
+    }
+}
+
+fn python_returns(body: &[py_ast::Stmt], code: &str, returns: &mut Vec<ReturnSite>) {
+    for stmt in body {
+        if let py_ast::Stmt::Return(ret) = stmt {
+            let shape = match ret.value.as_deref() {
+                None => ReturnShape::Nothing,
+                Some(py_ast::Expr::Constant(constant)) if constant.value.is_none() => ReturnShape::Null,
+                Some(py_ast::Expr::Dict(_) | py_ast::Expr::DictComp(_)) => ReturnShape::Object,
+                Some(py_ast::Expr::List(_) | py_ast::Expr::ListComp(_)) => ReturnShape::Array,
+                Some(_) => ReturnShape::Value,
+            };
+            returns.push(ReturnSite { shape, line_number: line_at(code, ret.range.start().to_usize()) });
+        }
+        for block in python_own_blocks(stmt) {
+            python_returns(block, code, returns);
+        }
+    }
+}
+
+fn python_yields(body: &[py_ast::Stmt]) -> bool {
+    let is_yield = |expr: &py_ast::Expr| matches!(expr, py_ast::Expr::Yield(_) | py_ast::Expr::YieldFrom(_));
+    body.iter().any(|stmt| match stmt {
+        py_ast::Stmt::Expr(expr) => is_yield(&expr.value),
+        py_ast::Stmt::Assign(assign) => is_yield(&assign.value),
+        _ => python_own_blocks(stmt).into_iter().any(python_yields),
+    })
+}
+
+/// Whether running `body` always ends in a `return` or `raise`
+fn python_always_exits(body: &[py_ast::Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        py_ast::Stmt::Return(_) | py_ast::Stmt::Raise(_) => true,
+        py_ast::Stmt::If(if_stmt) => python_always_exits(&if_stmt.body) && python_always_exits(&if_stmt.orelse),
+        py_ast::Stmt::With(with) => python_always_exits(&with.body),
+        py_ast::Stmt::AsyncWith(with) => python_always_exits(&with.body),
+        py_ast::Stmt::While(while_stmt) => matches!(&*while_stmt.test, py_ast::Expr::Constant(constant) if constant.value == py_ast::Constant::Bool(true)),
+        py_ast::Stmt::Try(try_stmt) => {
+            let handlers_exit = try_stmt.handlers.iter().all(|py_ast::ExceptHandler::ExceptHandler(handler)| python_always_exits(&handler.body));
+            python_always_exits(&try_stmt.finalbody)
+                || ((python_always_exits(&try_stmt.body) || python_always_exits(&try_stmt.orelse)) && handlers_exit)
+        }
+        _ => false,
+    })
+}
@@ -0,0 +1,150 @@
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+use crate::SourceLocation;
+
+/// Keywords whose next identifier is the name being declared
+const DECLARING_KEYWORDS: &[&str] = &[
+    "const", "let", "var", "mut", "function", "class", "def", "fn", "struct", "enum", "trait", "type", "interface", "static",
+];
+/// Keywords whose parenthesised parameters are declared along with the function
+const FUNCTION_KEYWORDS: &[&str] = &["function", "def", "fn"];
+
+/// `start..end` clamped to `code` and moved back onto char boundaries; a reversed range is swapped
+pub fn clamp_selection(code: &str, start: usize, end: usize) -> (usize, usize) {
+    let floor = |offset: usize| {
+        let mut offset = offset.min(code.len());
+        while !code.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+    let (start, end) = (floor(start), floor(end));
+    (start.min(end), start.max(end))
+}
+
+/// Source selected out of a larger file, and where it sits in that file
+pub struct Selection {
+    /// The selected source, dedented for Python so a method or nested block still parses on its own
+    pub source: String,
+    /// Lines in the file before the selection's first line
+    line_offset: u32,
+    /// Columns to add on the selection's first line, where it may start mid-line
+    first_line_columns: u32,
+    /// Columns to add on every line, for indentation removed by dedenting
+    dedent: u32,
+}
+
+impl Selection {
+    pub fn new(code: &str, start: usize, end: usize, language: &str) -> Self {
+        let selected = &code[start..end];
+        let line_start = code[..start].rfind('\n').map_or(0, |newline| newline + 1);
+        let line_offset = code[..start].matches('\n').count() as u32;
+        if !language.eq_ignore_ascii_case("python") {
+            let first_line_columns = code[line_start..start].chars().count() as u32;
+            return Selection { source: selected.to_string(), line_offset, first_line_columns, dedent: 0 };
+        }
+
+        // A selection starting mid-line keeps the indentation of that line
+        let lead = &code[line_start..start];
+        let lead: String = lead.chars().map(|c| if c.is_whitespace() { c } else { ' ' }).collect();
+        let lines: Vec<String> = format!("{}{}", lead, selected).lines().map(str::to_string).collect();
+
+        let indent = lines.iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let source = lines.iter()
+            .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Selection { source, line_offset, first_line_columns: 0, dedent: indent as u32 }
+    }
+
+    /// Move a location in the selected source to where it is in the whole file
+    pub fn relocate(&self, location: &mut SourceLocation) {
+        let column = |line: u32, column: u32| match line {
+            1 => column + self.first_line_columns + self.dedent,
+            _ => column + self.dedent,
+        };
+        location.start_column = column(location.start_line, location.start_column);
+        location.end_column = column(location.end_line, location.end_column);
+        location.start_line += self.line_offset;
+        location.end_line += self.line_offset;
+    }
+}
+
+/// Names the selection uses that are declared elsewhere in `code`, in order of first use. Names
+/// the selection declares itself shadow outer ones, and property accesses like `item.price` are
+/// not references to `price`
+pub fn outer_references(code: &str, start: usize, end: usize, language: &str) -> Vec<String> {
+    let tokens: Vec<Token> = Tokenizer::for_language(code, language)
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect();
+    let selected = |token: &Token| (start..end).contains(&token.offset);
+
+    let declarations = declared_names(&tokens);
+    let inner: Vec<&str> = declarations.iter().filter(|token| selected(token)).map(|token| token.text).collect();
+    let outer: Vec<&str> = declarations.iter().filter(|token| !selected(token)).map(|token| token.text).collect();
+
+    let mut references: Vec<String> = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let is_property = index > 0 && tokens[index - 1].text == ".";
+        if token.kind != TokenKind::Identifier || !selected(token) || is_property {
+            continue;
+        }
+        if outer.contains(&token.text) && !inner.contains(&token.text) && !references.iter().any(|name| name == token.text) {
+            references.push(token.text.to_string());
+        }
+    }
+    references
+}
+
+/// Identifier tokens that declare a name: those after a declaring keyword, on the left of a
+/// plain `=`, or in the parameter list of a function definition
+fn declared_names<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t Token<'a>> {
+    let mut declared = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Identifier {
+            continue;
+        }
+        let before = index.checked_sub(1).map(|before| &tokens[before]);
+        let after_keyword = before.is_some_and(|before| before.kind == TokenKind::Keyword && DECLARING_KEYWORDS.contains(&before.text));
+        let assigned = tokens.get(index + 1).is_some_and(|next| next.kind == TokenKind::Operator && next.text == "=")
+            && before.is_none_or(|before| before.text != ".");
+        if after_keyword || assigned {
+            declared.push(token);
+        }
+
+        // `function name(a, b)` declares `a` and `b` too
+        let defines_function = before.is_some_and(|before| FUNCTION_KEYWORDS.contains(&before.text));
+        if defines_function && tokens.get(index + 1).is_some_and(|next| next.text == "(") {
+            declared.extend(parameters(&tokens[index + 1..]));
+        }
+    }
+    declared
+}
+
+/// Parameter names in the parenthesised list opening `tokens`, skipping type annotations and defaults
+fn parameters<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t Token<'a>> {
+    let mut depth = 0;
+    let mut names = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.text {
+            "(" | "[" | "{" => depth += 1,
+            ")" | "]" | "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ if depth == 1 && token.kind == TokenKind::Identifier => {
+                let previous = tokens[index - 1].text;
+                if matches!(previous, "(" | "," | "*" | "**" | "...") {
+                    names.push(token);
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
@@ -0,0 +1,146 @@
+use crate::{AbortFlag, AnalysisOptions, CodingPattern, LanguageProfile, PatternAnalyzer};
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Thread-safe `PatternAnalyzer` for native embedding (e.g. a language server behind an `Arc`)
+pub struct SharedPatternAnalyzer {
+    inner: RwLock<PatternAnalyzer>,
+}
+
+impl SharedPatternAnalyzer {
+    pub fn new() -> Self {
+        Self::from_analyzer(PatternAnalyzer::new())
+    }
+
+    pub fn from_analyzer(analyzer: PatternAnalyzer) -> Self {
+        Self {
+            inner: RwLock::new(analyzer),
+        }
+    }
+
+    /// Analyze code and record the detected patterns. Parsing runs under the read lock, so
+    /// analyses of different files proceed in parallel; the write lock is only taken to merge
+    pub fn analyze_code(&self, code: &str, language: &str) -> Vec<CodingPattern> {
+        let options = AnalysisOptions::default();
+        let detected = self.read().detect_unrecorded(code, language, &options, &AbortFlag::default());
+        match detected {
+            Ok((language, mut analysis)) => {
+                self.write().record_detected(&language, &mut analysis, &options);
+                analysis.patterns
+            }
+            // Refused input records nothing, as with `PatternAnalyzer::analyze_code`
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Re-analyze only the changed regions of a file, parsing under the read lock like `analyze_code`
+    pub fn analyze_diff(&self, old_code: &str, new_code: &str, language: &str) -> Vec<CodingPattern> {
+        let diff = self.read().detect_diff(old_code, new_code, language);
+        self.write().record_diff(diff)
+    }
+
+    pub fn register_language_profile(&self, name: &str, profile: LanguageProfile) {
+        self.write().register_language_profile(name, profile);
+    }
+
+    pub fn get_pattern_count(&self) -> usize {
+        self.read().get_pattern_count()
+    }
+
+    pub fn get_patterns_by_language(&self, language: &str) -> Vec<CodingPattern> {
+        self.read().get_patterns_by_language(language)
+    }
+
+    /// Number of analyses run per language
+    pub fn get_language_stats(&self) -> HashMap<String, u32> {
+        self.read().language_stats.clone()
+    }
+
+    /// Run a read-only query against the underlying analyzer
+    pub fn with_analyzer<T>(&self, query: impl FnOnce(&PatternAnalyzer) -> T) -> T {
+        query(&self.read())
+    }
+
+    // A panic in one editor thread shouldn't take the analyzer down for the others
+    fn read(&self) -> RwLockReadGuard<'_, PatternAnalyzer> {
+        self.inner.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, PatternAnalyzer> {
+        self.inner.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for SharedPatternAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, OnceLock};
+    use std::thread;
+
+    #[test]
+    fn test_shared_analyzer_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedPatternAnalyzer>();
+    }
+
+    #[test]
+    fn test_detection_leaves_the_analyzer_readable() {
+        let slot: Arc<OnceLock<Arc<SharedPatternAnalyzer>>> = Arc::new(OnceLock::new());
+        let readable = Arc::new(AtomicBool::new(false));
+        let mut analyzer = PatternAnalyzer::new();
+        let (handle, seen) = (Arc::clone(&slot), Arc::clone(&readable));
+        analyzer.register_custom_predicate("probe", move |_| {
+            // Runs mid-detection; a writer would lock other readers out
+            seen.store(handle.get().unwrap().inner.try_read().is_ok(), Ordering::SeqCst);
+            true
+        });
+
+        let shared = Arc::new(SharedPatternAnalyzer::from_analyzer(analyzer));
+        slot.set(Arc::clone(&shared)).ok().unwrap();
+        shared.analyze_code("fn main() {}", "rust");
+        assert!(readable.load(Ordering::SeqCst));
+        assert_eq!(shared.get_pattern_count(), 2);
+        assert_eq!(shared.get_language_stats()["rust"], 1);
+    }
+
+    #[test]
+    fn test_concurrent_analysis_from_multiple_threads() {
+        let analyzer = Arc::new(SharedPatternAnalyzer::new());
+        let files = [
+            ("rust", "fn main() {}\nstruct Point { x: i32 }"),
+            ("python", "def main():\n    pass\n"),
+            ("javascript", "function main() { return 1; }"),
+        ];
+
+        let handles: Vec<_> = (0..6)
+            .map(|i| {
+                let analyzer = Arc::clone(&analyzer);
+                let (language, code) = files[i % files.len()];
+                thread::spawn(move || {
+                    for _ in 0..5 {
+                        analyzer.analyze_code(code, language);
+                        assert!(analyzer.get_pattern_count() > 0);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let stats = analyzer.get_language_stats();
+        assert_eq!(stats["rust"], 10);
+        assert_eq!(stats["python"], 10);
+        assert_eq!(stats["javascript"], 10);
+        assert_eq!(analyzer.get_patterns_by_language("rust").len(), 20);
+        assert_eq!(analyzer.with_analyzer(|a| a.get_pattern_count()), 40);
+    }
+}
@@ -1,14 +1,33 @@
-use crate::pattern_extractor::{StyleMetrics, PatternExtractor};
+use crate::pattern_extractor::{StyleMetrics, PatternAnalysis, PatternExtractor, IndentationType, BraceStyle, prefers_spaced_operators};
+use crate::local_storage_manager::{StyleViolation, Severity, StyleSuggestion, TextEdit};
 use crate::ast_parser::{ParsedAst, AstParser};
+use crate::comment_scanner::{CommentStats, comment_text_by_line, string_literals};
+use crate::error::AnalysisError;
+use crate::variable_naming_recognizer::Reliability;
+use crate::tokenizer::{SpacedToken, Token, Tokenizer, TokenKind, spaced_tokens};
+use serde::Serialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use regex::Regex;
 use lazy_static::lazy_static;
 
 /// Advanced coding style analyzer that provides detailed insights into coding preferences
 pub struct CodingStyleAnalyzer {
     pattern_extractor: PatternExtractor,
+    max_input_bytes: Option<usize>,
+    normalize_line_endings: bool,
+    parse_count: AtomicU32,
+    tab_width: u32,
+    allowed_numbers: Vec<f64>,
 }
 
+/// Columns a tab advances to when measuring line length
+pub const DEFAULT_TAB_WIDTH: u32 = 4;
+
+/// Numbers common enough that `find_magic_numbers` never flags them
+pub const DEFAULT_ALLOWED_NUMBERS: [f64; 3] = [0.0, 1.0, 2.0];
+
 #[derive(Debug, Clone)]
 pub struct DetailedStyleAnalysis {
     pub basic_metrics: StyleMetrics,
@@ -16,6 +35,65 @@ pub struct DetailedStyleAnalysis {
     pub formatting_preferences: FormattingPreferences,
     pub language_specific_patterns: LanguageSpecificPatterns,
     pub consistency_scores: ConsistencyScores,
+    pub debt_markers: DebtMarkers,
+    pub async_style_violations: Vec<StyleViolation>,
+}
+
+/// `TODO`, `FIXME`, `HACK` and `XXX` markers found in comments
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DebtMarkers {
+    pub counts: HashMap<String, u32>, // marker -> occurrences
+    pub locations: Vec<DebtMarker>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebtMarker {
+    pub marker: String,
+    pub line_number: u32,
+}
+
+/// A user-facing string literal not passed through a translation function
+#[derive(Debug, Clone, PartialEq)]
+pub struct HardcodedString {
+    pub text: String,
+    pub line_number: u32,
+}
+
+/// An imported name that nothing else in the file refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnusedImport {
+    pub name: String, // the local binding, i.e. the alias when renamed
+    pub module: String,
+    pub line_number: u32,
+}
+
+impl UnusedImport {
+    pub fn to_violation(&self) -> StyleViolation {
+        StyleViolation {
+            violation_type: "unused_import".to_string(),
+            line_number: self.line_number,
+            severity: Severity::Warning,
+            description: format!("`{}` is imported from `{}` but never used", self.name, self.module),
+        }
+    }
+}
+
+/// A numeric literal used inline where a named constant would say what it means
+#[derive(Debug, Clone, PartialEq)]
+pub struct MagicNumber {
+    pub text: String,
+    pub line_number: u32,
+}
+
+impl MagicNumber {
+    pub fn to_violation(&self) -> StyleViolation {
+        StyleViolation {
+            violation_type: "magic_number".to_string(),
+            line_number: self.line_number,
+            severity: Severity::Info,
+            description: format!("Extract `{}` into a named constant", self.text),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -90,12 +168,46 @@ pub struct DirectoryOrganization {
 
 #[derive(Debug, Clone)]
 pub struct FormattingPreferences {
+    pub line_ending: LineEnding, // dominant ending in the original source
     pub line_length_stats: LineLengthStats,
     pub spacing_preferences: SpacingPreferences,
     pub comment_style_preferences: CommentStylePreferences,
     pub import_organization: ImportOrganizationStyle,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    /// Most common ending in `code`; LF when there are no line breaks or on a tie
+    pub fn detect(code: &str) -> LineEnding {
+        let crlf = code.matches("\r\n").count();
+        let cr = code.matches('\r').count() - crlf;
+        let lf = code.matches('\n').count() - crlf;
+
+        if crlf > lf && crlf >= cr {
+            LineEnding::CrLf
+        } else if cr > lf && cr > crlf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+/// `code` with every CRLF and lone CR turned into LF, borrowed when already LF-only
+pub fn normalize_line_endings(code: &str) -> Cow<'_, str> {
+    if code.contains('\r') {
+        Cow::Owned(code.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(code)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LineLengthStats {
     pub average_length: f32,
@@ -271,43 +383,398 @@ pub enum RustTraitStyle {
     Mixed,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ConsistencyScores {
-    pub naming_consistency: f32,
-    pub formatting_consistency: f32,
-    pub style_consistency: f32,
-    pub overall_consistency: f32,
+    pub naming_consistency: f32,     // share of multi-word lowercase names in the dominant camelCase or snake_case
+    pub formatting_consistency: f32, // share of non-blank lines without an indentation violation
+    pub style_consistency: f32,      // `PatternAnalysis::consistency_score`
+    pub overall_consistency: f32,    // mean of the three above
+    pub sample_size: usize, // distinct identifiers in the analyzed code
+    pub reliability: Reliability,
 }
 
 lazy_static! {
     // Enhanced regex patterns for detailed analysis
     static ref FUNCTION_VERB_PATTERNS: Regex = Regex::new(r"^(create|make|build|generate|add|insert|remove|delete|update|modify|get|fetch|retrieve|find|search|calculate|compute|process|handle|manage|execute|run|start|stop|parse|validate|check|test|render|draw|save|load|open|close|connect|disconnect)").unwrap();
     static ref SEMANTIC_VARIABLE_PATTERNS: Regex = Regex::new(r"(count|total|sum|max|min|avg|list|array|map|dict|config|settings|options|params|args|result|response|request|data|info|details|status|state|flag|enabled|disabled)").unwrap();
-    static ref COMMENT_DENSITY: Regex = Regex::new(r#"(//|#|--|/\*|""")"#).unwrap();
     static ref IMPORT_PATTERNS: Regex = Regex::new(r"^(import|from|require|use|include)").unwrap();
+    static ref IMPORT_ALIAS: Regex = Regex::new(r"(\*|[A-Za-z_][\w.:]*)\s+as\s+([A-Za-z_$][\w$]*)").unwrap();
+
+    // Asynchronous control flow in JavaScript/TypeScript
+    static ref AWAIT_USAGE: Regex = Regex::new(r"\bawait\b").unwrap();
+    static ref PROMISE_USAGE: Regex = Regex::new(r"\.then\s*\(|\.catch\s*\(|\bnew\s+Promise\b|\bPromise\.(?:all|race|any|allSettled|resolve|reject)\b").unwrap();
+    static ref PROMISE_THEN: Regex = Regex::new(r"\.then\s*\(").unwrap();
+    static ref DEBT_MARKER: Regex = Regex::new(r"\b(TODO|FIXME|HACK|XXX)\b").unwrap();
+    // Literal prose: a capitalized word, or several words, and no path, URL or format syntax
+    static ref USER_FACING_TEXT: Regex = Regex::new(r"^\s*(?:[A-Z][a-z]{2,}[.!?:]?|[A-Za-z][\w',.!?-]*(?:\s+[\w',.!?:()-]+)+)\s*$").unwrap();
+    static ref TRANSLATION_CALL: Regex = Regex::new(r"(?:\b(?:t|_|_t|gettext|ngettext|pgettext|tr|translate|i18n\.t|intl\.formatMessage)|\$t)\s*\(\s*(?:\w+\s*,\s*)?$").unwrap();
+    static ref DEVELOPER_OUTPUT: Regex = Regex::new(r"\b(?:console|logger|logging|log)\s*\.\s*\w+\s*\(|\b(?:debug|trace|info|warn|error)!\s*\(|\b(?:assert\w*|require|import|from)\b|\buse\s").unwrap();
+    // Lines that already name their number: `const TAX_RATE = 0.08`, `static LIMIT: u32 = 64`, `#define MAX 3`
+    static ref CONSTANT_DECLARATION: Regex = Regex::new(r"\b[A-Z][A-Z0-9_]*\s*(?::|=[^=])|^\s*#define\b").unwrap();
+    static ref LOOP_HEADER: Regex = Regex::new(r"^\s*(?:for|while)\b").unwrap();
+    static ref NODE_CALLBACK: Regex = Regex::new(r"(?:function\s*)?\(\s*err(?:or)?\s*,").unwrap();
 }
 
 impl CodingStyleAnalyzer {
     pub fn new() -> Self {
         Self {
             pattern_extractor: PatternExtractor::new(),
+            max_input_bytes: None,
+            normalize_line_endings: true,
+            parse_count: AtomicU32::new(0),
+            tab_width: DEFAULT_TAB_WIDTH,
+            allowed_numbers: DEFAULT_ALLOWED_NUMBERS.to_vec(),
+        }
+    }
+
+    /// Replace the numbers `find_magic_numbers` accepts inline; ones written with a minus sign match by magnitude
+    pub fn with_allowed_numbers(mut self, numbers: &[f64]) -> Self {
+        self.allowed_numbers = numbers.to_vec();
+        self
+    }
+
+    /// Measure line lengths with tab stops every `width` columns
+    pub fn with_tab_width(mut self, width: u32) -> Self {
+        self.tab_width = width.max(1);
+        self
+    }
+
+    /// Number of times source has been handed to the AST parser
+    pub fn parse_count(&self) -> u32 {
+        self.parse_count.load(Ordering::Relaxed)
+    }
+
+    /// Just the `StyleMetrics`, from a single pass over the text. Skips parsing, naming and
+    /// language-specific analysis, so it also works on code that does not parse
+    pub fn quick_metrics(&self, code: &str, language: &str) -> StyleMetrics {
+        let code = if self.normalize_line_endings { normalize_line_endings(code) } else { Cow::Borrowed(code) };
+        self.pattern_extractor.analyze_style_metrics(&code, language)
+    }
+
+    /// Analyze CRLF and CR files as written instead of converting them to LF first
+    pub fn without_line_ending_normalization(mut self) -> Self {
+        self.normalize_line_endings = false;
+        self
+    }
+
+    /// Refuse input larger than `limit` bytes instead of parsing it
+    pub fn with_max_input_bytes(limit: usize) -> Self {
+        Self {
+            max_input_bytes: Some(limit),
+            ..Self::new()
         }
     }
 
     /// Perform comprehensive style analysis on source code
-    pub fn analyze_style(&self, code: &str, language: &str) -> Result<DetailedStyleAnalysis, String> {
+    pub fn analyze_style(&self, code: &str, language: &str) -> Result<DetailedStyleAnalysis, AnalysisError> {
+        if let Some(limit) = self.max_input_bytes {
+            AnalysisError::check_input_size(code, limit)?;
+        }
+        let line_ending = LineEnding::detect(code);
+        // A trailing `\r` would otherwise count toward line lengths and confuse lone-CR files
+        let code = if self.normalize_line_endings { normalize_line_endings(code) } else { Cow::Borrowed(code) };
+        let code = code.as_ref();
+        self.parse_count.fetch_add(1, Ordering::Relaxed);
         let basic_analysis = self.pattern_extractor.extract_patterns(code, language)?;
-        
+
+        let consistency_scores = self.calculate_consistency_scores(code, language, &basic_analysis);
+
         Ok(DetailedStyleAnalysis {
             basic_metrics: basic_analysis.style_metrics,
             naming_analysis: self.analyze_detailed_naming(code, language)?,
-            formatting_preferences: self.analyze_formatting_preferences(code, language),
+            formatting_preferences: self.analyze_formatting_preferences(code, language, line_ending),
             language_specific_patterns: self.analyze_language_specific_patterns(code, language),
-            consistency_scores: self.calculate_consistency_scores(code, language),
+            consistency_scores,
+            debt_markers: self.find_debt_markers(code, language),
+            async_style_violations: self.find_async_style_violations(code, language),
         })
     }
 
-    fn analyze_detailed_naming(&self, code: &str, language: &str) -> Result<DetailedNamingAnalysis, String> {
+    /// Locate technical-debt markers in comments; markers inside string literals are ignored
+    pub fn find_debt_markers(&self, code: &str, language: &str) -> DebtMarkers {
+        let mut markers = DebtMarkers::default();
+        for (index, comment) in comment_text_by_line(code, language).iter().enumerate() {
+            for found in DEBT_MARKER.find_iter(comment) {
+                *markers.counts.entry(found.as_str().to_string()).or_insert(0) += 1;
+                markers.locations.push(DebtMarker { marker: found.as_str().to_string(), line_number: index as u32 + 1 });
+            }
+        }
+        markers
+    }
+
+    /// Report lines whose indentation breaks the file's detected preference
+    pub fn find_indentation_violations(&self, code: &str, metrics: &StyleMetrics) -> Vec<StyleViolation> {
+        let mut violations = Vec::new();
+
+        for (index, line) in code.lines().enumerate() {
+            let content = line.trim_start_matches([' ', '\t']);
+            // Blank lines and block-comment continuations (` * ...`) carry no indentation intent
+            if content.is_empty() || content.starts_with('*') {
+                continue;
+            }
+
+            let indent = &line[..line.len() - content.len()];
+            let has_tabs = indent.contains('\t');
+            let has_spaces = indent.contains(' ');
+            let line_number = index as u32 + 1;
+
+            let violation = if has_tabs && has_spaces {
+                Some(("mixed_indentation", Severity::Warning, "Line mixes tabs and spaces in its indentation".to_string()))
+            } else if has_tabs && matches!(metrics.indentation_type, IndentationType::Spaces) {
+                Some(("tab_indentation", Severity::Warning, "Line is indented with tabs, the file uses spaces".to_string()))
+            } else if has_spaces && matches!(metrics.indentation_type, IndentationType::Tabs) {
+                Some(("space_indentation", Severity::Warning, "Line is indented with spaces, the file uses tabs".to_string()))
+            } else if has_spaces
+                && matches!(metrics.indentation_type, IndentationType::Spaces)
+                && metrics.indentation_size > 0
+                && !(indent.len() as u32).is_multiple_of(metrics.indentation_size)
+            {
+                Some((
+                    "indentation_width",
+                    Severity::Info,
+                    format!("Indented by {} spaces, not a multiple of {}", indent.len(), metrics.indentation_size),
+                ))
+            } else {
+                None
+            };
+
+            if let Some((violation_type, severity, description)) = violation {
+                violations.push(StyleViolation {
+                    violation_type: violation_type.to_string(),
+                    line_number,
+                    severity,
+                    description,
+                });
+            }
+        }
+
+        violations
+    }
+
+    /// Imported names never referenced outside import statements, for JavaScript, TypeScript,
+    /// Python and Rust. Side-effect imports, wildcards, `pub use` and `x as x` re-exports are
+    /// intentional and never reported; Python names listed in `__all__` count as used. Rust traits
+    /// imported only for their methods look unused to this scan
+    pub fn find_unused_imports(&self, code: &str, language: &str) -> Vec<UnusedImport> {
+        let statements = import_statements(code);
+        let in_import = |line: u32| statements.iter().any(|statement| (statement.first_line..=statement.last_line).contains(&line));
+        let exports_by_name = code.contains("__all__");
+
+        let mut references: HashMap<&str, usize> = HashMap::new();
+        for token in Tokenizer::for_language(code, language).filter(|token| !in_import(token.line)) {
+            let name = match token.kind {
+                TokenKind::Identifier | TokenKind::Keyword => token.text,
+                TokenKind::StringLiteral if exports_by_name => token.text.trim_matches(['\'', '"']),
+                _ => continue,
+            };
+            *references.entry(name).or_insert(0) += 1;
+        }
+
+        statements.iter()
+            .flat_map(|statement| {
+                imported_bindings(&statement.text, language)
+                    .into_iter()
+                    .map(|(name, module)| UnusedImport { name, module, line_number: statement.first_line })
+            })
+            .filter(|import| !references.contains_key(import.name.as_str()))
+            .collect()
+    }
+
+    /// User-facing string literals that are not wrapped in a translation call such as `t("...")`
+    /// or `gettext(...)`. Imports, logging, object keys and identifier-like tokens are skipped
+    pub fn find_hardcoded_strings(&self, code: &str, language: &str) -> Vec<HardcodedString> {
+        let lines: Vec<&str> = code.lines().collect();
+        string_literals(code, language)
+            .into_iter()
+            .filter(|literal| USER_FACING_TEXT.is_match(&literal.text))
+            .filter(|literal| {
+                let line = lines.get(literal.line_number as usize - 1).copied().unwrap_or_default();
+                let before: String = line.chars().take(literal.column).collect();
+                let after: String = line.chars().skip(literal.column + literal.text.chars().count() + 2).collect();
+                let is_key = after.trim_start().starts_with(':') || before.trim_end().ends_with('[');
+                !is_key && !TRANSLATION_CALL.is_match(&before) && !DEVELOPER_OUTPUT.is_match(&before)
+            })
+            .map(|literal| HardcodedString { text: literal.text, line_number: literal.line_number })
+            .collect()
+    }
+
+    /// Numeric literals outside the allowlist used inline in expressions. Array indices, loop headers
+    /// and constant declarations are skipped, as are numbers in comments and strings
+    pub fn find_magic_numbers(&self, code: &str, language: &str) -> Vec<MagicNumber> {
+        let lines: Vec<&str> = code.lines().collect();
+        let tokens: Vec<_> = Tokenizer::for_language(code, language).filter(|token| token.kind != TokenKind::Comment).collect();
+
+        tokens.iter().enumerate()
+            .filter(|(_, token)| token.kind == TokenKind::Number)
+            .filter_map(|(index, token)| {
+                let value = numeric_value(token.text)?;
+                let line = lines.get(token.line as usize - 1).copied().unwrap_or_default();
+                let is_index = index > 0 && tokens[index - 1].text == "[" && tokens.get(index + 1).is_some_and(|next| next.text == "]");
+                let skipped = is_index
+                    || LOOP_HEADER.is_match(line)
+                    || CONSTANT_DECLARATION.is_match(line)
+                    || self.allowed_numbers.iter().any(|allowed| (allowed.abs() - value).abs() < f64::EPSILON);
+                (!skipped).then(|| MagicNumber { text: token.text.to_string(), line_number: token.line })
+            })
+            .collect()
+    }
+
+    /// Report `.then()` chains in a file that also uses `await`, a sign of a half-finished
+    /// migration to async/await. Files using only one style are left alone; comments and strings
+    /// never count as either
+    pub fn find_async_style_violations(&self, code: &str, language: &str) -> Vec<StyleViolation> {
+        let tokens: Vec<Token> = Tokenizer::for_language(code, language)
+            .filter(|token| token.kind != TokenKind::Comment)
+            .collect();
+        if !tokens.iter().any(|token| token.text == "await") {
+            return Vec::new();
+        }
+
+        let mut then_lines: Vec<u32> = tokens
+            .windows(3)
+            .filter(|window| window[0].text == "." && window[1].text == "then" && window[2].text == "(")
+            .map(|window| window[1].line)
+            .collect();
+        then_lines.dedup();
+
+        then_lines
+            .into_iter()
+            .map(|line_number| StyleViolation {
+                violation_type: "promise_then_chain".to_string(),
+                line_number,
+                severity: Severity::Warning,
+                description: "`.then()` chain in a file that otherwise uses async/await".to_string(),
+            })
+            .collect()
+    }
+
+    /// Concrete fixes that bring brace placement and indentation in line with the file's dominant style
+    pub fn suggest_style_fixes(&self, code: &str, metrics: &StyleMetrics) -> Vec<StyleSuggestion> {
+        let lines: Vec<&str> = code.lines().collect();
+        let mut suggestions = self.brace_style_fixes(&lines, &metrics.brace_style);
+
+        for violation in self.find_indentation_violations(code, metrics) {
+            let line = lines[violation.line_number as usize - 1];
+            let Some(replacement) = normalize_indentation(line, metrics) else {
+                continue;
+            };
+            // Rounding an odd width guesses at intent; swapping tabs for spaces doesn't
+            let auto_applicable = violation.violation_type != "indentation_width";
+            suggestions.push(StyleSuggestion {
+                suggestion_id: format!("style_fix_{}_{}", violation.line_number, violation.violation_type),
+                suggestion_type: "indentation".to_string(),
+                description: violation.description,
+                confidence: if auto_applicable { 0.9 } else { 0.6 },
+                auto_applicable,
+                fix: Some(TextEdit {
+                    start_line: violation.line_number,
+                    end_line: violation.line_number,
+                    original: line.to_string(),
+                    replacement,
+                }),
+            });
+        }
+
+        suggestions.sort_by_key(|suggestion| suggestion.fix.as_ref().map(|fix| fix.start_line));
+        suggestions
+    }
+
+    /// Line edits switching JavaScript, TypeScript and Python strings to the file's dominant quote.
+    /// Strings containing the dominant quote are left alone, since converting them would need new
+    /// escapes; triple-quoted strings and template literals are never touched
+    pub fn suggest_quote_fixes(&self, code: &str, language: &str) -> Vec<StyleSuggestion> {
+        let strings = quoted_strings(code, language);
+        let Some(target) = dominant_quote(&strings) else {
+            return Vec::new();
+        };
+
+        let mut line_starts = vec![0];
+        line_starts.extend(code.match_indices('\n').map(|(index, _)| index + 1));
+
+        let requoted: Vec<(&QuotedString, String)> = strings.iter()
+            .filter(|string| string.quote != target)
+            .filter_map(|string| Some((string, string.requoted(target)?)))
+            .collect();
+
+        let lines: Vec<&str> = code.lines().collect();
+        let quote_name = if target == '"' { "double" } else { "single" };
+        requoted.chunk_by(|(a, _), (b, _)| a.token.line == b.token.line).map(|edits| {
+            let line_number = edits[0].0.token.line;
+            let line = lines[line_number as usize - 1];
+            let mut replacement = line.to_string();
+            // Right to left, so earlier byte offsets stay valid
+            for (string, requoted) in edits.iter().rev() {
+                let start = string.token.offset - line_starts[line_number as usize - 1];
+                replacement.replace_range(start..start + string.token.text.len(), requoted);
+            }
+            StyleSuggestion {
+                suggestion_id: format!("style_fix_{}_quote_style", line_number),
+                suggestion_type: "quote_style".to_string(),
+                description: format!("Use {} quotes, as in the rest of the file", quote_name),
+                confidence: 0.9,
+                auto_applicable: true,
+                fix: Some(TextEdit {
+                    start_line: line_number,
+                    end_line: line_number,
+                    original: line.to_string(),
+                    replacement,
+                }),
+            }
+        }).collect()
+    }
+
+    fn brace_style_fixes(&self, lines: &[&str], dominant: &BraceStyle) -> Vec<StyleSuggestion> {
+        let mut suggestions = Vec::new();
+
+        for (index, window) in lines.windows(2).enumerate() {
+            let (current, next) = (window[0], window[1]);
+            let trimmed = current.trim();
+            let line_number = index as u32 + 1;
+            // A trailing comment would swallow a brace moved onto its line
+            let has_comment = trimmed.contains("//") || trimmed.contains("/*");
+
+            let fix = match dominant {
+                BraceStyle::SameLine if next.trim() == "{" && !trimmed.is_empty() && !trimmed.ends_with('{') => Some((
+                    "Move the opening brace onto the preceding line",
+                    TextEdit {
+                        start_line: line_number,
+                        end_line: line_number + 1,
+                        original: format!("{}\n{}", current, next),
+                        replacement: format!("{} {{", current.trim_end()),
+                    },
+                )),
+                BraceStyle::NextLine if trimmed.ends_with('{') && opens_block(trimmed) && !has_comment => {
+                    let indent = &current[..current.len() - current.trim_start().len()];
+                    Some((
+                        "Move the opening brace onto its own line",
+                        TextEdit {
+                            start_line: line_number,
+                            end_line: line_number,
+                            original: current.to_string(),
+                            replacement: format!("{}\n{}{{", current.trim_end().trim_end_matches('{').trim_end(), indent),
+                        },
+                    ))
+                },
+                _ => None,
+            };
+
+            if let Some((description, edit)) = fix {
+                suggestions.push(StyleSuggestion {
+                    suggestion_id: format!("style_fix_{}_brace_style", line_number),
+                    suggestion_type: "brace_style".to_string(),
+                    description: description.to_string(),
+                    confidence: if has_comment { 0.6 } else { 0.9 },
+                    auto_applicable: !has_comment,
+                    fix: Some(edit),
+                });
+            }
+        }
+
+        suggestions
+    }
+
+    fn analyze_detailed_naming(&self, code: &str, language: &str) -> Result<DetailedNamingAnalysis, AnalysisError> {
+        self.parse_count.fetch_add(1, Ordering::Relaxed);
         let ast = AstParser::new().parse_code(code, language)?;
         
         Ok(DetailedNamingAnalysis {
@@ -381,19 +848,20 @@ impl CodingStyleAnalyzer {
         }
     }
 
-    fn analyze_formatting_preferences(&self, code: &str, _language: &str) -> FormattingPreferences {
+    fn analyze_formatting_preferences(&self, code: &str, language: &str, line_ending: LineEnding) -> FormattingPreferences {
         FormattingPreferences {
+            line_ending,
             line_length_stats: self.calculate_line_length_stats(code),
-            spacing_preferences: self.analyze_spacing_preferences(code),
-            comment_style_preferences: self.analyze_comment_style(code),
+            spacing_preferences: self.analyze_spacing_preferences(code, language),
+            comment_style_preferences: self.analyze_comment_style(code, language),
             import_organization: self.analyze_import_organization(code),
         }
     }
 
     fn calculate_line_length_stats(&self, code: &str) -> LineLengthStats {
         let lines: Vec<&str> = code.lines().collect();
-        let lengths: Vec<u32> = lines.iter().map(|line| line.len() as u32).collect();
-        
+        let lengths: Vec<u32> = lines.iter().map(|line| visual_width(line, self.tab_width)).collect();
+
         if lengths.is_empty() {
             return LineLengthStats {
                 average_length: 0.0,
@@ -423,83 +891,63 @@ impl CodingStyleAnalyzer {
         }
     }
 
-    fn analyze_spacing_preferences(&self, code: &str) -> SpacingPreferences {
+    fn analyze_spacing_preferences(&self, code: &str, language: &str) -> SpacingPreferences {
+        let tokens = spaced_tokens(code, language);
         SpacingPreferences {
-            around_operators: self.detect_space_around_operators(code),
-            around_keywords: self.detect_space_around_keywords(code),
-            around_commas: self.detect_space_around_commas(code),
-            around_semicolons: self.detect_space_around_semicolons(code),
-            inside_parentheses: self.detect_space_inside_parentheses(code),
-            inside_brackets: self.detect_space_inside_brackets(code),
-            inside_braces: self.detect_space_inside_braces(code),
+            around_operators: self.detect_space_around_operators(&tokens),
+            around_keywords: self.detect_space_around_keywords(&tokens),
+            around_commas: self.detect_space_after(&tokens, ","),
+            around_semicolons: self.detect_space_after(&tokens, ";"),
+            inside_parentheses: self.detect_space_inside(&tokens, "(", ")"),
+            inside_brackets: self.detect_space_inside(&tokens, "[", "]"),
+            inside_braces: self.detect_space_inside(&tokens, "{", "}"),
         }
     }
 
-    fn detect_space_around_operators(&self, code: &str) -> bool {
-        let spaced_operators = Regex::new(r"\s[+\-*/=<>!]+\s").unwrap();
-        let unspaced_operators = Regex::new(r"[^\s][+\-*/=<>!]+[^\s]").unwrap();
-
-        let spaced_count = spaced_operators.find_iter(code).count();
-        let unspaced_count = unspaced_operators.find_iter(code).count();
-
-        spaced_count > unspaced_count
-    }
-
-    fn detect_space_around_keywords(&self, code: &str) -> bool {
-        let with_spaces = Regex::new(r"\s(if|for|while|switch|catch)\s*\(").unwrap();
-        let without_spaces = Regex::new(r"(if|for|while|switch|catch)\(").unwrap();
-        
-        let with_count = with_spaces.find_iter(code).count();
-        let without_count = without_spaces.find_iter(code).count();
-        
-        with_count > without_count
+    fn detect_space_around_operators(&self, tokens: &[SpacedToken]) -> bool {
+        prefers_spaced_operators(tokens)
     }
 
-    fn detect_space_around_commas(&self, code: &str) -> bool {
-        let with_spaces = Regex::new(r",\s").unwrap();
-        let without_spaces = Regex::new(r",[^\s]").unwrap();
-        
-        let with_count = with_spaces.find_iter(code).count();
-        let without_count = without_spaces.find_iter(code).count();
-        
-        with_count > without_count
-    }
+    fn detect_space_around_keywords(&self, tokens: &[SpacedToken]) -> bool {
+        let keyword_calls = tokens.windows(2)
+            .filter(|pair| matches!(pair[0].token.text, "if" | "for" | "while" | "switch" | "catch") && pair[1].token.text == "(");
+
+        let (mut with_count, mut without_count) = (0, 0);
+        for pair in keyword_calls {
+            if pair[0].space_after {
+                with_count += 1;
+            } else {
+                without_count += 1;
+            }
+        }
 
-    fn detect_space_around_semicolons(&self, code: &str) -> bool {
-        let with_spaces = Regex::new(r";\s").unwrap();
-        let without_spaces = Regex::new(r";[^\s\n]").unwrap();
-        
-        let with_count = with_spaces.find_iter(code).count();
-        let without_count = without_spaces.find_iter(code).count();
-        
         with_count > without_count
     }
 
-    fn detect_space_inside_parentheses(&self, code: &str) -> bool {
-        let with_spaces = Regex::new(r"\(\s|\s\)").unwrap();
-        with_spaces.is_match(code)
-    }
+    /// Whether `punctuation` is more often followed by whitespace than not
+    fn detect_space_after(&self, tokens: &[SpacedToken], punctuation: &str) -> bool {
+        let (with_count, without_count) = tokens.iter()
+            .filter(|spaced| spaced.token.kind == TokenKind::Punctuation && spaced.token.text == punctuation)
+            .fold((0, 0), |(with, without), spaced| {
+                if spaced.space_after { (with + 1, without) } else { (with, without + 1) }
+            });
 
-    fn detect_space_inside_brackets(&self, code: &str) -> bool {
-        let with_spaces = Regex::new(r"\[\s|\s\]").unwrap();
-        with_spaces.is_match(code)
+        with_count > without_count
     }
 
-    fn detect_space_inside_braces(&self, code: &str) -> bool {
-        let with_spaces = Regex::new(r"\{\s|\s\}").unwrap();
-        with_spaces.is_match(code)
+    /// Whether any `open` is followed, or `close` preceded, by whitespace
+    fn detect_space_inside(&self, tokens: &[SpacedToken], open: &str, close: &str) -> bool {
+        tokens.iter()
+            .filter(|spaced| spaced.token.kind == TokenKind::Punctuation)
+            .any(|spaced| (spaced.token.text == open && spaced.space_after) || (spaced.token.text == close && spaced.space_before))
     }
 
-    fn analyze_comment_style(&self, code: &str) -> CommentStylePreferences {
-        let lines = code.lines().collect::<Vec<_>>();
-        let comment_count = COMMENT_DENSITY.find_iter(code).count();
-        let comment_density = if lines.len() > 0 { comment_count as f32 / lines.len() as f32 } else { 0.0 };
-
+    fn analyze_comment_style(&self, code: &str, language: &str) -> CommentStylePreferences {
         CommentStylePreferences {
             single_line_style: self.detect_single_line_comment_style(code),
             multi_line_style: self.detect_multi_line_comment_style(code),
             documentation_style: self.detect_documentation_style(code),
-            comment_density,
+            comment_density: CommentStats::scan(code, language).ratio(),
         }
     }
 
@@ -547,22 +995,88 @@ impl CodingStyleAnalyzer {
         }
     }
 
-    fn detect_import_grouping_style(&self, _code: &str) -> ImportGroupingStyle {
-        // TODO: Implement import grouping detection
-        ImportGroupingStyle::ByType
+    fn detect_import_grouping_style(&self, code: &str) -> ImportGroupingStyle {
+        let groups = import_groups(code);
+        if groups.iter().map(Vec::len).sum::<usize>() < 2 {
+            return ImportGroupingStyle::None;
+        }
+
+        // By type: every blank-line group holds one class, and no class is split across groups
+        let group_classes: Vec<Option<ImportClass>> = groups.iter()
+            .map(|group| {
+                let first = group[0].class;
+                group.iter().all(|import| import.class == first).then_some(first)
+            })
+            .collect();
+        let mut seen = Vec::new();
+        let by_type = groups.len() > 1 && group_classes.iter().all(|class| match class {
+            Some(class) if !seen.contains(class) => {
+                seen.push(*class);
+                true
+            },
+            _ => false,
+        });
+
+        if by_type {
+            ImportGroupingStyle::ByType
+        } else if groups.len() == 1 && is_sorted_by_module(&groups[0]) {
+            ImportGroupingStyle::Alphabetical
+        } else {
+            ImportGroupingStyle::None
+        }
     }
 
-    fn detect_import_sorting_style(&self, _code: &str) -> ImportSortingStyle {
-        // TODO: Implement import sorting detection
-        ImportSortingStyle::Alphabetical
+    fn detect_import_sorting_style(&self, code: &str) -> ImportSortingStyle {
+        let groups = import_groups(code);
+        if groups.iter().all(|group| is_sorted_by_module(group)) {
+            ImportSortingStyle::Alphabetical
+        } else if groups.iter().all(|group| group.windows(2).all(|pair| pair[0].line_length <= pair[1].line_length)) {
+            ImportSortingStyle::ByLength
+        } else {
+            ImportSortingStyle::Custom
+        }
     }
 
-    fn analyze_alias_usage(&self, _code: &str) -> AliasUsagePatterns {
-        // TODO: Implement alias usage analysis
+    fn analyze_alias_usage(&self, code: &str) -> AliasUsagePatterns {
+        let mut common_alias_patterns = HashMap::new();
+        let mut shorter = 0;
+        let mut longer = 0;
+
+        for ImportStatement { text: statement, .. } in import_statements(code) {
+            for capture in IMPORT_ALIAS.captures_iter(&statement) {
+                let alias = capture[2].to_string();
+                // `import * as R from 'ramda'` aliases the module itself
+                let original = if &capture[1] == "*" {
+                    match statement.rsplit("from").next().and_then(|rest| rest.split(['\'', '"']).nth(1)) {
+                        Some(module) => module.to_string(),
+                        None => continue,
+                    }
+                } else {
+                    capture[1].to_string()
+                };
+
+                let name = original.rsplit(['.', ':', '/']).next().unwrap_or(&original);
+                if alias.len() < name.len() {
+                    shorter += 1;
+                } else if alias.len() > name.len() {
+                    longer += 1;
+                }
+                common_alias_patterns.insert(original, alias);
+            }
+        }
+
+        let alias_length_preference = if longer == 0 || shorter > longer * 2 {
+            AliasLengthStyle::Short
+        } else if shorter == 0 || longer > shorter * 2 {
+            AliasLengthStyle::Descriptive
+        } else {
+            AliasLengthStyle::Context
+        };
+
         AliasUsagePatterns {
-            uses_aliases: false,
-            common_alias_patterns: HashMap::new(),
-            alias_length_preference: AliasLengthStyle::Short,
+            uses_aliases: !common_alias_patterns.is_empty(),
+            common_alias_patterns,
+            alias_length_preference,
         }
     }
 
@@ -591,25 +1105,39 @@ impl CodingStyleAnalyzer {
         }
     }
 
-    fn analyze_javascript_patterns(&self, _code: &str) -> JavaScriptPatterns {
+    fn analyze_javascript_patterns(&self, code: &str) -> JavaScriptPatterns {
         // TODO: Implement JavaScript-specific pattern analysis
         JavaScriptPatterns {
             function_declaration_style: JSFunctionStyle::FunctionDeclaration,
             object_property_style: JSPropertyStyle::NoQuotes,
-            async_await_usage: AsyncAwaitUsage {
-                prefers_async_await: true,
-                uses_promises: true,
-                uses_callbacks: false,
-            },
+            async_await_usage: self.analyze_async_usage(code),
             module_system_preference: JSModuleSystem::ES6Modules,
         }
     }
 
-    fn analyze_python_patterns(&self, _code: &str) -> PythonPatterns {
-        // TODO: Implement Python-specific pattern analysis
+    fn analyze_async_usage(&self, code: &str) -> AsyncAwaitUsage {
+        let count_lines = |pattern: &Regex| code.lines().filter(|line| pattern.is_match(line)).count();
+        let awaits = count_lines(&AWAIT_USAGE);
+        let then_chains = count_lines(&PROMISE_THEN);
+
+        AsyncAwaitUsage {
+            prefers_async_await: awaits > 0 && awaits >= then_chains,
+            uses_promises: count_lines(&PROMISE_USAGE) > 0,
+            uses_callbacks: count_lines(&NODE_CALLBACK) > 0,
+        }
+    }
+
+    fn analyze_python_patterns(&self, code: &str) -> PythonPatterns {
+        let string_quote_preference = match dominant_quote(&quoted_strings(code, "python")) {
+            Some('\'') => PythonQuoteStyle::SingleQuotes,
+            Some(_) => PythonQuoteStyle::DoubleQuotes,
+            None => PythonQuoteStyle::Mixed,
+        };
+
+        // TODO: Implement the remaining Python-specific pattern analysis
         PythonPatterns {
             import_style: PythonImportStyle::IndividualImports,
-            string_quote_preference: PythonQuoteStyle::DoubleQuotes,
+            string_quote_preference,
             class_definition_style: PythonClassStyle::ModernClass,
             function_annotation_usage: true,
         }
@@ -625,13 +1153,42 @@ impl CodingStyleAnalyzer {
         }
     }
 
-    fn calculate_consistency_scores(&self, _code: &str, _language: &str) -> ConsistencyScores {
-        // TODO: Implement consistency scoring algorithms
+    fn calculate_consistency_scores(&self, code: &str, language: &str, analysis: &PatternAnalysis) -> ConsistencyScores {
+        let identifiers: std::collections::HashSet<&str> = Tokenizer::for_language(code, language)
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .map(|token| token.text)
+            .collect();
+        let sample_size = identifiers.len();
+
+        // PascalCase types and SCREAMING_SNAKE constants sit alongside either style, and one-word
+        // names fit both, so only multi-word names starting in lowercase take a side
+        let (mut camel, mut snake) = (0, 0);
+        for name in &identifiers {
+            let words = name.trim_matches('_');
+            if !words.starts_with(|c: char| c.is_ascii_lowercase()) {
+                continue;
+            }
+            match (words.contains('_'), words.contains(|c: char| c.is_ascii_uppercase())) {
+                (true, false) => snake += 1,
+                (false, true) => camel += 1,
+                _ => {}
+            }
+        }
+        let naming_consistency = if camel + snake == 0 { 1.0 } else { camel.max(snake) as f32 / (camel + snake) as f32 };
+
+        let content_lines = code.lines().filter(|line| !line.trim().is_empty()).count();
+        let violations = self.find_indentation_violations(code, &analysis.style_metrics).len();
+        let formatting_consistency = if content_lines == 0 { 1.0 } else { 1.0 - violations as f32 / content_lines as f32 };
+
+        let style_consistency = analysis.consistency_score();
+
         ConsistencyScores {
-            naming_consistency: 0.85,
-            formatting_consistency: 0.90,
-            style_consistency: 0.88,
-            overall_consistency: 0.87,
+            naming_consistency,
+            formatting_consistency,
+            style_consistency,
+            overall_consistency: (naming_consistency + formatting_consistency + style_consistency) / 3.0,
+            sample_size,
+            reliability: Reliability::from_sample_size(sample_size),
         }
     }
 }
@@ -642,6 +1199,346 @@ impl Default for CodingStyleAnalyzer {
     }
 }
 
+// `x = {`, `f({` and `key: {` open literals, not blocks
+fn opens_block(trimmed: &str) -> bool {
+    let before = trimmed.trim_end_matches('{').trim_end();
+    !before.is_empty() && !before.ends_with(['=', '(', ',', ':', '['])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImportClass {
+    Stdlib,
+    External,
+    Internal,
+}
+
+#[derive(Debug, Clone)]
+struct ImportLine {
+    module: String,
+    class: ImportClass,
+    line_length: usize,
+}
+
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "buffer", "child_process", "crypto", "events", "fs", "http", "https", "net", "os", "path", "readline",
+    "stream", "url", "util", "zlib",
+];
+
+const PYTHON_STDLIB: &[&str] = &[
+    "abc", "argparse", "asyncio", "collections", "contextlib", "copy", "csv", "dataclasses", "datetime", "enum",
+    "functools", "hashlib", "io", "itertools", "json", "logging", "math", "os", "pathlib", "random", "re", "shutil",
+    "string", "subprocess", "sys", "tempfile", "threading", "time", "typing", "unittest", "uuid",
+];
+
+/// The file's leading import block, split into blank-line separated groups
+fn import_groups(code: &str) -> Vec<Vec<ImportLine>> {
+    let mut groups: Vec<Vec<ImportLine>> = Vec::new();
+    let mut current = Vec::new();
+
+    for line in code.lines() {
+        let trimmed = line.trim();
+        if let Some(import) = parse_import_line(trimmed) {
+            current.push(import);
+        } else if trimmed.is_empty() {
+            if !current.is_empty() {
+                groups.push(std::mem::take(&mut current));
+            }
+        } else if trimmed.starts_with("//") || trimmed.starts_with('#') && !trimmed.starts_with("#include") {
+            continue;
+        } else if !groups.is_empty() || !current.is_empty() {
+            // First code line after the imports ends the block
+            break;
+        }
+    }
+
+    if !current.is_empty() {
+        groups.push(current);
+    }
+    groups
+}
+
+/// An import statement, with multi-line `{ ... }` lists joined onto one line
+struct ImportStatement {
+    text: String,
+    first_line: u32,
+    last_line: u32,
+}
+
+fn import_statements(code: &str) -> Vec<ImportStatement> {
+    let mut statements = Vec::new();
+    let mut pending: Option<ImportStatement> = None;
+
+    for (index, line) in code.lines().enumerate() {
+        let trimmed = line.trim();
+        let line_number = index as u32 + 1;
+        if let Some(statement) = pending.as_mut() {
+            statement.text.push(' ');
+            statement.text.push_str(trimmed);
+            statement.last_line = line_number;
+            if trimmed.contains('}') || trimmed.contains(')') {
+                statements.extend(pending.take());
+            }
+        } else if IMPORT_PATTERNS.is_match(trimmed) {
+            let statement = ImportStatement { text: trimmed.to_string(), first_line: line_number, last_line: line_number };
+            if trimmed.ends_with('{') || trimmed.ends_with('(') {
+                pending = Some(statement);
+            } else {
+                statements.push(statement);
+            }
+        }
+    }
+
+    statements
+}
+
+/// `(local name, module)` pairs an import statement binds. Side-effect imports such as
+/// `import './styles.css'`, wildcards and `__future__` imports bind nothing worth checking
+fn imported_bindings(statement: &str, language: &str) -> Vec<(String, String)> {
+    let statement = statement.trim_end_matches(';').trim();
+    let names = |list: &str| -> Vec<String> {
+        list.split(',')
+            .map(|item| item.trim().trim_start_matches("type ").trim())
+            .filter(|item| !item.is_empty() && *item != "*" && *item != "self")
+            .filter_map(|item| {
+                // `x as x` is the explicit re-export idiom; `_` keeps a trait in scope on purpose
+                match item.split_once(" as ") {
+                    Some((original, alias)) if original.trim().rsplit("::").next() == Some(alias.trim()) || alias.trim() == "_" => None,
+                    Some((_, alias)) => Some(alias.trim().to_string()),
+                    None => Some(item.rsplit("::").next().unwrap_or(item).to_string()),
+                }
+            })
+            .collect()
+    };
+
+    match language.to_lowercase().as_str() {
+        "javascript" | "typescript" => {
+            let Some(rest) = statement.strip_prefix("import ") else { return Vec::new() };
+            let Some((clause, source)) = rest.rsplit_once(" from ") else { return Vec::new() };
+            let module = source.trim().trim_matches(['\'', '"']).to_string();
+            let clause = clause.trim().trim_start_matches("type ").trim();
+
+            let (default_and_namespace, named) = match clause.split_once('{') {
+                Some((before, braces)) => (before, braces.trim_end_matches('}')),
+                None => (clause, ""),
+            };
+            let mut bindings = names(named);
+            for part in default_and_namespace.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+                match part.strip_prefix("* as ") {
+                    Some(namespace) => bindings.push(namespace.trim().to_string()),
+                    None => bindings.push(part.to_string()),
+                }
+            }
+            bindings.into_iter().map(|name| (name, module.clone())).collect()
+        },
+        "python" => {
+            if let Some(rest) = statement.strip_prefix("from ") {
+                let Some((module, imported)) = rest.split_once(" import ") else { return Vec::new() };
+                if module.trim() == "__future__" {
+                    return Vec::new();
+                }
+                let imported = imported.trim().trim_start_matches('(').trim_end_matches(')');
+                names(imported).into_iter().map(|name| (name, module.trim().to_string())).collect()
+            } else if let Some(rest) = statement.strip_prefix("import ") {
+                rest.split(',')
+                    .map(str::trim)
+                    .filter_map(|item| match item.split_once(" as ") {
+                        Some((module, alias)) if module.trim() != alias.trim() => Some((alias.trim().to_string(), module.trim().to_string())),
+                        Some(_) => None,
+                        // `import os.path` binds `os`
+                        None => Some((item.split('.').next().unwrap_or(item).to_string(), item.to_string())),
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        },
+        "rust" => {
+            let Some(path) = statement.strip_prefix("use ") else { return Vec::new() };
+            match path.split_once('{') {
+                Some((prefix, list)) => {
+                    let module = prefix.trim_end_matches("::").to_string();
+                    // Nested groups are flattened; each item binds its last path segment
+                    names(&list.replace(['{', '}'], ",")).into_iter().map(|name| (name, module.clone())).collect()
+                },
+                None => {
+                    let module = path.rsplit_once("::").map(|(module, _)| module).unwrap_or_default().to_string();
+                    names(path).into_iter().map(|name| (name, module.clone())).collect()
+                },
+            }
+        },
+        _ => Vec::new(),
+    }
+    .into_iter()
+    .filter(|(name, _)| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$'))
+    .collect()
+}
+
+fn parse_import_line(line: &str) -> Option<ImportLine> {
+    let quoted = |text: &str| text.split(['\'', '"']).nth(1).map(str::to_string);
+
+    let (module, class) = if let Some(rest) = line.strip_prefix("use ") {
+        let path = rest.trim_end_matches(';').trim().to_string();
+        let class = if ["std::", "core::", "alloc::"].iter().any(|p| path.starts_with(p)) {
+            ImportClass::Stdlib
+        } else if ["crate::", "self::", "super::"].iter().any(|p| path.starts_with(p)) {
+            ImportClass::Internal
+        } else {
+            ImportClass::External
+        };
+        (path, class)
+    } else if let Some(rest) = line.strip_prefix("#include") {
+        let rest = rest.trim();
+        let class = if rest.starts_with('<') { ImportClass::Stdlib } else { ImportClass::Internal };
+        (rest.trim_matches(['<', '>', '"']).to_string(), class)
+    } else if line.starts_with("import ") && (line.contains('\'') || line.contains('"')) || line.contains("require(") {
+        let module = quoted(line.rsplit("from").next().unwrap_or(line))?;
+        let bare = module.strip_prefix("node:").unwrap_or(&module);
+        let class = if module.starts_with('.') || module.starts_with('/') || module.starts_with("@/") || module.starts_with("~/") {
+            ImportClass::Internal
+        } else if module.starts_with("node:") || NODE_BUILTINS.contains(&bare.split('/').next().unwrap_or(bare)) {
+            ImportClass::Stdlib
+        } else {
+            ImportClass::External
+        };
+        (module, class)
+    } else if let Some(rest) = line.strip_prefix("from ").or_else(|| line.strip_prefix("import ")) {
+        let module = rest.split([' ', ',']).next()?.to_string();
+        let root = module.split('.').next().unwrap_or(&module);
+        let class = if module.starts_with('.') {
+            ImportClass::Internal
+        } else if PYTHON_STDLIB.contains(&root) {
+            ImportClass::Stdlib
+        } else {
+            ImportClass::External
+        };
+        (module, class)
+    } else {
+        return None;
+    };
+
+    Some(ImportLine { module, class, line_length: line.len() })
+}
+
+fn is_sorted_by_module(group: &[ImportLine]) -> bool {
+    group.windows(2).all(|pair| pair[0].module.to_lowercase() <= pair[1].module.to_lowercase())
+}
+
+/// A single-line string in `'` or `"` quotes, possibly with a prefix such as Python's `f`
+struct QuotedString<'a> {
+    token: Token<'a>,
+    prefix_len: usize,
+    quote: char,
+}
+
+impl QuotedString<'_> {
+    /// The same string in `target` quotes, or `None` when its contents hold `target` and would
+    /// need escaping. Escaped copies of the old quote lose their now-needless backslash
+    fn requoted(&self, target: char) -> Option<String> {
+        let (prefix, quoted) = self.token.text.split_at(self.prefix_len);
+        let body = &quoted[1..quoted.len() - 1];
+        if body.contains(target) {
+            return None;
+        }
+        let raw = prefix.contains(['r', 'R']);
+        if raw && body.contains('\\') {
+            return None;
+        }
+
+        let mut requoted = format!("{}{}", prefix, target);
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                match chars.next() {
+                    Some(escaped) if escaped == self.quote => requoted.push(escaped),
+                    Some(escaped) => {
+                        requoted.push(c);
+                        requoted.push(escaped);
+                    }
+                    None => requoted.push(c),
+                }
+            } else {
+                requoted.push(c);
+            }
+        }
+        requoted.push(target);
+        Some(requoted)
+    }
+}
+
+/// Closed single-line strings whose quote style is a choice; Rust and other languages have none
+fn quoted_strings<'a>(code: &'a str, language: &str) -> Vec<QuotedString<'a>> {
+    if !matches!(language.to_lowercase().as_str(), "javascript" | "typescript" | "python") {
+        return Vec::new();
+    }
+
+    Tokenizer::for_language(code, language)
+        .filter(|token| token.kind == TokenKind::StringLiteral)
+        .filter_map(|token| {
+            let prefix_len = token.text.find(['"', '\'', '`'])?;
+            let quoted = &token.text[prefix_len..];
+            let quote = quoted.chars().next()?;
+            let triple = quoted.starts_with(&quote.to_string().repeat(3));
+            // An escaped final quote means the string ran to the end of the line unclosed
+            let closed = quoted.len() >= 2
+                && quoted.ends_with(quote)
+                && quoted[1..quoted.len() - 1].chars().rev().take_while(|&c| c == '\\').count() % 2 == 0;
+            (quote != '`' && !triple && closed && !quoted.contains('\n'))
+                .then_some(QuotedString { token, prefix_len, quote })
+        })
+        .collect()
+}
+
+/// The quote used by more strings than the other, if either is
+fn dominant_quote(strings: &[QuotedString]) -> Option<char> {
+    let singles = strings.iter().filter(|string| string.quote == '\'').count();
+    let doubles = strings.len() - singles;
+    match singles.cmp(&doubles) {
+        std::cmp::Ordering::Greater => Some('\''),
+        std::cmp::Ordering::Less => Some('"'),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// Value of a number token such as `0.08`, `1_000`, `0xFF` or `64u32`; `None` for version-like text
+fn numeric_value(text: &str) -> Option<f64> {
+    let digits: String = text.chars().filter(|&c| c != '_').collect();
+    let radix = [("0x", 16), ("0o", 8), ("0b", 2)].into_iter().find(|(prefix, _)| digits.to_lowercase().starts_with(prefix));
+    if let Some((prefix, radix)) = radix {
+        return u64::from_str_radix(&digits[prefix.len()..], radix).ok().map(|value| value as f64);
+    }
+    // Strip a type suffix such as `u32`, `f64` or `n`, keeping exponents like `1e9`
+    let end = digits.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E')).unwrap_or(digits.len());
+    digits[..end].parse().ok()
+}
+
+/// Columns `line` occupies: one per Unicode scalar, with tabs advancing to the next tab stop
+fn visual_width(line: &str, tab_width: u32) -> u32 {
+    line.chars().fold(0, |column, c| match c {
+        '\t' => (column / tab_width + 1) * tab_width,
+        _ => column + 1,
+    })
+}
+
+/// Re-indent a line in the dominant unit, preserving its nesting depth
+fn normalize_indentation(line: &str, metrics: &StyleMetrics) -> Option<String> {
+    let content = line.trim_start_matches([' ', '\t']);
+    let indent = &line[..line.len() - content.len()];
+    let unit = metrics.indentation_size.max(1) as usize;
+
+    // Tabs count as one level each; spaces round to the nearest level
+    let spaces = indent.chars().filter(|&c| c == ' ').count();
+    let depth = indent.chars().filter(|&c| c == '\t').count() + (spaces + unit / 2) / unit;
+
+    let normalized = match metrics.indentation_type {
+        IndentationType::Spaces => " ".repeat(depth * unit),
+        IndentationType::Tabs => "\t".repeat(depth),
+        IndentationType::Mixed => return None,
+    };
+
+    let fixed = format!("{}{}", normalized, content);
+    if fixed == line { None } else { Some(fixed) }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -701,19 +1598,68 @@ class ShoppingCart {
         assert!(!stats.distribution.is_empty());
     }
 
+    #[test]
+    fn test_line_lengths_use_visual_width() {
+        let analyzer = CodingStyleAnalyzer::new();
+        // Tab to column 4, then 6 chars; a mid-line tab stops at the next multiple of 4
+        assert_eq!(analyzer.calculate_line_length_stats("\treturn").median_length, 10);
+        assert_eq!(analyzer.calculate_line_length_stats("ab\tc").median_length, 5);
+        // 9 scalars, 12 bytes
+        assert_eq!(analyzer.calculate_line_length_stats("größe = é").median_length, 9);
+
+        let wide_tabs = CodingStyleAnalyzer::new().with_tab_width(8);
+        assert_eq!(wide_tabs.calculate_line_length_stats("\t\tx").median_length, 17);
+    }
+
+    #[test]
+    fn test_crlf_files_measure_like_their_lf_equivalent() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let lf = "function greet(name) {\n    return 'Hello, ' + name;\n}\n\nconst answer = 42;\n";
+        let crlf = lf.replace('\n', "\r\n");
+
+        let lf_style = analyzer.analyze_style(lf, "javascript").unwrap().formatting_preferences;
+        let crlf_style = analyzer.analyze_style(&crlf, "javascript").unwrap().formatting_preferences;
+        assert_eq!(lf_style.line_ending, LineEnding::Lf);
+        assert_eq!(crlf_style.line_ending, LineEnding::CrLf);
+        assert_eq!(crlf_style.line_length_stats.average_length, lf_style.line_length_stats.average_length);
+        assert_eq!(crlf_style.line_length_stats.median_length, lf_style.line_length_stats.median_length);
+        assert_eq!(crlf_style.line_length_stats.preferred_max_length, lf_style.line_length_stats.preferred_max_length);
+
+        // Classic Mac endings are split into lines only after normalization
+        let cr = lf.replace('\n', "\r");
+        assert_eq!(LineEnding::detect(&cr), LineEnding::Cr);
+        let cr_style = analyzer.analyze_style(&cr, "javascript").unwrap().formatting_preferences;
+        assert_eq!(cr_style.line_length_stats.median_length, lf_style.line_length_stats.median_length);
+        let raw = CodingStyleAnalyzer::new().without_line_ending_normalization()
+            .analyze_style(&cr, "javascript").unwrap().formatting_preferences;
+        assert_ne!(raw.line_length_stats.median_length, lf_style.line_length_stats.median_length);
+    }
+
     #[test]
     fn test_spacing_preferences() {
         let analyzer = CodingStyleAnalyzer::new();
         let spaced_code = "if (condition) { return a + b; }";
         let unspaced_code = "if(condition){return a+b;}";
         
-        let spaced_prefs = analyzer.analyze_spacing_preferences(spaced_code);
-        let unspaced_prefs = analyzer.analyze_spacing_preferences(unspaced_code);
+        let spaced_prefs = analyzer.analyze_spacing_preferences(spaced_code, "javascript");
+        let unspaced_prefs = analyzer.analyze_spacing_preferences(unspaced_code, "javascript");
         
         assert!(spaced_prefs.around_operators);
         assert!(!unspaced_prefs.around_operators);
     }
 
+    #[test]
+    fn test_spacing_preferences_ignore_string_contents() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "total=add(a,b)\nprint(\"a + b = c, d; e\", 'if (x) y')\nlog(\"x + y\")\n";
+        let prefs = analyzer.analyze_spacing_preferences(code, "python");
+
+        assert!(!prefs.around_operators);
+        assert!(!prefs.around_keywords);
+        assert!(!prefs.around_semicolons);
+        assert!(!prefs.inside_parentheses);
+    }
+
     #[test]
     fn test_comment_style_detection() {
         let analyzer = CodingStyleAnalyzer::new();
@@ -721,10 +1667,298 @@ class ShoppingCart {
         let js_code = "// This is a comment\nfunction test() {}";
         let py_code = "# This is a comment\ndef test(): pass";
         
-        let js_style = analyzer.analyze_comment_style(js_code);
-        let py_style = analyzer.analyze_comment_style(py_code);
+        let js_style = analyzer.analyze_comment_style(js_code, "javascript");
+        let py_style = analyzer.analyze_comment_style(py_code, "python");
         
         assert_eq!(js_style.single_line_style, "//");
         assert_eq!(py_style.single_line_style, "#");
     }
+
+    #[test]
+    fn test_comment_density_ignores_markers_in_strings() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "const api = 'https://api.example.com';\nconst cdn = \"//cdn.example.com\";\n// endpoints above\nfetch(api);\n";
+        assert_eq!(analyzer.analyze_comment_style(code, "javascript").comment_density, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn test_hardcoded_ui_strings_flagged_for_i18n() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "import { t } from 'i18n';\nconst headers = { 'Content-Type': 'application/json' };\nfunction render(user) {\n  title.textContent = \"Welcome back to your dashboard\";\n  button.textContent = t(\"Save changes\");\n  console.log(\"Rendering the dashboard now\");\n  return user[\"display name\"] || 'Guest';\n}\n";
+
+        let flagged = analyzer.find_hardcoded_strings(code, "javascript");
+        assert_eq!(flagged, vec![
+            HardcodedString { text: "Welcome back to your dashboard".to_string(), line_number: 4 },
+            HardcodedString { text: "Guest".to_string(), line_number: 7 },
+        ]);
+
+        let python = "from gettext import gettext as _\nprint(_(\"Upload finished\"))\nraise ValueError('File is too large to upload')\nmode = 'rb'\n";
+        let flagged = analyzer.find_hardcoded_strings(python, "python");
+        assert_eq!(flagged.iter().map(|s| s.line_number).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_debt_markers_only_counted_in_comments() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "// TODO: paginate\nfunction load() {\n  const note = \"TODO in a string\";\n  /* FIXME: retries\n     HACK around the cache */\n  return fetch(url); // XXX hardcoded\n}\n";
+
+        let markers = analyzer.find_debt_markers(code, "javascript");
+        let located: Vec<(&str, u32)> = markers.locations.iter().map(|m| (m.marker.as_str(), m.line_number)).collect();
+        assert_eq!(located, vec![("TODO", 1), ("FIXME", 4), ("HACK", 5), ("XXX", 6)]);
+        assert_eq!(markers.counts.get("TODO"), Some(&1));
+        assert_eq!(markers.counts.values().sum::<u32>(), 4);
+
+        let python = analyzer.find_debt_markers("label = '# TODO not this'\nx = 1  # TODO: this one\n", "python");
+        assert_eq!(python.locations, vec![DebtMarker { marker: "TODO".to_string(), line_number: 2 }]);
+    }
+
+    #[test]
+    fn test_indentation_violations() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "function total(items) {\n    let sum = 0;\n    for (const item of items) {\n\tsum += item;\n      log(sum);\n    }\n    /**\n     * Done\n     */\n    return sum;\n}\n";
+        let metrics = PatternExtractor::new().extract_patterns(code, "javascript").unwrap().style_metrics;
+        assert!(matches!(metrics.indentation_type, IndentationType::Spaces));
+
+        let violations = analyzer.find_indentation_violations(code, &metrics);
+        assert_eq!(violations.len(), 2);
+
+        assert_eq!(violations[0].violation_type, "tab_indentation");
+        assert_eq!(violations[0].line_number, 4);
+        assert!(matches!(violations[0].severity, Severity::Warning));
+
+        assert_eq!(violations[1].violation_type, "indentation_width");
+        assert_eq!(violations[1].line_number, 5);
+        assert!(matches!(violations[1].severity, Severity::Info));
+    }
+
+    #[test]
+    fn test_brace_style_fix_replacement() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "function a() {\n    return 1;\n}\nfunction b() {\n    return 2;\n}\nfunction c()\n{\n    return 3;\n}\n";
+        let metrics = PatternExtractor::new().extract_patterns(code, "javascript").unwrap().style_metrics;
+        assert!(matches!(metrics.brace_style, BraceStyle::SameLine));
+
+        let suggestions = analyzer.suggest_style_fixes(code, &metrics);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].suggestion_type, "brace_style");
+        assert!(suggestions[0].auto_applicable);
+
+        let fix = suggestions[0].fix.as_ref().unwrap();
+        assert_eq!((fix.start_line, fix.end_line), (7, 8));
+        assert_eq!(fix.original, "function c()\n{");
+        assert_eq!(fix.replacement, "function c() {");
+    }
+
+    #[test]
+    fn test_indentation_fix_replacement() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "function f(items) {\n    for (const item of items) {\n\t\tprint(item);\n    }\n}\n";
+        let metrics = PatternExtractor::new().extract_patterns(code, "javascript").unwrap().style_metrics;
+
+        let suggestions = analyzer.suggest_style_fixes(code, &metrics);
+        assert_eq!(suggestions.len(), 1);
+        let fix = suggestions[0].fix.as_ref().unwrap();
+        assert_eq!(fix.start_line, 3);
+        assert_eq!(fix.replacement, "        print(item);");
+        assert!(suggestions[0].auto_applicable);
+    }
+
+    #[test]
+    fn test_consistency_scores_report_sample_reliability() {
+        let analyzer = CodingStyleAnalyzer::new();
+
+        let tiny = analyzer.analyze_style("let userName = retryCount;\n", "javascript").unwrap().consistency_scores;
+        assert_eq!(tiny.sample_size, 2);
+        assert_eq!(tiny.reliability, Reliability::Low);
+
+        let large_code: String = (0..40).map(|index| format!("let value{} = base{} + 1;\n", index, index)).collect();
+        let large = analyzer.analyze_style(&large_code, "javascript").unwrap().consistency_scores;
+        assert_eq!(large.sample_size, 80);
+        assert_eq!(large.reliability, Reliability::High);
+    }
+
+    #[test]
+    fn test_consistency_scores_are_measured_from_the_code() {
+        let analyzer = CodingStyleAnalyzer::new();
+
+        let consistent = "function loadUser(userId) {\n  const MAX_RETRIES = 3;\n  return fetchUser(userId, MAX_RETRIES);\n}\n";
+        let scores = analyzer.analyze_style(consistent, "javascript").unwrap().consistency_scores;
+        assert_eq!(scores.naming_consistency, 1.0);
+        assert_eq!(scores.formatting_consistency, 1.0);
+        assert_eq!(scores.style_consistency, 1.0);
+        assert_eq!(scores.overall_consistency, 1.0);
+
+        // One snake_case name among three camelCase ones, and one tab-indented line of five
+        let mixed = "function loadUser(userId) {\n  const retry_count = 3;\n  const user = fetchUser(userId, retry_count);\n\treturn user;\n}\n";
+        let scores = analyzer.analyze_style(mixed, "javascript").unwrap().consistency_scores;
+        assert_eq!(scores.naming_consistency, 0.75);
+        assert_eq!(scores.formatting_consistency, 0.8);
+        assert!(scores.overall_consistency < 1.0);
+    }
+
+    #[test]
+    fn test_style_analysis_size_guard() {
+        let analyzer = CodingStyleAnalyzer::with_max_input_bytes(16);
+        let result = analyzer.analyze_style("def broken(:\n    pass\n", "python");
+        assert!(matches!(result, Err(AnalysisError::InputTooLarge { size: 22, limit: 16 })));
+    }
+
+    #[test]
+    fn test_import_grouping_and_sorting_detection() {
+        let analyzer = CodingStyleAnalyzer::new();
+
+        let grouped = "import fs from 'fs';\nimport path from 'path';\n\nimport axios from 'axios';\nimport react from 'react';\n\nimport { helper } from './helper';\nimport { utils } from './utils';\n\nhelper(fs, path, axios, react, utils);\n";
+        let style = analyzer.analyze_import_organization(grouped);
+        assert!(matches!(style.grouping_style, ImportGroupingStyle::ByType));
+        assert!(matches!(style.sorting_preference, ImportSortingStyle::Alphabetical));
+
+        let unsorted = "import react from 'react';\nimport { utils } from './utils';\nimport fs from 'fs';\nimport axios from 'axios';\n\nutils(react, fs, axios);\n";
+        let style = analyzer.analyze_import_organization(unsorted);
+        assert!(matches!(style.grouping_style, ImportGroupingStyle::None));
+        assert!(matches!(style.sorting_preference, ImportSortingStyle::Custom));
+
+        let python = "import json\nimport os\n\nimport requests\n\nfrom .models import User\n";
+        assert!(matches!(analyzer.analyze_import_organization(python).grouping_style, ImportGroupingStyle::ByType));
+    }
+
+    #[test]
+    fn test_alias_usage_per_language() {
+        let analyzer = CodingStyleAnalyzer::new();
+
+        let python = "import numpy as np\nimport pandas as pd\nfrom os import path as p\n";
+        let aliases = analyzer.analyze_alias_usage(python);
+        assert!(aliases.uses_aliases);
+        assert_eq!(aliases.common_alias_patterns.get("numpy").map(String::as_str), Some("np"));
+        assert_eq!(aliases.common_alias_patterns.get("path").map(String::as_str), Some("p"));
+        assert!(matches!(aliases.alias_length_preference, AliasLengthStyle::Short));
+
+        let javascript = "import {\n  useState as useLocalState,\n  useEffect as useMountEffect,\n} from 'react';\nimport * as lodash from 'lodash';\n";
+        let aliases = analyzer.analyze_alias_usage(javascript);
+        assert_eq!(aliases.common_alias_patterns.get("useState").map(String::as_str), Some("useLocalState"));
+        assert_eq!(aliases.common_alias_patterns.get("lodash").map(String::as_str), Some("lodash"));
+        assert!(matches!(aliases.alias_length_preference, AliasLengthStyle::Descriptive));
+
+        let rust = "use std::io::Result as IoResult;\nuse std::fmt::{Display as Show, Debug};\n";
+        let aliases = analyzer.analyze_alias_usage(rust);
+        assert_eq!(aliases.common_alias_patterns.get("std::io::Result").map(String::as_str), Some("IoResult"));
+        assert_eq!(aliases.common_alias_patterns.get("Display").map(String::as_str), Some("Show"));
+        assert!(matches!(aliases.alias_length_preference, AliasLengthStyle::Context));
+
+        assert!(!analyzer.analyze_alias_usage("import os\n").uses_aliases);
+    }
+
+    #[test]
+    fn test_quick_metrics_skip_the_full_analysis() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "// Sum the values\nfunction sum(values) {\n    let total = 0;\n    for (const v of values) {\n        total += v;\n    }\n    return total;\n}\n";
+
+        let quick = analyzer.quick_metrics(code, "javascript");
+        assert!(matches!(quick.indentation_type, IndentationType::Spaces));
+        assert_eq!(quick.indentation_size, 4);
+        assert!(matches!(quick.brace_style, BraceStyle::SameLine));
+        assert!((quick.comment_density - 1.0 / 7.0).abs() < 1e-6);
+        assert_eq!(analyzer.parse_count(), 0);
+
+        let full = analyzer.analyze_style(code, "javascript").unwrap();
+        assert_eq!(full.basic_metrics.indentation_size, quick.indentation_size);
+        assert_eq!(full.basic_metrics.comment_density, quick.comment_density);
+        assert_eq!(analyzer.parse_count(), 2);
+
+        // No parse is needed, so broken code still gets metrics
+        assert!(matches!(analyzer.quick_metrics("def broken(:\n\tpass\n", "python").indentation_type, IndentationType::Tabs));
+        assert!(analyzer.analyze_style("def broken(:\n\tpass\n", "python").is_err());
+    }
+
+    #[test]
+    fn test_then_chains_flagged_in_async_await_file() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "async function loadUser(id) {\n  const user = await fetchUser(id);\n  return user;\n}\n\nfunction loadPosts(id) {\n  return fetchPosts(id)\n    .then(posts => posts.filter(p => p.published))\n    .then(render);\n}\n// fetchAll().then(done) was the old way\n";
+
+        let violations = analyzer.find_async_style_violations(code, "javascript");
+        let lines: Vec<u32> = violations.iter().map(|v| v.line_number).collect();
+        assert_eq!(lines, vec![8, 9]);
+        assert!(violations.iter().all(|v| v.violation_type == "promise_then_chain"));
+
+        let usage = analyzer.analyze_async_usage(code);
+        assert!(usage.uses_promises && !usage.uses_callbacks && !usage.prefers_async_await);
+
+        // Consistent promise chaining is a style, not a smell
+        assert!(analyzer.find_async_style_violations("fetchUser(1).then(render);\n", "javascript").is_empty());
+
+        // Strings mentioning either style are not code
+        let strings = "const hint = \"use await\";\nconst doc = `call .then(cb)`;\nfetchUser(1).then(render);\n";
+        assert!(analyzer.find_async_style_violations(strings, "javascript").is_empty());
+
+        let analysis = analyzer.analyze_style(code, "javascript").unwrap();
+        assert_eq!(analysis.async_style_violations.len(), 2);
+    }
+
+    #[test]
+    fn test_inline_numbers_flagged_as_magic() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "const TAX_RATE = 0.2;\nfunction total(price, items) {\n  const tax = price * 0.08; // 8% sales tax\n  for (let i = 0; i < 10; i++) {}\n  const first = items[3];\n  return price + tax * 1 - 0x1F + 1_000;\n}\n";
+        let magic = analyzer.find_magic_numbers(code, "javascript");
+        let found: Vec<(&str, u32)> = magic.iter().map(|number| (number.text.as_str(), number.line_number)).collect();
+        assert_eq!(found, vec![("0.08", 3), ("0x1F", 6), ("1_000", 6)]);
+
+        let violation = magic[0].to_violation();
+        assert_eq!(violation.violation_type, "magic_number");
+        assert_eq!(violation.description, "Extract `0.08` into a named constant");
+
+        // A custom allowlist accepts the project's common values, matching by magnitude
+        let lenient = CodingStyleAnalyzer::new().with_allowed_numbers(&[0.0, 1.0, 31.0, 1000.0]);
+        assert_eq!(lenient.find_magic_numbers(code, "javascript").len(), 1);
+        assert!(lenient.find_magic_numbers("offset = -31\n", "python").is_empty());
+
+        // Docstrings and `#` comments follow the language's own syntax
+        let python = "def retry(call):\n    '''Try 5 times, waiting 30s'''\n    return call(timeout=45)  # 45s\n";
+        let found: Vec<String> = analyzer.find_magic_numbers(python, "python").into_iter().map(|number| number.text).collect();
+        assert_eq!(found, vec!["45"]);
+        // `#` starts a private field in JavaScript, not a comment
+        let magic = analyzer.find_magic_numbers("class Pool {\n  grow() { this.#size += 16; }\n}\n", "javascript");
+        assert_eq!(magic.len(), 1);
+    }
+
+    #[test]
+    fn test_quote_fixes_skip_strings_holding_the_dominant_quote() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "name = \"alice\"\ngreeting = \"hello\"\nlabel = 'bob' + 'it\\'s'\nquip = 'say \"hi\"'\ndone = \"yes\"\nmode = \"fast\"\n";
+
+        let fixes = analyzer.suggest_quote_fixes(code, "python");
+        assert_eq!(fixes.len(), 1);
+        let fix = fixes[0].fix.as_ref().unwrap();
+        assert!(fixes[0].auto_applicable);
+        assert_eq!((fix.start_line, fix.end_line), (3, 3));
+        assert_eq!(fix.replacement, "label = \"bob\" + \"it's\"");
+
+        // `'say "hi"'` would need escapes in double quotes, so line 4 is left alone
+        assert!(fixes.iter().all(|fix| fix.fix.as_ref().unwrap().start_line != 4));
+        assert!(matches!(
+            analyzer.analyze_python_patterns(code).string_quote_preference,
+            PythonQuoteStyle::DoubleQuotes
+        ));
+
+        // Template literals don't count, and a tie has no dominant style to fix towards
+        let js = "const a = 'x';\nconst b = \"y\";\nconst c = `z`;\n";
+        assert!(analyzer.suggest_quote_fixes(js, "javascript").is_empty());
+        assert!(analyzer.suggest_quote_fixes("let c = 'x';\nlet s = \"y\";", "rust").is_empty());
+    }
+
+    #[test]
+    fn test_only_genuinely_unused_imports_flagged() {
+        let analyzer = CodingStyleAnalyzer::new();
+        let code = "import { useState, useEffect as onMount } from 'react';\nimport * as R from 'ramda';\nimport './styles.css';\n\nexport function Counter() {\n  const [count] = useState(0);\n  return `${count} useEffect R`;\n}\n";
+        let unused = analyzer.find_unused_imports(code, "javascript");
+        let found: Vec<(&str, &str, u32)> = unused.iter().map(|import| (import.name.as_str(), import.module.as_str(), import.line_number)).collect();
+        assert_eq!(found, vec![("onMount", "react", 1), ("R", "ramda", 2)]);
+        assert_eq!(unused[0].to_violation().violation_type, "unused_import");
+
+        let python = "from __future__ import annotations\nimport os\nimport numpy as np\nfrom .models import User as User, Order\n\ndef home():\n    return os.getcwd()  # np\n";
+        let names: Vec<String> = analyzer.find_unused_imports(python, "python").into_iter().map(|import| import.name).collect();
+        assert_eq!(names, vec!["np", "Order"]);
+
+        let rust = "use std::collections::{HashMap, HashSet};\nuse std::io::Write as _;\npub use crate::model::Report;\n\nfn index() -> HashMap<String, u32> {\n    HashMap::new()\n}\n";
+        let names: Vec<String> = analyzer.find_unused_imports(rust, "rust").into_iter().map(|import| import.name).collect();
+        assert_eq!(names, vec!["HashSet"]);
+    }
 }
@@ -1,6 +1,9 @@
 use crate::pattern_scoring_engine::{PatternScoringEngine, ScoringResult, PatternScore, ScoringContext};
 use crate::pattern_extractor::{PatternAnalysis, NamingStyle};
 use crate::user_behavior_tracker::BehaviorAnalysis;
+use crate::context_aware_filter::CodingContext;
+use crate::tokenizer::{Token, TokenKind, Tokenizer};
+use crate::variable_naming_recognizer::{VariableNamingRecognizer, convert_case};
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 
@@ -10,6 +13,7 @@ pub struct SuggestionGenerationEngine {
     suggestion_config: SuggestionConfiguration,
     template_engine: TemplateEngine,
     refactoring_engine: RefactoringEngine,
+    naming_recognizer: VariableNamingRecognizer,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,6 +132,182 @@ pub enum GenerationStrategy {
     UserPreferenceDriven,
 }
 
+// Scores above this count as a deciding factor when explaining a suggestion
+const STRONG_SIGNAL_SCORE: f32 = 0.5;
+
+fn line_before_cursor(code: &str, cursor_position: &CodePosition) -> String {
+    code.lines()
+        .nth(cursor_position.line as usize)
+        .map(|line| line.chars().take(cursor_position.column as usize).collect())
+        .unwrap_or_default()
+}
+
+/// Human-readable reason shown next to a completion, e.g. "Fills the open function call"
+fn completion_reasoning(pattern: &PatternScore, line_prefix: &str) -> String {
+    let mut reasons = Vec::new();
+    if line_prefix.matches('(').count() > line_prefix.matches(')').count() {
+        reasons.push("fills the open function call".to_string());
+    }
+    if pattern.user_preference_score >= STRONG_SIGNAL_SCORE && pattern.user_preference_score >= pattern.context_score {
+        reasons.push("matches a pattern you use often".to_string());
+    } else if pattern.context_score >= STRONG_SIGNAL_SCORE {
+        reasons.push("fits the surrounding code".to_string());
+    }
+    if reasons.is_empty() {
+        reasons.push(format!("resembles the `{}` pattern found in this code", pattern.pattern_id));
+    }
+
+    let reasoning = reasons.join(" and ");
+    let mut chars = reasoning.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => reasoning,
+    }
+}
+
+/// What a declaration's type and initializer say the variable will hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeclaredKind {
+    Counter,
+    Flag,
+    Collection,
+    Value,
+}
+
+impl DeclaredKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DeclaredKind::Counter => "counter",
+            DeclaredKind::Flag => "flag",
+            DeclaredKind::Collection => "collection",
+            DeclaredKind::Value => "value",
+        }
+    }
+}
+
+// Type names and initializer words that reveal the declared kind
+const COUNTER_TYPES: &[&str] = &[
+    "int", "integer", "number", "usize", "isize", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64",
+];
+const FLAG_TYPES: &[&str] = &["bool", "boolean"];
+const COLLECTION_TYPES: &[&str] = &[
+    "Vec", "VecDeque", "HashMap", "HashSet", "BTreeMap", "BTreeSet", "Array", "Map", "Set", "List", "list",
+    "dict", "set",
+];
+const MEASURE_WORDS: &[&str] = &["length", "len", "count", "size"];
+
+/// The declared kind and subject read off the line being declared, e.g. `let | = users.length;`
+struct DeclarationHint {
+    kind: DeclaredKind,
+    subject: Option<String>,
+}
+
+impl DeclarationHint {
+    fn from_line(line: &str, language: &str) -> Self {
+        let tokens: Vec<Token> = Tokenizer::for_language(line, language)
+            .filter(|token| token.kind != TokenKind::Comment)
+            .collect();
+        let assignment = tokens.iter().position(|token| token.text == "=");
+        let (target, initializer) = match assignment {
+            Some(index) => (&tokens[..index], &tokens[index + 1..]),
+            None => (&tokens[..], &tokens[..0]),
+        };
+        let annotation: Vec<&str> = target.iter()
+            .skip_while(|token| token.text != ":")
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .map(|token| token.text)
+            .collect();
+        let is_word = |token: &&Token, words: &[&str]| words.contains(&token.text);
+
+        let kind = if annotation.iter().any(|ty| COLLECTION_TYPES.contains(ty))
+            || initializer.first().is_some_and(|token| token.text == "[")
+            || initializer.iter().any(|token| is_word(&token, COLLECTION_TYPES) || token.text == "vec")
+        {
+            DeclaredKind::Collection
+        } else if annotation.iter().any(|ty| FLAG_TYPES.contains(ty))
+            || initializer.iter().any(|token| matches!(token.text, "true" | "false" | "True" | "False"))
+        {
+            DeclaredKind::Flag
+        } else if annotation.iter().any(|ty| COUNTER_TYPES.contains(ty))
+            || initializer.iter().any(|token| is_word(&token, MEASURE_WORDS))
+            || (initializer.len() == 1 || initializer.get(1).is_some_and(|token| token.text == ";"))
+                && initializer.first().is_some_and(|token| token.kind == TokenKind::Number && token.text.chars().all(|c| c.is_ascii_digit()))
+        {
+            DeclaredKind::Counter
+        } else {
+            DeclaredKind::Value
+        };
+
+        // The thing being counted or held: the last identifier in the initializer that isn't
+        // a call, else a user type in the annotation such as `User` in `Vec<User>`
+        let subject = initializer.iter().enumerate().rev()
+            .filter(|(index, token)| token.kind == TokenKind::Identifier && initializer.get(index + 1).is_none_or(|next| next.text != "("))
+            .map(|(_, token)| token.text)
+            .find(|word| !MEASURE_WORDS.contains(word) && !COLLECTION_TYPES.contains(word) && *word != "vec")
+            .or_else(|| annotation.iter().copied().find(|ty| {
+                !COLLECTION_TYPES.contains(ty) && !COUNTER_TYPES.contains(ty) && !FLAG_TYPES.contains(ty)
+            }))
+            .map(singular);
+
+        DeclarationHint { kind, subject }
+    }
+}
+
+/// The most recently mentioned plural identifier above the cursor, singularized
+fn nearest_collection_name(preceding_lines: &[&str], language: &str) -> Option<String> {
+    preceding_lines.iter().rev().find_map(|line| {
+        let identifiers: Vec<&str> = Tokenizer::for_language(line, language)
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .map(|token| token.text)
+            .collect();
+        identifiers.into_iter().rev()
+            .find(|word| word.len() > 3 && singular(word) != *word)
+            .map(singular)
+    })
+}
+
+/// `users` -> `user`, `entries` -> `entry`; words like `status` or `class` stay as they are
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if word.ends_with('s') && !["ss", "us", "is"].iter().any(|ending| word.ends_with(ending)) {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
+
+fn plural(word: &str) -> String {
+    match word.strip_suffix('y') {
+        Some(stem) if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) => format!("{}ies", stem),
+        _ => format!("{}s", word),
+    }
+}
+
+/// Candidate names, best first, as snake_case word lists ready for `convert_case`
+fn name_candidates(kind: DeclaredKind, subject: Option<&str>) -> Vec<String> {
+    // The subject may itself be camelCase; normalize its words first
+    let subject = subject.map(|subject| convert_case(subject, &NamingStyle::SnakeCase));
+    match (kind, subject) {
+        (DeclaredKind::Counter, Some(subject)) => vec![
+            format!("{}_count", subject),
+            format!("total_{}", plural(&subject)),
+            format!("num_{}", plural(&subject)),
+        ],
+        (DeclaredKind::Counter, None) => vec!["count".to_string(), "total".to_string(), "counter".to_string()],
+        (DeclaredKind::Flag, Some(subject)) => vec![
+            format!("is_{}", subject),
+            format!("has_{}", subject),
+            format!("{}_enabled", subject),
+        ],
+        (DeclaredKind::Flag, None) => vec!["is_valid".to_string(), "is_enabled".to_string(), "has_changed".to_string()],
+        (DeclaredKind::Collection, Some(subject)) => vec![plural(&subject), format!("{}_list", subject)],
+        (DeclaredKind::Collection, None) => vec!["items".to_string(), "results".to_string(), "values".to_string()],
+        (DeclaredKind::Value, Some(subject)) => vec![subject.clone(), format!("current_{}", subject)],
+        (DeclaredKind::Value, None) => vec![],
+    }
+}
+
 /// Template engine for generating code suggestions
 pub struct TemplateEngine {
     templates: HashMap<String, CodeTemplate>,
@@ -219,6 +399,7 @@ impl SuggestionGenerationEngine {
             suggestion_config: SuggestionConfiguration::default(),
             template_engine: TemplateEngine::new(),
             refactoring_engine: RefactoringEngine::new(),
+            naming_recognizer: VariableNamingRecognizer::new(),
         }
     }
 
@@ -228,6 +409,7 @@ impl SuggestionGenerationEngine {
             suggestion_config: config,
             template_engine: TemplateEngine::new(),
             refactoring_engine: RefactoringEngine::new(),
+            naming_recognizer: VariableNamingRecognizer::new(),
         }
     }
 
@@ -295,6 +477,60 @@ impl SuggestionGenerationEngine {
         })
     }
 
+    /// Ranked names for the variable being declared on the cursor line, written in the file's
+    /// dominant variable naming style. The declared type and initializer pick the kind of name
+    /// (counter, flag, collection) and the identifiers they mention pick its subject
+    pub fn suggest_variable_names(&self, context: &CodingContext) -> Vec<CodeSuggestion> {
+        let language = context.file_language.as_str();
+        let style = self.naming_recognizer.detect_variable_style(&context.file_content, language);
+        let style_name = match style {
+            NamingStyle::SnakeCase => "snake_case",
+            _ => "camelCase",
+        };
+
+        let declaration = DeclarationHint::from_line(&context.current_line, language);
+        let subject = declaration.subject.clone().or_else(|| {
+            let preceding: Vec<&str> = context.file_content.lines().take(context.cursor_position.row as usize).collect();
+            nearest_collection_name(&preceding, language)
+        });
+
+        let position = CodePosition {
+            line: context.cursor_position.row,
+            column: context.cursor_position.column,
+            start_offset: context.cursor_position.offset as usize,
+            end_offset: context.cursor_position.offset as usize,
+        };
+        let mut seen = std::collections::HashSet::new();
+        name_candidates(declaration.kind, subject.as_deref())
+            .into_iter()
+            .map(|words| convert_case(&words, &style))
+            .filter(|name| seen.insert(name.clone()))
+            .enumerate()
+            .map(|(rank, name)| {
+                let confidence = (0.9 - rank as f32 * 0.1).max(0.3);
+                CodeSuggestion {
+                    id: format!("variable_name_{}", name),
+                    suggestion_type: SuggestionType::VariableNaming,
+                    title: format!("Name it `{}`", name),
+                    description: format!("{} name in {}", declaration.kind.as_str(), style_name),
+                    suggested_code: name,
+                    original_code: None,
+                    confidence_score: confidence,
+                    pattern_match_score: confidence,
+                    user_preference_score: 1.0,
+                    context_relevance: if subject.is_some() { 0.8 } else { 0.4 },
+                    position: position.clone(),
+                    preview_changes: vec![],
+                    reasoning: match &subject {
+                        Some(subject) => format!("Describes the {} of `{}`, written in {} like the rest of this file", declaration.kind.as_str(), subject, style_name),
+                        None => format!("A common {} name, written in {} like the rest of this file", declaration.kind.as_str(), style_name),
+                    },
+                    related_patterns: vec!["variable_naming".to_string()],
+                }
+            })
+            .collect()
+    }
+
     fn create_scoring_context(&self, code: &str, cursor_position: &CodePosition, language: &str) -> Result<ScoringContext, String> {
         let lines: Vec<&str> = code.lines().collect();
         let current_line = lines.get(cursor_position.line as usize).unwrap_or(&"").to_string();
@@ -331,11 +567,13 @@ impl SuggestionGenerationEngine {
         
         // Analyze the current context for completion opportunities
         let context = self.analyze_completion_context(code, cursor_position)?;
+        let line_prefix = line_before_cursor(code, cursor_position);
         
         // Generate suggestions based on top-scored patterns
         for pattern in scoring_result.scored_patterns.iter().take(5) {
             if pattern.composite_score > self.suggestion_config.confidence_threshold {
-                if let Some(suggestion) = self.create_completion_from_pattern(pattern, &context, language) {
+                if let Some(mut suggestion) = self.create_completion_from_pattern(pattern, &context, language) {
+                    suggestion.reasoning = completion_reasoning(pattern, &line_prefix);
                     suggestions.push(suggestion);
                 }
             }
@@ -452,7 +690,7 @@ impl SuggestionGenerationEngine {
         suggestions.sort_by(|a, b| {
             let score_a = a.confidence_score + a.pattern_match_score + a.user_preference_score + a.context_relevance;
             let score_b = b.confidence_score + b.pattern_match_score + b.user_preference_score + b.context_relevance;
-            score_b.partial_cmp(&score_a).unwrap()
+            score_b.total_cmp(&score_a)
         });
         
         // Limit to max suggestions
@@ -709,4 +947,115 @@ mod tests {
         assert_eq!(context.current_file_type, "javascript");
         assert_eq!(context.project_context, "code_completion");
     }
+
+    fn pattern_score(pattern_id: &str, context_score: f32, user_preference_score: f32) -> PatternScore {
+        PatternScore {
+            pattern_id: pattern_id.to_string(),
+            relevance_score: 0.9,
+            confidence_score: 0.9,
+            frequency_score: 0.5,
+            recency_score: 0.5,
+            context_score,
+            user_preference_score,
+            composite_score: 0.9,
+            last_updated: 0,
+            usage_count: 1,
+        }
+    }
+
+    fn scoring_result(scored_patterns: Vec<PatternScore>) -> ScoringResult {
+        ScoringResult {
+            scored_patterns,
+            ranking_metrics: crate::pattern_scoring_engine::RankingMetrics {
+                total_patterns: 0,
+                high_confidence_count: 0,
+                medium_confidence_count: 0,
+                low_confidence_count: 0,
+                average_score: 0.0,
+                score_variance: 0.0,
+            },
+            confidence_distribution: crate::pattern_scoring_engine::ConfidenceDistribution {
+                high_confidence: vec![],
+                medium_confidence: vec![],
+                low_confidence: vec![],
+                threshold_adjustments: HashMap::new(),
+            },
+            adaptive_adjustments: crate::pattern_scoring_engine::AdaptiveAdjustments {
+                learning_rate_adjustments: HashMap::new(),
+                weight_modifications: crate::pattern_scoring_engine::ScoringConfiguration::default(),
+                pattern_decay_factors: HashMap::new(),
+                context_boost_factors: HashMap::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_completions_carry_reasoning() {
+        let engine = SuggestionGenerationEngine::new();
+        let scoring_result = scoring_result(vec![
+            pattern_score("fetch_call", 0.2, 0.8),
+            pattern_score("loop_body", 0.7, 0.1),
+            pattern_score("plain", 0.1, 0.1),
+        ]);
+        let code = "let data = fetch(url, ";
+
+        let open_call = CodePosition { line: 0, column: 22, start_offset: 22, end_offset: 22 };
+        let suggestions = engine.generate_completion_suggestions(code, &open_call, &scoring_result, "javascript").unwrap();
+        let reasons: Vec<&str> = suggestions.iter().map(|s| s.reasoning.as_str()).collect();
+        assert_eq!(reasons, vec![
+            "Fills the open function call and matches a pattern you use often",
+            "Fills the open function call and fits the surrounding code",
+            "Fills the open function call",
+        ]);
+
+        let before_call = CodePosition { line: 0, column: 4, start_offset: 4, end_offset: 4 };
+        let suggestions = engine.generate_completion_suggestions(code, &before_call, &scoring_result, "javascript").unwrap();
+        assert!(suggestions.iter().all(|s| !s.reasoning.is_empty()));
+        assert_eq!(suggestions[2].reasoning, "Resembles the `plain` pattern found in this code");
+    }
+
+    fn coding_context(file_content: &str, language: &str, row: u32) -> CodingContext {
+        let current_line = file_content.lines().nth(row as usize).unwrap_or_default().to_string();
+        CodingContext {
+            file_path: "src/report.js".to_string(),
+            file_language: language.to_string(),
+            file_content: file_content.to_string(),
+            cursor_position: crate::context_aware_filter::CursorPosition { row, column: 4, offset: 0 },
+            current_line,
+            additional_cursors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_counter_names_follow_detected_style() {
+        let engine = SuggestionGenerationEngine::new();
+
+        let camel = "const activeUsers = loadUsers();\nlet pageTitle = 'Report';\nlet  = activeUsers.length;\n";
+        let suggestions = engine.suggest_variable_names(&coding_context(camel, "javascript", 2));
+        let names: Vec<&str> = suggestions.iter().map(|s| s.suggested_code.as_str()).collect();
+        assert_eq!(names, vec!["activeUserCount", "totalActiveUsers", "numActiveUsers"]);
+        assert!(suggestions.iter().all(|s| s.suggestion_type == SuggestionType::VariableNaming));
+        assert!(suggestions[0].confidence_score > suggestions[1].confidence_score);
+
+        // A bare `0` counts whatever collection was mentioned last
+        let snake = "active_users = load_users()\npage_title = 'Report'\nfor user in active_users:\n     = 0\n";
+        let suggestions = engine.suggest_variable_names(&coding_context(snake, "python", 3));
+        assert_eq!(suggestions[0].suggested_code, "active_user_count");
+        assert!(suggestions.iter().all(|s| s.suggested_code.ends_with("_count") || s.suggested_code.contains("_users")));
+    }
+
+    #[test]
+    fn test_flag_and_collection_names_from_declared_type() {
+        let engine = SuggestionGenerationEngine::new();
+
+        let flag = engine.suggest_variable_names(&coding_context("let  = false;\n", "javascript", 0));
+        assert_eq!(flag[0].suggested_code, "isValid");
+
+        let rust = "let  : Vec<Order> = Vec::new();\n";
+        let names: Vec<String> = engine.suggest_variable_names(&coding_context(rust, "rust", 0))
+            .into_iter()
+            .map(|s| s.suggested_code)
+            .collect();
+        assert_eq!(names, vec!["orders", "order_list"]);
+    }
 }
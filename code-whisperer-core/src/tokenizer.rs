@@ -0,0 +1,438 @@
+/// Coarse lexical category of a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Keyword, // only from `Tokenizer::for_language`
+    Number,
+    StringLiteral,
+    Comment,
+    Operator,
+    Punctuation,
+}
+
+impl TokenKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenKind::Identifier => "identifier",
+            TokenKind::Keyword => "keyword",
+            TokenKind::Number => "number",
+            TokenKind::StringLiteral => "string",
+            TokenKind::Comment => "comment",
+            TokenKind::Operator => "operator",
+            TokenKind::Punctuation => "punctuation",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub kind: TokenKind,
+    pub offset: usize, // byte offset into the source
+    pub line: u32,     // 1-based line the token starts on
+    pub column: u32,   // chars before the token on that line
+}
+
+/// How a language writes comments and strings, and which words it reserves
+struct LexicalSyntax {
+    line_comments: &'static [&'static str],
+    block_comments: bool,
+    nested_block_comments: bool,
+    quotes: &'static [char],
+    multiline_quote: Option<char>, // JS template literals
+    triple_quotes: bool,           // Python `"""` and `'''` strings
+    string_prefixes: &'static [&'static str], // `f"..."`, `b"..."`; Rust `r#"..."#` raw strings too
+    char_literals_only: bool,      // `'` starts a string only as a closed char literal; otherwise a lifetime
+    keywords: &'static [&'static str],
+}
+
+const GENERIC_SYNTAX: LexicalSyntax = LexicalSyntax {
+    line_comments: &["//", "#"],
+    block_comments: true,
+    nested_block_comments: false,
+    quotes: &['"', '\'', '`'],
+    multiline_quote: None,
+    triple_quotes: false,
+    string_prefixes: &[],
+    char_literals_only: false,
+    keywords: &[],
+};
+
+const JAVASCRIPT_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete",
+    "do", "else", "export", "extends", "false", "finally", "for", "function", "if", "import", "in", "instanceof",
+    "let", "new", "null", "of", "return", "static", "super", "switch", "this", "throw", "true", "try", "typeof",
+    "var", "void", "while", "with", "yield",
+];
+const TYPESCRIPT_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "break", "case", "catch", "class", "const", "continue", "debugger",
+    "declare", "default", "delete", "do", "else", "enum", "export", "extends", "false", "finally", "for",
+    "function", "if", "implements", "import", "in", "instanceof", "interface", "let", "namespace", "new", "null",
+    "of", "private", "protected", "public", "readonly", "return", "static", "super", "switch", "this", "throw",
+    "true", "try", "type", "typeof", "var", "void", "while", "with", "yield",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda",
+    "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+const RUST_KEYWORDS: &[&str] = &[
+    "Self", "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+const JAVASCRIPT_SYNTAX: LexicalSyntax = LexicalSyntax {
+    line_comments: &["//"],
+    multiline_quote: Some('`'),
+    keywords: JAVASCRIPT_KEYWORDS,
+    ..GENERIC_SYNTAX
+};
+const TYPESCRIPT_SYNTAX: LexicalSyntax = LexicalSyntax { keywords: TYPESCRIPT_KEYWORDS, ..JAVASCRIPT_SYNTAX };
+const PYTHON_SYNTAX: LexicalSyntax = LexicalSyntax {
+    line_comments: &["#"],
+    block_comments: false,
+    quotes: &['"', '\''],
+    triple_quotes: true,
+    string_prefixes: &["r", "u", "b", "f", "br", "rb", "fr", "rf"],
+    keywords: PYTHON_KEYWORDS,
+    ..GENERIC_SYNTAX
+};
+const RUST_SYNTAX: LexicalSyntax = LexicalSyntax {
+    line_comments: &["//"],
+    nested_block_comments: true,
+    quotes: &['"', '\''],
+    string_prefixes: &["b", "r", "br"],
+    char_literals_only: true,
+    keywords: RUST_KEYWORDS,
+    ..GENERIC_SYNTAX
+};
+
+fn lexical_syntax(language: &str) -> &'static LexicalSyntax {
+    match language.to_lowercase().as_str() {
+        "javascript" => &JAVASCRIPT_SYNTAX,
+        "typescript" => &TYPESCRIPT_SYNTAX,
+        "python" => &PYTHON_SYNTAX,
+        "rust" => &RUST_SYNTAX,
+        _ => &GENERIC_SYNTAX,
+    }
+}
+
+/// Streaming tokenizer that never fails, so it also covers code too broken to parse
+pub struct Tokenizer<'a> {
+    source: &'a str,
+    position: usize,
+    line: u32,
+    column: u32,
+    syntax: &'static LexicalSyntax,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Language-agnostic: `//`, `#` and `/* */` comments, every quote style, no keywords
+    pub fn new(source: &'a str) -> Self {
+        Self { source, position: 0, line: 1, column: 0, syntax: &GENERIC_SYNTAX }
+    }
+
+    /// Tokenize with `language`'s comment and string syntax and report its reserved words as
+    /// `Keyword`s. Unknown languages get the agnostic rules of `new`
+    pub fn for_language(source: &'a str, language: &str) -> Self {
+        Self { syntax: lexical_syntax(language), ..Self::new(source) }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn advance_bytes(&mut self, count: usize) {
+        let end = (self.position + count).min(self.source.len());
+        while self.position < end {
+            self.bump();
+        }
+    }
+
+    fn consume_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&predicate) {
+            self.bump();
+        }
+    }
+
+    /// Consume up to and including `terminator`, skipping escaped chars. Single-line strings
+    /// left unterminated stop before the line break
+    fn consume_string(&mut self, terminator: &str, multiline: bool) {
+        while let Some(c) = self.peek() {
+            if self.rest().starts_with(terminator) {
+                self.advance_bytes(terminator.len());
+                return;
+            }
+            if c == '\n' && !multiline {
+                return;
+            }
+            self.bump();
+            if c == '\\' {
+                self.bump();
+            }
+        }
+    }
+
+    // Rust raw strings have no escapes: `r#"a "quoted" b"#`
+    fn consume_raw_string(&mut self, hashes: usize) {
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        match self.rest().find(&terminator) {
+            Some(end) => self.advance_bytes(end + terminator.len()),
+            None => self.advance_bytes(self.rest().len()),
+        }
+    }
+
+    fn consume_block_comment(&mut self) {
+        self.advance_bytes(2);
+        let mut depth = 1;
+        while depth > 0 && self.peek().is_some() {
+            if self.syntax.nested_block_comments && self.rest().starts_with("/*") {
+                self.advance_bytes(2);
+                depth += 1;
+            } else if self.rest().starts_with("*/") {
+                self.advance_bytes(2);
+                depth -= 1;
+            } else {
+                self.bump();
+            }
+        }
+    }
+
+    /// A string opening at the cursor, possibly after a prefix such as `f` or `r#` already consumed
+    fn consume_quoted(&mut self, raw_hashes: Option<usize>) {
+        let quote = self.peek().unwrap_or('"');
+        if let Some(hashes) = raw_hashes {
+            self.bump();
+            self.consume_raw_string(hashes);
+        } else if self.syntax.triple_quotes && self.rest().starts_with(&quote.to_string().repeat(3)) {
+            let triple = quote.to_string().repeat(3);
+            self.advance_bytes(3);
+            self.consume_string(&triple, true);
+        } else {
+            self.bump();
+            self.consume_string(&quote.to_string(), self.syntax.multiline_quote == Some(quote));
+        }
+    }
+
+    // `'x'` or `'\n'`, as opposed to a lifetime such as `'a`
+    fn at_char_literal(&self) -> bool {
+        let mut chars = self.rest().chars().skip(1);
+        match chars.next() {
+            Some('\\') => true,
+            Some(_) => chars.next() == Some('\''),
+            None => false,
+        }
+    }
+
+    /// An identifier just consumed may be a string prefix (`f"..."`, `br#"..."#`); returns
+    /// whether it turned into a string
+    fn continue_prefixed_string(&mut self, prefix: &str) -> bool {
+        if !self.syntax.string_prefixes.iter().any(|p| p.eq_ignore_ascii_case(prefix)) {
+            return false;
+        }
+        let rest = self.rest();
+        if self.syntax.char_literals_only && prefix.contains('r') {
+            let hashes = rest.len() - rest.trim_start_matches('#').len();
+            if rest[hashes..].starts_with('"') {
+                self.advance_bytes(hashes);
+                self.consume_quoted(Some(hashes));
+                return true;
+            }
+            return false;
+        }
+        match self.peek() {
+            Some(c) if self.syntax.quotes.contains(&c) && (c != '\'' || !self.syntax.char_literals_only || self.at_char_literal()) => {
+                self.consume_quoted(None);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.consume_while(char::is_whitespace);
+        let (start, line, column) = (self.position, self.line, self.column);
+        let c = self.peek()?;
+        let rest = self.rest();
+        let is_identifier_char = |n: char| n.is_alphanumeric() || n == '_' || n == '$';
+
+        let kind = if self.syntax.line_comments.iter().any(|marker| rest.starts_with(marker)) {
+            self.consume_while(|n| n != '\n');
+            TokenKind::Comment
+        } else if self.syntax.block_comments && rest.starts_with("/*") {
+            self.consume_block_comment();
+            TokenKind::Comment
+        } else if self.syntax.quotes.contains(&c) && !(c == '\'' && self.syntax.char_literals_only) {
+            self.consume_quoted(None);
+            TokenKind::StringLiteral
+        } else if c == '\'' && self.syntax.char_literals_only {
+            if self.at_char_literal() {
+                self.consume_quoted(None);
+                TokenKind::StringLiteral
+            } else {
+                // A lifetime or label: `'a`, `'static`
+                self.bump();
+                self.consume_while(is_identifier_char);
+                TokenKind::Identifier
+            }
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            self.consume_while(is_identifier_char);
+            let word = &self.source[start..self.position];
+            if self.continue_prefixed_string(word) {
+                TokenKind::StringLiteral
+            } else if self.syntax.keywords.contains(&word) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Identifier
+            }
+        } else if c.is_ascii_digit() {
+            self.consume_while(|n| n.is_ascii_alphanumeric() || n == '.' || n == '_');
+            TokenKind::Number
+        } else if "()[]{},;:.".contains(c) {
+            self.bump();
+            TokenKind::Punctuation
+        } else {
+            self.bump();
+            self.consume_while(|n| "=<>!&|+-*/%^~?".contains(n));
+            TokenKind::Operator
+        };
+
+        Some(Token {
+            text: &self.source[start..self.position],
+            kind,
+            offset: start,
+            line,
+            column,
+        })
+    }
+}
+
+/// Tokenize a whole source string
+pub fn tokenize(source: &str) -> Vec<Token<'_>> {
+    Tokenizer::new(source).collect()
+}
+
+/// A code token and whether whitespace separates it from its neighbours
+pub(crate) struct SpacedToken<'a> {
+    pub token: Token<'a>,
+    pub space_before: bool,
+    pub space_after: bool,
+}
+
+/// The non-comment tokens of `code`, so spacing inside strings and comments is never measured
+pub(crate) fn spaced_tokens<'a>(code: &'a str, language: &str) -> Vec<SpacedToken<'a>> {
+    let is_space_at = |index: usize| code[index..].starts_with(char::is_whitespace);
+    let is_space_before = |index: usize| code[..index].ends_with(char::is_whitespace);
+
+    Tokenizer::for_language(code, language)
+        .filter(|token| token.kind != TokenKind::Comment)
+        .map(|token| SpacedToken {
+            space_before: is_space_before(token.offset),
+            space_after: is_space_at(token.offset + token.text.len()),
+            token,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_basic_statement() {
+        let tokens = tokenize("let total = price * 2; // sum");
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Identifier,
+            TokenKind::Identifier,
+            TokenKind::Operator,
+            TokenKind::Identifier,
+            TokenKind::Operator,
+            TokenKind::Number,
+            TokenKind::Punctuation,
+            TokenKind::Comment,
+        ]);
+        assert_eq!(tokens[3].text, "price");
+        assert_eq!(tokens[7].text, "// sum");
+    }
+
+    #[test]
+    fn test_tokenize_broken_code_does_not_panic() {
+        let tokens = tokenize("function broken( { \"unterminated\n x => /* open");
+        assert!(tokens.iter().any(|t| t.kind == TokenKind::StringLiteral));
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Comment);
+        assert!(tokens.iter().any(|t| t.text == "=>"));
+    }
+
+    fn kinds_of<'a>(tokens: &[Token<'a>], kind: TokenKind) -> Vec<&'a str> {
+        tokens.iter().filter(|t| t.kind == kind).map(|t| t.text).collect()
+    }
+
+    #[test]
+    fn test_javascript_keywords_and_template_literals() {
+        let tokens: Vec<_> = Tokenizer::for_language("const msg = `hi\n${name}`; // '#' note\nif (done) return", "javascript").collect();
+        assert_eq!(kinds_of(&tokens, TokenKind::Keyword), vec!["const", "if", "return"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::Identifier), vec!["msg", "done"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::StringLiteral), vec!["`hi\n${name}`"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::Comment), vec!["// '#' note"]);
+
+        let typescript: Vec<_> = Tokenizer::for_language("interface Point { x: number }", "typescript").collect();
+        assert_eq!(kinds_of(&typescript, TokenKind::Keyword), vec!["interface"]);
+    }
+
+    #[test]
+    fn test_python_strings_and_hash_comments() {
+        let code = "def greet(name):\n    \"\"\"Say \"hi\" # twice\"\"\"\n    return f\"hi {name}\"  # done";
+        let tokens: Vec<_> = Tokenizer::for_language(code, "python").collect();
+        assert_eq!(kinds_of(&tokens, TokenKind::Keyword), vec!["def", "return"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::Identifier), vec!["greet", "name"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::StringLiteral), vec!["\"\"\"Say \"hi\" # twice\"\"\"", "f\"hi {name}\""]);
+        assert_eq!(kinds_of(&tokens, TokenKind::Comment), vec!["# done"]);
+    }
+
+    #[test]
+    fn test_rust_lifetimes_raw_strings_and_nested_comments() {
+        let code = "fn first<'a>(s: &'a str) -> char { '/' }\n/* a /* b */ c */ let r = r#\"say \"x\"\"#; #[derive]";
+        let tokens: Vec<_> = Tokenizer::for_language(code, "rust").collect();
+        assert_eq!(kinds_of(&tokens, TokenKind::Keyword), vec!["fn", "let"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::Identifier), vec!["first", "'a", "s", "'a", "str", "char", "r", "derive"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::StringLiteral), vec!["'/'", "r#\"say \"x\"\"#"]);
+        assert_eq!(kinds_of(&tokens, TokenKind::Comment), vec!["/* a /* b */ c */"]);
+    }
+
+    #[test]
+    fn test_tokens_carry_line_and_column() {
+        let tokens = tokenize("a = 1\n  /* two\n lines */ b");
+        let positions: Vec<(&str, u32, u32)> = tokens.iter().map(|t| (t.text, t.line, t.column)).collect();
+        assert_eq!(positions, vec![
+            ("a", 1, 0),
+            ("=", 1, 2),
+            ("1", 1, 4),
+            ("/* two\n lines */", 2, 2),
+            ("b", 3, 10),
+        ]);
+        // Without a language, reserved words stay identifiers
+        assert!(tokenize("if x").iter().all(|t| t.kind == TokenKind::Identifier));
+    }
+}
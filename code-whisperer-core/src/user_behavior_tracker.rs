@@ -1,6 +1,61 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc, Duration, Timelike, Datelike};
+use wasm_bindgen::prelude::*;
+
+/// Source of the current time, injectable so temporal logic can be tested
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time; uses `js_sys::Date` when running under WASM
+#[derive(Debug, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            DateTime::<Utc>::from_timestamp_millis(js_sys::Date::now() as i64).unwrap_or_default()
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Utc::now()
+        }
+    }
+}
+
+/// Manually controlled clock for tests
+#[derive(Debug)]
+pub struct MockClock {
+    current: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { current: Mutex::new(start) }
+    }
+
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.current.lock().unwrap() = time;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut current = self.current.lock().unwrap();
+        *current += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.current.lock().unwrap()
+    }
+}
+
+fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
 
 /// Privacy-conscious user behavior tracking system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +64,11 @@ pub struct UserBehaviorTracker {
     pattern_preferences: PatternPreferences,
     learning_context: LearningContext,
     privacy_settings: PrivacySettings,
+    hourly_activity: HashMap<u32, HourlyActivity>, // hour (0-23) -> activity across sessions
+    #[serde(default)]
+    rejection_reasons: HashMap<RejectionReason, u32>,
+    #[serde(skip, default = "default_clock")]
+    clock: Arc<dyn Clock>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +94,22 @@ pub struct SessionData {
     pub suggestions_rejected: u32,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HourlyActivity {
+    pub lines_written: u32,
+    pub suggestions_accepted: u32,
+}
+
+impl HourlyActivity {
+    /// Weighted activity score; an accepted suggestion counts as several written lines
+    pub fn productivity_score(&self) -> u32 {
+        self.lines_written + self.suggestions_accepted * ACCEPTED_SUGGESTION_WEIGHT
+    }
+}
+
+const ACCEPTED_SUGGESTION_WEIGHT: u32 = 5;
+const MAX_PEAK_HOURS: usize = 4;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatternPreferences {
     pub naming_style_preferences: HashMap<String, f32>, // style -> preference_score
@@ -82,11 +158,22 @@ pub struct ContextAwareness {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuggestionFeedback {
     pub acceptance_rate: f32,        // 0.0 to 1.0
-    pub rejection_reasons: HashMap<String, u32>, // reason -> count
+    pub rejection_reasons: HashMap<RejectionReason, u32>, // reason -> count
     pub feedback_quality: FeedbackQuality,
     pub improvement_suggestions: Vec<String>,
 }
 
+/// Why the user turned a suggestion down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    Irrelevant,
+    WrongStyle,
+    Incorrect,
+    Duplicate,
+    Other,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LearningContext {
     pub current_skill_focus: Vec<String>,
@@ -362,23 +449,31 @@ pub struct SharingPreferences {
 
 impl UserBehaviorTracker {
     pub fn new() -> Self {
+        Self::with_clock(default_clock())
+    }
+
+    /// Create a tracker that reads time from the given clock
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
-            session_data: SessionData::new(),
+            session_data: SessionData::new(clock.now()),
             pattern_preferences: PatternPreferences::default(),
             learning_context: LearningContext::default(),
             privacy_settings: PrivacySettings::default(),
+            hourly_activity: HashMap::new(),
+            rejection_reasons: HashMap::new(),
+            clock,
         }
     }
 
     /// Start a new coding session
     pub fn start_session(&mut self) -> String {
-        self.session_data = SessionData::new();
+        self.session_data = SessionData::new(self.now());
         self.session_data.session_id.clone()
     }
 
     /// End the current coding session
     pub fn end_session(&mut self) {
-        self.session_data.end_time = Some(Utc::now());
+        self.session_data.end_time = Some(self.now());
         if let Some(end_time) = self.session_data.end_time {
             self.session_data.active_duration = end_time - self.session_data.start_time;
         }
@@ -386,25 +481,63 @@ impl UserBehaviorTracker {
 
     /// Track user interaction with a suggestion
     pub fn track_suggestion_interaction(&mut self, suggestion_id: &str, accepted: bool, feedback: Option<String>) {
+        self.track_suggestion_interaction_with_reason(suggestion_id, accepted, feedback, None);
+    }
+
+    /// Track user interaction with a suggestion, recording why it was rejected
+    pub fn track_suggestion_interaction_with_reason(
+        &mut self,
+        suggestion_id: &str,
+        accepted: bool,
+        feedback: Option<String>,
+        reason: Option<RejectionReason>,
+    ) {
+        if !accepted {
+            if let Some(reason) = reason {
+                *self.rejection_reasons.entry(reason).or_insert(0) += 1;
+            }
+        }
+
         if accepted {
             self.session_data.suggestions_accepted += 1;
+            let hour = self.now().hour();
+            self.hourly_activity.entry(hour).or_default().suggestions_accepted += 1;
         } else {
             self.session_data.suggestions_rejected += 1;
         }
 
         // Update learning context based on interaction
         if let Some(feedback_text) = feedback {
-            self.process_feedback(suggestion_id, &feedback_text);
+            self.process_feedback(suggestion_id, &feedback_text, accepted);
         }
     }
 
     /// Track coding activity
     pub fn track_coding_activity(&mut self, language: &str, lines_written: u32, keystrokes: u32) {
+        let now = self.now();
+        self.track_coding_activity_at(language, lines_written, keystrokes, now);
+    }
+
+    /// Track coding activity that happened at a specific time
+    pub fn track_coding_activity_at(&mut self, language: &str, lines_written: u32, keystrokes: u32, at: DateTime<Utc>) {
         if !self.session_data.languages_used.contains(&language.to_string()) {
             self.session_data.languages_used.push(language.to_string());
         }
         self.session_data.lines_written += lines_written;
         self.session_data.keystrokes += keystrokes;
+        self.hourly_activity.entry(at.hour()).or_default().lines_written += lines_written;
+    }
+
+    /// Most productive hours of day, based on activity recorded across sessions
+    pub fn peak_productivity_hours(&self) -> Vec<u32> {
+        let mut hours: Vec<(u32, u32)> = self.hourly_activity
+            .iter()
+            .map(|(hour, activity)| (*hour, activity.productivity_score()))
+            .filter(|(_, score)| *score > 0)
+            .collect();
+
+        hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hours.into_iter().take(MAX_PEAK_HOURS).map(|(hour, _)| hour).collect()
     }
 
     /// Track pattern usage
@@ -438,9 +571,15 @@ impl UserBehaviorTracker {
             insights.push("You're consistently engaging with learning opportunities.".to_string());
         }
 
-        // Analyze temporal patterns
-        let current_hour = Utc::now().hour();
-        if (9..=11).contains(&current_hour) || (14..=16).contains(&current_hour) {
+        // Analyze temporal patterns, falling back to typical hours until we have data
+        let current_hour = self.now().hour();
+        let peak_hours = self.peak_productivity_hours();
+        let is_peak_hour = if peak_hours.is_empty() {
+            (9..=11).contains(&current_hour) || (14..=16).contains(&current_hour)
+        } else {
+            peak_hours.contains(&current_hour)
+        };
+        if is_peak_hour {
             insights.push("This appears to be one of your productive coding hours.".to_string());
         }
 
@@ -470,21 +609,72 @@ impl UserBehaviorTracker {
 
     /// Delete user data (GDPR compliance)
     pub fn delete_user_data(&mut self) {
-        self.session_data = SessionData::new();
+        self.session_data = SessionData::new(self.now());
         self.pattern_preferences = PatternPreferences::default();
         self.learning_context = LearningContext::default();
+        self.hourly_activity.clear();
+        self.rejection_reasons.clear();
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    fn process_feedback(&mut self, _suggestion_id: &str, feedback: &str, accepted: bool) {
+        // Only rejections tell us about friction points
+        if accepted {
+            return;
+        }
+
+        let mistake_type = classify_feedback(feedback);
+        // Privacy-conscious: keep the user's words only as context, never the code
+        let context = feedback.trim().to_string();
+
+        if let Some(mistake) = self.learning_context.recent_mistakes
+            .iter_mut()
+            .find(|m| m.pattern_type == mistake_type) {
+            mistake.frequency += 1;
+            mistake.context = context;
+        } else {
+            self.learning_context.recent_mistakes.push(MistakePattern {
+                pattern_type: mistake_type.to_string(),
+                frequency: 1,
+                context,
+                correction_time: Duration::zero(),
+                learning_progress: 0.0,
+            });
+        }
+
+        if !self.learning_context.improvement_areas.iter().any(|a| a == mistake_type) {
+            self.learning_context.improvement_areas.push(mistake_type.to_string());
+        }
     }
 
-    fn process_feedback(&mut self, _suggestion_id: &str, _feedback: &str) {
-        // Process user feedback to improve future suggestions
-        // This would involve sentiment analysis and pattern recognition
+    fn analyze_error_prone_patterns(&self) -> Vec<ErrorPronePattern> {
+        let mut patterns: Vec<ErrorPronePattern> = self.learning_context.recent_mistakes
+            .iter()
+            .map(|mistake| ErrorPronePattern {
+                pattern_description: format!("Frequently rejected {} suggestions", mistake.pattern_type),
+                frequency: mistake.frequency,
+                severity: match mistake.frequency {
+                    0..=2 => ErrorSeverity::Low,
+                    3..=5 => ErrorSeverity::Medium,
+                    6..=10 => ErrorSeverity::High,
+                    _ => ErrorSeverity::Critical,
+                },
+                suggested_alternatives: suggested_alternatives_for(&mistake.pattern_type),
+            })
+            .collect();
+
+        patterns.sort_by_key(|p| std::cmp::Reverse(p.frequency));
+        patterns
     }
 
     fn analyze_coding_patterns(&self) -> CodingPatterns {
         CodingPatterns {
             most_used_patterns: HashMap::new(), // Would be populated from tracked data
             temporal_patterns: TemporalPatterns {
-                peak_productivity_hours: vec![9, 10, 14, 15], // Example data
+                peak_productivity_hours: self.peak_productivity_hours(),
                 weekly_patterns: HashMap::new(),
                 session_length_patterns: vec![Duration::minutes(45)],
                 break_patterns: vec![Duration::minutes(15)],
@@ -499,7 +689,7 @@ impl UserBehaviorTracker {
                     communication_patterns: Vec::new(),
                 },
             },
-            error_prone_patterns: Vec::new(),
+            error_prone_patterns: self.analyze_error_prone_patterns(),
             productivity_patterns: ProductivityPatterns {
                 fast_completion_patterns: Vec::new(),
                 slow_completion_patterns: Vec::new(),
@@ -549,7 +739,7 @@ impl UserBehaviorTracker {
     }
 
     fn analyze_context(&self) -> ContextAwareness {
-        let now = Utc::now();
+        let now = self.now();
         
         ContextAwareness {
             project_context: ProjectContext {
@@ -591,7 +781,7 @@ impl UserBehaviorTracker {
 
         SuggestionFeedback {
             acceptance_rate,
-            rejection_reasons: HashMap::new(), // Would track specific reasons
+            rejection_reasons: self.rejection_reasons.clone(),
             feedback_quality: FeedbackQuality {
                 relevance_score: 0.8,
                 timeliness_score: 0.9,
@@ -607,16 +797,46 @@ impl UserBehaviorTracker {
 
     fn clear_tracking_data(&mut self) {
         // Clear all tracking data while preserving essential functionality
-        self.session_data = SessionData::new();
+        self.session_data = SessionData::new(self.now());
         self.learning_context = LearningContext::default();
+        self.hourly_activity.clear();
+        self.rejection_reasons.clear();
+    }
+}
+
+/// Classify free-form rejection feedback into a mistake category
+fn classify_feedback(feedback: &str) -> &'static str {
+    let text = feedback.to_lowercase();
+    let contains_any = |keywords: &[&str]| keywords.iter().any(|k| text.contains(k));
+
+    if contains_any(&["name", "naming", "rename", "identifier", "variable"]) {
+        "naming"
+    } else if contains_any(&["style", "format", "indent", "spacing", "brace", "whitespace"]) {
+        "style"
+    } else if contains_any(&["relevant", "unrelated", "context", "useless", "not needed", "unnecessary"]) {
+        "irrelevant"
+    } else if contains_any(&["wrong", "incorrect", "bug", "error", "broken", "doesn't work"]) {
+        "incorrect"
+    } else {
+        "other"
+    }
+}
+
+fn suggested_alternatives_for(mistake_type: &str) -> Vec<String> {
+    match mistake_type {
+        "naming" => vec!["Learn naming conventions from the current file".to_string()],
+        "style" => vec!["Match the detected formatting style of the project".to_string()],
+        "irrelevant" => vec!["Raise the context relevance threshold".to_string()],
+        "incorrect" => vec!["Require higher confidence before suggesting".to_string()],
+        _ => Vec::new(),
     }
 }
 
 impl SessionData {
-    fn new() -> Self {
+    fn new(start_time: DateTime<Utc>) -> Self {
         Self {
             session_id: uuid::Uuid::new_v4().to_string(),
-            start_time: Utc::now(),
+            start_time,
             end_time: None,
             active_duration: Duration::zero(),
             languages_used: Vec::new(),
@@ -679,6 +899,56 @@ impl Default for PrivacySettings {
     }
 }
 
+/// JavaScript-facing session tracker; timestamps come from `js_sys::Date` via `SystemClock`
+#[wasm_bindgen]
+pub struct WasmBehaviorTracker {
+    tracker: UserBehaviorTracker,
+}
+
+#[wasm_bindgen]
+impl WasmBehaviorTracker {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmBehaviorTracker {
+        WasmBehaviorTracker {
+            tracker: UserBehaviorTracker::with_clock(Arc::new(SystemClock)),
+        }
+    }
+
+    /// Start a new session and return its id
+    #[wasm_bindgen]
+    pub fn start_session(&mut self) -> String {
+        self.tracker.start_session()
+    }
+
+    #[wasm_bindgen]
+    pub fn track_activity(&mut self, language: &str, lines: u32, keystrokes: u32) {
+        self.tracker.track_coding_activity(language, lines, keystrokes);
+    }
+
+    #[wasm_bindgen]
+    pub fn track_suggestion(&mut self, id: &str, accepted: bool, feedback: Option<String>) {
+        self.tracker.track_suggestion_interaction(id, accepted, feedback);
+    }
+
+    /// Current `BehaviorAnalysis` as JSON
+    #[wasm_bindgen]
+    pub fn analyze_json(&self) -> String {
+        serde_json::to_string(&self.tracker.analyze_behavior()).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Full tracked data as JSON, subject to the privacy settings
+    #[wasm_bindgen]
+    pub fn export_json(&self) -> Result<String, JsValue> {
+        self.tracker.export_user_data().map_err(|e| JsValue::from_str(&e))
+    }
+}
+
+impl Default for WasmBehaviorTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -829,4 +1099,189 @@ mod tests {
         assert!(analysis.context_awareness.temporal_context.time_of_day < 24);
         assert!(analysis.context_awareness.temporal_context.day_of_week < 7);
     }
+
+    #[test]
+    fn test_rejection_feedback_creates_mistake_patterns() {
+        let mut tracker = UserBehaviorTracker::new();
+        tracker.start_session();
+
+        tracker.track_suggestion_interaction("s1", false, Some("Bad variable name".to_string()));
+        tracker.track_suggestion_interaction("s2", false, Some("please rename this differently".to_string()));
+        tracker.track_suggestion_interaction("s3", false, Some("Wrong indent style".to_string()));
+        tracker.track_suggestion_interaction("s4", false, Some("not relevant here".to_string()));
+
+        let mistakes = &tracker.learning_context.recent_mistakes;
+        let frequency_of = |kind: &str| mistakes.iter().find(|m| m.pattern_type == kind).map(|m| m.frequency);
+        assert_eq!(frequency_of("naming"), Some(2));
+        assert_eq!(frequency_of("style"), Some(1));
+        assert_eq!(frequency_of("irrelevant"), Some(1));
+        assert_eq!(mistakes.len(), 3);
+    }
+
+    #[test]
+    fn test_accepted_feedback_is_not_a_mistake() {
+        let mut tracker = UserBehaviorTracker::new();
+        tracker.track_suggestion_interaction("s1", true, Some("nice naming".to_string()));
+        assert!(tracker.learning_context.recent_mistakes.is_empty());
+    }
+
+    #[test]
+    fn test_error_prone_patterns_surface_recurring_mistakes() {
+        let mut tracker = UserBehaviorTracker::new();
+        for i in 0..4 {
+            tracker.track_suggestion_interaction(&format!("s{}", i), false, Some("formatting is off".to_string()));
+        }
+        tracker.track_suggestion_interaction("s9", false, Some("meh".to_string()));
+
+        let analysis = tracker.analyze_behavior();
+        let error_prone = &analysis.coding_patterns.error_prone_patterns;
+        assert_eq!(error_prone.len(), 2);
+        assert_eq!(error_prone[0].frequency, 4);
+        assert!(error_prone[0].pattern_description.contains("style"));
+        assert!(matches!(error_prone[0].severity, ErrorSeverity::Medium));
+        assert!(error_prone[1].pattern_description.contains("other"));
+    }
+
+    fn at_hour(hour: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(2024, 3, 4, hour, 15, 0).unwrap()
+    }
+
+    #[test]
+    fn test_peak_productivity_hours_from_activity() {
+        let mut tracker = UserBehaviorTracker::new();
+        tracker.start_session();
+        tracker.track_coding_activity_at("rust", 120, 600, at_hour(22));
+        tracker.track_coding_activity_at("rust", 80, 400, at_hour(23));
+        tracker.end_session();
+
+        // Activity accumulates across sessions
+        tracker.start_session();
+        tracker.track_coding_activity_at("rust", 50, 250, at_hour(22));
+        tracker.track_coding_activity_at("rust", 5, 25, at_hour(9));
+
+        let peaks = tracker.peak_productivity_hours();
+        assert_eq!(peaks[0], 22);
+        assert_eq!(peaks[1], 23);
+        assert_eq!(peaks.len(), 3);
+
+        let analysis = tracker.analyze_behavior();
+        assert_eq!(analysis.coding_patterns.temporal_patterns.peak_productivity_hours, peaks);
+    }
+
+    #[test]
+    fn test_peak_productivity_hours_capped_and_cleared() {
+        let mut tracker = UserBehaviorTracker::new();
+        assert!(tracker.peak_productivity_hours().is_empty());
+
+        for hour in 0..8 {
+            tracker.track_coding_activity_at("python", 10 + hour, 50, at_hour(hour));
+        }
+        assert_eq!(tracker.peak_productivity_hours(), vec![7, 6, 5, 4]);
+
+        tracker.delete_user_data();
+        assert!(tracker.peak_productivity_hours().is_empty());
+    }
+
+    #[test]
+    fn test_mock_clock_drives_session_duration() {
+        let clock = Arc::new(MockClock::new(at_hour(10)));
+        let mut tracker = UserBehaviorTracker::with_clock(clock.clone());
+
+        tracker.start_session();
+        clock.advance(Duration::minutes(90));
+        tracker.end_session();
+
+        assert_eq!(tracker.session_data.start_time, at_hour(10));
+        assert_eq!(tracker.session_data.active_duration, Duration::minutes(90));
+    }
+
+    #[test]
+    fn test_personalized_insights_temporal_branches() {
+        let productive_hour_insight = "This appears to be one of your productive coding hours.";
+        let clock = Arc::new(MockClock::new(at_hour(10)));
+        let mut tracker = UserBehaviorTracker::with_clock(clock.clone());
+
+        // Without recorded activity the typical working hours are used
+        assert!(tracker.get_personalized_insights().iter().any(|i| i == productive_hour_insight));
+        clock.set(at_hour(21));
+        assert!(!tracker.get_personalized_insights().iter().any(|i| i == productive_hour_insight));
+
+        // Once activity is recorded, the user's own peak hours take over
+        tracker.track_coding_activity("rust", 200, 1000);
+        assert!(tracker.get_personalized_insights().iter().any(|i| i == productive_hour_insight));
+        clock.set(at_hour(10));
+        assert!(!tracker.get_personalized_insights().iter().any(|i| i == productive_hour_insight));
+
+        let analysis = tracker.analyze_behavior();
+        assert_eq!(analysis.context_awareness.temporal_context.time_of_day, 10);
+        assert_eq!(analysis.context_awareness.temporal_context.day_of_week, 1); // 2024-03-04 is a Monday
+    }
+
+    #[test]
+    fn test_wasm_behavior_tracker_session() {
+        let mut tracker = WasmBehaviorTracker::new();
+        assert!(!tracker.start_session().is_empty());
+
+        tracker.track_activity("rust", 12, 340);
+        tracker.track_activity("python", 3, 60);
+        tracker.track_suggestion("s1", true, None);
+        tracker.track_suggestion("s2", false, Some("not relevant".to_string()));
+
+        let exported: serde_json::Value = serde_json::from_str(&tracker.export_json().unwrap()).unwrap();
+        let session = &exported["session_data"];
+        assert_eq!(session["lines_written"], 15);
+        assert_eq!(session["keystrokes"], 400);
+        assert_eq!(session["suggestions_accepted"], 1);
+        assert_eq!(session["suggestions_rejected"], 1);
+        assert_eq!(session["languages_used"], serde_json::json!(["rust", "python"]));
+
+        let analysis: serde_json::Value = serde_json::from_str(&tracker.analyze_json()).unwrap();
+        assert!(analysis.get("suggestion_feedback").is_some());
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn test_wasm_behavior_tracker_session_in_javascript() {
+        let before = js_sys::Date::now() as i64;
+        let mut tracker = WasmBehaviorTracker::new();
+        tracker.start_session();
+        tracker.track_activity("typescript", 20, 500);
+        tracker.track_suggestion("s1", true, None);
+        tracker.track_suggestion("s2", true, Some("handy".to_string()));
+
+        let exported: serde_json::Value = serde_json::from_str(&tracker.export_json().unwrap()).unwrap();
+        let session = &exported["session_data"];
+        assert_eq!(session["lines_written"], 20);
+        assert_eq!(session["keystrokes"], 500);
+        assert_eq!(session["suggestions_accepted"], 2);
+        assert_eq!(session["languages_used"], serde_json::json!(["typescript"]));
+
+        // Timestamps come from `js_sys::Date`, not a native clock
+        let start: DateTime<Utc> = serde_json::from_value(session["start_time"].clone()).unwrap();
+        assert!(start.timestamp_millis() >= before);
+        assert!(start.timestamp_millis() <= js_sys::Date::now() as i64);
+    }
+
+    #[test]
+    fn test_rejection_reasons_are_aggregated() {
+        let mut tracker = UserBehaviorTracker::new();
+        tracker.start_session();
+
+        tracker.track_suggestion_interaction_with_reason("s1", false, None, Some(RejectionReason::Irrelevant));
+        tracker.track_suggestion_interaction_with_reason("s2", false, None, Some(RejectionReason::WrongStyle));
+        tracker.track_suggestion_interaction_with_reason("s3", false, Some("again".to_string()), Some(RejectionReason::Irrelevant));
+        tracker.track_suggestion_interaction_with_reason("s4", false, None, None);
+        // A reason on an accepted suggestion is ignored
+        tracker.track_suggestion_interaction_with_reason("s5", true, None, Some(RejectionReason::Duplicate));
+
+        let reasons = tracker.analyze_behavior().suggestion_feedback.rejection_reasons;
+        assert_eq!(reasons.len(), 2);
+        assert_eq!(reasons.get(&RejectionReason::Irrelevant), Some(&2));
+        assert_eq!(reasons.get(&RejectionReason::WrongStyle), Some(&1));
+        assert_eq!(tracker.session_data.suggestions_rejected, 4);
+
+        tracker.delete_user_data();
+        assert!(tracker.analyze_behavior().suggestion_feedback.rejection_reasons.is_empty());
+    }
 }
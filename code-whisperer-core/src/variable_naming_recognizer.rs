@@ -1,11 +1,105 @@
 use crate::ast_parser::{AstParser, ParsedAst};
-use std::collections::HashMap;
+use crate::error::AnalysisError;
+use crate::exception_handling::python_child_blocks;
+use std::collections::{HashMap, HashSet};
 use regex::Regex;
 use lazy_static::lazy_static;
+use quote::ToTokens;
+use rustpython_parser::ast::{self as py_ast, Ranged};
+use serde::Serialize;
+use swc_common::Spanned;
+use swc_ecma_ast as swc_ast;
 
 /// Variable naming pattern recognition system
 pub struct VariableNamingRecognizer {
     parser: AstParser,
+    semantic_patterns: Vec<(SemanticCategory, Regex)>,
+}
+
+/// One of the `SemanticCategories` buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SemanticCategory {
+    DataContainers,
+    StateVariables,
+    CountersIterators,
+    FlagsBooleans,
+    Configuration,
+    Identifiers,
+    TemporalVariables,
+    SizeDimensions,
+}
+
+impl SemanticCategory {
+    pub const ALL: [SemanticCategory; 8] = [
+        SemanticCategory::DataContainers,
+        SemanticCategory::StateVariables,
+        SemanticCategory::CountersIterators,
+        SemanticCategory::FlagsBooleans,
+        SemanticCategory::Configuration,
+        SemanticCategory::Identifiers,
+        SemanticCategory::TemporalVariables,
+        SemanticCategory::SizeDimensions,
+    ];
+
+    fn default_pattern(self) -> &'static Regex {
+        match self {
+            SemanticCategory::DataContainers => &DATA_CONTAINER_PATTERNS,
+            SemanticCategory::StateVariables => &STATE_PATTERNS,
+            SemanticCategory::CountersIterators => &COUNTER_PATTERNS,
+            SemanticCategory::FlagsBooleans => &FLAG_PATTERNS,
+            SemanticCategory::Configuration => &CONFIG_PATTERNS,
+            SemanticCategory::Identifiers => &ID_PATTERNS,
+            SemanticCategory::TemporalVariables => &TEMPORAL_PATTERNS,
+            SemanticCategory::SizeDimensions => &SIZE_PATTERNS,
+        }
+    }
+}
+
+/// Per-category vocabulary overrides for domain-specific names. Patterns are matched against
+/// lowercased variable names; categories left alone keep the built-in pattern
+#[derive(Debug, Clone, Default)]
+pub struct SemanticPatternConfig {
+    overrides: HashMap<SemanticCategory, String>,
+    extensions: HashMap<SemanticCategory, Vec<String>>,
+}
+
+impl SemanticPatternConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the built-in pattern for `category`
+    pub fn override_pattern(mut self, category: SemanticCategory, pattern: &str) -> Self {
+        self.overrides.insert(category, pattern.to_string());
+        self
+    }
+
+    /// Also match `pattern` for `category`, alongside the built-in or overridden pattern
+    pub fn extend_pattern(mut self, category: SemanticCategory, pattern: &str) -> Self {
+        self.extensions.entry(category).or_default().push(pattern.to_string());
+        self
+    }
+
+    fn compile(&self) -> Result<Vec<(SemanticCategory, Regex)>, regex::Error> {
+        SemanticCategory::ALL
+            .iter()
+            .map(|&category| {
+                let extensions = self.extensions.get(&category).map(Vec::as_slice).unwrap_or_default();
+                let regex = match (self.overrides.get(&category), extensions.is_empty()) {
+                    (None, true) => category.default_pattern().clone(),
+                    (base, _) => {
+                        let base = base.map(String::as_str).unwrap_or_else(|| category.default_pattern().as_str());
+                        let alternatives: Vec<String> = std::iter::once(base)
+                            .chain(extensions.iter().map(String::as_str))
+                            .map(|pattern| format!("(?:{})", pattern))
+                            .collect();
+                        Regex::new(&alternatives.join("|"))?
+                    }
+                };
+                Ok((category, regex))
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +110,31 @@ pub struct VariableNamingAnalysis {
     pub scope_based_patterns: ScopeBasedPatterns,
     pub type_based_patterns: TypeBasedPatterns,
     pub consistency_metrics: NamingConsistencyMetrics,
+    pub naming_smells: Vec<NamingSmell>,
+    pub boolean_naming_violations: Vec<NamingSmell>,
+    pub collection_naming_violations: Vec<NamingSmell>,
+}
+
+/// A name worth renaming, e.g. a single letter living well beyond a tight loop
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamingSmell {
+    pub name: String,
+    pub line_number: u32,
+    pub reason: String,
+}
+
+/// The style a file should settle on, and the renames that would get it there
+#[derive(Debug, Clone)]
+pub struct CanonicalStyleSuggestion {
+    pub style: crate::pattern_extractor::NamingStyle,
+    pub renames: Vec<NameRename>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameRename {
+    pub name: String,
+    pub line_number: u32,
+    pub suggested: String,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +159,34 @@ pub struct SemanticCategories {
     pub size_dimensions: HashMap<String, u32>,    // width, height, size, length, count
 }
 
+impl SemanticCategories {
+    pub fn names(&self, category: SemanticCategory) -> &HashMap<String, u32> {
+        match category {
+            SemanticCategory::DataContainers => &self.data_containers,
+            SemanticCategory::StateVariables => &self.state_variables,
+            SemanticCategory::CountersIterators => &self.counters_iterators,
+            SemanticCategory::FlagsBooleans => &self.flags_booleans,
+            SemanticCategory::Configuration => &self.configuration,
+            SemanticCategory::Identifiers => &self.identifiers,
+            SemanticCategory::TemporalVariables => &self.temporal_variables,
+            SemanticCategory::SizeDimensions => &self.size_dimensions,
+        }
+    }
+
+    fn names_mut(&mut self, category: SemanticCategory) -> &mut HashMap<String, u32> {
+        match category {
+            SemanticCategory::DataContainers => &mut self.data_containers,
+            SemanticCategory::StateVariables => &mut self.state_variables,
+            SemanticCategory::CountersIterators => &mut self.counters_iterators,
+            SemanticCategory::FlagsBooleans => &mut self.flags_booleans,
+            SemanticCategory::Configuration => &mut self.configuration,
+            SemanticCategory::Identifiers => &mut self.identifiers,
+            SemanticCategory::TemporalVariables => &mut self.temporal_variables,
+            SemanticCategory::SizeDimensions => &mut self.size_dimensions,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AbbreviationPatterns {
     pub common_abbreviations: HashMap<String, String>, // btn -> button
@@ -105,15 +252,59 @@ pub struct NamingConsistencyMetrics {
     pub style_consistency: f32,        // 0-1, how consistent naming style is
     pub semantic_consistency: f32,     // 0-1, how consistent semantic patterns are
     pub abbreviation_consistency: f32, // 0-1, consistency in abbreviation usage
-    pub scope_consistency: f32,        // 0-1, consistency across different scopes
+    pub scope_consistency: f32,        // 0-1, style agreement within each scope, weighted by scope size
     pub overall_consistency: f32,      // 0-1, overall naming consistency
+    pub sample_size: usize,            // distinct names the scores were computed from
+    pub reliability: Reliability,
 }
 
+// Distinct names a consistency score needs before it counts as medium, then high, reliability
+const MEDIUM_RELIABILITY_SAMPLE: usize = 10;
+const HIGH_RELIABILITY_SAMPLE: usize = 30;
+
+/// How far a consistency score can be trusted given its sample: a perfect score over two
+/// identifiers says little about the author's habits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Reliability {
+    Low,
+    Medium,
+    High,
+}
+
+impl Reliability {
+    pub fn from_sample_size(sample_size: usize) -> Self {
+        if sample_size >= HIGH_RELIABILITY_SAMPLE {
+            Reliability::High
+        } else if sample_size >= MEDIUM_RELIABILITY_SAMPLE {
+            Reliability::Medium
+        } else {
+            Reliability::Low
+        }
+    }
+
+    /// Factor for discounting a score by its reliability, e.g. when ranking
+    pub fn weight(&self) -> f32 {
+        match self {
+            Reliability::Low => 0.5,
+            Reliability::Medium => 0.8,
+            Reliability::High => 1.0,
+        }
+    }
+}
+
+// Loop bodies up to this many lines may use single-letter counters
+const MAX_SHORT_LOOP_BODY_LINES: usize = 10;
+
 lazy_static! {
+    // Single-letter loop counters and declarations
+    static ref LOOP_COUNTER: Regex = Regex::new(r"\bfor\s*\(?\s*(?:(?:let|var|const|mut)\s+)*([A-Za-z])\b").unwrap();
+    static ref BOOLEAN_FUNCTION: Regex = Regex::new(r"\b(?:fn|def|function)\s+(\w+)\s*(?:<[^>]*>)?\([^)]*\)\s*(?:->|:)\s*(?:bool|boolean|Bool)\b").unwrap();
+    static ref SINGLE_LETTER_DECLARATION: Regex = Regex::new(r"(?:\b(?:let|const|var)\s+(?:mut\s+)?|^\s*)([A-Za-z])\s*(?::[^=]+)?=[^=>]").unwrap();
+
     // Semantic pattern regexes
     static ref DATA_CONTAINER_PATTERNS: Regex = Regex::new(r"(list|array|collection|set|map|dict|vector|queue|stack|buffer|cache|pool|batch|group|series|sequence)").unwrap();
     static ref STATE_PATTERNS: Regex = Regex::new(r"(current|previous|next|last|first|initial|final|temp|temporary|old|new|original|updated|modified|cached|stored|loaded|saved)").unwrap();
-    static ref COUNTER_PATTERNS: Regex = Regex::new(r"(count|counter|index|idx|iterator|iter|position|pos|offset|step|page|limit|size|length|len|total|sum|max|min|avg|mean)").unwrap();
+    static ref COUNTER_PATTERNS: Regex = Regex::new(r"(count|counter|num|index|idx|iterator|iter|position|pos|offset|step|page|limit|size|length|len|total|sum|max|min|avg|mean)").unwrap();
     static ref FLAG_PATTERNS: Regex = Regex::new(r"(flag|enabled|disabled|active|inactive|valid|invalid|ready|busy|loading|loaded|error|success|complete|finished|started|stopped|paused|running|waiting|pending|cancelled|expired|locked|unlocked)").unwrap();
     static ref CONFIG_PATTERNS: Regex = Regex::new(r"(config|configuration|settings|options|params|parameters|args|arguments|props|properties|attributes|metadata|data|info|details|spec|specification)").unwrap();
     static ref ID_PATTERNS: Regex = Regex::new(r"(id|identifier|key|name|title|label|tag|code|token|hash|uuid|guid|slug|handle|reference|ref|link|url|uri|path|address)").unwrap();
@@ -125,6 +316,10 @@ lazy_static! {
     
     // Array/list patterns
     static ref PLURAL_PATTERNS: Regex = Regex::new(r"(s|es|ies|ves|children|people|data|items)$").unwrap();
+    static ref COLLECTION_SUFFIX: Regex = Regex::new(r"(list|array|collection|set|vec|vector|queue|stack|batch|group)$").unwrap();
+    static ref COLLECTION_TYPE: Regex = Regex::new(r"^(vec|vecdeque|list|array|set|hashset|btreeset|sequence|iterable)\b|\[\]$|^\[").unwrap();
+    static ref MAPPING_TYPE: Regex = Regex::new(r"^(map|hashmap|btreemap|weakmap|dict|record|object)\b").unwrap();
+    static ref WRAPPER_TYPE: Regex = Regex::new(r"^(?:(?:option|box|rc|arc)\s*<|optional\[)(.*)[>\]]$").unwrap();
     
     // Common abbreviations
     static ref COMMON_ABBREVIATIONS: [(&'static str, &'static str); 50] = [
@@ -148,34 +343,186 @@ impl VariableNamingRecognizer {
     pub fn new() -> Self {
         Self {
             parser: AstParser::new(),
+            semantic_patterns: SemanticCategory::ALL.iter().map(|&category| (category, category.default_pattern().clone())).collect(),
         }
     }
 
+    /// Categorize names with `config`'s vocabulary; fails if a user pattern is not a valid regex
+    pub fn with_semantic_patterns(config: &SemanticPatternConfig) -> Result<Self, regex::Error> {
+        Ok(Self {
+            semantic_patterns: config.compile()?,
+            ..Self::new()
+        })
+    }
+
     /// Analyze variable naming patterns in source code
-    pub fn analyze_variable_naming(&self, code: &str, language: &str) -> Result<VariableNamingAnalysis, String> {
+    pub fn analyze_variable_naming(&self, code: &str, language: &str) -> Result<VariableNamingAnalysis, AnalysisError> {
         let ast = self.parser.parse_code(code, language)?;
-        let variables = self.extract_variables(&ast, language)?;
+        let variables = self.extract_variables(&ast, code, language)?;
+        let semantic_categories = self.categorize_semantics(&variables);
 
         Ok(VariableNamingAnalysis {
             naming_patterns: self.analyze_naming_patterns(&variables),
-            semantic_categories: self.categorize_semantics(&variables),
+            consistency_metrics: self.calculate_consistency_metrics(&variables, &semantic_categories),
+            semantic_categories,
             abbreviation_patterns: self.analyze_abbreviations(&variables),
             scope_based_patterns: self.analyze_scope_patterns(&variables),
             type_based_patterns: self.analyze_type_patterns(&variables),
-            consistency_metrics: self.calculate_consistency_metrics(&variables),
+            naming_smells: self.detect_single_letter_smells(code),
+            boolean_naming_violations: self.detect_boolean_naming_violations(&variables, code),
+            collection_naming_violations: self.detect_collection_naming_violations(&variables),
         })
     }
 
-    fn extract_variables(&self, ast: &ParsedAst, language: &str) -> Result<Vec<VariableInfo>, String> {
+    /// Flag collection-typed variables with singular names, and plural names on non-collection types
+    pub fn detect_collection_naming_violations(&self, variables: &[VariableInfo]) -> Vec<NamingSmell> {
+        variables
+            .iter()
+            .filter_map(|var| {
+                let var_type = var.var_type.as_deref().filter(|var_type| !is_mapping_type(var_type))?;
+                let name = var.name.to_lowercase();
+                let collection_name = looks_plural(&name) || COLLECTION_SUFFIX.is_match(&name);
+
+                let reason = if is_collection_type(var_type) {
+                    if collection_name {
+                        return None;
+                    }
+                    format!("collection `{}` of type `{}` has a singular name", var.name, var_type)
+                } else {
+                    // "data" is as often a single value as a collection
+                    if !looks_plural(&name) || name.ends_with("data") {
+                        return None;
+                    }
+                    format!("`{}` of type `{}` holds a single value but has a plural name", var.name, var_type)
+                };
+
+                Some(NamingSmell { name: var.name.clone(), line_number: var.line_number, reason })
+            })
+            .collect()
+    }
+
+    /// Flag boolean variables and boolean-returning functions whose names lack an `is`/`has`/`can`-style prefix
+    pub fn detect_boolean_naming_violations(&self, variables: &[VariableInfo], code: &str) -> Vec<NamingSmell> {
+        let has_boolean_prefix = |name: &str| BOOLEAN_PATTERNS.is_match(&name.trim_start_matches('_').to_lowercase());
+
+        let mut violations: Vec<NamingSmell> = variables
+            .iter()
+            .filter(|var| var.var_type.as_deref().is_some_and(|t| matches!(t.to_lowercase().as_str(), "bool" | "boolean")))
+            .filter(|var| !has_boolean_prefix(&var.name))
+            .map(|var| NamingSmell {
+                name: var.name.clone(),
+                line_number: var.line_number,
+                reason: format!("boolean variable `{}` lacks a prefix such as `is` or `has`", var.name),
+            })
+            .collect();
+
+        for (index, line) in code.lines().enumerate() {
+            for capture in BOOLEAN_FUNCTION.captures_iter(line) {
+                let name = &capture[1];
+                if !has_boolean_prefix(name) {
+                    violations.push(NamingSmell {
+                        name: name.to_string(),
+                        line_number: index as u32 + 1,
+                        reason: format!("function `{}` returns a boolean but lacks a prefix such as `is` or `has`", name),
+                    });
+                }
+            }
+        }
+
+        violations.sort_by_key(|violation| violation.line_number);
+        violations
+    }
+
+    /// Flag single-letter variables other than loop counters where they are first assigned, and
+    /// loop counters of loops longer than a few lines
+    pub fn detect_single_letter_smells(&self, code: &str) -> Vec<NamingSmell> {
+        let lines: Vec<&str> = code.lines().collect();
+        let mut smells = Vec::new();
+        let mut declared = HashSet::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            let line_number = index as u32 + 1;
+            let counter = LOOP_COUNTER.captures(line).map(|c| c[1].to_string());
+
+            if let Some(name) = &counter {
+                let body_lines = loop_body_lines(&lines, index);
+                if body_lines > MAX_SHORT_LOOP_BODY_LINES {
+                    smells.push(NamingSmell {
+                        name: name.clone(),
+                        line_number,
+                        reason: format!("loop counter `{}` used across a {}-line loop body", name, body_lines),
+                    });
+                }
+            }
+
+            if let Some(capture) = SINGLE_LETTER_DECLARATION.captures(line) {
+                let name = capture[1].to_string();
+                // Later assignments to the same name are reassignments, not new variables
+                if counter.as_ref() != Some(&name) && declared.insert(name.clone()) {
+                    smells.push(NamingSmell {
+                        reason: format!("single-letter variable `{}` is not a loop counter", name),
+                        name,
+                        line_number,
+                    });
+                }
+            }
+        }
+
+        smells
+    }
+
+    /// Pick the dominant camelCase/snake_case style for variable names, falling back to the
+    /// language default on a tie, and suggest a rename for every name in the other style
+    pub fn suggest_canonical_style(&self, code: &str, language: &str) -> Result<CanonicalStyleSuggestion, AnalysisError> {
+        self.parser.parse_code(code, language)?;
+        let variables = self.styled_variables(code, language);
+        let style = dominant_variable_style(&variables, language);
+
+        let renames = variables
+            .into_iter()
+            .filter(|(_, var_style)| std::mem::discriminant(var_style) != std::mem::discriminant(&style))
+            .map(|(var, _)| NameRename {
+                suggested: convert_case(&var.name, &style),
+                name: var.name,
+                line_number: var.line_number,
+            })
+            .collect();
+
+        Ok(CanonicalStyleSuggestion { style, renames })
+    }
+
+    /// The dominant variable naming style of `code`, as in `suggest_canonical_style` but without
+    /// requiring it to parse, so code still being typed gets an answer
+    pub(crate) fn detect_variable_style(&self, code: &str, language: &str) -> crate::pattern_extractor::NamingStyle {
+        dominant_variable_style(&self.styled_variables(code, language), language)
+    }
+
+    /// Distinct multi-word variable names with the casing style each shows
+    fn styled_variables(&self, code: &str, language: &str) -> Vec<(VariableInfo, crate::pattern_extractor::NamingStyle)> {
+        // Code that does not parse yet still has declarations to read off its lines
+        let variables = match self.parser.parse_code(code, language) {
+            Ok(ast) => self.extract_variables(&ast, code, language),
+            Err(_) => self.extract_generic_variables(&code.lines().map(str::to_string).collect::<Vec<_>>(), language),
+        };
+        let mut seen = HashSet::new();
+        variables
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|var| seen.insert(var.name.clone()))
+            .filter_map(|var| variable_case(&var.name).map(|style| (var, style)))
+            .collect()
+    }
+
+    fn extract_variables(&self, ast: &ParsedAst, code: &str, language: &str) -> Result<Vec<VariableInfo>, AnalysisError> {
         match ast {
             ParsedAst::JavaScript(module) => {
-                self.extract_javascript_variables(module, language)
+                self.extract_javascript_variables(module, code, language)
             },
             ParsedAst::Python(suite) => {
-                self.extract_python_variables(suite, language)
+                self.extract_python_variables(suite, code, language)
             },
             ParsedAst::Rust(items) => {
-                self.extract_rust_variables(items, language)
+                self.extract_rust_variables(items, code, language)
             },
             ParsedAst::Generic(lines) => {
                 self.extract_generic_variables(lines, language)
@@ -183,49 +530,29 @@ impl VariableNamingRecognizer {
         }
     }
 
-    fn extract_javascript_variables(&self, _module: &swc_ecma_ast::Module, language: &str) -> Result<Vec<VariableInfo>, String> {
-        // TODO: Implement JavaScript variable extraction
-        Ok(vec![
-            VariableInfo {
-                name: "userCount".to_string(),
-                var_type: Some("number".to_string()),
-                scope: VariableScope::Local,
-                line_number: 1,
-                is_constant: false,
-                language: language.to_string(),
-            }
-        ])
+    fn extract_javascript_variables(&self, module: &swc_ast::Module, code: &str, language: &str) -> Result<Vec<VariableInfo>, AnalysisError> {
+        let mut extractor = JsVariables { code, language, variables: Vec::new() };
+        for item in &module.body {
+            extractor.module_item(item);
+        }
+        Ok(extractor.variables)
     }
 
-    fn extract_python_variables(&self, _suite: &rustpython_parser::ast::Suite, language: &str) -> Result<Vec<VariableInfo>, String> {
-        // TODO: Implement Python variable extraction
-        Ok(vec![
-            VariableInfo {
-                name: "user_count".to_string(),
-                var_type: Some("int".to_string()),
-                scope: VariableScope::Local,
-                line_number: 1,
-                is_constant: false,
-                language: language.to_string(),
-            }
-        ])
+    fn extract_python_variables(&self, suite: &py_ast::Suite, code: &str, language: &str) -> Result<Vec<VariableInfo>, AnalysisError> {
+        let mut extractor = PyVariables { code, language, variables: Vec::new(), declared: HashSet::new() };
+        extractor.stmts(suite, VariableScope::Global);
+        Ok(extractor.variables)
     }
 
-    fn extract_rust_variables(&self, _items: &[syn::Item], language: &str) -> Result<Vec<VariableInfo>, String> {
-        // TODO: Implement Rust variable extraction
-        Ok(vec![
-            VariableInfo {
-                name: "user_count".to_string(),
-                var_type: Some("usize".to_string()),
-                scope: VariableScope::Local,
-                line_number: 1,
-                is_constant: false,
-                language: language.to_string(),
-            }
-        ])
+    fn extract_rust_variables(&self, items: &[syn::Item], code: &str, language: &str) -> Result<Vec<VariableInfo>, AnalysisError> {
+        let mut extractor = RustVariables { code, language, variables: Vec::new(), search_from: 0 };
+        for item in items {
+            extractor.item(item);
+        }
+        Ok(extractor.variables)
     }
 
-    fn extract_generic_variables(&self, lines: &[String], language: &str) -> Result<Vec<VariableInfo>, String> {
+    fn extract_generic_variables(&self, lines: &[String], language: &str) -> Result<Vec<VariableInfo>, AnalysisError> {
         let mut variables = Vec::new();
         
         // Simple regex patterns for common variable declarations
@@ -355,12 +682,14 @@ impl VariableNamingRecognizer {
         let mut suffixes = HashMap::new();
 
         for name in names {
-            // Extract potential prefixes (first 2-4 characters if followed by uppercase or underscore)
+            // Extract potential prefixes (first 2-4 characters if followed by uppercase or underscore).
+            // Lengths are in bytes, so split points inside a multi-byte char are skipped
             if name.len() > 3 {
                 for len in 2..=4.min(name.len() - 1) {
-                    let prefix = &name[..len];
-                    let remaining = &name[len..];
-                    
+                    let (Some(prefix), Some(remaining)) = (name.get(..len), name.get(len..)) else {
+                        continue;
+                    };
+
                     if remaining.chars().next().map_or(false, |c| c.is_uppercase() || c == '_') {
                         *prefixes.entry(prefix.to_lowercase()).or_insert(0) += 1;
                     }
@@ -370,9 +699,11 @@ impl VariableNamingRecognizer {
             // Extract potential suffixes (last 2-6 characters if preceded by lowercase or underscore)
             if name.len() > 3 {
                 for len in 2..=6.min(name.len() - 1) {
-                    let suffix = &name[name.len() - len..];
-                    let preceding = &name[..name.len() - len];
-                    
+                    let split = name.len() - len;
+                    let (Some(suffix), Some(preceding)) = (name.get(split..), name.get(..split)) else {
+                        continue;
+                    };
+
                     if preceding.chars().last().map_or(false, |c| c.is_lowercase() || c == '_') {
                         *suffixes.entry(suffix.to_lowercase()).or_insert(0) += 1;
                     }
@@ -445,30 +776,12 @@ impl VariableNamingRecognizer {
 
         for var in variables {
             let name_lower = var.name.to_lowercase();
-            
-            if DATA_CONTAINER_PATTERNS.is_match(&name_lower) {
-                *categories.data_containers.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if STATE_PATTERNS.is_match(&name_lower) {
-                *categories.state_variables.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if COUNTER_PATTERNS.is_match(&name_lower) {
-                *categories.counters_iterators.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if FLAG_PATTERNS.is_match(&name_lower) || BOOLEAN_PATTERNS.is_match(&name_lower) {
-                *categories.flags_booleans.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if CONFIG_PATTERNS.is_match(&name_lower) {
-                *categories.configuration.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if ID_PATTERNS.is_match(&name_lower) {
-                *categories.identifiers.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if TEMPORAL_PATTERNS.is_match(&name_lower) {
-                *categories.temporal_variables.entry(var.name.clone()).or_insert(0) += 1;
-            }
-            if SIZE_PATTERNS.is_match(&name_lower) {
-                *categories.size_dimensions.entry(var.name.clone()).or_insert(0) += 1;
+
+            for (category, pattern) in &self.semantic_patterns {
+                let boolean_prefix = *category == SemanticCategory::FlagsBooleans && BOOLEAN_PATTERNS.is_match(&name_lower);
+                if pattern.is_match(&name_lower) || boolean_prefix {
+                    *categories.names_mut(*category).entry(var.name.clone()).or_insert(0) += 1;
+                }
             }
         }
 
@@ -586,12 +899,14 @@ impl VariableNamingRecognizer {
             common_names.insert(var.name.clone(), 1);
             
             // Extract prefixes and suffixes
-            if var.name.len() > 3 {
-                let prefix = &var.name[..3];
-                *prefix_usage.entry(prefix.to_string()).or_insert(0) += 1;
-                
-                let suffix = &var.name[var.name.len().saturating_sub(3)..];
-                *suffix_usage.entry(suffix.to_string()).or_insert(0) += 1;
+            // Counted in chars, so names like `is日x` don't split a multi-byte char
+            let chars: Vec<char> = var.name.chars().collect();
+            if chars.len() > 3 {
+                let prefix: String = chars[..3].iter().collect();
+                *prefix_usage.entry(prefix).or_insert(0) += 1;
+
+                let suffix: String = chars[chars.len() - 3..].iter().collect();
+                *suffix_usage.entry(suffix).or_insert(0) += 1;
             }
         }
 
@@ -603,7 +918,7 @@ impl VariableNamingRecognizer {
         }
     }
 
-    fn calculate_consistency_metrics(&self, variables: &[VariableInfo]) -> NamingConsistencyMetrics {
+    fn calculate_consistency_metrics(&self, variables: &[VariableInfo], categories: &SemanticCategories) -> NamingConsistencyMetrics {
         let names: Vec<String> = variables.iter().map(|v| v.name.clone()).collect();
         
         // Calculate style consistency
@@ -611,12 +926,13 @@ impl VariableNamingRecognizer {
         let max_style_percentage = style_distribution.values().fold(0.0f32, |max, &val| max.max(val));
         let style_consistency = max_style_percentage;
 
-        // Placeholder calculations for other metrics
-        let semantic_consistency = 0.8;
+        let semantic_consistency = self.calculate_semantic_consistency(categories);
+        // Placeholder calculation
         let abbreviation_consistency = 0.75;
-        let scope_consistency = 0.85;
+        let scope_consistency = self.calculate_scope_consistency(variables);
         
         let overall_consistency = (style_consistency + semantic_consistency + abbreviation_consistency + scope_consistency) / 4.0;
+        let sample_size = names.iter().collect::<HashSet<_>>().len();
 
         NamingConsistencyMetrics {
             style_consistency,
@@ -624,6 +940,755 @@ impl VariableNamingRecognizer {
             abbreviation_consistency,
             scope_consistency,
             overall_consistency,
+            sample_size,
+            reliability: Reliability::from_sample_size(sample_size),
+        }
+    }
+
+    /// How uniformly each semantic category words its names: counters that all say `count`
+    /// score 1.0, a mix of `count`, `num` and `total` scores lower. Categories with fewer than
+    /// two names have nothing to agree on and are skipped
+    fn calculate_semantic_consistency(&self, categories: &SemanticCategories) -> f32 {
+        let mut judged = 0;
+        let mut consistent = 0;
+        for (category, pattern) in &self.semantic_patterns {
+            let names = categories.names(*category);
+            // The last matching word is usually the head noun (`pageCount` is a count, not a page).
+            // Flags may be categorized by their `is`/`has` prefix alone; those have no marker word
+            let mut markers: HashMap<String, u32> = HashMap::new();
+            for (name, count) in names {
+                if let Some(marker) = pattern.find_iter(&name.to_lowercase()).last() {
+                    *markers.entry(marker.as_str().to_string()).or_insert(0) += count;
+                }
+            }
+            let total: u32 = markers.values().sum();
+            if total < 2 {
+                continue;
+            }
+            judged += total;
+            consistent += markers.values().max().copied().unwrap_or(0);
+        }
+
+        if judged == 0 {
+            1.0
+        } else {
+            consistent as f32 / judged as f32
+        }
+    }
+
+    /// Share of names following their scope's dominant style, over all scopes. Single-word
+    /// lowercase names (`total`) fit camelCase and snake_case alike, so they are not counted
+    fn calculate_scope_consistency(&self, variables: &[VariableInfo]) -> f32 {
+        let mut scopes: HashMap<VariableScope, Vec<String>> = HashMap::new();
+        for variable in variables.iter().filter(|v| shows_word_separation(&v.name)) {
+            scopes.entry(variable.scope).or_default().push(variable.name.clone());
+        }
+
+        let classified: usize = scopes.values().map(Vec::len).sum();
+        if classified == 0 {
+            return 1.0;
+        }
+        let consistent: f32 = scopes.values()
+            .map(|names| {
+                let dominant_share = self.calculate_style_distribution(names).values().fold(0.0f32, |max, &share| max.max(share));
+                dominant_share * names.len() as f32
+            })
+            .sum();
+        consistent / classified as f32
+    }
+}
+
+/// Whether a name reveals its casing style: it joins words with `_` or an inner capital
+fn shows_word_separation(name: &str) -> bool {
+    let inner = name.trim_matches('_');
+    inner.contains('_') || inner.chars().skip(1).any(|c| c.is_uppercase())
+}
+
+/// CamelCase or SnakeCase for multi-word variable names; single words, PascalCase types
+/// and SCREAMING constants read the same under either style and return `None`
+fn variable_case(name: &str) -> Option<crate::pattern_extractor::NamingStyle> {
+    use crate::pattern_extractor::NamingStyle;
+
+    let inner = name.trim_matches('_');
+    if !shows_word_separation(name) || !inner.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return None;
+    }
+    match (inner.contains('_'), inner.chars().any(|c| c.is_uppercase())) {
+        (false, true) => Some(NamingStyle::CamelCase),
+        (true, false) => Some(NamingStyle::SnakeCase),
+        _ => None,
+    }
+}
+
+/// CamelCase or SnakeCase, whichever more of `variables` use; ties go to the language's convention
+fn dominant_variable_style(
+    variables: &[(VariableInfo, crate::pattern_extractor::NamingStyle)],
+    language: &str,
+) -> crate::pattern_extractor::NamingStyle {
+    use crate::pattern_extractor::NamingStyle;
+
+    let camel = variables.iter().filter(|(_, style)| matches!(style, NamingStyle::CamelCase)).count();
+    let snake = variables.len() - camel;
+    match camel.cmp(&snake) {
+        std::cmp::Ordering::Greater => NamingStyle::CamelCase,
+        std::cmp::Ordering::Less => NamingStyle::SnakeCase,
+        std::cmp::Ordering::Equal => match language.to_lowercase().as_str() {
+            "rust" | "python" => NamingStyle::SnakeCase,
+            _ => NamingStyle::CamelCase,
+        },
+    }
+}
+
+/// Re-join the words of a camelCase or snake_case name in `style`, keeping leading underscores
+pub(crate) fn convert_case(name: &str, style: &crate::pattern_extractor::NamingStyle) -> String {
+    let inner = name.trim_start_matches('_');
+    let leading = &name[..name.len() - inner.len()];
+
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for c in inner.chars() {
+        if c == '_' {
+            words.push(String::new());
+        } else {
+            let boundary = c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+            if boundary || words.is_empty() {
+                words.push(String::new());
+            }
+            if let Some(word) = words.last_mut() {
+                word.extend(c.to_lowercase());
+            }
+        }
+        previous = Some(c);
+    }
+    words.retain(|word| !word.is_empty());
+
+    let joined = match style {
+        crate::pattern_extractor::NamingStyle::SnakeCase => words.join("_"),
+        _ => words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) if index > 0 => first.to_uppercase().chain(chars).collect(),
+                    _ => word.clone(),
+                }
+            })
+            .collect(),
+    };
+    format!("{}{}", leading, joined)
+}
+
+/// Plural by `PLURAL_PATTERNS`, excluding singular words that merely end in `s` (class, status, analysis)
+fn looks_plural(name: &str) -> bool {
+    PLURAL_PATTERNS.is_match(name) && !["ss", "us", "is"].iter().any(|ending| name.ends_with(ending))
+}
+
+fn is_collection_type(var_type: &str) -> bool {
+    COLLECTION_TYPE.is_match(&contained_type(var_type))
+}
+
+/// Maps are named for what they index (`config`, `priceBySku`), so neither number rule applies
+fn is_mapping_type(var_type: &str) -> bool {
+    MAPPING_TYPE.is_match(&contained_type(var_type))
+}
+
+/// `var_type` lowercased, without whitespace, references or `Option`/`Box`/`Rc`/`Arc`/`Optional` wrappers
+fn contained_type(var_type: &str) -> String {
+    let mut normalized = var_type.trim().to_lowercase();
+    loop {
+        let unwrapped = match normalized.strip_prefix('&') {
+            Some(referenced) => referenced.trim_start().strip_prefix("mut ").unwrap_or(referenced),
+            None => match WRAPPER_TYPE.captures(&normalized).and_then(|capture| capture.get(1)) {
+                Some(inner) => inner.as_str(),
+                None => return normalized.chars().filter(|c| !c.is_whitespace()).collect(),
+            },
+        };
+        normalized = unwrapped.trim().to_string();
+    }
+}
+
+/// Non-blank lines in the body of the loop starting at `start`, by braces or else by indentation
+fn loop_body_lines(lines: &[&str], start: usize) -> usize {
+    let header = lines[start];
+    if header.contains('{') {
+        let mut depth = 0i32;
+        for (offset, line) in lines[start..].iter().enumerate() {
+            depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            // A loop written on one line has no body lines of its own
+            if depth <= 0 {
+                return lines[(start + 1).min(start + offset)..start + offset]
+                    .iter()
+                    .filter(|l| !l.trim().is_empty())
+                    .count();
+            }
+        }
+        return lines[start + 1..].iter().filter(|l| !l.trim().is_empty()).count();
+    }
+
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let header_indent = indent(header);
+    lines[start + 1..]
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .take_while(|l| indent(l) > header_indent)
+        .count()
+}
+
+/// 1-based line holding the byte at `offset`
+fn line_at(code: &str, offset: usize) -> u32 {
+    code.get(..offset).unwrap_or(code).matches('\n').count() as u32 + 1
+}
+
+/// The type a JavaScript initializer evidently has, for the literals that settle a boolean or array
+fn js_literal_type(expr: &swc_ast::Expr) -> Option<String> {
+    use swc_ast::{BinaryOp, Expr};
+    let var_type = match expr {
+        Expr::Lit(swc_ast::Lit::Bool(_)) => "boolean",
+        Expr::Unary(unary) if unary.op == swc_ast::UnaryOp::Bang => "boolean",
+        Expr::Bin(binary) if matches!(
+            binary.op,
+            BinaryOp::EqEq | BinaryOp::NotEq | BinaryOp::EqEqEq | BinaryOp::NotEqEq
+                | BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq | BinaryOp::InstanceOf | BinaryOp::In
+        ) => "boolean",
+        Expr::Array(_) => "Array",
+        Expr::New(new) => match &*new.callee {
+            Expr::Ident(ident) if matches!(&*ident.sym, "Array" | "Set") => &*ident.sym,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(var_type.to_string())
+}
+
+/// Declarations in a JavaScript or TypeScript module: top-level bindings are globals, function
+/// bodies hold locals, and class properties and `this.name` assignments are fields
+struct JsVariables<'a> {
+    code: &'a str,
+    language: &'a str,
+    variables: Vec<VariableInfo>,
+}
+
+impl JsVariables<'_> {
+    fn push(&mut self, name: &str, var_type: Option<String>, scope: VariableScope, span: swc_common::Span, is_constant: bool) {
+        if scope == VariableScope::Field && self.variables.iter().any(|var| var.scope == scope && var.name == name) {
+            return;
+        }
+        self.variables.push(VariableInfo {
+            name: name.to_string(),
+            var_type,
+            scope,
+            line_number: line_at(self.code, span.lo.0.saturating_sub(1) as usize),
+            is_constant,
+            language: self.language.to_string(),
+        });
+    }
+
+    fn annotation(&self, annotation: Option<&swc_ast::TsTypeAnn>) -> Option<String> {
+        let span = annotation?.type_ann.span();
+        let source = self.code.get(span.lo.0.saturating_sub(1) as usize..span.hi.0.saturating_sub(1) as usize)?;
+        Some(source.trim().to_string())
+    }
+
+    fn module_item(&mut self, item: &swc_ast::ModuleItem) {
+        match item {
+            swc_ast::ModuleItem::Stmt(stmt) => self.stmt(stmt, VariableScope::Global),
+            swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDecl(export)) => self.decl(&export.decl, VariableScope::Global),
+            swc_ast::ModuleItem::ModuleDecl(swc_ast::ModuleDecl::ExportDefaultDecl(export)) => match &export.decl {
+                swc_ast::DefaultDecl::Fn(fn_expr) => self.function(&fn_expr.function),
+                swc_ast::DefaultDecl::Class(class_expr) => self.class(&class_expr.class),
+                swc_ast::DefaultDecl::TsInterfaceDecl(_) => {}
+            },
+            swc_ast::ModuleItem::ModuleDecl(_) => {}
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[swc_ast::Stmt], scope: VariableScope) {
+        for stmt in stmts {
+            self.stmt(stmt, scope);
+        }
+    }
+
+    /// Nested blocks stay in the scope around them; only function bodies open a local one
+    fn stmt(&mut self, stmt: &swc_ast::Stmt, scope: VariableScope) {
+        use swc_ast::Stmt;
+        match stmt {
+            Stmt::Decl(decl) => self.decl(decl, scope),
+            Stmt::Block(block) => self.stmts(&block.stmts, scope),
+            Stmt::Expr(expr) => self.expr(&expr.expr),
+            Stmt::If(if_stmt) => {
+                self.stmt(&if_stmt.cons, scope);
+                if let Some(alt) = &if_stmt.alt {
+                    self.stmt(alt, scope);
+                }
+            }
+            Stmt::For(for_stmt) => {
+                if let Some(swc_ast::VarDeclOrExpr::VarDecl(var)) = &for_stmt.init {
+                    self.var(var, scope);
+                }
+                self.stmt(&for_stmt.body, scope);
+            }
+            Stmt::ForIn(for_in) => {
+                if let swc_ast::ForHead::VarDecl(var) = &for_in.left {
+                    self.var(var, scope);
+                }
+                self.stmt(&for_in.body, scope);
+            }
+            Stmt::ForOf(for_of) => {
+                if let swc_ast::ForHead::VarDecl(var) = &for_of.left {
+                    self.var(var, scope);
+                }
+                self.stmt(&for_of.body, scope);
+            }
+            Stmt::While(while_stmt) => self.stmt(&while_stmt.body, scope),
+            Stmt::DoWhile(do_while) => self.stmt(&do_while.body, scope),
+            Stmt::Labeled(labeled) => self.stmt(&labeled.body, scope),
+            Stmt::Switch(switch) => {
+                for case in &switch.cases {
+                    self.stmts(&case.cons, scope);
+                }
+            }
+            Stmt::Try(try_stmt) => {
+                self.stmts(&try_stmt.block.stmts, scope);
+                if let Some(handler) = &try_stmt.handler {
+                    if let Some(param) = &handler.param {
+                        self.pat(param, None, scope, false);
+                    }
+                    self.stmts(&handler.body.stmts, scope);
+                }
+                if let Some(finalizer) = &try_stmt.finalizer {
+                    self.stmts(&finalizer.stmts, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn decl(&mut self, decl: &swc_ast::Decl, scope: VariableScope) {
+        match decl {
+            swc_ast::Decl::Var(var) => self.var(var, scope),
+            swc_ast::Decl::Fn(fn_decl) => self.function(&fn_decl.function),
+            swc_ast::Decl::Class(class_decl) => self.class(&class_decl.class),
+            _ => {}
+        }
+    }
+
+    fn var(&mut self, var: &swc_ast::VarDecl, scope: VariableScope) {
+        let is_constant = var.kind == swc_ast::VarDeclKind::Const;
+        for declarator in &var.decls {
+            let inferred = declarator.init.as_deref().and_then(js_literal_type);
+            self.pat(&declarator.name, inferred, scope, is_constant);
+            if let Some(init) = &declarator.init {
+                self.expr(init);
+            }
+        }
+    }
+
+    /// Every name a binding pattern declares; `inferred` types a bare name that has no annotation
+    fn pat(&mut self, pat: &swc_ast::Pat, inferred: Option<String>, scope: VariableScope, is_constant: bool) {
+        match pat {
+            swc_ast::Pat::Ident(binding) => {
+                let var_type = self.annotation(binding.type_ann.as_deref()).or(inferred);
+                self.push(&binding.id.sym, var_type, scope, binding.id.span, is_constant);
+            }
+            swc_ast::Pat::Assign(assign) => self.pat(&assign.left, js_literal_type(&assign.right), scope, is_constant),
+            swc_ast::Pat::Rest(rest) => self.pat(&rest.arg, self.annotation(rest.type_ann.as_deref()), scope, is_constant),
+            swc_ast::Pat::Array(array) => {
+                for elem in array.elems.iter().flatten() {
+                    self.pat(elem, None, scope, is_constant);
+                }
+            }
+            swc_ast::Pat::Object(object) => {
+                for prop in &object.props {
+                    match prop {
+                        swc_ast::ObjectPatProp::KeyValue(key_value) => self.pat(&key_value.value, None, scope, is_constant),
+                        swc_ast::ObjectPatProp::Assign(assign) => {
+                            let inferred = assign.value.as_deref().and_then(js_literal_type);
+                            self.push(&assign.key.id.sym, inferred, scope, assign.key.id.span, is_constant);
+                        }
+                        swc_ast::ObjectPatProp::Rest(rest) => self.pat(&rest.arg, None, scope, is_constant),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Functions passed or assigned as values, and `this.name = value` fields
+    fn expr(&mut self, expr: &swc_ast::Expr) {
+        use swc_ast::Expr;
+        match expr {
+            Expr::Fn(fn_expr) => self.function(&fn_expr.function),
+            Expr::Arrow(arrow) => self.arrow(arrow),
+            Expr::Class(class_expr) => self.class(&class_expr.class),
+            Expr::Paren(paren) => self.expr(&paren.expr),
+            Expr::Call(call) => {
+                for arg in &call.args {
+                    self.expr(&arg.expr);
+                }
+            }
+            Expr::Assign(assign) => {
+                if let swc_ast::AssignTarget::Simple(swc_ast::SimpleAssignTarget::Member(member)) = &assign.left {
+                    if let (Expr::This(_), swc_ast::MemberProp::Ident(prop)) = (&*member.obj, &member.prop) {
+                        self.push(&prop.sym, js_literal_type(&assign.right), VariableScope::Field, prop.span, false);
+                    }
+                }
+                self.expr(&assign.right);
+            }
+            _ => {}
+        }
+    }
+
+    fn function(&mut self, function: &swc_ast::Function) {
+        for param in &function.params {
+            self.pat(&param.pat, None, VariableScope::Parameter, false);
+        }
+        if let Some(body) = &function.body {
+            self.stmts(&body.stmts, VariableScope::Local);
+        }
+    }
+
+    fn arrow(&mut self, arrow: &swc_ast::ArrowExpr) {
+        for param in &arrow.params {
+            self.pat(param, None, VariableScope::Parameter, false);
+        }
+        match &*arrow.body {
+            swc_ast::BlockStmtOrExpr::BlockStmt(body) => self.stmts(&body.stmts, VariableScope::Local),
+            swc_ast::BlockStmtOrExpr::Expr(body) => self.expr(body),
+        }
+    }
+
+    fn class(&mut self, class: &swc_ast::Class) {
+        for member in &class.body {
+            match member {
+                swc_ast::ClassMember::ClassProp(prop) => {
+                    if let swc_ast::PropName::Ident(key) = &prop.key {
+                        let var_type = self.annotation(prop.type_ann.as_deref()).or_else(|| prop.value.as_deref().and_then(js_literal_type));
+                        self.push(&key.sym, var_type, VariableScope::Field, key.span, prop.readonly);
+                    }
+                }
+                swc_ast::ClassMember::PrivateProp(prop) => {
+                    let var_type = self.annotation(prop.type_ann.as_deref()).or_else(|| prop.value.as_deref().and_then(js_literal_type));
+                    self.push(&prop.key.id.sym, var_type, VariableScope::Field, prop.key.span, prop.readonly);
+                }
+                swc_ast::ClassMember::Constructor(constructor) => {
+                    for param in &constructor.params {
+                        match param {
+                            swc_ast::ParamOrTsParamProp::Param(param) => self.pat(&param.pat, None, VariableScope::Parameter, false),
+                            // `constructor(private readonly name: string)` declares a field
+                            swc_ast::ParamOrTsParamProp::TsParamProp(prop) => match &prop.param {
+                                swc_ast::TsParamPropParam::Ident(binding) => {
+                                    let var_type = self.annotation(binding.type_ann.as_deref());
+                                    self.push(&binding.id.sym, var_type, VariableScope::Field, binding.id.span, prop.readonly);
+                                }
+                                swc_ast::TsParamPropParam::Assign(assign) => {
+                                    self.pat(&assign.left, js_literal_type(&assign.right), VariableScope::Field, prop.readonly);
+                                }
+                            },
+                        }
+                    }
+                    if let Some(body) = &constructor.body {
+                        self.stmts(&body.stmts, VariableScope::Local);
+                    }
+                }
+                swc_ast::ClassMember::Method(method) => self.function(&method.function),
+                swc_ast::ClassMember::PrivateMethod(method) => self.function(&method.function),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The type a Python value evidently has, for the literals that settle a boolean or collection
+fn py_literal_type(expr: &py_ast::Expr) -> Option<String> {
+    use py_ast::Expr;
+    let var_type = match expr {
+        Expr::Constant(constant) if matches!(constant.value, py_ast::Constant::Bool(_)) => "bool",
+        Expr::Compare(_) => "bool",
+        Expr::UnaryOp(unary) if matches!(unary.op, py_ast::UnaryOp::Not) => "bool",
+        Expr::List(_) | Expr::ListComp(_) => "list",
+        Expr::Set(_) | Expr::SetComp(_) => "set",
+        _ => return None,
+    };
+    Some(var_type.to_string())
+}
+
+/// Assignments in a Python module: module-level names are globals, function bodies hold locals,
+/// and class-level names and `self.name` assignments are fields
+struct PyVariables<'a> {
+    code: &'a str,
+    language: &'a str,
+    variables: Vec<VariableInfo>,
+    declared: HashSet<(String, VariableScope)>, // reassignments in the same function are one variable
+}
+
+impl PyVariables<'_> {
+    fn push(&mut self, name: &str, var_type: Option<String>, scope: VariableScope, offset: usize) {
+        let is_field = scope == VariableScope::Field;
+        if !self.declared.insert((name.to_string(), scope)) || (is_field && self.variables.iter().any(|var| var.scope == scope && var.name == name)) {
+            return;
+        }
+        // Python has no constants; UPPER_CASE is its convention for them
+        let is_constant = name.chars().any(char::is_alphabetic) && !name.chars().any(char::is_lowercase);
+        self.variables.push(VariableInfo {
+            name: name.to_string(),
+            var_type,
+            scope,
+            line_number: line_at(self.code, offset),
+            is_constant,
+            language: self.language.to_string(),
+        });
+    }
+
+    fn source(&self, expr: &py_ast::Expr) -> String {
+        self.code.get(expr.start().to_usize()..expr.end().to_usize()).unwrap_or_default().to_string()
+    }
+
+    fn stmts(&mut self, body: &[py_ast::Stmt], scope: VariableScope) {
+        for stmt in body {
+            self.stmt(stmt, scope);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &py_ast::Stmt, scope: VariableScope) {
+        use py_ast::Stmt;
+        match stmt {
+            Stmt::FunctionDef(def) => return self.function(&def.args, &def.body),
+            Stmt::AsyncFunctionDef(def) => return self.function(&def.args, &def.body),
+            Stmt::ClassDef(def) => return self.stmts(&def.body, VariableScope::Field),
+            Stmt::Assign(assign) => {
+                let inferred = py_literal_type(&assign.value);
+                for target in &assign.targets {
+                    self.target(target, inferred.clone(), scope);
+                }
+            }
+            Stmt::AnnAssign(assign) => self.target(&assign.target, Some(self.source(&assign.annotation)), scope),
+            Stmt::For(for_stmt) => self.target(&for_stmt.target, None, scope),
+            Stmt::AsyncFor(for_stmt) => self.target(&for_stmt.target, None, scope),
+            _ => {}
+        }
+        // Blocks of an `if`, loop or `try` share the scope around them
+        for block in python_child_blocks(stmt) {
+            self.stmts(block, scope);
+        }
+    }
+
+    fn target(&mut self, target: &py_ast::Expr, var_type: Option<String>, scope: VariableScope) {
+        match target {
+            py_ast::Expr::Name(name) => self.push(name.id.as_str(), var_type, scope, target.start().to_usize()),
+            py_ast::Expr::Attribute(attribute) if matches!(&*attribute.value, py_ast::Expr::Name(owner) if owner.id.as_str() == "self") => {
+                self.push(attribute.attr.as_str(), var_type, VariableScope::Field, target.start().to_usize());
+            }
+            py_ast::Expr::Tuple(tuple) => {
+                for element in &tuple.elts {
+                    self.target(element, None, scope);
+                }
+            }
+            py_ast::Expr::List(list) => {
+                for element in &list.elts {
+                    self.target(element, None, scope);
+                }
+            }
+            py_ast::Expr::Starred(starred) => self.target(&starred.value, None, scope),
+            _ => {}
+        }
+    }
+
+    fn function(&mut self, args: &py_ast::Arguments, body: &[py_ast::Stmt]) {
+        let outer = std::mem::take(&mut self.declared);
+        for arg in args.posonlyargs.iter().chain(&args.args).chain(&args.kwonlyargs) {
+            let var_type = arg.def.annotation.as_deref().map(|annotation| self.source(annotation))
+                .or_else(|| arg.default.as_deref().and_then(py_literal_type));
+            self.parameter(&arg.def, var_type);
+        }
+        for arg in args.vararg.iter().chain(&args.kwarg) {
+            let var_type = arg.annotation.as_deref().map(|annotation| self.source(annotation));
+            self.parameter(arg, var_type);
+        }
+        self.stmts(body, VariableScope::Local);
+        self.declared = outer;
+    }
+
+    fn parameter(&mut self, arg: &py_ast::Arg, var_type: Option<String>) {
+        if !matches!(arg.arg.as_str(), "self" | "cls") {
+            self.push(arg.arg.as_str(), var_type, VariableScope::Parameter, arg.range.start().to_usize());
+        }
+    }
+}
+
+/// The type a Rust initializer evidently has, for the literals that settle a boolean or `Vec`
+fn rust_literal_type(expr: &syn::Expr) -> Option<String> {
+    let var_type = match expr {
+        syn::Expr::Lit(literal) if matches!(literal.lit, syn::Lit::Bool(_)) => "bool",
+        syn::Expr::Binary(binary) if matches!(
+            binary.op,
+            syn::BinOp::Eq(_) | syn::BinOp::Ne(_) | syn::BinOp::Lt(_) | syn::BinOp::Le(_)
+                | syn::BinOp::Gt(_) | syn::BinOp::Ge(_) | syn::BinOp::And(_) | syn::BinOp::Or(_)
+        ) => "bool",
+        syn::Expr::Macro(mac) if mac.mac.path.is_ident("vec") => "Vec",
+        _ => return None,
+    };
+    Some(var_type.to_string())
+}
+
+/// Bindings in Rust items: consts are globals, statics are statics, struct fields are fields,
+/// and `let` bindings in function bodies are locals
+struct RustVariables<'a> {
+    code: &'a str,
+    language: &'a str,
+    variables: Vec<VariableInfo>,
+    search_from: usize, // declarations are found in source order, since syn spans carry no positions here
+}
+
+impl RustVariables<'_> {
+    /// The line of the next match of `pattern`, or of the last declaration found if there is none
+    fn locate(&mut self, pattern: &str) -> u32 {
+        let start = self.search_from.min(self.code.len());
+        match Regex::new(pattern).ok().and_then(|regex| regex.find(&self.code[start..])) {
+            Some(found) => {
+                self.search_from = start + found.end();
+                line_at(self.code, start + found.start())
+            }
+            None => line_at(self.code, start),
+        }
+    }
+
+    fn push(&mut self, name: String, var_type: Option<String>, scope: VariableScope, is_constant: bool, line_number: u32) {
+        self.variables.push(VariableInfo { name, var_type, scope, line_number, is_constant, language: self.language.to_string() });
+    }
+
+    fn item(&mut self, item: &syn::Item) {
+        match item {
+            syn::Item::Const(item) => {
+                let name = item.ident.to_string();
+                let line = self.locate(&format!(r"\bconst\s+{}\b", regex::escape(&name)));
+                self.push(name, Some(item.ty.to_token_stream().to_string()), VariableScope::Global, true, line);
+            }
+            syn::Item::Static(item) => {
+                let name = item.ident.to_string();
+                let line = self.locate(&format!(r"\bstatic\s+(?:mut\s+)?{}\b", regex::escape(&name)));
+                let is_constant = matches!(item.mutability, syn::StaticMutability::None);
+                self.push(name, Some(item.ty.to_token_stream().to_string()), VariableScope::Static, is_constant, line);
+            }
+            syn::Item::Struct(item) => {
+                self.locate(&format!(r"\bstruct\s+{}\b", regex::escape(&item.ident.to_string())));
+                for field in &item.fields {
+                    if let Some(ident) = &field.ident {
+                        let name = ident.to_string();
+                        let line = self.locate(&format!(r"\b{}\s*:", regex::escape(&name)));
+                        self.push(name, Some(field.ty.to_token_stream().to_string()), VariableScope::Field, false, line);
+                    }
+                }
+            }
+            syn::Item::Fn(function) => self.function(&function.sig, &function.block),
+            syn::Item::Impl(item) => {
+                for member in &item.items {
+                    if let syn::ImplItem::Fn(method) = member {
+                        self.function(&method.sig, &method.block);
+                    }
+                }
+            }
+            syn::Item::Trait(item) => {
+                for member in &item.items {
+                    if let syn::TraitItem::Fn(syn::TraitItemFn { sig, default: Some(block), .. }) = member {
+                        self.function(sig, block);
+                    }
+                }
+            }
+            syn::Item::Mod(item) => {
+                for inner in item.content.iter().flat_map(|(_, items)| items) {
+                    self.item(inner);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn function(&mut self, sig: &syn::Signature, block: &syn::Block) {
+        self.locate(&format!(r"\bfn\s+{}\b", regex::escape(&sig.ident.to_string())));
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(typed) = input {
+                self.pat(&typed.pat, Some(typed.ty.to_token_stream().to_string()), VariableScope::Parameter);
+            }
+        }
+        self.block(block);
+    }
+
+    fn block(&mut self, block: &syn::Block) {
+        for stmt in &block.stmts {
+            match stmt {
+                syn::Stmt::Local(local) => {
+                    let inferred = local.init.as_ref().and_then(|init| rust_literal_type(&init.expr));
+                    self.pat(&local.pat, inferred, VariableScope::Local);
+                    if let Some(init) = &local.init {
+                        self.expr(&init.expr);
+                    }
+                }
+                syn::Stmt::Item(item) => self.item(item),
+                syn::Stmt::Expr(expr, _) => self.expr(expr),
+                syn::Stmt::Macro(_) => {}
+            }
+        }
+    }
+
+    /// Bindings in the blocks, loops, matches and closures an expression holds
+    fn expr(&mut self, expr: &syn::Expr) {
+        match expr {
+            syn::Expr::Block(block) => self.block(&block.block),
+            syn::Expr::Unsafe(block) => self.block(&block.block),
+            syn::Expr::If(if_expr) => {
+                self.block(&if_expr.then_branch);
+                if let Some((_, otherwise)) = &if_expr.else_branch {
+                    self.expr(otherwise);
+                }
+            }
+            syn::Expr::ForLoop(for_loop) => {
+                self.pat(&for_loop.pat, None, VariableScope::Local);
+                self.block(&for_loop.body);
+            }
+            syn::Expr::While(while_loop) => self.block(&while_loop.body),
+            syn::Expr::Loop(loop_expr) => self.block(&loop_expr.body),
+            syn::Expr::Match(match_expr) => {
+                for arm in &match_expr.arms {
+                    self.expr(&arm.body);
+                }
+            }
+            syn::Expr::Closure(closure) => self.expr(&closure.body),
+            _ => {}
+        }
+    }
+
+    /// Every binding in `pat`; `var_type` types a bare binding, and `name: Type` supplies its own
+    fn pat(&mut self, pat: &syn::Pat, var_type: Option<String>, scope: VariableScope) {
+        match pat {
+            syn::Pat::Ident(binding) => {
+                let name = binding.ident.to_string();
+                let line = self.locate(&format!(r"\b{}\b", regex::escape(&name)));
+                self.push(name, var_type, scope, false, line);
+            }
+            syn::Pat::Type(typed) => self.pat(&typed.pat, Some(typed.ty.to_token_stream().to_string()), scope),
+            syn::Pat::Reference(reference) => self.pat(&reference.pat, None, scope),
+            syn::Pat::Tuple(tuple) => {
+                for elem in &tuple.elems {
+                    self.pat(elem, None, scope);
+                }
+            }
+            syn::Pat::TupleStruct(tuple) => {
+                for elem in &tuple.elems {
+                    self.pat(elem, None, scope);
+                }
+            }
+            syn::Pat::Slice(slice) => {
+                for elem in &slice.elems {
+                    self.pat(elem, None, scope);
+                }
+            }
+            syn::Pat::Struct(pattern) => {
+                for field in &pattern.fields {
+                    self.pat(&field.pat, None, scope);
+                }
+            }
+            _ => {}
         }
     }
 }
@@ -638,7 +1703,7 @@ pub struct VariableInfo {
     pub language: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum VariableScope {
     Local,
     Global,
@@ -769,4 +1834,255 @@ mod tests {
         assert_eq!(variables[1].name, "maxRetries");
         assert!(variables[1].is_constant);
     }
+
+    #[test]
+    fn test_single_letter_smells_spare_tight_loop_counters() {
+        let recognizer = VariableNamingRecognizer::new();
+        let mut code = String::from("function total(items) {\n  let sum = 0;\n  for (let i = 0; i < items.length; i++) {\n    sum += items[i];\n  }\n  let d = new Date();\n  for (let j = 0; j < 3; j++) {\n");
+        for n in 0..12 {
+            code.push_str(&format!("    step{}(j);\n", n));
+        }
+        code.push_str("  }\n  return sum;\n}\n");
+
+        let smells = recognizer.detect_single_letter_smells(&code);
+        let flagged: Vec<(&str, u32)> = smells.iter().map(|s| (s.name.as_str(), s.line_number)).collect();
+        assert_eq!(flagged, vec![("d", 6), ("j", 7)]);
+        assert!(smells[1].reason.contains("12-line"));
+    }
+
+    #[test]
+    fn test_single_letter_smells_in_indented_loops() {
+        let recognizer = VariableNamingRecognizer::new();
+        let code = "for k in keys:\n    print(k)\n\nx = load()\ntotal = x + 1\n";
+        let smells = recognizer.detect_single_letter_smells(code);
+        assert_eq!(smells.len(), 1);
+        assert_eq!((smells[0].name.as_str(), smells[0].line_number), ("x", 4));
+    }
+
+    #[test]
+    fn test_single_letter_smells_on_one_line_loops_and_reassignments() {
+        let recognizer = VariableNamingRecognizer::new();
+        assert!(recognizer.detect_single_letter_smells("for (let i = 0; i < n; i++) { s += i; }\n").is_empty());
+        assert!(recognizer.analyze_variable_naming("for (let i = 0; i < n; i++) { s += i; }\n", "javascript").is_ok());
+
+        let code = "let t = 0;\nfor (const item of items) {\n  let q = item.qty;\n  t = t + q;\n}\n";
+        let smells = recognizer.detect_single_letter_smells(code);
+        let flagged: Vec<(&str, u32)> = smells.iter().map(|s| (s.name.as_str(), s.line_number)).collect();
+        assert_eq!(flagged, vec![("t", 1), ("q", 3)]);
+        assert_eq!(smells[1].reason, "single-letter variable `q` is not a loop counter");
+    }
+
+    fn typed_variable(name: &str, var_type: &str, line_number: u32) -> VariableInfo {
+        VariableInfo {
+            name: name.to_string(),
+            var_type: Some(var_type.to_string()),
+            scope: VariableScope::Local,
+            line_number,
+            is_constant: false,
+            language: "rust".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_boolean_naming_violations() {
+        let recognizer = VariableNamingRecognizer::new();
+        let flagged = |code: &str, language: &str| -> Vec<(String, u32)> {
+            let analysis = recognizer.analyze_variable_naming(code, language).unwrap();
+            analysis.boolean_naming_violations.into_iter().map(|v| (v.name, v.line_number)).collect()
+        };
+
+        let rust = "struct Widget {\n    active: bool,\n    is_visible: bool,\n    has_children: bool,\n    count: i32,\n}\n\nimpl Widget {\n    fn is_empty(&self) -> bool { self.count == 0 }\n    fn visible(&self) -> bool { self.is_visible }\n}\n";
+        assert_eq!(flagged(rust, "rust"), vec![("active".to_string(), 2), ("visible".to_string(), 10)]);
+
+        let typescript = "let active = true;\nconst isActive = !active;\nfunction ready(visible = false): boolean {\n  return visible;\n}\n";
+        assert_eq!(flagged(typescript, "typescript"), vec![("active".to_string(), 1), ("visible".to_string(), 3), ("ready".to_string(), 3)]);
+    }
+
+    #[test]
+    fn test_collection_naming_violations() {
+        let recognizer = VariableNamingRecognizer::new();
+        let flagged = |code: &str, language: &str| -> Vec<NamingSmell> {
+            recognizer.analyze_variable_naming(code, language).unwrap().collection_naming_violations
+        };
+
+        let typescript = "const userList: User[] = [];\nconst user = [];\nconst items = [];\nconst children: Node[] = [];\nlet names: string = 'a, b';\nlet status: string = 'ok';\nconst orders: Map<string, Order> = new Map();\n";
+        let violations = flagged(typescript, "typescript");
+        let names: Vec<(&str, u32)> = violations.iter().map(|v| (v.name.as_str(), v.line_number)).collect();
+        assert_eq!(names, vec![("user", 2), ("names", 5)]);
+        assert!(violations[0].reason.contains("singular"));
+        assert!(violations[1].reason.contains("plural"));
+
+        // Wrapped collections are still collections, and maps are exempt either way
+        let rust = "fn checksum(record: &[u8], entries: Option<Vec<Entry>>, lookups: HashMap<String, u32>) -> u32 {\n    let rows = vec![];\n    0\n}\n";
+        let names: Vec<String> = flagged(rust, "rust").into_iter().map(|v| v.name).collect();
+        assert_eq!(names, vec!["record"]);
+    }
+
+    fn scoped_variable(name: &str, scope: VariableScope) -> VariableInfo {
+        VariableInfo { scope, ..typed_variable(name, "i32", 1) }
+    }
+
+    #[test]
+    fn test_scope_consistency_drops_when_a_scope_mixes_styles() {
+        let recognizer = VariableNamingRecognizer::new();
+        let metrics = |code: &str| recognizer.analyze_variable_naming(code, "javascript").unwrap().consistency_metrics;
+        let uniform = metrics("const MAX_RETRIES = 3;\nfunction load() {\n  let userName = '';\n  let retryCount = 0;\n  let total = 1;\n}\n");
+        let mixed = metrics("const MAX_RETRIES = 3;\nfunction load() {\n  let userName = '';\n  let user_name = '';\n  let retryCount = 0;\n  let retry_delay = 5;\n}\n");
+
+        assert_eq!(uniform.scope_consistency, 1.0);
+        // Half the local names break the local style; the lone global is consistent
+        assert_eq!(mixed.scope_consistency, 3.0 / 5.0);
+        assert!(mixed.overall_consistency < uniform.overall_consistency);
+    }
+
+    #[test]
+    fn test_consistency_reliability_grows_with_sample_size() {
+        let recognizer = VariableNamingRecognizer::new();
+        let metrics = |variables: &[VariableInfo]| recognizer.calculate_consistency_metrics(variables, &recognizer.categorize_semantics(variables));
+
+        let tiny = vec![
+            scoped_variable("userName", VariableScope::Local),
+            scoped_variable("retryCount", VariableScope::Local),
+            scoped_variable("userName", VariableScope::Local),
+        ];
+        let tiny_metrics = metrics(&tiny);
+        assert_eq!(tiny_metrics.style_consistency, 1.0);
+        assert_eq!(tiny_metrics.sample_size, 2);
+        assert_eq!(tiny_metrics.reliability, Reliability::Low);
+
+        let large: Vec<VariableInfo> = (0..40).map(|index| scoped_variable(&format!("itemCount{}", index), VariableScope::Local)).collect();
+        let large_metrics = metrics(&large);
+        assert_eq!(large_metrics.sample_size, 40);
+        assert_eq!(large_metrics.reliability, Reliability::High);
+        assert!(Reliability::Low.weight() < Reliability::High.weight());
+    }
+
+    #[test]
+    fn test_semantic_consistency_rewards_uniform_counter_names() {
+        let recognizer = VariableNamingRecognizer::new();
+        let semantic_consistency = |code: &str| {
+            recognizer.analyze_variable_naming(code, "javascript").unwrap().consistency_metrics.semantic_consistency
+        };
+
+        let consistent = semantic_consistency("let userCount = 0;\nlet retryCount = 0;\nlet pageCount = 0;\n");
+        let inconsistent = semantic_consistency("let userCount = 0;\nlet numRetries = 0;\nlet pageTotal = 0;\n");
+        assert_eq!(consistent, 1.0);
+        assert!(inconsistent < consistent);
+        assert_eq!(semantic_consistency("let userName = '';\n"), 1.0);
+    }
+
+    #[test]
+    fn test_semantic_pattern_config_adds_domain_vocabulary() {
+        let variables = vec![scoped_variable("tallyOfVotes", VariableScope::Local), scoped_variable("userCount", VariableScope::Local)];
+        let default = VariableNamingRecognizer::new().categorize_semantics(&variables);
+        assert!(!default.counters_iterators.contains_key("tallyOfVotes"));
+
+        let overridden = VariableNamingRecognizer::with_semantic_patterns(
+            &SemanticPatternConfig::new().override_pattern(SemanticCategory::CountersIterators, "tally"),
+        ).unwrap().categorize_semantics(&variables);
+        assert_eq!(overridden.counters_iterators.keys().collect::<Vec<_>>(), vec!["tallyOfVotes"]);
+        // Other categories keep their defaults
+        assert!(overridden.size_dimensions.contains_key("userCount"));
+
+        let extended = VariableNamingRecognizer::with_semantic_patterns(
+            &SemanticPatternConfig::new().extend_pattern(SemanticCategory::CountersIterators, "tally"),
+        ).unwrap().categorize_semantics(&variables);
+        assert_eq!(extended.counters_iterators.len(), 2);
+
+        let invalid = SemanticPatternConfig::new().extend_pattern(SemanticCategory::Identifiers, "(unclosed");
+        assert!(VariableNamingRecognizer::with_semantic_patterns(&invalid).is_err());
+    }
+
+    fn declared(code: &str, language: &str) -> Vec<(String, VariableScope, u32)> {
+        let recognizer = VariableNamingRecognizer::new();
+        let ast = recognizer.parser.parse_code(code, language).unwrap();
+        recognizer.extract_variables(&ast, code, language).unwrap().into_iter().map(|var| (var.name, var.scope, var.line_number)).collect()
+    }
+
+    fn expected(variables: &[(&str, VariableScope, u32)]) -> Vec<(String, VariableScope, u32)> {
+        variables.iter().map(|&(name, scope, line)| (name.to_string(), scope, line)).collect()
+    }
+
+    #[test]
+    fn test_javascript_variables_come_from_the_ast() {
+        use VariableScope::*;
+        let code = "const MAX_RETRIES = 3;\nclass Cart {\n  items = [];\n  constructor(owner) {\n    this.isOpen = true;\n  }\n  add(item, { quantity = 1 }) {\n    for (const entry of this.items) {}\n    return item;\n  }\n}\nexport function total(cart) {\n  let sum = 0;\n  cart.items.forEach((line) => { const price = line.price; sum += price; });\n  return sum;\n}\n";
+        assert_eq!(declared(code, "javascript"), expected(&[
+            ("MAX_RETRIES", Global, 1), ("items", Field, 3), ("owner", Parameter, 4), ("isOpen", Field, 5),
+            ("item", Parameter, 7), ("quantity", Parameter, 7), ("entry", Local, 8),
+            ("cart", Parameter, 12), ("sum", Local, 13), ("line", Parameter, 14), ("price", Local, 14),
+        ]));
+
+        let recognizer = VariableNamingRecognizer::new();
+        let ast = recognizer.parser.parse_code(code, "javascript").unwrap();
+        let variables = recognizer.extract_variables(&ast, code, "javascript").unwrap();
+        assert!(variables[0].is_constant && !variables[8].is_constant);
+        assert_eq!(variables[1].var_type.as_deref(), Some("Array"));
+        assert_eq!(variables[3].var_type.as_deref(), Some("boolean"));
+    }
+
+    #[test]
+    fn test_python_variables_come_from_the_ast() {
+        use VariableScope::*;
+        let code = "MAX_RETRIES = 3\nclass Cart:\n    currency = \"EUR\"\n    def __init__(self, owner: str, items=[]):\n        self.owner = owner\n        self.is_open = True\n    def add(self, item):\n        for index, entry in enumerate(self.items):\n            total = index\n        total = 0\n        return total\n";
+        assert_eq!(declared(code, "python"), expected(&[
+            ("MAX_RETRIES", Global, 1), ("currency", Field, 3), ("owner", Parameter, 4), ("items", Parameter, 4),
+            ("owner", Field, 5), ("is_open", Field, 6), ("item", Parameter, 7), ("index", Local, 8), ("entry", Local, 8),
+            ("total", Local, 9),
+        ]));
+
+        let recognizer = VariableNamingRecognizer::new();
+        let ast = recognizer.parser.parse_code(code, "python").unwrap();
+        let variables = recognizer.extract_variables(&ast, code, "python").unwrap();
+        assert!(variables[0].is_constant && !variables[1].is_constant);
+        let types: Vec<Option<&str>> = variables[2..6].iter().map(|var| var.var_type.as_deref()).collect();
+        assert_eq!(types, vec![Some("str"), Some("list"), None, Some("bool")]);
+    }
+
+    #[test]
+    fn test_rust_variables_come_from_the_ast() {
+        use VariableScope::*;
+        let code = "const MAX_RETRIES: u32 = 3;\nstatic mut COUNTER: usize = 0;\nstruct Cart {\n    items: Vec<Item>,\n    is_open: bool,\n}\nimpl Cart {\n    fn total(&self, discount: f64) -> f64 {\n        let mut sum = 0.0;\n        for (index, item) in self.items.iter().enumerate() {\n            let ready = index > 0;\n        }\n        sum\n    }\n}\n";
+        assert_eq!(declared(code, "rust"), expected(&[
+            ("MAX_RETRIES", Global, 1), ("COUNTER", Static, 2), ("items", Field, 4), ("is_open", Field, 5),
+            ("discount", Parameter, 8), ("sum", Local, 9), ("index", Local, 10), ("item", Local, 10), ("ready", Local, 11),
+        ]));
+
+        let recognizer = VariableNamingRecognizer::new();
+        let ast = recognizer.parser.parse_code(code, "rust").unwrap();
+        let variables = recognizer.extract_variables(&ast, code, "rust").unwrap();
+        assert!(variables[0].is_constant && !variables[1].is_constant);
+        assert_eq!(variables[4].var_type.as_deref(), Some("f64"));
+        assert_eq!(variables[8].var_type.as_deref(), Some("bool"));
+    }
+
+    #[test]
+    fn test_non_ascii_names_do_not_panic() {
+        let recognizer = VariableNamingRecognizer::new();
+        let names = vec!["日本語".to_string(), "größeWert".to_string(), "größe_wert".to_string()];
+        let (_, suffixes) = recognizer.extract_prefix_suffix_patterns(&names);
+        assert_eq!(suffixes.get("wert"), Some(&2));
+        assert!(recognizer.analyze_variable_naming("日本語 = 3\n", "generic").is_ok());
+        // Boolean-looking names have their first three chars counted, which used to split `日`
+        assert!(recognizer.analyze_variable_naming("let is日x = true;\n", "javascript").is_ok());
+        assert!(recognizer.analyze_variable_naming("is日x = 1\nhas日本 = 2\n", "python").is_ok());
+    }
+
+    #[test]
+    fn test_canonical_style_flags_snake_case_strays_in_camel_case_file() {
+        let recognizer = VariableNamingRecognizer::new();
+        let code = "const maxRetries = 3;\nlet currentUser = null;\nlet page_size = 20;\nfunction loadUser(userId) {\n    const requestUrl = buildUrl(userId);\n    let retry_count = 0;\n    return fetchJson(requestUrl);\n}\n";
+
+        let suggestion = recognizer.suggest_canonical_style(code, "javascript").unwrap();
+        assert!(matches!(suggestion.style, crate::pattern_extractor::NamingStyle::CamelCase));
+        assert_eq!(suggestion.renames, vec![
+            NameRename { name: "page_size".to_string(), line_number: 3, suggested: "pageSize".to_string() },
+            NameRename { name: "retry_count".to_string(), line_number: 6, suggested: "retryCount".to_string() },
+        ]);
+
+        // An even split falls back to the language default
+        let split = recognizer.suggest_canonical_style("user_name = 1\nuserID = 2\n", "python").unwrap();
+        assert!(matches!(split.style, crate::pattern_extractor::NamingStyle::SnakeCase));
+        assert_eq!(split.renames[0].suggested, "user_id");
+    }
 }
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// WASM-optimized cache for frequently accessed data
+#[wasm_bindgen]
+pub struct WasmCache {
+    cache: HashMap<String, String>,
+    max_size: usize,
+    access_count: HashMap<String, u32>,
+}
+
+#[wasm_bindgen]
+impl WasmCache {
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_size: usize) -> WasmCache {
+        WasmCache {
+            cache: HashMap::new(),
+            max_size,
+            access_count: HashMap::new(),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get(&mut self, key: &str) -> Option<String> {
+        if let Some(value) = self.cache.get(key) {
+            // Update access count for LRU eviction
+            *self.access_count.entry(key.to_string()).or_insert(0) += 1;
+            Some(value.clone())
+        } else {
+            None
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        // Check if we need to evict items
+        if self.cache.len() >= self.max_size && !self.cache.contains_key(key) {
+            self.evict_least_used();
+        }
+
+        self.cache.insert(key.to_string(), value.to_string());
+        self.access_count.insert(key.to_string(), 1);
+        true
+    }
+
+    #[wasm_bindgen]
+    pub fn has(&self, key: &str) -> bool {
+        self.cache.contains_key(key)
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.access_count.clear();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    #[wasm_bindgen]
+    pub fn size(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_stats(&self) -> String {
+        serde_json::to_string(&serde_json::json!({
+            "current_size": self.cache.len(),
+            "max_size": self.max_size,
+            "total_keys": self.access_count.len(),
+            "most_accessed": self.get_most_accessed_key()
+        })).unwrap_or_default()
+    }
+
+    fn evict_least_used(&mut self) {
+        if let Some((least_used_key, _)) = self.access_count.iter()
+            .min_by_key(|(_, count)| *count) {
+            let key_to_remove = least_used_key.clone();
+            self.cache.remove(&key_to_remove);
+            self.access_count.remove(&key_to_remove);
+        }
+    }
+
+    fn get_most_accessed_key(&self) -> Option<String> {
+        self.access_count.iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(key, _)| key.clone())
+    }
+}
@@ -1,27 +1,49 @@
 use wasm_bindgen::prelude::*;
-use std::time::Instant;
-use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use serde::Deserialize;
 use crate::{
+    PatternAnalyzer, AnalysisOptions,
     ast_parser::AstParser,
-    pattern_extractor::PatternExtractor,
-    user_behavior_tracker::UserBehaviorTracker,
+    pattern_extractor::{PatternExtractor, PatternAnalysis},
+    user_behavior_tracker::{RejectionReason, UserBehaviorTracker},
     pattern_scoring_engine::{PatternScoringEngine, ScoringContext},
     suggestion_generation_engine::SuggestionGenerationEngine,
-    context_aware_filter::{ContextAwareFilter, CodingContext},
-    wasm_serializer::{WasmSerializer, SerializableAnalysisResult, ComprehensiveAnalysis},
+    context_aware_filter::{ContextAwareFilter, CodingContext, CursorPosition, CursorSuggestions, FilterConfiguration, ProjectInfo},
+    suggestion_generation_engine::CodeSuggestion,
+    wasm_serializer::{WasmSerializer, SerializableAnalysisResult, ComprehensiveAnalysis, OutputVerbosity},
     local_storage_manager::{LocalStorageManager, StorageType},
+    style_analyzer::CodingStyleAnalyzer,
+    code_structure_analyzer::{cyclomatic_complexity, rank_hotspots, CodeStructureAnalyzer},
+    variable_naming_recognizer::VariableNamingRecognizer,
+    local_storage_manager::Severity,
+    error::AnalysisError,
+    wasm_cache::WasmCache,
+    language_capability::{language_capability, supported_languages},
+    normalized_ast::to_normalized_ast,
 };
 
+/// Inputs above this size are refused before parsing, to protect the WASM heap
+pub const DEFAULT_MAX_INPUT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Pattern analyses kept per engine, keyed by content hash
+pub const DEFAULT_ANALYSIS_CACHE_SIZE: usize = 64;
+
 /// Main WASM interface for Code Whisperer engine
 #[wasm_bindgen]
 pub struct CodeWhispererEngine {
-    ast_parser: AstParser,
     pattern_extractor: PatternExtractor,
     behavior_tracker: UserBehaviorTracker,
     scoring_engine: PatternScoringEngine,
     suggestion_engine: SuggestionGenerationEngine,
     filter: ContextAwareFilter,
     storage_manager: LocalStorageManager,
+    analysis_cache: WasmCache,
+    parse_count: u32,
+    ignore_patterns: Vec<String>,
+    max_input_bytes: usize,
 }
 
 /// Configuration for the Code Whisperer engine
@@ -32,6 +54,27 @@ pub struct EngineConfig {
     max_suggestions: usize,
     confidence_threshold: f32,
     enable_context_filtering: bool,
+    max_input_bytes: usize,
+    enabled_languages: Vec<String>, // empty means every language
+    ignore_patterns: Vec<String>,   // globs for generated or vendored paths, e.g. `*.min.js`
+    verbosity: OutputVerbosity,     // how much of the analysis `analyze_and_suggest` serializes
+    suggestion_cooldown_ms: u32,    // how long shown or dismissed suggestions stay hidden, 0 disables
+}
+
+/// Validating builder for `EngineConfig`; also the shape of the JSON accepted by `configure_engine`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineConfigBuilder {
+    enable_learning: bool,
+    enable_storage: bool,
+    max_suggestions: usize,
+    confidence_threshold: f32,
+    enable_context_filtering: bool,
+    max_input_bytes: usize,
+    enabled_languages: Vec<String>,
+    ignore_patterns: Vec<String>,
+    verbosity: OutputVerbosity,
+    suggestion_cooldown_ms: u32,
 }
 
 /// Context information from the editor
@@ -40,6 +83,7 @@ pub struct EditorContext {
     file_path: String,
     language: String,
     cursor_position: u32,
+    additional_cursors: Vec<u32>,
     selected_text: Option<String>,
     surrounding_context: Option<String>,
     project_type: Option<String>,
@@ -55,9 +99,44 @@ impl EngineConfig {
             max_suggestions: 10,
             confidence_threshold: 0.6,
             enable_context_filtering: true,
+            max_input_bytes: DEFAULT_MAX_INPUT_BYTES,
+            enabled_languages: Vec::new(),
+            ignore_patterns: Vec::new(),
+            verbosity: OutputVerbosity::Full,
+            suggestion_cooldown_ms: 0,
         }
     }
 
+    /// Build a validated config from a JSON object of `EngineConfigBuilder` fields
+    #[wasm_bindgen]
+    pub fn from_json(json: &str) -> Result<EngineConfig, JsValue> {
+        EngineConfig::parse_json(json).map_err(|e| JsValue::from_str(&e))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn enabled_languages(&self) -> Vec<String> {
+        self.enabled_languages.clone()
+    }
+
+    /// Whether analysis is allowed for `language`; every language is when none were listed
+    #[wasm_bindgen]
+    pub fn is_language_enabled(&self, language: &str) -> bool {
+        self.enabled_languages.is_empty()
+            || self.enabled_languages.iter().any(|enabled| enabled.eq_ignore_ascii_case(language))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ignore_patterns(&self) -> Vec<String> {
+        self.ignore_patterns.clone()
+    }
+
+    /// Whether project analysis skips `path`. Patterns without a `/` match the file name, others the
+    /// whole path; `*` and `?` stay within one path segment while `**` spans directories
+    #[wasm_bindgen]
+    pub fn is_path_ignored(&self, path: &str) -> bool {
+        path_ignored(&self.ignore_patterns, path)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn enable_learning(&self) -> bool {
         self.enable_learning
@@ -107,6 +186,162 @@ impl EngineConfig {
     pub fn set_enable_context_filtering(&mut self, value: bool) {
         self.enable_context_filtering = value;
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_input_bytes(&self) -> usize {
+        self.max_input_bytes
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_input_bytes(&mut self, value: usize) {
+        self.max_input_bytes = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn verbosity(&self) -> OutputVerbosity {
+        self.verbosity
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_verbosity(&mut self, value: OutputVerbosity) {
+        self.verbosity = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn suggestion_cooldown_ms(&self) -> u32 {
+        self.suggestion_cooldown_ms
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_suggestion_cooldown_ms(&mut self, value: u32) {
+        self.suggestion_cooldown_ms = value;
+    }
+}
+
+impl EngineConfig {
+    pub fn builder() -> EngineConfigBuilder {
+        EngineConfigBuilder::new()
+    }
+
+    pub fn parse_json(json: &str) -> Result<EngineConfig, String> {
+        serde_json::from_str::<EngineConfigBuilder>(json)
+            .map_err(|e| format!("Invalid engine config: {}", e))?
+            .build()
+    }
+}
+
+impl Default for EngineConfigBuilder {
+    fn default() -> Self {
+        let defaults = EngineConfig::new();
+        EngineConfigBuilder {
+            enable_learning: defaults.enable_learning,
+            enable_storage: defaults.enable_storage,
+            max_suggestions: defaults.max_suggestions,
+            confidence_threshold: defaults.confidence_threshold,
+            enable_context_filtering: defaults.enable_context_filtering,
+            max_input_bytes: defaults.max_input_bytes,
+            enabled_languages: defaults.enabled_languages,
+            ignore_patterns: defaults.ignore_patterns,
+            verbosity: defaults.verbosity,
+            suggestion_cooldown_ms: defaults.suggestion_cooldown_ms,
+        }
+    }
+}
+
+impl EngineConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable_learning(mut self, value: bool) -> Self {
+        self.enable_learning = value;
+        self
+    }
+
+    pub fn enable_storage(mut self, value: bool) -> Self {
+        self.enable_storage = value;
+        self
+    }
+
+    pub fn max_suggestions(mut self, value: usize) -> Self {
+        self.max_suggestions = value;
+        self
+    }
+
+    pub fn confidence_threshold(mut self, value: f32) -> Self {
+        self.confidence_threshold = value;
+        self
+    }
+
+    pub fn enable_context_filtering(mut self, value: bool) -> Self {
+        self.enable_context_filtering = value;
+        self
+    }
+
+    pub fn max_input_bytes(mut self, value: usize) -> Self {
+        self.max_input_bytes = value;
+        self
+    }
+
+    pub fn enabled_languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.enabled_languages = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn ignore_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn verbosity(mut self, value: OutputVerbosity) -> Self {
+        self.verbosity = value;
+        self
+    }
+
+    pub fn suggestion_cooldown_ms(mut self, value: u32) -> Self {
+        self.suggestion_cooldown_ms = value;
+        self
+    }
+
+    /// Check ranges and produce the config
+    pub fn build(self) -> Result<EngineConfig, String> {
+        if !(0.0..=1.0).contains(&self.confidence_threshold) {
+            return Err(format!("confidence_threshold must be within [0, 1], got {}", self.confidence_threshold));
+        }
+        if self.max_suggestions == 0 {
+            return Err("max_suggestions must be positive".to_string());
+        }
+        if self.max_input_bytes == 0 {
+            return Err("max_input_bytes must be positive".to_string());
+        }
+        if self.enabled_languages.iter().any(|language| language.trim().is_empty()) {
+            return Err("enabled_languages must not contain blank names".to_string());
+        }
+        if self.ignore_patterns.iter().any(|pattern| pattern.trim().is_empty()) {
+            return Err("ignore_patterns must not contain blank patterns".to_string());
+        }
+
+        Ok(EngineConfig {
+            enable_learning: self.enable_learning,
+            enable_storage: self.enable_storage,
+            max_suggestions: self.max_suggestions,
+            confidence_threshold: self.confidence_threshold,
+            enable_context_filtering: self.enable_context_filtering,
+            max_input_bytes: self.max_input_bytes,
+            enabled_languages: self.enabled_languages.iter().map(|language| language.trim().to_lowercase()).collect(),
+            ignore_patterns: self.ignore_patterns.iter().map(|pattern| pattern.trim().to_string()).collect(),
+            verbosity: self.verbosity,
+            suggestion_cooldown_ms: self.suggestion_cooldown_ms,
+        })
+    }
 }
 
 #[wasm_bindgen]
@@ -121,12 +356,26 @@ impl EditorContext {
             file_path,
             language,
             cursor_position,
+            additional_cursors: Vec::new(),
             selected_text: None,
             surrounding_context: None,
             project_type: None,
         }
     }
 
+    /// Create a multi-cursor context; the first position is the primary cursor
+    #[wasm_bindgen]
+    pub fn with_cursors(
+        file_path: String,
+        language: String,
+        cursor_positions: Vec<u32>,
+    ) -> EditorContext {
+        let mut positions = cursor_positions.into_iter();
+        let mut context = EditorContext::new(file_path, language, positions.next().unwrap_or(0));
+        context.additional_cursors = positions.collect();
+        context
+    }
+
     #[wasm_bindgen(getter)]
     pub fn file_path(&self) -> String {
         self.file_path.clone()
@@ -157,6 +406,24 @@ impl EditorContext {
         self.cursor_position = value;
     }
 
+    /// All active cursor positions, primary cursor first
+    #[wasm_bindgen(getter)]
+    pub fn cursor_positions(&self) -> Vec<u32> {
+        std::iter::once(self.cursor_position)
+            .chain(self.additional_cursors.iter().copied())
+            .collect()
+    }
+
+    #[wasm_bindgen]
+    pub fn add_cursor(&mut self, position: u32) {
+        self.additional_cursors.push(position);
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_additional_cursors(&mut self) {
+        self.additional_cursors.clear();
+    }
+
     #[wasm_bindgen(getter)]
     pub fn selected_text(&self) -> Option<String> {
         self.selected_text.clone()
@@ -197,16 +464,22 @@ impl CodeWhispererEngine {
         console_error_panic_hook::set_once();
 
         Ok(CodeWhispererEngine {
-            ast_parser: AstParser::new(),
             pattern_extractor: PatternExtractor::new(),
             behavior_tracker: UserBehaviorTracker::new(),
             scoring_engine: PatternScoringEngine::new(),
             suggestion_engine: SuggestionGenerationEngine::new(),
-            filter: ContextAwareFilter::new(),
+            filter: ContextAwareFilter::with_config(FilterConfiguration {
+                suggestion_cooldown: Duration::from_millis(config.suggestion_cooldown_ms.into()),
+                ..FilterConfiguration::default()
+            }),
             storage_manager: LocalStorageManager::new(
                 StorageType::LocalFile,
                 "/tmp/code_whisperer".to_string()
             ),
+            analysis_cache: WasmCache::new(DEFAULT_ANALYSIS_CACHE_SIZE),
+            parse_count: 0,
+            ignore_patterns: config.ignore_patterns.clone(),
+            max_input_bytes: config.max_input_bytes(),
         })
     }
 
@@ -220,30 +493,37 @@ impl CodeWhispererEngine {
     ) -> SerializableAnalysisResult {
         let start_time = Instant::now();
 
-        // Step 1: Parse the code
-        let ast_result = match self.ast_parser.parse_code(code, &editor_context.language()) {
-            Ok(ast) => ast,
-            Err(e) => {
-                return WasmSerializer::create_analysis_result(
-                    None,
-                    start_time.elapsed().as_millis() as u64,
-                    &editor_context.language(),
-                    code.len(),
-                    Some(format!("AST parsing failed: {}", e)),
-                );
-            }
-        };
+        // Oversized input (e.g. minified bundles) is refused before it reaches the parser
+        if let Err(error) = AnalysisError::check_input_size(code, config.max_input_bytes()) {
+            return WasmSerializer::create_analysis_result(
+                None,
+                start_time.elapsed().as_millis() as u64,
+                &editor_context.language(),
+                code.len(),
+                Some(error.to_json()),
+            );
+        }
 
-        // Step 2: Extract patterns
-        let pattern_analysis = match self.pattern_extractor.extract_patterns(code, &editor_context.language()) {
+        if !config.is_language_enabled(&editor_context.language()) {
+            return WasmSerializer::create_analysis_result(
+                None,
+                start_time.elapsed().as_millis() as u64,
+                &editor_context.language(),
+                code.len(),
+                Some(AnalysisError::UnsupportedLanguage { language: editor_context.language() }.to_json()),
+            );
+        }
+
+        // Steps 1-2: Parse the code and extract patterns, unless this content was already analyzed
+        let pattern_analysis = match self.cached_pattern_analysis(code, &editor_context.language()) {
             Ok(analysis) => analysis,
-            Err(e) => {
+            Err(error) => {
                 return WasmSerializer::create_analysis_result(
                     None,
                     start_time.elapsed().as_millis() as u64,
                     &editor_context.language(),
                     code.len(),
-                    Some(format!("Pattern extraction failed: {}", e)),
+                    Some(error.to_json()),
                 );
             }
         };
@@ -315,26 +595,17 @@ impl CodeWhispererEngine {
                 file_language: editor_context.language().to_string(),
                 file_content: code.to_string(),
                 current_line: editor_context.surrounding_context().unwrap_or_default(),
-                cursor_position: crate::context_aware_filter::CursorPosition {
-                    row: editor_context.cursor_position() / 1000,
-                    column: editor_context.cursor_position() % 1000,
-                    offset: editor_context.cursor_position(),
-                },
+                cursor_position: cursor_from_offset(editor_context.cursor_position()),
+                additional_cursors: editor_context.additional_cursors.iter().map(|&c| cursor_from_offset(c)).collect(),
             };
 
-            match self.filter.filter_suggestions(
+            match self.filter.filter_suggestions_per_cursor(
                 suggestion_result.suggestions.clone(),
                 &coding_context,
                 &behavior_analysis,
-                &crate::context_aware_filter::ProjectInfo {
-                    project_path: "/tmp/unknown".to_string(),
-                    project_name: "unknown".to_string(),
-                    dependencies: vec![],
-                    file_count: 1,
-                    total_lines_of_code: code.len() as u32,
-                },
+                &single_file_project(&editor_context.file_path(), code),
             ) {
-                Ok(filtered) => Some(filtered),
+                Ok(per_cursor) => Some(merge_cursor_suggestions(per_cursor)),
                 Err(_) => {
                     // Fall back to unfiltered suggestions if filtering fails
                     None
@@ -362,15 +633,55 @@ impl CodeWhispererEngine {
         );
 
         // Step 9: Return serialized result
-        WasmSerializer::create_analysis_result(
+        WasmSerializer::create_analysis_result_with_verbosity(
             Some(comprehensive_analysis),
             start_time.elapsed().as_millis() as u64,
             &editor_context.language(),
             code.len(),
             None,
+            config.verbosity(),
         )
     }
 
+    /// Ranked, filtered suggestions for the primary cursor of a `CodingContext` JSON, returned as
+    /// `{"suggestions": [CodeSuggestion, ...]}`. `config_json` takes the shape accepted by
+    /// `configure_engine`, with an empty string meaning the defaults; bad input, a disabled
+    /// language or code that fails to parse yields `{"error": AnalysisError}`
+    #[wasm_bindgen]
+    pub fn get_suggestions(&mut self, context_json: &str, config_json: &str) -> String {
+        let result = serde_json::from_str::<CodingContext>(context_json)
+            .map_err(|e| AnalysisError::InvalidInput { message: format!("Invalid coding context: {}", e) })
+            .and_then(|context| {
+                let config = match config_json.trim() {
+                    "" => EngineConfig::new(),
+                    json => EngineConfig::parse_json(json).map_err(|message| AnalysisError::InvalidInput { message })?,
+                };
+                self.suggest_at_cursor(&context, &config)
+            });
+        let result = match result {
+            Ok(suggestions) => serde_json::json!({ "suggestions": suggestions }),
+            Err(error) => serde_json::json!({ "error": error }),
+        };
+        serde_json::to_string(&result).unwrap_or_default()
+    }
+
+    /// Precompute pattern analysis for the files open in a project, given as a `{ path: code }` map,
+    /// so the first real query is served from cache. Returns how many files are now cached; paths
+    /// matching the config's `ignore_patterns` and files over its `max_input_bytes` are skipped and
+    /// not counted
+    #[wasm_bindgen]
+    pub fn warm(&mut self, files: JsValue, language: &str) -> Result<usize, JsValue> {
+        let files: HashMap<String, String> = serde_wasm_bindgen::from_value(files)
+            .map_err(|e| JsValue::from_str(&format!("Invalid file map: {}", e)))?;
+        Ok(self.warm_files(&files, language))
+    }
+
+    /// Number of times code actually reached the parser, i.e. cache misses
+    #[wasm_bindgen(getter)]
+    pub fn parse_count(&self) -> u32 {
+        self.parse_count
+    }
+
     /// Provide feedback on a suggestion to improve learning
     #[wasm_bindgen]
     pub fn provide_feedback(
@@ -379,11 +690,38 @@ impl CodeWhispererEngine {
         accepted: bool,
         user_context: Option<String>,
     ) -> bool {
-        // Record user feedback for learning
-        self.behavior_tracker.track_suggestion_interaction(suggestion_id, accepted, user_context);
+        self.provide_feedback_with_reason(suggestion_id, accepted, user_context, None)
+    }
+
+    /// Provide feedback with why a rejected suggestion was turned down: one of `irrelevant`,
+    /// `wrong_style`, `incorrect`, `duplicate` or `other`. Returns false for an unknown reason
+    #[wasm_bindgen]
+    pub fn provide_feedback_with_reason(
+        &mut self,
+        suggestion_id: &str,
+        accepted: bool,
+        user_context: Option<String>,
+        rejection_reason: Option<String>,
+    ) -> bool {
+        let reason = match rejection_reason.map(|reason| serde_json::from_value::<RejectionReason>(serde_json::Value::String(reason))) {
+            Some(Ok(reason)) => Some(reason),
+            Some(Err(_)) => return false,
+            None => None,
+        };
+        // Record user feedback for learning, and so the filter can suppress types the user keeps rejecting
+        self.behavior_tracker.track_suggestion_interaction_with_reason(suggestion_id, accepted, user_context, reason);
+        self.filter.record_suggestion_feedback(suggestion_id, accepted, reason);
         true
     }
 
+    /// Hide a suggestion returned by `get_suggestions` until the configured `suggestion_cooldown_ms`
+    /// passes, e.g. because the user closed it. Returns false for an id the engine has not shown
+    /// recently; with no cool-down configured the suggestion may reappear at once
+    #[wasm_bindgen]
+    pub fn dismiss_suggestion(&mut self, suggestion_id: &str) -> bool {
+        self.filter.dismiss_suggestion_by_id(suggestion_id)
+    }
+
     /// Get analysis statistics
     #[wasm_bindgen]
     pub fn get_statistics(&self) -> String {
@@ -426,7 +764,10 @@ impl CodeWhispererEngine {
     /// Update engine configuration
     #[wasm_bindgen]
     pub fn update_config(&mut self, config: &EngineConfig) {
-        // TODO: Apply configuration changes to components
+        self.ignore_patterns = config.ignore_patterns.clone();
+        self.max_input_bytes = config.max_input_bytes();
+        self.filter.set_suggestion_cooldown(Duration::from_millis(config.suggestion_cooldown_ms.into()));
+        // TODO: Apply the remaining configuration changes to components
         web_sys::console::log_1(&format!("Configuration updated: learning={}, storage={}", 
             config.enable_learning(), config.enable_storage()).into());
     }
@@ -440,21 +781,137 @@ impl CodeWhispererEngine {
     /// Get supported languages
     #[wasm_bindgen]
     pub fn get_supported_languages() -> Vec<String> {
-        vec![
-            "javascript".to_string(),
-            "typescript".to_string(),
-            "python".to_string(),
-            "rust".to_string(),
-        ]
+        supported_languages().into_iter()
+            .filter(|capability| capability.ast_supported)
+            .map(|capability| capability.name)
+            .collect()
     }
 
     /// Check if a language is supported
     #[wasm_bindgen]
     pub fn is_language_supported(language: &str) -> bool {
-        matches!(language.to_lowercase().as_str(), "javascript" | "typescript" | "python" | "rust")
+        language_capability(language).ast_supported
     }
 }
 
+impl CodeWhispererEngine {
+    pub fn warm_files(&mut self, files: &HashMap<String, String>, language: &str) -> usize {
+        let mut paths: Vec<&String> = files.keys().filter(|path| !path_ignored(&self.ignore_patterns, path)).collect();
+        paths.sort();
+
+        let mut warmed = 0;
+        for path in paths {
+            let code = &files[path];
+            let key = analysis_cache_key(code, language);
+            if self.analysis_cache.has(&key) {
+                warmed += 1;
+                continue;
+            }
+            // Warming never evicts: entries already cached are more likely to be queried
+            if self.analysis_cache.size() >= self.analysis_cache.max_size() {
+                break;
+            }
+            if code.len() <= self.max_input_bytes && self.cached_pattern_analysis(code, language).is_ok() {
+                warmed += 1;
+            }
+        }
+        warmed
+    }
+
+    /// Suggestions for `context`'s primary cursor, including names for a value being declared there,
+    /// filtered and ranked for the context. Only the best `max_suggestions` that reach
+    /// `confidence_threshold` are kept; with context filtering disabled, suggestions are ranked by
+    /// confidence alone. Code that fails to parse is reported as the parser's `AnalysisError`
+    pub fn suggest_at_cursor(&mut self, context: &CodingContext, config: &EngineConfig) -> Result<Vec<CodeSuggestion>, AnalysisError> {
+        let code = &context.file_content;
+        let language = context.file_language.to_lowercase();
+        AnalysisError::check_input_size(code, config.max_input_bytes())?;
+        if !config.is_language_enabled(&language) {
+            return Err(AnalysisError::UnsupportedLanguage { language });
+        }
+
+        let patterns = self.cached_pattern_analysis(code, &language)?;
+        let behavior = self.behavior_tracker.analyze_behavior();
+        let cursor = &context.cursor_position;
+        let position = crate::suggestion_generation_engine::CodePosition {
+            line: cursor.row,
+            column: cursor.column,
+            start_offset: cursor.offset as usize,
+            end_offset: cursor.offset as usize,
+        };
+        let mut generated = self.suggestion_engine.generate_suggestions(code, position, &patterns, &behavior, &language)
+            .map_err(|message| AnalysisError::Internal { message: format!("Suggestion generation failed: {}", message) })?
+            .suggestions;
+        generated.extend(self.suggestion_engine.suggest_variable_names(context));
+
+        let mut suggestions = if config.enable_context_filtering() {
+            self.filter.filter_suggestions(generated, context, &behavior, &single_file_project(&context.file_path, code))
+                .map_err(|message| AnalysisError::Internal { message: format!("Suggestion filtering failed: {}", message) })?
+        } else {
+            let mut suggestions = generated;
+            suggestions.sort_by(|a, b| b.confidence_score.total_cmp(&a.confidence_score));
+            suggestions
+        };
+        suggestions.retain(|suggestion| suggestion.confidence_score >= config.confidence_threshold());
+        suggestions.truncate(config.max_suggestions());
+        Ok(suggestions)
+    }
+
+    fn cached_pattern_analysis(&mut self, code: &str, language: &str) -> Result<PatternAnalysis, AnalysisError> {
+        let key = analysis_cache_key(code, language);
+        if let Some(analysis) = self.analysis_cache.get(&key).and_then(|json| serde_json::from_str(&json).ok()) {
+            return Ok(analysis);
+        }
+
+        self.parse_count += 1;
+        let analysis = self.pattern_extractor.extract_patterns(code, language)?;
+
+        if let Ok(json) = serde_json::to_string(&analysis) {
+            self.analysis_cache.set(&key, &json);
+        }
+        Ok(analysis)
+    }
+}
+
+fn analysis_cache_key(code: &str, language: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
+    format!("{}:{:016x}", language, hasher.finish())
+}
+
+// The project around a lone file, when the editor sends no project details
+fn single_file_project(path: &str, code: &str) -> ProjectInfo {
+    ProjectInfo {
+        project_path: "/tmp/unknown".to_string(),
+        project_name: "unknown".to_string(),
+        dependencies: vec![],
+        file_count: 1,
+        total_lines_of_code: code.len() as u32,
+        files: vec![path.to_string()],
+        manifests: HashMap::new(),
+        directories: vec![],
+    }
+}
+
+// Cursor offsets from the editor encode `row * 1000 + column`
+fn cursor_from_offset(position: u32) -> CursorPosition {
+    CursorPosition {
+        row: position / 1000,
+        column: position % 1000,
+        offset: position,
+    }
+}
+
+// Combine per-cursor results, keeping the first occurrence of each suggestion
+fn merge_cursor_suggestions(per_cursor: Vec<CursorSuggestions>) -> Vec<CodeSuggestion> {
+    let mut seen = std::collections::HashSet::new();
+    per_cursor
+        .into_iter()
+        .flat_map(|cursor| cursor.suggestions)
+        .filter(|suggestion| seen.insert(suggestion.id.clone()))
+        .collect()
+}
+
 /// Initialize the WASM module with default settings
 /// Note: This is now handled by wasm_init() in the wasm-specific module
 pub fn init() {
@@ -470,6 +927,12 @@ pub fn create_default_config() -> EngineConfig {
     EngineConfig::new()
 }
 
+/// Parse a JSON config object such as `{"max_suggestions": 5, "enabled_languages": ["rust"]}`
+#[wasm_bindgen]
+pub fn configure_engine(json: &str) -> Result<EngineConfig, JsValue> {
+    EngineConfig::from_json(json)
+}
+
 /// Utility function to validate code syntax
 #[wasm_bindgen]
 pub fn validate_syntax(code: &str, language: &str) -> String {
@@ -486,6 +949,270 @@ pub fn validate_syntax(code: &str, language: &str) -> String {
     }
 }
 
+/// Validate code, returning `{"valid": bool, "diagnostics": [AnalysisError, ...]}` JSON
+#[wasm_bindgen]
+pub fn validate_syntax_detailed(code: &str, language: &str) -> String {
+    let diagnostics = syntax_diagnostics(&mut AstParser::new(), code, language);
+    serde_json::to_string(&serde_json::json!({
+        "valid": diagnostics.is_empty(),
+        "diagnostics": diagnostics,
+    })).unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+struct BatchSourceFile {
+    code: String,
+    language: String,
+}
+
+/// Validate many files in one call. Takes `{"path": {"code": ..., "language": ...}}` JSON and
+/// returns `{"valid": bool, "files": {"path": [AnalysisError, ...]}}` listing only files with diagnostics
+#[wasm_bindgen]
+pub fn validate_syntax_batch(files_json: &str) -> String {
+    let files: BTreeMap<String, BatchSourceFile> = match serde_json::from_str(files_json) {
+        Ok(files) => files,
+        Err(err) => {
+            let error = AnalysisError::InvalidInput { message: format!("Invalid batch input: {}", err) };
+            return serde_json::to_string(&serde_json::json!({ "valid": false, "error": error })).unwrap_or_default();
+        }
+    };
+
+    let mut parser = AstParser::new();
+    let broken: BTreeMap<String, Vec<AnalysisError>> = files
+        .into_iter()
+        .map(|(path, file)| (path, syntax_diagnostics(&mut parser, &file.code, &file.language)))
+        .filter(|(_, diagnostics)| !diagnostics.is_empty())
+        .collect();
+    serde_json::to_string(&serde_json::json!({
+        "valid": broken.is_empty(),
+        "files": broken,
+    })).unwrap_or_default()
+}
+
+/// Style analysis as `{"basic_metrics": ..., "consistency_scores": ...}` JSON, or `{"error": AnalysisError}`
+/// when the code does not parse
+#[wasm_bindgen]
+pub fn analyze_style(code: &str, language: &str) -> String {
+    let result = match CodingStyleAnalyzer::new().analyze_style(code, language) {
+        Ok(analysis) => serde_json::json!({
+            "basic_metrics": analysis.basic_metrics,
+            "consistency_scores": analysis.consistency_scores,
+        }),
+        Err(error) => serde_json::json!({ "error": error }),
+    };
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+/// The code's syntax tree in the language-independent form of `to_normalized_ast`, as
+/// `{"nodes": [AstNode, ...]}` JSON, or `{"error": AnalysisError}` when it does not parse or is
+/// over `DEFAULT_MAX_INPUT_BYTES`
+#[wasm_bindgen]
+pub fn parse_to_ast_json(code: &str, language: &str) -> String {
+    let parsed = AnalysisError::check_input_size(code, DEFAULT_MAX_INPUT_BYTES)
+        .and_then(|_| AstParser::new().parse_code(code, language));
+    let result = match parsed {
+        Ok(ast) => serde_json::json!({ "nodes": to_normalized_ast(&ast, code) }),
+        Err(error) => serde_json::json!({ "error": error }),
+    };
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+/// `StyleMetrics` JSON from a single text pass, without parsing; much cheaper than `analyze_style`
+#[wasm_bindgen]
+pub fn quick_style_metrics(code: &str, language: &str) -> String {
+    serde_json::to_string(&CodingStyleAnalyzer::new().quick_metrics(code, language)).unwrap_or_default()
+}
+
+/// Smells listed in a project summary, most severe first
+const SUMMARY_TOP_SMELLS: usize = 10;
+/// Top-level keys of `analyze_project_summary` kept at `OutputVerbosity::Summary`
+const PROJECT_SUMMARY_KEYS: &[&str] = &[
+    "file_count", "total_functions", "total_classes", "average_complexity", "average_consistency", "dominant_naming_style",
+];
+
+#[derive(Default)]
+struct LanguageTotals {
+    files: u32,
+    functions: u32,
+    classes: u32,
+    complexity: f32,
+}
+
+/// Structure and style analysis across a project, for the dashboard shown on project open. Takes the
+/// `{"path": {"code": ..., "language": ...}}` JSON of `validate_syntax_batch` and returns totals, average
+/// complexity and style consistency, the dominant variable naming style, the top smells, a per-language
+/// breakdown and the `get_pattern_heatmap` of the project's patterns by file. Files that fail are listed
+/// under `"errors"` and left out of every total
+#[wasm_bindgen]
+pub fn analyze_project_summary(files_json: &str) -> String {
+    analyze_project_summary_with_config(files_json, &EngineConfig::new())
+}
+
+/// `analyze_project_summary` skipping paths that match `config`'s `ignore_patterns`; skipped
+/// paths are listed under `"ignored"` and count toward no total or language. `config`'s verbosity
+/// trims the result to the totals and averages at `Summary`, and drops the heatmap at `Standard`
+#[wasm_bindgen]
+pub fn analyze_project_summary_with_config(files_json: &str, config: &EngineConfig) -> String {
+    let files: BTreeMap<String, BatchSourceFile> = match serde_json::from_str(files_json) {
+        Ok(files) => files,
+        Err(err) => {
+            let error = AnalysisError::InvalidInput { message: format!("Invalid project input: {}", err) };
+            return serde_json::to_string(&serde_json::json!({ "error": error })).unwrap_or_default();
+        }
+    };
+
+    let structure_analyzer = CodeStructureAnalyzer::new();
+    let style_analyzer = CodingStyleAnalyzer::new();
+    let naming = VariableNamingRecognizer::new();
+    let mut patterns = PatternAnalyzer::new();
+    let mut languages: BTreeMap<String, LanguageTotals> = BTreeMap::new();
+    let mut naming_styles: BTreeMap<String, u32> = BTreeMap::new();
+    let mut smells = Vec::new();
+    let mut errors = BTreeMap::new();
+    let mut consistency = 0.0;
+    let mut ignored = Vec::new();
+
+    for (path, file) in &files {
+        if config.is_path_ignored(path) {
+            ignored.push(path);
+            continue;
+        }
+        let analyzed = structure_analyzer.analyze_code_structure(&file.code, &file.language).and_then(|structure| Ok((
+            structure,
+            style_analyzer.analyze_style(&file.code, &file.language)?,
+            naming.suggest_canonical_style(&file.code, &file.language)?,
+        )));
+        let (structure, style, canonical) = match analyzed {
+            Ok(analyzed) => analyzed,
+            Err(error) => {
+                errors.insert(path.clone(), error);
+                continue;
+            }
+        };
+
+        let totals = languages.entry(file.language.to_lowercase()).or_default();
+        totals.files += 1;
+        totals.functions += structure.definitions.functions;
+        totals.classes += structure.definitions.classes;
+        totals.complexity += structure.complexity_metrics.cyclomatic_complexity;
+        consistency += style.consistency_scores.overall_consistency;
+        *naming_styles.entry(format!("{:?}", canonical.style)).or_insert(0) += 1;
+        smells.extend(structure.smells.into_iter().map(|smell| (path, smell)));
+        let options = AnalysisOptions { file_path: Some(path.clone()), ..AnalysisOptions::default() };
+        patterns.analyze_code_with_options(&file.code, &file.language, &options);
+    }
+
+    // Errors first, then by file and line
+    smells.sort_by_key(|(path, smell)| (!matches!(smell.severity, Severity::Error), *path, smell.line_number));
+    let top_smells: Vec<serde_json::Value> = smells.iter().take(SUMMARY_TOP_SMELLS).map(|(path, smell)| serde_json::json!({
+        "file": path,
+        "kind": format!("{:?}", smell.kind),
+        "name": smell.name,
+        "line_number": smell.line_number,
+        "severity": smell.severity,
+        "description": smell.description,
+    })).collect();
+
+    let analyzed_files: u32 = languages.values().map(|totals| totals.files).sum();
+    let average = |total: f32, count: u32| if count == 0 { 0.0 } else { total / count as f32 };
+    // Ties go to the alphabetically first style, so the result is stable
+    let dominant_naming_style = naming_styles.iter()
+        .fold(None, |best: Option<(&String, u32)>, (style, &count)| match best {
+            Some((_, best_count)) if best_count >= count => best,
+            _ => Some((style, count)),
+        })
+        .map(|(style, _)| style.clone());
+    let breakdown: BTreeMap<&String, serde_json::Value> = languages.iter().map(|(language, totals)| (language, serde_json::json!({
+        "files": totals.files,
+        "functions": totals.functions,
+        "classes": totals.classes,
+        "average_complexity": average(totals.complexity, totals.files),
+    }))).collect();
+
+    let mut summary = serde_json::json!({
+        "file_count": analyzed_files,
+        "total_functions": languages.values().map(|totals| totals.functions).sum::<u32>(),
+        "total_classes": languages.values().map(|totals| totals.classes).sum::<u32>(),
+        "average_complexity": average(languages.values().map(|totals| totals.complexity).sum(), analyzed_files),
+        "average_consistency": average(consistency, analyzed_files),
+        "dominant_naming_style": dominant_naming_style,
+        "top_smells": top_smells,
+        "languages": breakdown,
+        "heatmap": patterns.pattern_heatmap(),
+        "errors": errors,
+        "ignored": ignored,
+    });
+    config.verbosity().trim(&mut summary, PROJECT_SUMMARY_KEYS, &["/heatmap"]);
+    serde_json::to_string(&summary).unwrap_or_default()
+}
+
+/// Rank project files as maintenance hotspots. Takes the `analyze_project_summary` file map and
+/// `{"path": change_count}` JSON, e.g. commit counts the editor reads from git, and returns
+/// `{"hotspots": [Hotspot, ...]}` with complex, frequently changed files first
+#[wasm_bindgen]
+pub fn analyze_hotspots(files_json: &str, change_counts_json: &str) -> String {
+    let input = serde_json::from_str::<BTreeMap<String, BatchSourceFile>>(files_json)
+        .and_then(|files| Ok((files, serde_json::from_str::<HashMap<String, u32>>(change_counts_json)?)));
+    let result = match input {
+        Ok((files, change_counts)) => {
+            let complexities: Vec<(String, f32)> = files.into_iter()
+                .map(|(path, file)| {
+                    let complexity = cyclomatic_complexity(&file.code, &file.language);
+                    (path, complexity)
+                })
+                .collect();
+            serde_json::json!({ "hotspots": rank_hotspots(&complexities, &change_counts) })
+        }
+        Err(err) => {
+            let error = AnalysisError::InvalidInput { message: format!("Invalid hotspot input: {}", err) };
+            serde_json::json!({ "error": error })
+        }
+    };
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+fn path_ignored(patterns: &[String], path: &str) -> bool {
+    let path: Vec<char> = path.replace('\\', "/").trim_start_matches("./").chars().collect();
+    let file_name = &path[path.iter().rposition(|&c| c == '/').map_or(0, |slash| slash + 1)..];
+    patterns.iter().any(|pattern| {
+        let pattern: Vec<char> = pattern.trim_start_matches("./").chars().collect();
+        if pattern.contains(&'/') {
+            glob_match(pattern.strip_prefix(&['/']).unwrap_or(&pattern), &path)
+        } else {
+            glob_match(&pattern, file_name)
+        }
+    })
+}
+
+/// `*` and `?` stay within a path segment, `**` spans any number of them. Filled in from the ends
+/// of both strings, so each pattern suffix is matched against each text suffix once
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    // matches[i][j]: does `pattern[i..]` match `text[j..]`
+    let mut matches = vec![vec![false; text.len() + 1]; pattern.len() + 1];
+    matches[pattern.len()][text.len()] = true;
+
+    for i in (0..pattern.len()).rev() {
+        for j in (0..=text.len()).rev() {
+            let next = text.get(j);
+            matches[i][j] = match (pattern[i], pattern.get(i + 1)) {
+                ('*', Some('*')) => {
+                    // `a/**/b` also matches `a/b`
+                    let skips_slash = pattern.get(i + 2) == Some(&'/') && matches[i + 3][j];
+                    skips_slash || matches[i + 2][j] || (next.is_some() && matches[i][j + 1])
+                }
+                ('*', _) => matches[i + 1][j] || (next.is_some_and(|&c| c != '/') && matches[i][j + 1]),
+                ('?', _) => next.is_some_and(|&c| c != '/') && matches[i + 1][j + 1],
+                (c, _) => next == Some(&c) && matches[i + 1][j + 1],
+            };
+        }
+    }
+    matches[0][0]
+}
+
+fn syntax_diagnostics(parser: &mut AstParser, code: &str, language: &str) -> Vec<AnalysisError> {
+    parser.parse_code(code, language).err().into_iter().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -519,6 +1246,25 @@ mod tests {
         assert_eq!(context.cursor_position(), 100);
     }
 
+    #[test]
+    fn test_multi_cursor_editor_context() {
+        let mut context = EditorContext::with_cursors(
+            "test.rs".to_string(),
+            "rust".to_string(),
+            vec![3, 1008],
+        );
+        assert_eq!(context.cursor_position(), 3);
+        assert_eq!(context.cursor_positions(), vec![3, 1008]);
+
+        context.add_cursor(2004);
+        assert_eq!(context.cursor_positions().len(), 3);
+        context.clear_additional_cursors();
+        assert_eq!(context.cursor_positions(), vec![3]);
+
+        let single = EditorContext::new("test.rs".to_string(), "rust".to_string(), 7);
+        assert_eq!(single.cursor_positions(), vec![7]);
+    }
+
     #[test]
     fn test_language_support() {
         assert!(CodeWhispererEngine::is_language_supported("javascript"));
@@ -533,4 +1279,433 @@ mod tests {
         let version = CodeWhispererEngine::get_version();
         assert!(!version.is_empty());
     }
+
+    #[test]
+    fn test_oversized_input_is_refused_before_parsing() {
+        let mut config = EngineConfig::new();
+        assert_eq!(config.max_input_bytes(), DEFAULT_MAX_INPUT_BYTES);
+        config.set_max_input_bytes(1024);
+
+        let mut engine = CodeWhispererEngine::new(&config).unwrap();
+        let context = EditorContext::new("bundle.min.js".to_string(), "javascript".to_string(), 0);
+        // Unparseable on purpose: a parse attempt would report a syntax error instead
+        let code = "function broken( {".repeat(100);
+
+        let result = engine.analyze_and_suggest(&code, &context, &config);
+        let error: serde_json::Value = serde_json::from_str(&result.error_message().unwrap()).unwrap();
+        assert_eq!(error["kind"], "input_too_large");
+        assert_eq!(error["size"], code.len());
+        assert_eq!(error["limit"], 1024);
+    }
+
+    #[test]
+    fn test_summary_verbosity_keeps_only_counts_and_scores() {
+        let code = "function add(a, b) {\n  return a + b;\n}\n\nconst total = add(1, 2);\n";
+        let context = EditorContext::new("src/math.js".to_string(), "javascript".to_string(), 4000);
+        let result = |verbosity| {
+            let config = EngineConfig::builder().verbosity(verbosity).build().unwrap();
+            let mut engine = CodeWhispererEngine::new(&config).unwrap();
+            engine.analyze_and_suggest(code, &context, &config)
+        };
+        let serialized_size = |result: &SerializableAnalysisResult| {
+            result.analysis_data().map_or(0, |data| data.len())
+                + result.suggestions_data().map_or(0, |data| data.len())
+                + result.metadata_json().len()
+        };
+        let (summary, standard, full) = (
+            result(OutputVerbosity::Summary),
+            result(OutputVerbosity::Standard),
+            result(OutputVerbosity::Full),
+        );
+        assert!(serialized_size(&summary) < serialized_size(&standard));
+        assert!(serialized_size(&standard) < serialized_size(&full));
+        assert!(summary.suggestions_data().is_none() && full.suggestions_data().is_some());
+        let (summary, standard) = (summary.analysis_data().unwrap(), standard.analysis_data().unwrap());
+
+        let summary: serde_json::Value = serde_json::from_str(&summary).unwrap();
+        let keys: Vec<&String> = summary.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["counts", "scores"]);
+        assert!(summary["counts"]["patterns"].is_u64() && summary["counts"]["suggestions"].is_u64());
+        assert!(summary["scores"]["average_pattern_score"].is_number());
+
+        let standard: serde_json::Value = serde_json::from_str(&standard).unwrap();
+        assert!(standard["scoring"].get("confidence_distribution").is_none());
+        assert!(standard["patterns"]["patterns"].is_array());
+
+        let config = EngineConfig::parse_json("{\"verbosity\": \"summary\"}").unwrap();
+        assert_eq!(config.verbosity(), OutputVerbosity::Summary);
+        assert_eq!(EngineConfig::new().verbosity(), OutputVerbosity::Full);
+    }
+
+    #[test]
+    fn test_project_summary_verbosity_trims_the_whole_result() {
+        let files = r#"{
+            "src/a.js": {"code": "function add(a, b) { return a + b; }", "language": "javascript"},
+            "src/b.py": {"code": "def greet(name):\n    return name\n", "language": "python"}
+        }"#;
+        let summary = |verbosity| {
+            let config = EngineConfig::builder().verbosity(verbosity).build().unwrap();
+            analyze_project_summary_with_config(files, &config)
+        };
+        let (brief, standard, full) = (summary(OutputVerbosity::Summary), summary(OutputVerbosity::Standard), summary(OutputVerbosity::Full));
+        assert!(brief.len() < standard.len() && standard.len() < full.len());
+        assert_eq!(full, analyze_project_summary(files));
+
+        let brief: serde_json::Value = serde_json::from_str(&brief).unwrap();
+        let keys: Vec<&str> = brief.as_object().unwrap().keys().map(String::as_str).collect();
+        assert!(keys.iter().all(|key| PROJECT_SUMMARY_KEYS.contains(key)));
+        assert_eq!(brief["file_count"], 2);
+
+        let standard: serde_json::Value = serde_json::from_str(&standard).unwrap();
+        assert!(standard.get("heatmap").is_none() && standard["top_smells"].is_array());
+    }
+
+    #[test]
+    fn test_warmed_files_are_served_from_cache() {
+        let config = EngineConfig::new();
+        let mut engine = CodeWhispererEngine::new(&config).unwrap();
+        let mut files = HashMap::new();
+        files.insert("src/a.js".to_string(), "function add(a, b) { return a + b; }".to_string());
+        files.insert("src/b.js".to_string(), "const greet = (name) => `hi ${name}`;".to_string());
+
+        assert_eq!(engine.warm_files(&files, "javascript"), 2);
+        assert_eq!(engine.parse_count(), 2);
+
+        let context = EditorContext::new("src/a.js".to_string(), "javascript".to_string(), 0);
+        let result = engine.analyze_and_suggest(&files["src/a.js"], &context, &config);
+        assert!(result.error_message().is_none());
+        assert_eq!(engine.parse_count(), 2);
+
+        // Warming again and analyzing new content are the only things that parse
+        assert_eq!(engine.warm_files(&files, "javascript"), 2);
+        engine.analyze_and_suggest("let x = 1;", &context, &config);
+        assert_eq!(engine.parse_count(), 3);
+    }
+
+    #[test]
+    fn test_warming_skips_files_over_the_configured_input_limit() {
+        let config = EngineConfig::builder().max_input_bytes(64).build().unwrap();
+        let mut engine = CodeWhispererEngine::new(&config).unwrap();
+        let mut files = HashMap::new();
+        files.insert("src/small.js".to_string(), "const one = 1;".to_string());
+        files.insert("src/bundle.js".to_string(), "const value = 1;\n".repeat(10));
+
+        assert_eq!(engine.warm_files(&files, "javascript"), 1);
+        assert_eq!(engine.parse_count(), 1);
+    }
+
+    #[test]
+    fn test_config_builder_rejects_out_of_range_values() {
+        assert!(EngineConfig::builder().confidence_threshold(1.5).build().is_err());
+        assert!(EngineConfig::builder().confidence_threshold(-0.1).build().is_err());
+        assert!(EngineConfig::builder().confidence_threshold(f32::NAN).build().is_err());
+        assert!(EngineConfig::builder().max_suggestions(0).build().is_err());
+        assert!(EngineConfig::builder().max_input_bytes(0).build().is_err());
+        assert!(EngineConfig::builder().enabled_languages([" "]).build().is_err());
+
+        assert!(EngineConfig::parse_json("{\"confidence_threshold\": 2.0}").is_err());
+        assert!(EngineConfig::parse_json("{\"max_sugestions\": 5}").is_err());
+        assert!(EngineConfig::parse_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_valid_config_constructs_engine() {
+        let config = EngineConfig::builder()
+            .max_suggestions(3)
+            .confidence_threshold(0.8)
+            .enable_storage(false)
+            .enabled_languages(["Rust"])
+            .build()
+            .unwrap();
+        assert_eq!(config.max_suggestions(), 3);
+        assert_eq!(config.confidence_threshold(), 0.8);
+        assert!(!config.enable_storage());
+        assert!(config.enable_learning());
+        assert!(CodeWhispererEngine::new(&config).is_ok());
+
+        let parsed = EngineConfig::parse_json("{\"max_suggestions\": 5, \"enabled_languages\": [\"python\"]}").unwrap();
+        assert_eq!(parsed.max_suggestions(), 5);
+        assert_eq!(parsed.max_input_bytes(), DEFAULT_MAX_INPUT_BYTES);
+        assert!(parsed.is_language_enabled("Python"));
+        assert!(!parsed.is_language_enabled("rust"));
+
+        let mut engine = CodeWhispererEngine::new(&parsed).unwrap();
+        let context = EditorContext::new("main.rs".to_string(), "rust".to_string(), 0);
+        let result = engine.analyze_and_suggest("fn main() {}", &context, &parsed);
+        let error: serde_json::Value = serde_json::from_str(&result.error_message().unwrap()).unwrap();
+        assert_eq!(error["kind"], "unsupported_language");
+        assert_eq!(engine.parse_count(), 0);
+    }
+
+    #[test]
+    fn test_batch_validation_reports_only_broken_files() {
+        let files = serde_json::json!({
+            "src/ok.js": { "code": "const add = (a, b) => a + b;", "language": "javascript" },
+            "src/broken.py": { "code": "def broken(:\n    pass\n", "language": "python" },
+        });
+        let result: serde_json::Value = serde_json::from_str(&validate_syntax_batch(&files.to_string())).unwrap();
+
+        assert_eq!(result["valid"], false);
+        let reported = result["files"].as_object().unwrap();
+        assert_eq!(reported.keys().collect::<Vec<_>>(), vec!["src/broken.py"]);
+        assert_eq!(reported["src/broken.py"][0]["kind"], "parse_failed");
+        assert_eq!(reported["src/broken.py"][0]["line"], 1);
+
+        let single: serde_json::Value = serde_json::from_str(&validate_syntax_detailed("const x = 1;", "javascript")).unwrap();
+        assert_eq!(single["valid"], true);
+        assert_eq!(single["diagnostics"].as_array().unwrap().len(), 0);
+
+        let invalid: serde_json::Value = serde_json::from_str(&validate_syntax_batch("[1, 2]")).unwrap();
+        assert_eq!(invalid["error"]["kind"], "invalid_input");
+    }
+
+    #[test]
+    fn test_analyze_style_reports_errors_as_json() {
+        let broken: serde_json::Value = serde_json::from_str(&analyze_style("def broken(:\n    pass\n", "python")).unwrap();
+        assert_eq!(broken["error"]["kind"], "parse_failed");
+
+        let styled: serde_json::Value = serde_json::from_str(&analyze_style("const 日本 = 'é';\n", "javascript")).unwrap();
+        assert!(styled["error"].is_null());
+        assert!(styled["consistency_scores"]["overall_consistency"].is_number());
+
+        let metrics: serde_json::Value = serde_json::from_str(&quick_style_metrics("fn main() {\n    run();\n}\n", "rust")).unwrap();
+        assert_eq!(metrics["indentation_type"], "Spaces");
+        assert_eq!(metrics["comment_density"], 0.0);
+    }
+
+    #[test]
+    fn test_parse_to_ast_json_exports_normalized_nodes() {
+        let tree: serde_json::Value = serde_json::from_str(&parse_to_ast_json("export function greet(name) {\n  return `hi ${name}`;\n}\n", "javascript")).unwrap();
+        let greet = &tree["nodes"][0];
+        assert_eq!(greet["node_type"], "function_declaration");
+        assert_eq!(greet["properties"]["exported"], true);
+        assert_eq!(greet["children"].as_array().map(Vec::len), Some(2));
+        assert_eq!(greet["location"]["start_line"], 1);
+
+        let broken: serde_json::Value = serde_json::from_str(&parse_to_ast_json("def broken(:\n", "python")).unwrap();
+        assert_eq!(broken["error"]["kind"], "parse_failed");
+
+        let huge = "x = 1\n".repeat(DEFAULT_MAX_INPUT_BYTES / 6 + 1);
+        let refused: serde_json::Value = serde_json::from_str(&parse_to_ast_json(&huge, "python")).unwrap();
+        assert_eq!(refused["error"]["kind"], "input_too_large");
+    }
+
+    #[test]
+    fn test_complex_frequently_changed_file_ranks_as_hotspot() {
+        let files = serde_json::json!({
+            "src/billing.js": {
+                "code": "function charge(order) {\n  if (order.paid) return;\n  for (const item of order.items) {\n    if (item.taxable && order.region) {\n      item.tax = rate(order.region);\n    } else if (item.discount) {\n      item.tax = 0;\n    }\n  }\n  switch (order.kind) {\n    case 'gift': wrap(order); break;\n    case 'rush': expedite(order); break;\n  }\n}\n",
+                "language": "javascript",
+            },
+            "src/constants.js": { "code": "export const VERSION = '1.0';\n", "language": "javascript" },
+            "src/format.py": { "code": "def money(value):\n    if value < 0:\n        return '-'\n    return f'{value:.2f}'\n", "language": "python" },
+        });
+        let changes = serde_json::json!({ "src/billing.js": 40, "src/constants.js": 2, "src/format.py": 3 });
+        let ranked: serde_json::Value = serde_json::from_str(&analyze_hotspots(&files.to_string(), &changes.to_string())).unwrap();
+
+        let paths: Vec<&str> = ranked["hotspots"].as_array().unwrap().iter().map(|hotspot| hotspot["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, ["src/billing.js", "src/format.py", "src/constants.js"]);
+        assert_eq!(ranked["hotspots"][0]["score"], 1.0);
+        assert_eq!(ranked["hotspots"][2]["change_count"], 2);
+
+        // A complex file nobody touches is no hotspot
+        let stable = serde_json::json!({ "src/constants.js": 30 });
+        let ranked: serde_json::Value = serde_json::from_str(&analyze_hotspots(&files.to_string(), &stable.to_string())).unwrap();
+        assert_eq!(ranked["hotspots"][0]["path"], "src/constants.js");
+        assert_eq!(ranked["hotspots"][1]["score"], 0.0);
+
+        let error: serde_json::Value = serde_json::from_str(&analyze_hotspots(&files.to_string(), "[1, 2]")).unwrap();
+        assert_eq!(error["error"]["kind"], "invalid_input");
+    }
+
+    #[test]
+    fn test_project_summary_totals() {
+        let long_body = "    total += 1;\n".repeat(60);
+        let files = serde_json::json!({
+            "src/cart.js": {
+                "code": format!("class Cart {{\n  add(item) {{\n    this.items.push(item);\n  }}\n  total() {{\n    let total = 0;\n{}    return total;\n  }}\n}}\n", long_body),
+                "language": "javascript",
+            },
+            "src/user.js": {
+                "code": "class User {\n  constructor(name) {\n    this.userName = name;\n  }\n}\nclass Admin {\n  grant() {\n    let accessLevel = 1;\n  }\n}\n",
+                "language": "javascript",
+            },
+            "tools/report.py": {
+                "code": "class Report:\n    def render(self):\n        if self.rows:\n            return self.rows\n        return []\n",
+                "language": "python",
+            },
+            "tools/broken.py": { "code": "def broken(:\n", "language": "python" },
+        });
+        let summary: serde_json::Value = serde_json::from_str(&analyze_project_summary(&files.to_string())).unwrap();
+
+        assert_eq!(summary["file_count"], 3);
+        assert_eq!(summary["total_classes"], 4);
+        assert_eq!(summary["total_functions"], 5);
+        assert_eq!(summary["languages"]["javascript"]["files"], 2);
+        assert_eq!(summary["languages"]["javascript"]["functions"], 4);
+        assert_eq!(summary["languages"]["python"]["classes"], 1);
+        // One plus a point for the `if`; block colons are not branches
+        assert_eq!(summary["languages"]["python"]["average_complexity"], 2.0);
+        assert_eq!(summary["errors"]["tools/broken.py"]["kind"], "parse_failed");
+        assert_eq!(summary["dominant_naming_style"], "CamelCase");
+
+        let smells = summary["top_smells"].as_array().unwrap();
+        assert_eq!(smells.len(), 1);
+        assert_eq!((smells[0]["file"].as_str(), smells[0]["kind"].as_str()), (Some("src/cart.js"), Some("LongMethod")));
+        assert_eq!(smells[0]["line_number"], 5);
+        assert_eq!(summary["heatmap"]["src/user.js"]["class_definition"], 2);
+        assert_eq!(summary["heatmap"]["tools/report.py"]["class_definition"], 1);
+        assert!(summary["heatmap"]["tools/broken.py"].is_null());
+
+        let invalid: serde_json::Value = serde_json::from_str(&analyze_project_summary("[]")).unwrap();
+        assert_eq!(invalid["error"]["kind"], "invalid_input");
+    }
+
+    #[test]
+    fn test_ignore_patterns_exclude_matching_files() {
+        let config = EngineConfig::builder()
+            .ignore_patterns(["*.min.js", "vendor/**", "src/gen/*.py"])
+            .build()
+            .unwrap();
+        assert!(config.is_path_ignored("dist/app.min.js"));
+        assert!(config.is_path_ignored("vendor/lib/deep/util.js"));
+        assert!(config.is_path_ignored("./src/gen/schema.py"));
+        assert!(!config.is_path_ignored("src/gen/nested/schema.py"));
+        assert!(!config.is_path_ignored("src/admin.js"));
+        assert!(EngineConfig::builder().ignore_patterns(["a/**/b.js"]).build().unwrap().is_path_ignored("a/b.js"));
+
+        // Many stars against a long near-miss would take exponential time with backtracking
+        let stars = EngineConfig::builder().ignore_patterns(["**/*a*a*a*a*a*a*a*a*a*a*b", "*a*a*a*a*a*a*a*a*a*a*b"]).build().unwrap();
+        assert!(!stars.is_path_ignored(&format!("src/{}", "a".repeat(200))));
+        assert!(stars.is_path_ignored(&format!("src/{}b", "a".repeat(200))));
+        assert!(EngineConfig::builder().ignore_patterns([""]).build().is_err());
+
+        let files = serde_json::json!({
+            "src/app.js": { "code": "class App {\n  start() {\n    run();\n  }\n}\n", "language": "javascript" },
+            "dist/app.min.js": { "code": "class A{b(){c()}}class D{e(){f()}}", "language": "javascript" },
+            "src/gen/schema.py": { "code": "class Schema:\n    def load(self):\n        pass\n", "language": "python" },
+        });
+        let summary: serde_json::Value = serde_json::from_str(&analyze_project_summary_with_config(&files.to_string(), &config)).unwrap();
+        assert_eq!(summary["file_count"], 1);
+        assert_eq!(summary["total_classes"], 1);
+        assert_eq!(summary["languages"]["javascript"]["files"], 1);
+        assert!(summary["languages"]["python"].is_null());
+        assert_eq!(summary["ignored"], serde_json::json!(["dist/app.min.js", "src/gen/schema.py"]));
+
+        let unfiltered: serde_json::Value = serde_json::from_str(&analyze_project_summary(&files.to_string())).unwrap();
+        assert_eq!(unfiltered["file_count"], 3);
+
+        let mut engine = CodeWhispererEngine::new(&config).unwrap();
+        let open_files: HashMap<String, String> = files.as_object().unwrap().iter()
+            .map(|(path, file)| (path.clone(), file["code"].as_str().unwrap().to_string()))
+            .collect();
+        assert_eq!(engine.warm_files(&open_files, "javascript"), 1);
+        assert_eq!(engine.parse_count(), 1);
+    }
+
+    #[test]
+    fn test_suggestions_for_function_definition_context() {
+        let code = "function calculateTotal(items) {\n    let total = 0;\n    for (const item of items) {\n        total += item.price;\n    }\n    return total;\n}\n\nfunction applyDiscount(items) {\n}\n";
+        let context = serde_json::json!({
+            "file_path": "cart.js",
+            "file_language": "javascript",
+            "file_content": code,
+            "current_line": "function applyDiscount(items) {",
+            "cursor_position": { "row": 8, "column": 32, "offset": code.len() - 3 },
+        }).to_string();
+        let mut engine = CodeWhispererEngine::new(&EngineConfig::new()).unwrap();
+
+        let result: serde_json::Value = serde_json::from_str(&engine.get_suggestions(&context, "")).unwrap();
+        let suggestions = result["suggestions"].as_array().unwrap();
+        assert!(!suggestions.is_empty());
+        assert!(suggestions.iter().any(|suggestion| suggestion["suggested_code"] == "currentItem"));
+        // Only kinds that fit a function definition survive, best first
+        assert!(suggestions.iter().all(|suggestion| matches!(suggestion["suggestion_type"].as_str(), Some("VariableNaming" | "CodeCompletion"))));
+        let confidences: Vec<f64> = suggestions.iter().map(|suggestion| suggestion["confidence_score"].as_f64().unwrap()).collect();
+        assert!(confidences.iter().all(|&confidence| confidence >= 0.6));
+        assert!(confidences.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        let strict: serde_json::Value = serde_json::from_str(&engine.get_suggestions(&context, r#"{"confidence_threshold": 0.95}"#)).unwrap();
+        assert_eq!(strict["suggestions"], serde_json::json!([]));
+
+        let error: serde_json::Value = serde_json::from_str(&engine.get_suggestions("{\"file_path\": 1}", "")).unwrap();
+        assert_eq!(error["error"]["kind"], "invalid_input");
+        let disabled: serde_json::Value = serde_json::from_str(&engine.get_suggestions(&context, r#"{"enabled_languages": ["rust"]}"#)).unwrap();
+        assert_eq!(disabled["error"]["kind"], "unsupported_language");
+
+        let mut broken: serde_json::Value = serde_json::from_str(&context).unwrap();
+        broken["file_content"] = serde_json::json!("function broken( {\n");
+        let unparsed: serde_json::Value = serde_json::from_str(&engine.get_suggestions(&broken.to_string(), "")).unwrap();
+        assert_eq!(unparsed["error"]["kind"], "parse_failed");
+    }
+
+    #[test]
+    fn test_suggestion_cooldown_is_opt_in_and_dismissal_takes_shown_ids() {
+        let code = "function calculateTotal(items) {\n    let total = 0;\n    for (const item of items) {\n        total += item.price;\n    }\n    return total;\n}\n\nfunction applyDiscount(items) {\n}\n";
+        let context = serde_json::json!({
+            "file_path": "cart.js",
+            "file_language": "javascript",
+            "file_content": code,
+            "current_line": "function applyDiscount(items) {",
+            "cursor_position": { "row": 8, "column": 32, "offset": code.len() - 3 },
+        }).to_string();
+        let shown_code = |engine: &mut CodeWhispererEngine| -> Vec<(String, String)> {
+            let result: serde_json::Value = serde_json::from_str(&engine.get_suggestions(&context, "")).unwrap();
+            result["suggestions"].as_array().unwrap().iter()
+                .map(|suggestion| (suggestion["id"].as_str().unwrap().to_string(), suggestion["suggested_code"].as_str().unwrap().to_string()))
+                .collect()
+        };
+
+        let mut engine = CodeWhispererEngine::new(&EngineConfig::new()).unwrap();
+        let shown = shown_code(&mut engine);
+        assert!(!shown.is_empty());
+        let code_of = |suggestions: &[(String, String)]| suggestions.iter().map(|(_, code)| code.clone()).collect::<Vec<_>>();
+        assert_eq!(code_of(&shown_code(&mut engine)), code_of(&shown));
+
+        let config = EngineConfig::parse_json(r#"{"suggestion_cooldown_ms": 60000}"#).unwrap();
+        assert_eq!(config.suggestion_cooldown_ms(), 60000);
+        let mut engine = CodeWhispererEngine::new(&config).unwrap();
+        let shown = shown_code(&mut engine);
+        assert!(engine.dismiss_suggestion(&shown[0].0));
+        assert!(!engine.dismiss_suggestion("never_shown"));
+        let repeated = code_of(&shown_code(&mut engine));
+        assert!(code_of(&shown).iter().all(|code| !repeated.contains(code)));
+    }
+
+    #[test]
+    fn test_rejected_feedback_suppresses_suggestion_type() {
+        let code = "function calculateTotal(items) {\n    let total = 0;\n    for (const item of items) {\n        total += item.price;\n    }\n    return total;\n}\n\nfunction applyDiscount(items) {\n}\n";
+        let context = serde_json::json!({
+            "file_path": "cart.js",
+            "file_language": "javascript",
+            "file_content": code,
+            "current_line": "function applyDiscount(items) {",
+            "cursor_position": { "row": 8, "column": 32, "offset": code.len() - 3 },
+        }).to_string();
+        let mut engine = CodeWhispererEngine::new(&EngineConfig::new()).unwrap();
+        let naming_ids = |engine: &mut CodeWhispererEngine| -> Vec<String> {
+            let result: serde_json::Value = serde_json::from_str(&engine.get_suggestions(&context, "")).unwrap();
+            result["suggestions"].as_array().unwrap().iter()
+                .filter(|suggestion| suggestion["suggestion_type"] == "VariableNaming")
+                .map(|suggestion| suggestion["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let ids = naming_ids(&mut engine);
+        assert!(!ids.is_empty());
+        assert!(!engine.provide_feedback_with_reason(&ids[0], false, None, Some("boring".to_string())));
+        // Rejections as duplicates leave the type alone
+        for id in ids.iter().cycle().take(3) {
+            assert!(engine.provide_feedback_with_reason(id, false, None, Some("duplicate".to_string())));
+        }
+        assert!(!naming_ids(&mut engine).is_empty());
+
+        for id in ids.iter().cycle().take(3) {
+            assert!(engine.provide_feedback_with_reason(id, false, None, Some("wrong_style".to_string())));
+        }
+        assert!(naming_ids(&mut engine).is_empty());
+        let reasons = engine.behavior_tracker.analyze_behavior().suggestion_feedback.rejection_reasons;
+        assert_eq!(reasons.get(&RejectionReason::Duplicate), Some(&3));
+        assert_eq!(reasons.get(&RejectionReason::WrongStyle), Some(&3));
+    }
 }
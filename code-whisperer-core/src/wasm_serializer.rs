@@ -102,6 +102,45 @@ pub struct ComprehensiveAnalysis {
     pub metadata: SerializableMetadata,
 }
 
+/// How much of an analysis result is serialized
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputVerbosity {
+    /// Counts and top-level scores only. A `ComprehensiveAnalysis` becomes `{"counts", "scores"}`
+    /// with no `suggestions_data`
+    Summary,
+    /// Everything but the bulkiest detail: the per-confidence copies of the pattern scores, the
+    /// adaptive adjustments, the project heatmap and pattern feedback histories
+    Standard,
+    /// The whole analysis, as before verbosity could be chosen
+    #[default]
+    Full,
+}
+
+impl OutputVerbosity {
+    /// Trim a serialized result in place: `Summary` keeps only the top-level `summary_keys`, and
+    /// `Standard` removes the JSON pointers in `full_only`
+    pub fn trim(self, value: &mut serde_json::Value, summary_keys: &[&str], full_only: &[&str]) {
+        match self {
+            OutputVerbosity::Full => {}
+            OutputVerbosity::Standard => {
+                for pointer in full_only {
+                    let (parent, key) = pointer.rsplit_once('/').unwrap_or(("", pointer));
+                    if let Some(serde_json::Value::Object(parent)) = value.pointer_mut(parent) {
+                        parent.remove(key);
+                    }
+                }
+            }
+            OutputVerbosity::Summary => {
+                if let serde_json::Value::Object(map) = value {
+                    map.retain(|key, _| summary_keys.contains(&key.as_str()));
+                }
+            }
+        }
+    }
+}
+
 /// Serializable suggestion for JavaScript consumption
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SerializableSuggestion {
@@ -122,6 +161,25 @@ impl WasmSerializer {
         language: &str,
         code_length: usize,
         error_message: Option<String>,
+    ) -> SerializableAnalysisResult {
+        Self::create_analysis_result_with_verbosity(
+            comprehensive_analysis,
+            processing_time_ms,
+            language,
+            code_length,
+            error_message,
+            OutputVerbosity::Full,
+        )
+    }
+
+    /// `create_analysis_result`, keeping only as much of the analysis as `verbosity` asks for
+    pub fn create_analysis_result_with_verbosity(
+        comprehensive_analysis: Option<ComprehensiveAnalysis>,
+        processing_time_ms: u64,
+        language: &str,
+        code_length: usize,
+        error_message: Option<String>,
+        verbosity: OutputVerbosity,
     ) -> SerializableAnalysisResult {
         let metadata = SerializableMetadata {
             processing_time_ms,
@@ -154,10 +212,12 @@ impl WasmSerializer {
 
         let analysis_data = comprehensive_analysis
             .as_ref()
-            .and_then(|analysis| serde_json::to_string(analysis).ok());
+            .and_then(|analysis| Self::analysis_json(analysis, &metadata, verbosity));
 
+        // The summary already counts the suggestions
         let suggestions_data = comprehensive_analysis
             .as_ref()
+            .filter(|_| verbosity != OutputVerbosity::Summary)
             .and_then(|analysis| analysis.suggestions.as_ref())
             .and_then(|suggestions| {
                 let serializable_suggestions: Vec<SerializableSuggestion> = suggestions
@@ -188,6 +248,33 @@ impl WasmSerializer {
         )
     }
 
+    fn analysis_json(analysis: &ComprehensiveAnalysis, metadata: &SerializableMetadata, verbosity: OutputVerbosity) -> Option<String> {
+        let value = match verbosity {
+            OutputVerbosity::Full | OutputVerbosity::Standard => {
+                let mut value = serde_json::to_value(analysis).ok()?;
+                verbosity.trim(&mut value, &[], &["/scoring/confidence_distribution", "/scoring/adaptive_adjustments"]);
+                value
+            }
+            OutputVerbosity::Summary => {
+                let ranking = analysis.scoring.as_ref().map(|scoring| &scoring.ranking_metrics);
+                serde_json::json!({
+                    "counts": {
+                        "patterns": metadata.patterns_found,
+                        "suggestions": metadata.suggestions_generated,
+                        "high_confidence": ranking.map_or(0, |ranking| ranking.high_confidence_count),
+                        "medium_confidence": ranking.map_or(0, |ranking| ranking.medium_confidence_count),
+                        "low_confidence": ranking.map_or(0, |ranking| ranking.low_confidence_count),
+                    },
+                    "scores": {
+                        "average_pattern_score": ranking.map_or(0.0, |ranking| ranking.average_score),
+                        "average_suggestion_confidence": metadata.confidence_score,
+                    },
+                })
+            }
+        };
+        serde_json::to_string(&value).ok()
+    }
+
     /// Create a comprehensive analysis from individual components
     pub fn create_comprehensive_analysis(
         patterns: Option<&PatternAnalysis>,
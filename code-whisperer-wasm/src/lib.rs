@@ -7,13 +7,22 @@ pub use code_whisperer_core::wasm_interface::{
     EditorContext,
     create_default_config,
     validate_syntax,
+    validate_syntax_detailed,
+    validate_syntax_batch,
+    analyze_style,
+    quick_style_metrics,
+    analyze_project_summary,
+    analyze_project_summary_with_config,
     init,
 };
+pub use code_whisperer_core::WasmCache;
+pub use code_whisperer_core::analyze_notebook;
 
 // WASM-specific utilities and optimizations
 use wasm_bindgen::prelude::*;
 use web_sys::{Performance, Window};
 use std::collections::HashMap;
+use code_whisperer_core::{AstParser, PatternAnalyzer};
 
 /// Performance monitor for WASM operations
 #[wasm_bindgen]
@@ -67,90 +76,6 @@ impl WasmPerformanceMonitor {
     }
 }
 
-/// WASM-optimized cache for frequently accessed data
-#[wasm_bindgen]
-pub struct WasmCache {
-    cache: HashMap<String, String>,
-    max_size: usize,
-    access_count: HashMap<String, u32>,
-}
-
-#[wasm_bindgen]
-impl WasmCache {
-    #[wasm_bindgen(constructor)]
-    pub fn new(max_size: usize) -> WasmCache {
-        WasmCache {
-            cache: HashMap::new(),
-            max_size,
-            access_count: HashMap::new(),
-        }
-    }
-
-    #[wasm_bindgen]
-    pub fn get(&mut self, key: &str) -> Option<String> {
-        if let Some(value) = self.cache.get(key) {
-            // Update access count for LRU eviction
-            *self.access_count.entry(key.to_string()).or_insert(0) += 1;
-            Some(value.clone())
-        } else {
-            None
-        }
-    }
-
-    #[wasm_bindgen]
-    pub fn set(&mut self, key: &str, value: &str) -> bool {
-        // Check if we need to evict items
-        if self.cache.len() >= self.max_size && !self.cache.contains_key(key) {
-            self.evict_least_used();
-        }
-
-        self.cache.insert(key.to_string(), value.to_string());
-        self.access_count.insert(key.to_string(), 1);
-        true
-    }
-
-    #[wasm_bindgen]
-    pub fn has(&self, key: &str) -> bool {
-        self.cache.contains_key(key)
-    }
-
-    #[wasm_bindgen]
-    pub fn clear(&mut self) {
-        self.cache.clear();
-        self.access_count.clear();
-    }
-
-    #[wasm_bindgen]
-    pub fn size(&self) -> usize {
-        self.cache.len()
-    }
-
-    #[wasm_bindgen]
-    pub fn get_stats(&self) -> String {
-        serde_json::to_string(&serde_json::json!({
-            "current_size": self.cache.len(),
-            "max_size": self.max_size,
-            "total_keys": self.access_count.len(),
-            "most_accessed": self.get_most_accessed_key()
-        })).unwrap_or_default()
-    }
-
-    fn evict_least_used(&mut self) {
-        if let Some((least_used_key, _)) = self.access_count.iter()
-            .min_by_key(|(_, count)| *count) {
-            let key_to_remove = least_used_key.clone();
-            self.cache.remove(&key_to_remove);
-            self.access_count.remove(&key_to_remove);
-        }
-    }
-
-    fn get_most_accessed_key(&self) -> Option<String> {
-        self.access_count.iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(key, _)| key.clone())
-    }
-}
-
 /// Worker thread manager for background processing
 #[wasm_bindgen]
 pub struct WasmWorkerManager {
@@ -259,20 +184,9 @@ impl WasmMemoryOptimizer {
     /// Get memory pressure information
     #[wasm_bindgen]
     pub fn get_memory_pressure() -> String {
-        let memory = wasm_bindgen::memory().unchecked_into::<js_sys::WebAssembly::Memory>();
-        let buffer = memory.buffer();
-        let array = js_sys::Uint8Array::new(&buffer);
-        let byte_length = array.byte_length();
-        
-        // Estimate memory pressure based on usage
+        let byte_length = Self::heap_byte_length();
         let mb_used = (byte_length as f64) / (1024.0 * 1024.0);
-        let pressure_level = if mb_used < 10.0 {
-            "low"
-        } else if mb_used < 50.0 {
-            "medium"
-        } else {
-            "high"
-        };
+        let pressure_level = Self::pressure_level(mb_used);
 
         serde_json::to_string(&serde_json::json!({
             "memory_used_mb": mb_used,
@@ -287,6 +201,30 @@ impl WasmMemoryOptimizer {
         })).unwrap_or_default()
     }
 
+    /// Report the current pressure level to `analyzer`, which caps its retained patterns
+    /// while pressure is high. Returns the number of patterns evicted.
+    #[wasm_bindgen]
+    pub fn relieve_pattern_pressure(analyzer: &mut PatternAnalyzer) -> usize {
+        let mb_used = (Self::heap_byte_length() as f64) / (1024.0 * 1024.0);
+        analyzer.set_memory_pressure(Self::pressure_level(mb_used))
+    }
+
+    fn heap_byte_length() -> u32 {
+        let memory = wasm_bindgen::memory().unchecked_into::<js_sys::WebAssembly::Memory>();
+        js_sys::Uint8Array::new(&memory.buffer()).byte_length()
+    }
+
+    // Estimate memory pressure based on usage
+    fn pressure_level(mb_used: f64) -> &'static str {
+        if mb_used < 10.0 {
+            "low"
+        } else if mb_used < 50.0 {
+            "medium"
+        } else {
+            "high"
+        }
+    }
+
     /// Optimize memory by clearing unnecessary data
     #[wasm_bindgen]
     pub fn optimize_memory() -> String {
@@ -364,3 +302,102 @@ pub fn benchmark_analysis(code: &str, language: &str, iterations: usize) -> Stri
         "memory_usage": performance_monitor.get_memory_usage()
     })).unwrap_or_default()
 }
+
+/// Benchmark analysis with separate timings for AST parsing, pattern extraction and regex fallback
+#[wasm_bindgen]
+pub fn benchmark_analysis_detailed(code: &str, language: &str, iterations: usize) -> String {
+    // The Performance API needs a browser window, so native builds time with `Instant` instead
+    #[cfg(target_arch = "wasm32")]
+    let mut performance_monitor = WasmPerformanceMonitor::new().ok();
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut performance_monitor: Option<WasmPerformanceMonitor> = None;
+
+    let parser = AstParser::new();
+    let analyzer = PatternAnalyzer::new();
+    let runs = iterations.max(1);
+
+    let mut ast_parse_ms = 0.0;
+    let mut pattern_extraction_ms = 0.0;
+    let mut regex_fallback_ms = 0.0;
+    let mut ast_patterns = 0;
+    let mut regex_patterns = 0;
+    let mut ast_parse_succeeded = true;
+
+    for _ in 0..runs {
+        let (parsed, duration) = time_phase(&mut performance_monitor, "ast_parse", || parser.parse_code(code, language));
+        ast_parse_ms += duration;
+
+        match parsed {
+            Ok(ast) => {
+                let (patterns, duration) = time_phase(&mut performance_monitor, "pattern_extraction", || {
+                    parser.extract_patterns(&ast, code, language)
+                });
+                pattern_extraction_ms += duration;
+                ast_patterns = patterns.len();
+            }
+            Err(_) => ast_parse_succeeded = false,
+        }
+
+        let (patterns, duration) = time_phase(&mut performance_monitor, "regex_fallback", || {
+            analyzer.analyze_with_regex_only(code, language)
+        });
+        regex_fallback_ms += duration;
+        regex_patterns = patterns.len();
+    }
+
+    let memory_usage = performance_monitor
+        .as_ref()
+        .map(|monitor| monitor.get_memory_usage())
+        .unwrap_or_default();
+
+    serde_json::to_string(&serde_json::json!({
+        "iterations": runs,
+        "ast_parse_ms": ast_parse_ms / runs as f64,
+        "pattern_extraction_ms": pattern_extraction_ms / runs as f64,
+        "ast_total_ms": (ast_parse_ms + pattern_extraction_ms) / runs as f64,
+        "regex_fallback_ms": regex_fallback_ms / runs as f64,
+        "ast_parse_succeeded": ast_parse_succeeded,
+        "ast_pattern_count": ast_patterns,
+        "regex_pattern_count": regex_patterns,
+        "memory_usage": memory_usage
+    })).unwrap_or_default()
+}
+
+fn time_phase<T>(monitor: &mut Option<WasmPerformanceMonitor>, operation: &str, phase: impl FnOnce() -> T) -> (T, f64) {
+    match monitor {
+        Some(monitor) => {
+            monitor.start_timing(operation);
+            let result = phase();
+            (result, monitor.end_timing(operation).unwrap_or(0.0))
+        }
+        None => {
+            let start = std::time::Instant::now();
+            let result = phase();
+            (result, start.elapsed().as_secs_f64() * 1000.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detailed_benchmark_reports_phase_timings() {
+        let report = benchmark_analysis_detailed("function add(a, b) { return a + b; }", "javascript", 3);
+        let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+        assert!(json["ast_parse_ms"].as_f64().unwrap() >= 0.0);
+        assert!(json["pattern_extraction_ms"].is_number());
+        assert!(json["regex_fallback_ms"].as_f64().unwrap() >= 0.0);
+        assert_eq!(json["iterations"], 3);
+        assert_eq!(json["ast_parse_succeeded"], true);
+    }
+
+    #[test]
+    fn test_pressure_level_thresholds() {
+        assert_eq!(WasmMemoryOptimizer::pressure_level(4.0), "low");
+        assert_eq!(WasmMemoryOptimizer::pressure_level(10.0), "medium");
+        assert_eq!(WasmMemoryOptimizer::pressure_level(64.0), "high");
+    }
+}